@@ -0,0 +1,508 @@
+//! An asynchronous client for applications that already run on `tokio`, such as bots that watch
+//! many subreddits. Requires the `async` feature.
+//!
+//! `AsyncRedditClient` is deliberately small. It has the basic request methods of
+//! `RedditClient` (`get_json`, `post_json` and `post_success`), which can be awaited instead of
+//! blocking the thread, plus replying, voting, subreddit listings and a stream of new posts.
+//! Listings and streams yield the raw `SubmissionData` of each post, since the structures in
+//! `new_rawr::structures` borrow a blocking `RedditClient`. Other endpoints can be called with
+//! the request methods directly.
+//!
+//! Requests are sent straight to Reddit with `hyper`, so most of what `RedditClient.send()` does
+//! is **not** done by this client:
+//!
+//! - the rate limit is not tracked, and requests are never delayed (see
+//!   `RedditClient.set_rate_limit_policy()`); HTTP 429 fails with an error instead of being
+//!   retried;
+//! - failed requests are not retried, apart from refreshing the token once after HTTP 401;
+//! - there is no dry-run mode, but read-only mode is supported;
+//! - request hooks, metrics, `tracing` spans, response caches and compressed responses are not
+//!   supported.
+//!
+//! Use a blocking `RedditClient` (e.g. in `tokio::task::spawn_blocking`) if you need any of
+//! these.
+//!
+//! Authenticators are shared with the blocking client. Logging in and refreshing tokens are run
+//! on a separate thread, so they do not block the async runtime.
+//! # Examples
+//! Watching several subreddits concurrently, without a thread per stream:
+//!
+//! ```rust,no_run
+//! use futures::stream::{self, StreamExt};
+//! use new_rawr::async_client::AsyncRedditClient;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! # #[tokio::main]
+//! # async fn main() {
+//! let client = AsyncRedditClient::new("new_rawr", AnonymousAuthenticator::new())
+//!     .await
+//!     .expect("Could not log in");
+//! let streams = ["rust", "programming", "learnprogramming"]
+//!     .iter()
+//!     .map(|name| client.subreddit(name).new_stream().into_stream().boxed_local());
+//! let mut posts = stream::select_all(streams);
+//! while let Some(post) = posts.next().await {
+//!     println!("/r/{}: {}", post.subreddit, post.title);
+//! }
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use futures::stream::{self, Stream};
+use http::{Method, StatusCode};
+use http::header::{CONTENT_TYPE, USER_AGENT};
+use hyper::{Body, Client, Request};
+use hyper_tls::HttpsConnector;
+use tokio::sync::oneshot;
+
+use crate::auth::Authenticator;
+use crate::client::{self, RedditClient};
+use crate::endpoints::{self, Endpoints};
+use crate::errors::APIError;
+use crate::http::{AuthHostBackend, HttpRequest, HttpResponse, HyperBackend};
+use crate::http::proxy::{Proxy, ProxyConnector};
+use crate::options::ListingRequest;
+use crate::responses::listing::{self, SubmissionData};
+
+/// A client to connect to Reddit whose requests can be awaited. See the module-level
+/// documentation for examples.
+pub struct AsyncRedditClient {
+    http: Client<HttpsConnector<ProxyConnector>>,
+    user_agent: String,
+    authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+    endpoints: Endpoints,
+    proxy: Option<Proxy>,
+    raw_json: bool,
+    read_only: AtomicBool,
+}
+
+impl AsyncRedditClient {
+    /// Creates an `AsyncRedditClient` and logs in with the authenticator. See
+    /// `RedditClient::new()` for how to choose the user agent and authenticator. Like
+    /// `RedditClient::new()`, this uses the proxy set in the environment, if any, and fails if it
+    /// is invalid.
+    pub async fn new(user_agent: &str,
+                     authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>)
+                     -> Result<AsyncRedditClient, APIError> {
+        AsyncRedditClient::with_endpoints(user_agent, authenticator, Endpoints::default()).await
+    }
+
+    /// Creates an `AsyncRedditClient` that sends requests to the specified hosts, and logs in at
+    /// `endpoints.auth_host`. See `RedditClient::with_endpoints()`.
+    pub async fn with_endpoints(user_agent: &str,
+                                authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                                endpoints: Endpoints)
+                                -> Result<AsyncRedditClient, APIError> {
+        AsyncRedditClient::create(user_agent, authenticator, endpoints, Proxy::from_env()?).await
+    }
+
+    /// Creates an `AsyncRedditClient` that sends all requests through the specified proxy. See
+    /// `RedditClientBuilder.proxy()`.
+    pub async fn with_proxy(user_agent: &str,
+                            authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                            proxy: Proxy)
+                            -> Result<AsyncRedditClient, APIError> {
+        AsyncRedditClient::create(user_agent, authenticator, Endpoints::default(), Some(proxy))
+            .await
+    }
+
+    async fn create(user_agent: &str,
+                    authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                    endpoints: Endpoints,
+                    proxy: Option<Proxy>)
+                    -> Result<AsyncRedditClient, APIError> {
+        let connector = HttpsConnector::new_with_connector(ProxyConnector::new(proxy.clone()));
+        let client = AsyncRedditClient {
+            http: Client::builder().build::<_, Body>(connector),
+            user_agent: user_agent.to_owned(),
+            authenticator,
+            endpoints,
+            proxy,
+            raw_json: true,
+            read_only: AtomicBool::new(false),
+        };
+        client.authenticate(false).await?;
+        Ok(client)
+    }
+
+    /// Sends all requests to different hosts. See `RedditClient.set_endpoints()`; use
+    /// `with_endpoints()` to log in at a different host too.
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = endpoints;
+    }
+
+    /// Disables or enables `raw_json=1` on every request. See `RedditClient.set_raw_json()`.
+    pub fn set_raw_json(&mut self, val: bool) {
+        self.raw_json = val;
+    }
+
+    /// Enables or disables read-only mode, in which requests that would change something fail
+    /// with `APIError::ReadOnlyMode`. See `RedditClient.set_read_only()`.
+    pub fn set_read_only(&self, val: bool) {
+        self.read_only.store(val, Ordering::SeqCst);
+    }
+
+    /// `true` if the client is in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Logs in (or refreshes the token) on a separate thread, because authenticators send their
+    /// requests through a blocking `HttpBackend`.
+    async fn authenticate(&self, refresh: bool) -> Result<(), APIError> {
+        let authenticator = self.authenticator.clone();
+        let user_agent = self.user_agent.to_owned();
+        let endpoints = self.endpoints.clone();
+        let proxy = self.proxy.clone();
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let hyper = proxy.map_or_else(HyperBackend::direct, HyperBackend::with_proxy);
+            let backend = AuthHostBackend { backend: &hyper, endpoints: &endpoints };
+            let mut authenticator = authenticator.lock().unwrap();
+            let result = if refresh {
+                authenticator.refresh_token(&backend, &user_agent)
+            } else {
+                authenticator.login(&backend, &user_agent)
+            };
+            let _ = sender.send(result);
+        });
+        receiver.await
+            .unwrap_or_else(|_| Err(APIError::ValidationError(String::from("Authentication failed"))))
+    }
+
+    /// Logs out and invalidates the tokens of the authenticator. Unlike `RedditClient`, this is
+    /// not done automatically when the client is dropped.
+    pub async fn logout(self) -> Result<(), APIError> {
+        let authenticator = self.authenticator.clone();
+        let user_agent = self.user_agent.to_owned();
+        let endpoints = self.endpoints.clone();
+        let proxy = self.proxy.clone();
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let hyper = proxy.map_or_else(HyperBackend::direct, HyperBackend::with_proxy);
+            let backend = AuthHostBackend { backend: &hyper, endpoints: &endpoints };
+            let _ = sender.send(authenticator.lock().unwrap().logout(&backend, &user_agent));
+        });
+        receiver.await.unwrap_or_else(|_| Err(APIError::ValidationError(String::from("Logout failed"))))
+    }
+
+    fn build_request(&self,
+                     method: Method,
+                     dest: &str,
+                     oauth_required: bool)
+                     -> Result<HttpRequest, APIError> {
+        let authenticator = self.authenticator.lock().unwrap();
+        let oauth_supported = authenticator.oauth();
+        if oauth_required && !oauth_supported {
+            return Err(APIError::ValidationError(String::from("OAuth is required to use this \
+                                                               endpoint, but your \
+                                                               authenticator does not support \
+                                                               it")));
+        }
+        let url = format!("{}{}", self.endpoints.host(oauth_supported), dest);
+        let url = RedditClient::with_raw_json(&url, self.raw_json);
+        let mut request = HttpRequest::new(method, &url);
+        for (name, value) in authenticator.headers()? {
+            request = request.header(name, &value);
+        }
+        Ok(request.header(USER_AGENT, &self.user_agent))
+    }
+
+    /// Creates a request to the specified endpoint with the authentication and user agent
+    /// headers set, refreshing the token first if it has expired.
+    pub async fn request(&self,
+                         method: Method,
+                         dest: &str,
+                         oauth_required: bool)
+                         -> Result<HttpRequest, APIError> {
+        match self.build_request(method.clone(), dest, oauth_required) {
+            Err(APIError::ExpiredToken) => {
+                self.authenticate(true).await?;
+                self.build_request(method, dest, oauth_required)
+            }
+            other => other,
+        }
+    }
+
+    /// Sends a request and waits for the whole response. Non-2xx status codes are not treated as
+    /// errors. Fails with `APIError::ReadOnlyMode` if the request would change something while the
+    /// client is in read-only mode. Unlike `RedditClient.send()`, the request is sent as it is:
+    /// see the module-level documentation for what is not supported.
+    pub async fn send(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        if self.is_read_only() && request.is_mutating() {
+            return Err(APIError::ReadOnlyMode);
+        }
+        let mut builder = Request::builder().method(request.method).uri(request.url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        let request = builder.body(Body::from(request.body))
+            .map_err(|err| APIError::ValidationError(err.to_string()))?;
+        let response = self.http.request(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+
+    /// Sends a request, refreshing the token and trying once more if Reddit returns 401
+    /// Unauthorized. Fails if the final response does not have a success status.
+    async fn send_checked(&self,
+                          method: Method,
+                          dest: &str,
+                          body: Option<&str>,
+                          oauth_required: bool)
+                          -> Result<HttpResponse, APIError> {
+        let mut refreshed = false;
+        loop {
+            let mut request = self.request(method.clone(), dest, oauth_required).await?;
+            if let Some(body) = body {
+                request = request.header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(body);
+            }
+            let response = self.send(request).await?;
+            if response.status == StatusCode::UNAUTHORIZED && !refreshed {
+                self.authenticate(true).await?;
+                refreshed = true;
+            } else if response.status.is_success() {
+                return Ok(response);
+            } else {
+                let body = String::from_utf8_lossy(&response.body);
+                return Err(APIError::from_response(response.status, &body));
+            }
+        }
+    }
+
+    /// Sends a GET request and returns the response body. See `RedditClient.get_json()`.
+    pub async fn get_json(&self, dest: &str, oauth_required: bool) -> Result<String, APIError> {
+        self.send_checked(Method::GET, dest, None, oauth_required).await?.text()
+    }
+
+    /// Sends a POST request with a form body and returns the response body. See
+    /// `RedditClient.post_json()`.
+    pub async fn post_json(&self,
+                           dest: &str,
+                           body: &str,
+                           oauth_required: bool)
+                           -> Result<String, APIError> {
+        let text = self.send_checked(Method::POST, dest, Some(body), oauth_required).await?.text()?;
+        APIError::check_errors(&text)?;
+        Ok(text)
+    }
+
+    /// Sends a POST request with a form body and checks that it succeeded. See
+    /// `RedditClient.post_success()`.
+    pub async fn post_success(&self,
+                              dest: &str,
+                              body: &str,
+                              oauth_required: bool)
+                              -> Result<(), APIError> {
+        let response = self.send_checked(Method::POST, dest, Some(body), oauth_required).await?;
+        APIError::check_errors(&String::from_utf8_lossy(&response.body))
+    }
+
+    /// URL encodes the string. See `RedditClient.url_escape()`.
+    pub fn url_escape(&self, item: String) -> String {
+        client::url_escape(&item)
+    }
+
+    /// Replies to the submission, comment or message with the specified full name (e.g.
+    /// `t3_4uule8`).
+    pub async fn reply(&self, thing_id: &str, text: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&text={}&thing_id={}",
+                           self.url_escape(text.to_owned()),
+                           thing_id);
+        self.post_success(endpoints::COMMENT, &body, false).await
+    }
+
+    /// Votes on the submission or comment with the specified full name. `dir` is 1 to upvote,
+    /// -1 to downvote and 0 to cancel a vote.
+    pub async fn vote(&self, thing_id: &str, dir: i8) -> Result<(), APIError> {
+        let body = format!("dir={}&id={}", dir, thing_id);
+        self.post_success(endpoints::VOTE, &body, false).await
+    }
+
+    /// Provides an interface to the listings of the specified subreddit.
+    pub fn subreddit(&self, name: &str) -> AsyncSubreddit<'_> {
+        AsyncSubreddit {
+            client: self,
+            name: self.url_escape(name.to_owned()),
+        }
+    }
+}
+
+/// The listings of a subreddit, for use with an `AsyncRedditClient`. Created with
+/// `AsyncRedditClient.subreddit()`.
+pub struct AsyncSubreddit<'a> {
+    client: &'a AsyncRedditClient,
+    /// The name of the subreddit (without `/r/`).
+    pub name: String,
+}
+
+impl<'a> AsyncSubreddit<'a> {
+    /// Gets a listing of posts in this subreddit, as configured by the `ListingRequest` (see
+    /// `Subreddit.listing()`). No request is sent until the first item is requested.
+    pub fn listing(&self, request: ListingRequest) -> Result<AsyncListing<'a>, APIError> {
+        Ok(AsyncListing::new(self.client,
+                             request.query_stem(&self.name)?,
+                             request.anchor.to_string()))
+    }
+
+    /// Gets an `AsyncPostStream` of new posts in this subreddit.
+    pub fn new_stream(&self) -> AsyncPostStream<'a> {
+        let url = format!("{}?limit=5", endpoints::subreddit_listing(&self.name, "new"));
+        AsyncPostStream::new(self.client, url)
+    }
+}
+
+/// A paginated listing of posts which fetches each page when it is needed. Like `Listing`, this
+/// can be very long, so limit how many items you consume.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::async_client::AsyncRedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::options::ListingRequest;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = AsyncRedditClient::new("new_rawr", AnonymousAuthenticator::new()).await.unwrap();
+/// let mut hot = client.subreddit("rust").listing(ListingRequest::default()).unwrap();
+/// for _ in 0..50 {
+///     match hot.next().await.expect("Could not fetch page") {
+///         Some(post) => println!("{}", post.title),
+///         None => break,
+///     }
+/// }
+/// # }
+/// ```
+pub struct AsyncListing<'a> {
+    client: &'a AsyncRedditClient,
+    query_stem: String,
+    anchor: String,
+    items: VecDeque<SubmissionData>,
+    after: Option<String>,
+    started: bool,
+}
+
+impl<'a> AsyncListing<'a> {
+    fn new(client: &'a AsyncRedditClient, query_stem: String, anchor: String) -> AsyncListing<'a> {
+        AsyncListing {
+            client,
+            query_stem,
+            anchor,
+            items: VecDeque::new(),
+            after: None,
+            started: false,
+        }
+    }
+
+    /// The next post, fetching the next page if necessary. Returns `Ok(None)` at the end of
+    /// the listing. After an error, calling this again retries the same page.
+    pub async fn next(&mut self) -> Result<Option<SubmissionData>, APIError> {
+        if self.items.is_empty() {
+            let url = match (self.started, &self.after) {
+                (false, _) => format!("{}&{}", self.query_stem, self.anchor),
+                (true, Some(after)) => format!("{}&after={}", self.query_stem, after),
+                (true, None) => return Ok(None),
+            };
+            let string = self.client.get_json(&url, false).await?;
+            let page: listing::Listing = serde_json::from_str(&string)?;
+            self.started = true;
+            self.after = page.data.after;
+            self.items.extend(page.data.children.into_iter().map(|child| child.data));
+        }
+        Ok(self.items.pop_front())
+    }
+
+    /// Converts the listing into a `Stream`. The stream ends after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<SubmissionData, APIError>> + 'a {
+        stream::unfold(Some(self), |listing| async move {
+            let mut listing = listing?;
+            match listing.next().await {
+                Ok(Some(post)) => Some((Ok(post), Some(listing))),
+                Ok(None) => None,
+                Err(err) => Some((Err(err), None)),
+            }
+        })
+    }
+}
+
+/// An infinite stream of new posts which polls every 5 seconds, yielding each post once,
+/// oldest first. Failed polls are skipped, as with `PostStream`.
+pub struct AsyncPostStream<'a> {
+    client: &'a AsyncRedditClient,
+    url: String,
+    set: VecDeque<String>,
+    pending: VecDeque<SubmissionData>,
+    interval: Duration,
+    polled: bool,
+}
+
+impl<'a> AsyncPostStream<'a> {
+    /// Internal method. Use `AsyncSubreddit.new_stream()` instead.
+    pub fn new(client: &'a AsyncRedditClient, url: String) -> AsyncPostStream<'a> {
+        AsyncPostStream {
+            client,
+            url,
+            set: VecDeque::new(),
+            pending: VecDeque::new(),
+            interval: Duration::new(5, 0),
+            polled: false,
+        }
+    }
+
+    /// Sets the time to wait between polls.
+    pub fn with_interval(mut self, interval: Duration) -> AsyncPostStream<'a> {
+        self.interval = interval;
+        self
+    }
+
+    /// Polls once without waiting, returning only the posts that have not been seen before.
+    pub async fn poll(&mut self) -> Result<Vec<SubmissionData>, APIError> {
+        let string = self.client.get_json(&self.url, false).await?;
+        let page: listing::Listing = serde_json::from_str(&string)?;
+        let mut fresh = Vec::new();
+        for child in page.data.children.into_iter().rev() {
+            if !self.set.contains(&child.data.name) {
+                self.set.push_back(child.data.name.to_owned());
+                if self.set.len() > 10 {
+                    self.set.pop_front();
+                }
+                fresh.push(child.data);
+            }
+        }
+        Ok(fresh)
+    }
+
+    /// Waits for the next new post.
+    pub async fn next(&mut self) -> SubmissionData {
+        loop {
+            if let Some(post) = self.pending.pop_front() {
+                return post;
+            }
+            if self.polled {
+                tokio::time::sleep(self.interval).await;
+            }
+            self.polled = true;
+            if let Ok(fresh) = self.poll().await {
+                self.pending.extend(fresh);
+            }
+        }
+    }
+
+    /// Converts the stream into a `Stream`, e.g. to merge it with other streams using
+    /// `futures::stream::select_all`.
+    pub fn into_stream(self) -> impl Stream<Item = SubmissionData> + 'a {
+        stream::unfold(self, |mut posts| async move {
+            let post = posts.next().await;
+            Some((post, posts))
+        })
+    }
+}
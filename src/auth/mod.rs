@@ -9,12 +9,20 @@
 //! it may be preferable to use OAuth if larger batches of data are being processed from the API.
 //! # Authenticator Summary
 //! - `AnonymousAuthenticator` - uses the legacy API (so it has a lower rate limit) but requires
-//! no credentials at all. Choose this if you just want to **browse the API without registering**.
+//!   no credentials at all. Choose this if you just want to **browse the API without registering**.
 //! - `PasswordAuthenticator` - uses the OAuth API (so higher rate limits), but requires a
-//! registered account and registration on the 'apps' page (see below). Choose this for **bots**
-//! or scripts that use lots of data.
-//!
-//! TODO: Add authenticators for the other flows and document them.
+//!   registered account and registration on the 'apps' page (see below). Choose this for **bots**
+//!   or scripts that use lots of data.
+//! - `CodeFlowAuthenticator` - uses the OAuth API, and acts on behalf of users who have approved
+//!   your app on Reddit, without ever seeing their passwords. Choose this for **web apps** and
+//!   other apps used by people other than yourself. Register the app as a **web app** (or
+//!   **installed app**, for apps that cannot keep a secret) with your real redirect URL.
+//! - `InstalledClientAuthenticator` - like `ClientCredentialsAuthenticator`, but for apps that
+//!   cannot keep a client secret, such as **desktop and mobile apps**. Register the app as an
+//!   **installed app**.
+//! - `ClientCredentialsAuthenticator` - uses the OAuth API without logging in as any user, so it
+//!   can only read public data. Choose this for **read-only scripts** that need the higher rate
+//!   limit but have no account to log in as. Register the app as a **script** or **web app**.
 //!
 //! # Registering Your App (for OAuth-based authenticators)
 //! **Note: this does not apply to `AnonymousAuthenticator`**.
@@ -50,44 +58,235 @@
 //! PasswordAuthenticator::new(CLIENT_ID, CLIENT_SECRET, USERNAME, PASSWORD);
 //! ```
 
-#![allow(unknown_lints, doc_markdown)]
+#![allow(unknown_lints, clippy::doc_markdown, clippy::new_ret_no_self)]
+
+pub mod scope;
+pub mod store;
 
 use std::sync::{Arc, Mutex};
-use hyper;
-use std::io::Read;
 use serde_json;
-use hyper::{Client, Request, Body, Method};
-use hyper::HeaderMap;
-use hyper::client::HttpConnector;
-use hyper::header::{AUTHORIZATION, USER_AGENT, CONTENT_TYPE, HeaderName};
-use futures::{AsyncReadExt, SinkExt};
+use http::Method;
+use http::header::{AUTHORIZATION, USER_AGENT, CONTENT_TYPE, HeaderName};
+use crate::client::url_escape;
+use crate::clock;
+use crate::endpoints;
 use crate::errors::APIError;
+use crate::http::{HttpBackend, HttpRequest};
 use crate::responses::auth::TokenResponseData;
-use hyper::http::request::Builder;
-use std::iter::Map;
 use std::collections::HashMap;
-use hyper_tls::HttpsConnector;
-use std::time::{SystemTime, UNIX_EPOCH};
-use futures::future::ok;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use self::scope::ScopeSet;
+use self::store::{StoredToken, TokenStore};
+
+/// How long before the expiry reported by Reddit that a token is treated as expired, in
+/// milliseconds, so that it is refreshed before requests start failing.
+const EXPIRY_MARGIN: u128 = 60 * 1000;
+
+/// The time in milliseconds since the epoch, or `None` if the clock cannot be read.
+fn now_millis() -> Option<u128> {
+    clock::unix_time().ok().map(|now| now.as_millis())
+}
+
+/// The time (in milliseconds since the epoch) at which a token that expires in `expires_in`
+/// seconds should be refreshed.
+fn expire_time(expires_in: u64) -> u128 {
+    now_millis().unwrap_or(0) + (expires_in as u128 * 1000).saturating_sub(EXPIRY_MARGIN)
+}
+
+/// Returns `true` if a token should be refreshed at `expire_time`. Where the clock cannot be
+/// read, tokens are never treated as expired, and are only refreshed once Reddit rejects them.
+fn is_expired(expire_time: u128) -> bool {
+    matches!(now_millis(), Some(now) if now >= expire_time)
+}
+
+/// Revokes an access or refresh token. `hint` is the type of token, either `access_token` or
+/// `refresh_token`.
+fn revoke_token(backend: &dyn HttpBackend,
+                user_agent: &str,
+                basic_auth: &str,
+                token: &str,
+                hint: &str)
+                -> Result<(), APIError> {
+    let body = format!("token={}&token_type_hint={}", url_escape(token), hint);
+    let request = HttpRequest::new(Method::POST, endpoints::REVOKE_TOKEN)
+        .header(AUTHORIZATION, basic_auth)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(USER_AGENT, user_agent)
+        .body(&body);
+    let res = backend.execute(request)?;
+
+    if !res.status.is_success() {
+        Err(APIError::HTTPError(res.status))
+    } else {
+        Ok(())
+    }
+}
+
+/// The access token of an OAuth authenticator, and the `TokenStore` that it is saved to.
+#[derive(Default)]
+struct TokenState {
+    access_token: Option<String>,
+    expire_time: Option<u128>,
+    store: Option<Box<dyn TokenStore + Send>>,
+    /// Whether the store has already been checked for a token from a previous run.
+    restored: bool,
+    /// The scopes to ask for when logging in, or `None` for Reddit's default.
+    requested: Option<ScopeSet>,
+    /// The scopes that Reddit granted, separated by spaces.
+    granted: Option<String>,
+}
+
+impl TokenState {
+    /// On the first login only, loads the token saved by a previous run, using its access token
+    /// if it has not expired. Check `is_valid()` afterwards to see whether a login is needed.
+    fn restore(&mut self) -> Option<StoredToken> {
+        if self.restored {
+            return None;
+        }
+        self.restored = true;
+        let stored = self.store.as_ref()?.load()?;
+        let expire_time = (stored.expires_at as u128 * 1000).saturating_sub(EXPIRY_MARGIN);
+        if !stored.access_token.is_empty() {
+            self.access_token = Some(stored.access_token.to_owned());
+            self.expire_time = Some(expire_time);
+            self.granted = Some(stored.scope.to_owned());
+        }
+        Some(stored)
+    }
+
+    fn is_valid(&self) -> bool {
+        match (&self.access_token, self.expire_time) {
+            (Some(_), Some(expire_time)) => !is_expired(expire_time),
+            _ => false,
+        }
+    }
+
+    /// Uses a newly issued token, saving it to the store (if any).
+    fn update(&mut self,
+              response: &TokenResponseData,
+              refresh_token: Option<String>)
+              -> Result<(), APIError> {
+        self.restored = true;
+        self.access_token = Some(response.access_token.to_owned());
+        self.expire_time = Some(expire_time(response.expires_in));
+        self.granted = Some(response.scope.to_owned());
+        match self.store {
+            Some(ref store) => {
+                store.save(&StoredToken {
+                    access_token: response.access_token.to_owned(),
+                    refresh_token,
+                    expires_at: (now_millis().unwrap_or(0) / 1000) as u64 + response.expires_in,
+                    scope: response.scope.to_owned(),
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the access token so that it can be revoked, also removing it from the store.
+    fn take(&mut self, refresh_token: Option<String>) -> Option<String> {
+        let access_token = self.access_token.take();
+        if let Some(ref store) = self.store {
+            // The token is about to be revoked, so a failure to save only means that the next
+            // run tries it once before logging in again.
+            let _ = store.save(&StoredToken {
+                refresh_token,
+                ..StoredToken::default()
+            });
+        }
+        access_token
+    }
+
+    /// The `scope` parameter of a token request, if specific scopes were requested.
+    fn scope_param(&self) -> String {
+        match self.requested {
+            Some(ref scopes) => format!("&scope={}", url_escape(&scopes.to_string())),
+            None => String::new(),
+        }
+    }
+
+    /// When the access token expires, as reported by Reddit (not including the margin that
+    /// `is_valid()` leaves for refreshing it).
+    fn expires_at(&self) -> Option<SystemTime> {
+        self.access_token.as_ref()?;
+        let millis = self.expire_time? + EXPIRY_MARGIN;
+        Some(UNIX_EPOCH + Duration::from_millis(millis as u64))
+    }
+
+    /// The granted scopes, or `default` if Reddit has not said (e.g. before logging in).
+    fn scopes(&self, default: &str) -> Vec<String> {
+        let granted = self.granted.as_deref().unwrap_or(default);
+        granted.split(' ').filter(|scope| !scope.is_empty()).map(|scope| scope.to_owned()).collect()
+    }
+
+    fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+        match (&self.access_token, self.expire_time) {
+            (Some(token), Some(expire_time)) if !is_expired(expire_time) => {
+                let mut map = HashMap::new();
+                map.insert(AUTHORIZATION, format!("Bearer {}", token));
+                Ok(map)
+            }
+            _ => Err(APIError::ExpiredToken),
+        }
+    }
+}
 
 /// Trait for any method of authenticating with the Reddit API.
 pub trait Authenticator {
     /// Logs in and fetches relevant tokens.
-    fn login(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError>;
-    /// Called if a token expiration error occurs.
-    fn refresh_token(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError> {
-        self.login(client, user_agent)
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError>;
+    /// Called when `headers()` returns `APIError::ExpiredToken` or a request is rejected as
+    /// unauthorized. By default, this logs in again.
+    fn refresh_token(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        self.login(backend, user_agent)
     }
     /// Logs out and invalidates tokens if applicable.
-    fn logout(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError>;
-    /// A list of OAuth scopes that this `Authenticator` can access. Currently, the result of this
-    /// is not used, but the correct scopes should be returned. If all scopes can be accessed,
-    /// this is signified by a vec!["*"]. If it is read-only, the result is vec!["read"].
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError>;
+    /// Revokes the refresh token (if any), which also invalidates every access token issued from
+    /// it, so that the app can no longer act for the user until they approve it again. By
+    /// default, this does nothing, as only `CodeFlowAuthenticator` receives refresh tokens.
+    #[allow(unused_variables)]
+    fn revoke_refresh_token(&mut self,
+                            backend: &dyn HttpBackend,
+                            user_agent: &str)
+                            -> Result<(), APIError> {
+        Ok(())
+    }
+    /// Revokes the current access token, e.g. to rotate credentials. Unlike `logout()`, the
+    /// authenticator stays usable: the next request gets a new token (with the refresh token, if
+    /// there is one). By default, this calls `logout()`, which revokes the access token for every
+    /// OAuth authenticator.
+    fn revoke_token(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        self.logout(backend, user_agent)
+    }
+    /// When the current access token expires, or `None` if there is no token (e.g. anonymous
+    /// authenticators, or after logging out).
+    fn token_expires_at(&self) -> Option<SystemTime> {
+        None
+    }
+    /// A list of OAuth scopes that this `Authenticator` can access, as granted by Reddit when
+    /// logging in. If all scopes can be accessed, this is signified by a vec!["*"]. If it is
+    /// read-only, the result is vec!["read"]. See `RedditClient.has_scope()`.
     fn scopes(&self) -> Vec<String>;
-    /// Returns the headers needed to authenticate. Must be done **after** `login()`.
+    /// Returns the headers needed to authenticate. Must be done **after** `login()`. Returns
+    /// `APIError::ExpiredToken` if the token has expired (or is about to), so that the client
+    /// refreshes it before sending the request.
     fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError>;
     /// `true` if this authentication method requires the OAuth API.
     fn oauth(&self) -> bool;
+    /// The name of the account this `Authenticator` logs in as, if it logs in as a user.
+    fn username(&self) -> Option<String> {
+        None
+    }
+    /// The refresh token received when logging in, if any. Store this to log in as the same user
+    /// later without asking them again (see `CodeFlowAuthenticator::from_refresh_token()`).
+    fn saved_refresh_token(&self) -> Option<String> {
+        None
+    }
+    /// Sets where tokens are saved, so that they can be reused after a restart (see the `store`
+    /// module). Authenticators that do not use tokens ignore this.
+    #[allow(unused_variables)]
+    fn set_token_store(&mut self, store: Box<dyn TokenStore + Send>) {}
 }
 
 /// An anonymous login authenticator.
@@ -95,13 +294,13 @@ pub struct AnonymousAuthenticator;
 
 impl Authenticator for AnonymousAuthenticator {
     #[allow(unused_variables)]
-    fn login(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError> {
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
         // Don't log in, because we're anonymous!
         Ok(())
     }
 
     #[allow(unused_variables)]
-    fn logout(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError> {
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
         // Can't log out if we're not logged in.
         Ok(())
     }
@@ -135,108 +334,560 @@ impl AnonymousAuthenticator {
 /// Authenticates using a username and password with OAuth. See the module-level documentation for
 /// usage.
 pub struct PasswordAuthenticator {
-    access_token: Option<String>,
     client_id: String,
     client_secret: String,
     username: String,
     password: String,
-    expire_time: Option<u128>,
+    token: TokenState,
 }
 
 impl Authenticator for PasswordAuthenticator {
-    fn login(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError> {
-        let url = "https://www.reddit.com/api/v1/access_token";
-        let body = format!("grant_type=password&username={}&password={}",
-                           &self.username,
-                           &self.password);
-        let request = Request::builder().method(Method::POST).uri(url)
-            .header(AUTHORIZATION, format!("Basic {}", base64::encode(format!("{}:{}", self.client_id.to_owned(), self.client_secret.to_owned()))))
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        if self.token.restore().is_some() && self.token.is_valid() {
+            return Ok(());
+        }
+        let body = format!("grant_type=password&username={}&password={}{}",
+                           url_escape(&self.username),
+                           url_escape(&self.password),
+                           self.token.scope_param());
+        let request = HttpRequest::new(Method::POST, endpoints::ACCESS_TOKEN)
+            .header(AUTHORIZATION, &self.basic_auth())
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
             .header(USER_AGENT, user_agent)
-            .body(Body::from(body));
-        if request.is_err() {
-            return Err(APIError::ExhaustedListing);
+            .body(&body);
+        let response = backend.execute(request)?;
+        if response.status != http::StatusCode::OK {
+            return Err(APIError::HTTPError(response.status));
         }
-        let request = request.unwrap();
+        let token_response: TokenResponseData = serde_json::from_str(&response.text()?)?;
+        self.token.update(&token_response, None)
+    }
+
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        let access_token = match self.token.take(None) {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        revoke_token(backend, user_agent, &self.basic_auth(), &access_token, "access_token")
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        self.token.scopes("*")
+    }
+
+    fn token_expires_at(&self) -> Option<SystemTime> {
+        self.token.expires_at()
+    }
+
+    fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+        self.token.headers()
+    }
+
+    fn oauth(&self) -> bool {
+        true
+    }
+
+    fn set_token_store(&mut self, store: Box<dyn TokenStore + Send>) {
+        self.token.store = Some(store);
+    }
+
+    fn username(&self) -> Option<String> {
+        Some(self.username.to_owned())
+    }
+}
+
+impl PasswordAuthenticator {
+    /// Creates a new `PasswordAuthenticator`. If you do not have a client ID and secret (or do
+    /// not know what these are), you need to fetch one using the instructions in the module
+    /// documentation.
+    pub fn new(client_id: &str, client_secret: &str, username: &str, password: &str) -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        PasswordAuthenticator::create(client_id, client_secret, username, password, None)
+    }
 
-        let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
-        let mut result = runtime.block_on(client.request(request));
-        if result.is_err() {
-            return Err(APIError::ExhaustedListing);
+    /// Creates a new `PasswordAuthenticator` that only asks for the specified scopes, instead
+    /// of every scope. See the `scope` module.
+    pub fn with_scopes(client_id: &str,
+                       client_secret: &str,
+                       username: &str,
+                       password: &str,
+                       scopes: ScopeSet)
+                       -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        PasswordAuthenticator::create(client_id, client_secret, username, password, Some(scopes))
+    }
+
+    fn create(client_id: &str,
+              client_secret: &str,
+              username: &str,
+              password: &str,
+              scopes: Option<ScopeSet>)
+              -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(PasswordAuthenticator {
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            username: username.to_owned(),
+            password: password.to_owned(),
+            token: TokenState {
+                requested: scopes,
+                ..TokenState::default()
+            },
+        })))
+    }
+
+    fn basic_auth(&self) -> String {
+        format!("Basic {}", base64::encode(format!("{}:{}", self.client_id, self.client_secret)))
+    }
+}
+
+/// Authenticates as an app, without logging in as any user (the 'application only' OAuth flow).
+/// This has the rate limits of the OAuth API, but can only read public data. See the
+/// module-level documentation for getting a client ID and secret.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::ClientCredentialsAuthenticator;
+/// let auth = ClientCredentialsAuthenticator::new("CLIENT_ID", "CLIENT_SECRET");
+/// let client = RedditClient::new("linux:my_script:v0.1 (by /u/me)", auth);
+/// ```
+pub struct ClientCredentialsAuthenticator {
+    client_id: String,
+    client_secret: String,
+    token: TokenState,
+}
+
+impl Authenticator for ClientCredentialsAuthenticator {
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        if self.token.restore().is_some() && self.token.is_valid() {
+            return Ok(());
         }
-        let result = result.unwrap();
-        if result.status() != hyper::StatusCode::OK {
-            Err(APIError::HTTPError(result.status()))
-        } else {
-            let value = runtime.block_on(hyper::body::to_bytes(result.into_body()));
-
-            let value = String::from_utf8(value.unwrap().to_vec());
-            let string = value.unwrap();
-            let result1 = serde_json::from_str(&string);
-            if result1.is_ok() {
-                let token_response: TokenResponseData = result1.unwrap();
-                self.access_token = Some(token_response.access_token);
-                let x = (token_response.expires_in * 1000);
-                let x1 = (x as u128) + SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
-                self.expire_time = Some(x1);
-                return Ok(());
-            }
-            return Err(APIError::ExhaustedListing);
+        let request = HttpRequest::new(Method::POST, endpoints::ACCESS_TOKEN)
+            .header(AUTHORIZATION, &self.basic_auth())
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(USER_AGENT, user_agent)
+            .body(&format!("grant_type=client_credentials{}", self.token.scope_param()));
+        let response = backend.execute(request)?;
+        if response.status != http::StatusCode::OK {
+            return Err(APIError::HTTPError(response.status));
         }
+        let token_response: TokenResponseData = serde_json::from_str(&response.text()?)?;
+        self.token.update(&token_response, None)
     }
 
-    fn logout(&mut self, client: &Client<HttpsConnector<HttpConnector>>, user_agent: &str) -> Result<(), APIError> {
-        let url = "https://www.reddit.com/api/v1/revoke_token";
-        let body = format!("token={}", &self.access_token.to_owned().unwrap());
-        let request = Request::builder().method(Method::POST).uri(url)
-            .header(AUTHORIZATION, format!("Basic {}", base64::encode(format!("{}:{}", self.client_id.to_owned(), self.client_secret.to_owned()))))
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        let access_token = match self.token.take(None) {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        revoke_token(backend, user_agent, &self.basic_auth(), &access_token, "access_token")
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        self.token.scopes("read")
+    }
+
+    fn token_expires_at(&self) -> Option<SystemTime> {
+        self.token.expires_at()
+    }
+
+    fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+        self.token.headers()
+    }
+
+    fn oauth(&self) -> bool {
+        true
+    }
+
+    fn set_token_store(&mut self, store: Box<dyn TokenStore + Send>) {
+        self.token.store = Some(store);
+    }
+}
+
+impl ClientCredentialsAuthenticator {
+    /// Creates a new `ClientCredentialsAuthenticator`. See the module-level documentation for
+    /// getting a client ID and secret.
+    pub fn new(client_id: &str, client_secret: &str) -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        ClientCredentialsAuthenticator::create(client_id, client_secret, None)
+    }
+
+    /// Creates a new `ClientCredentialsAuthenticator` that only asks for the specified scopes.
+    /// See the `scope` module.
+    pub fn with_scopes(client_id: &str,
+                       client_secret: &str,
+                       scopes: ScopeSet)
+                       -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        ClientCredentialsAuthenticator::create(client_id, client_secret, Some(scopes))
+    }
+
+    fn create(client_id: &str,
+              client_secret: &str,
+              scopes: Option<ScopeSet>)
+              -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(ClientCredentialsAuthenticator {
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            token: TokenState {
+                requested: scopes,
+                ..TokenState::default()
+            },
+        })))
+    }
+
+    fn basic_auth(&self) -> String {
+        format!("Basic {}", base64::encode(format!("{}:{}", self.client_id, self.client_secret)))
+    }
+}
+
+/// The device ID sent by `InstalledClientAuthenticator` when none is provided, which asks Reddit
+/// not to track the device.
+pub const DO_NOT_TRACK_DEVICE: &str = "DO_NOT_TRACK_THIS_DEVICE";
+
+/// Authenticates an installed app (which has no client secret) without logging in as any user,
+/// using Reddit's `installed_client` grant. Each installation should send its own device ID, a
+/// unique string of 20-30 characters, so Reddit can apply rate limits per device.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::InstalledClientAuthenticator;
+/// let auth = InstalledClientAuthenticator::new("CLIENT_ID", Some("b3a7a0b2-92c7-4d2e-b1f0"));
+/// let client = RedditClient::new("windows:my_app:v0.1 (by /u/me)", auth);
+/// ```
+pub struct InstalledClientAuthenticator {
+    client_id: String,
+    device_id: String,
+    token: TokenState,
+}
+
+impl Authenticator for InstalledClientAuthenticator {
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        if self.device_id.len() < 20 || self.device_id.len() > 30 {
+            return Err(APIError::ValidationError(String::from("The device ID must be 20-30 \
+                                                               characters long")));
+        }
+        if self.token.restore().is_some() && self.token.is_valid() {
+            return Ok(());
+        }
+        let body = format!("grant_type={}&device_id={}{}",
+                           url_escape("https://oauth.reddit.com/grants/installed_client"),
+                           url_escape(&self.device_id),
+                           self.token.scope_param());
+        let request = HttpRequest::new(Method::POST, endpoints::ACCESS_TOKEN)
+            .header(AUTHORIZATION, &self.basic_auth())
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
             .header(USER_AGENT, user_agent)
-            .body(Body::from(body));
+            .body(&body);
+        let response = backend.execute(request)?;
+        if response.status != http::StatusCode::OK {
+            return Err(APIError::HTTPError(response.status));
+        }
+        let token_response: TokenResponseData = serde_json::from_str(&response.text()?)?;
+        self.token.update(&token_response, None)
+    }
+
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        let access_token = match self.token.take(None) {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        revoke_token(backend, user_agent, &self.basic_auth(), &access_token, "access_token")
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        self.token.scopes("read")
+    }
+
+    fn token_expires_at(&self) -> Option<SystemTime> {
+        self.token.expires_at()
+    }
 
+    fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+        self.token.headers()
+    }
+
+    fn oauth(&self) -> bool {
+        true
+    }
+
+    fn set_token_store(&mut self, store: Box<dyn TokenStore + Send>) {
+        self.token.store = Some(store);
+    }
+}
+
+impl InstalledClientAuthenticator {
+    /// Creates a new `InstalledClientAuthenticator` for the client ID of an installed app. If
+    /// `device_id` is `None`, `DO_NOT_TRACK_DEVICE` is sent instead.
+    pub fn new(client_id: &str, device_id: Option<&str>) -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        InstalledClientAuthenticator::create(client_id, device_id, None)
+    }
+
+    /// Creates a new `InstalledClientAuthenticator` that only asks for the specified scopes.
+    /// See the `scope` module.
+    pub fn with_scopes(client_id: &str,
+                       device_id: Option<&str>,
+                       scopes: ScopeSet)
+                       -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        InstalledClientAuthenticator::create(client_id, device_id, Some(scopes))
+    }
+
+    fn create(client_id: &str,
+              device_id: Option<&str>,
+              scopes: Option<ScopeSet>)
+              -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(InstalledClientAuthenticator {
+            client_id: client_id.to_owned(),
+            device_id: device_id.unwrap_or(DO_NOT_TRACK_DEVICE).to_owned(),
+            token: TokenState {
+                requested: scopes,
+                ..TokenState::default()
+            },
+        })))
+    }
 
-        let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
+    fn basic_auth(&self) -> String {
+        // Installed apps have no secret, so the password is empty.
+        format!("Basic {}", base64::encode(format!("{}:", self.client_id)))
+    }
+}
 
-        let res = runtime.block_on(client.request(request.unwrap())).unwrap();
+/// Authenticates as a user who has approved your app using the OAuth authorization code flow.
+/// See the module-level documentation for registering the app.
+///
+/// 1. Send the user to `CodeFlowAuthenticator::authorize_url()`, with a random `state`.
+/// 2. Reddit redirects them back to your redirect URL. Pass that URL to
+///    `CodeFlowAuthenticator::parse_redirect()` to check the state and get the code.
+/// 3. Create the authenticator with the code. The code is exchanged for tokens when the
+///    `RedditClient` is created, and can only be used once.
+/// 4. If you asked for a permanent token, store `Authenticator.saved_refresh_token()` and use
+///    `CodeFlowAuthenticator::from_refresh_token()` next time.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::CodeFlowAuthenticator;
+/// let redirect_uri = "https://example.com/callback";
+/// let url = CodeFlowAuthenticator::authorize_url("CLIENT_ID", redirect_uri,
+///                                                &["identity", "read"], "RANDOM_STATE", true);
+/// // ... redirect the user to `url`, and receive the callback ...
+/// let callback = "https://example.com/callback?state=RANDOM_STATE&code=CODE";
+/// let code = CodeFlowAuthenticator::parse_redirect(callback, "RANDOM_STATE")
+///     .expect("The user did not approve the app");
+/// let auth = CodeFlowAuthenticator::new("CLIENT_ID", "CLIENT_SECRET", redirect_uri, &code);
+/// let client = RedditClient::new("linux:my_app:v0.1 (by /u/me)", auth);
+/// let refresh_token = client.get_authenticator().saved_refresh_token();
+/// ```
+pub struct CodeFlowAuthenticator {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    code: Option<String>,
+    refresh_token: Option<String>,
+    token: TokenState,
+}
 
-        if !res.status().is_success() {
-            Err(APIError::HTTPError(res.status()))
+impl Authenticator for CodeFlowAuthenticator {
+    fn login(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        if let Some(stored) = self.token.restore() {
+            if self.refresh_token.is_none() {
+                self.refresh_token = stored.refresh_token;
+            }
+            if self.code.is_none() && self.token.is_valid() {
+                return Ok(());
+            }
+        }
+        let body = if let Some(code) = self.code.take() {
+            format!("grant_type=authorization_code&code={}&redirect_uri={}",
+                    url_escape(&code),
+                    url_escape(&self.redirect_uri))
+        } else if let Some(ref refresh_token) = self.refresh_token {
+            format!("grant_type=refresh_token&refresh_token={}", url_escape(refresh_token))
         } else {
-            Ok(())
+            return Err(APIError::ValidationError(String::from("The authorization code has \
+                                                               already been used, and there \
+                                                               is no refresh token")));
+        };
+        let request = HttpRequest::new(Method::POST, endpoints::ACCESS_TOKEN)
+            .header(AUTHORIZATION, &self.basic_auth())
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(USER_AGENT, user_agent)
+            .body(&body);
+        let response = backend.execute(request)?;
+        if response.status != http::StatusCode::OK {
+            return Err(APIError::HTTPError(response.status));
         }
+        let token_response: TokenResponseData = serde_json::from_str(&response.text()?)?;
+        if token_response.refresh_token.is_some() {
+            self.refresh_token = token_response.refresh_token.to_owned();
+        }
+        self.token.update(&token_response, self.refresh_token.to_owned())
+    }
+
+    fn logout(&mut self, backend: &dyn HttpBackend, user_agent: &str) -> Result<(), APIError> {
+        // Only the access token is revoked, so a stored refresh token stays valid.
+        let access_token = match self.token.take(self.refresh_token.to_owned()) {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        revoke_token(backend, user_agent, &self.basic_auth(), &access_token, "access_token")
     }
 
     fn scopes(&self) -> Vec<String> {
-        vec![String::from("*")]
+        self.token.scopes("")
+    }
+
+    fn token_expires_at(&self) -> Option<SystemTime> {
+        self.token.expires_at()
     }
 
     fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
-        let i = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
-        if i >= self.expire_time.unwrap() {
-            return Err(APIError::ExpiredToken);
-        }
-        let mut map = HashMap::new();
-        map.insert(AUTHORIZATION, format!("Bearer {}", self.access_token.to_owned().unwrap()));
-        Ok(map)
+        self.token.headers()
     }
 
     fn oauth(&self) -> bool {
         true
     }
+
+    fn set_token_store(&mut self, store: Box<dyn TokenStore + Send>) {
+        self.token.store = Some(store);
+    }
+
+    fn revoke_refresh_token(&mut self,
+                            backend: &dyn HttpBackend,
+                            user_agent: &str)
+                            -> Result<(), APIError> {
+        let refresh_token = match self.refresh_token.take() {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        self.token.take(None);
+        revoke_token(backend, user_agent, &self.basic_auth(), &refresh_token, "refresh_token")
+    }
+
+    fn saved_refresh_token(&self) -> Option<String> {
+        self.refresh_token.to_owned()
+    }
 }
 
-impl PasswordAuthenticator {
-    /// Creates a new `PasswordAuthenticator`. If you do not have a client ID and secret (or do
-    /// not know what these are), you need to fetch one using the instructions in the module
-    /// documentation.
-    pub fn new(client_id: &str, client_secret: &str, username: &str, password: &str) -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
-        Arc::new(Mutex::new(Box::new(PasswordAuthenticator {
+impl CodeFlowAuthenticator {
+    /// Creates a `CodeFlowAuthenticator` that exchanges an authorization code (see
+    /// `CodeFlowAuthenticator::parse_redirect()`) for tokens when logging in. `redirect_uri`
+    /// must be the same as the one used in the authorization URL. Installed apps, which have no
+    /// client secret, should pass an empty string.
+    pub fn new(client_id: &str,
+               client_secret: &str,
+               redirect_uri: &str,
+               code: &str)
+               -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        CodeFlowAuthenticator::create(client_id, client_secret, redirect_uri, Some(code), None)
+    }
+
+    /// Creates a `CodeFlowAuthenticator` that logs in with a refresh token saved from an earlier
+    /// login (see `Authenticator.saved_refresh_token()`).
+    pub fn from_refresh_token(client_id: &str,
+                              client_secret: &str,
+                              refresh_token: &str)
+                              -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        CodeFlowAuthenticator::create(client_id, client_secret, "", None, Some(refresh_token))
+    }
+
+    fn create(client_id: &str,
+              client_secret: &str,
+              redirect_uri: &str,
+              code: Option<&str>,
+              refresh_token: Option<&str>)
+              -> Arc<Mutex<Box<dyn Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(CodeFlowAuthenticator {
             client_id: client_id.to_owned(),
             client_secret: client_secret.to_owned(),
-            username: username.to_owned(),
-            password: password.to_owned(),
-            expire_time: None,
-            access_token: None,
+            redirect_uri: redirect_uri.to_owned(),
+            code: code.map(|code| code.to_owned()),
+            refresh_token: refresh_token.map(|token| token.to_owned()),
+            token: TokenState::default(),
         })))
     }
+
+    /// The URL to send users to in order to approve your app. `state` should be a random string
+    /// that you check in the redirect, to protect against cross-site request forgery. If
+    /// `permanent` is `true`, a refresh token is issued so that you can keep acting on behalf of
+    /// the user; otherwise access expires after an hour. The scope names are those of
+    /// `scope::Scope` (e.g. `Scope::ModPosts.to_string()`).
+    /// # Examples
+    /// ```
+    /// use new_rawr::auth::CodeFlowAuthenticator;
+    /// let url = CodeFlowAuthenticator::authorize_url("abc", "https://example.com/cb",
+    ///                                                &["identity", "read"], "xyz", true);
+    /// assert_eq!(url, "https://www.reddit.com/api/v1/authorize?client_id=abc\
+    ///                  &response_type=code&state=xyz\
+    ///                  &redirect_uri=https%3A%2F%2Fexample.com%2Fcb\
+    ///                  &duration=permanent&scope=identity+read");
+    /// ```
+    pub fn authorize_url(client_id: &str,
+                         redirect_uri: &str,
+                         scopes: &[&str],
+                         state: &str,
+                         permanent: bool)
+                         -> String {
+        format!("{}?client_id={}&response_type=code&state={}&redirect_uri={}&duration={}&scope={}",
+                endpoints::AUTHORIZE,
+                url_escape(client_id),
+                url_escape(state),
+                url_escape(redirect_uri),
+                if permanent { "permanent" } else { "temporary" },
+                url_escape(&scopes.join(" ")))
+    }
+
+    /// Extracts the authorization code from the URL (or query string) that Reddit redirected the
+    /// user to. Returns a `ValidationError` if the state does not match `expected_state`, or if
+    /// the user declined access.
+    /// # Examples
+    /// ```
+    /// use new_rawr::auth::CodeFlowAuthenticator;
+    /// let url = "https://example.com/cb?state=xyz&code=Ab-C_d";
+    /// assert_eq!(CodeFlowAuthenticator::parse_redirect(url, "xyz").unwrap(), "Ab-C_d");
+    /// assert!(CodeFlowAuthenticator::parse_redirect(url, "other").is_err());
+    /// let declined = "https://example.com/cb?state=xyz&error=access_denied";
+    /// assert!(CodeFlowAuthenticator::parse_redirect(declined, "xyz").is_err());
+    /// ```
+    pub fn parse_redirect(url: &str, expected_state: &str) -> Result<String, APIError> {
+        let query = url.splitn(2, '?').last().unwrap_or("");
+        let query = query.split('#').next().unwrap_or("");
+        let mut params = HashMap::new();
+        for pair in query.split('&') {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("");
+            params.insert(url_unescape(key), url_unescape(value));
+        }
+        if params.get("state").map(|state| state.as_str()) != Some(expected_state) {
+            return Err(APIError::ValidationError(String::from("The state in the redirect does \
+                                                               not match")));
+        }
+        if let Some(error) = params.get("error") {
+            return Err(APIError::ValidationError(format!("Authorization failed: {}", error)));
+        }
+        params.remove("code")
+            .ok_or_else(|| APIError::ValidationError(String::from("No code in the redirect")))
+    }
+
+    fn basic_auth(&self) -> String {
+        format!("Basic {}", base64::encode(format!("{}:{}", self.client_id, self.client_secret)))
+    }
+}
+
+/// Decodes a URL query component (`+` and `%XX` escapes).
+fn url_unescape(item: &str) -> String {
+    let bytes = item.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'+', _) => res.push(b' '),
+            (b'%', Some(byte)) => {
+                res.push(byte);
+                i += 2;
+            }
+            (byte, _) => res.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&res).into_owned()
 }
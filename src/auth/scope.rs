@@ -0,0 +1,230 @@
+//! OAuth scopes, which limit what an app can do with a token. Request scopes when creating an
+//! authenticator (e.g. `PasswordAuthenticator::with_scopes()`) and check the scopes that Reddit
+//! actually granted with `RedditClient.has_scope()`.
+//! # Examples
+//! ```rust,no_run
+//! use new_rawr::auth::PasswordAuthenticator;
+//! use new_rawr::auth::scope::{Scope, ScopeSet};
+//! use new_rawr::client::RedditClient;
+//! let scopes = ScopeSet::from_scopes(&[Scope::Read, Scope::ModPosts]);
+//! let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", scopes);
+//! let client = RedditClient::new("new_rawr", auth);
+//! if !client.has_scope(Scope::ModPosts) {
+//!     println!("Cannot remove posts with this token");
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// An OAuth scope. See [Reddit's API documentation](https://www.reddit.com/dev/api/oauth) for
+/// the endpoints that each scope allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    /// Every scope (`*`).
+    All,
+    /// Updating preferences and account settings.
+    Account,
+    /// Spending and giving Reddit gold and coins.
+    Creddits,
+    /// Editing and deleting your own posts and comments.
+    Edit,
+    /// Choosing your own user and post flair.
+    Flair,
+    /// Reading your voting, saving and posting history.
+    History,
+    /// Reading your username and account details.
+    Identity,
+    /// Managing live threads.
+    LiveManage,
+    /// Changing subreddit settings, styles and rules.
+    ModConfig,
+    /// Inviting and removing approved users and bans.
+    ModContributors,
+    /// Managing user and post flair in moderated subreddits.
+    ModFlair,
+    /// Reading the moderation log.
+    ModLog,
+    /// Reading and sending modmail.
+    ModMail,
+    /// Inviting and removing other moderators.
+    ModOthers,
+    /// Approving, removing, locking and distinguishing posts and comments.
+    ModPosts,
+    /// Accepting invitations and leaving as a moderator.
+    ModSelf,
+    /// Reading subreddit traffic statistics.
+    ModTraffic,
+    /// Editing moderated wiki pages and their settings.
+    ModWiki,
+    /// Reading the subreddits you subscribe to, moderate or contribute to.
+    MySubreddits,
+    /// Reading and sending private messages.
+    PrivateMessages,
+    /// Reading posts, comments and subreddits.
+    Read,
+    /// Reporting and hiding posts and comments.
+    Report,
+    /// Saving and unsaving posts and comments.
+    Save,
+    /// Editing structured styles (the redesign).
+    StructuredStyles,
+    /// Submitting posts and comments.
+    Submit,
+    /// Subscribing to subreddits and following users.
+    Subscribe,
+    /// Voting on posts and comments.
+    Vote,
+    /// Editing wiki pages.
+    WikiEdit,
+    /// Reading wiki pages.
+    WikiRead,
+}
+
+impl Scope {
+    /// Every scope except `All`.
+    pub const SPECIFIC: &'static [Scope] = &[Scope::Account,
+                                             Scope::Creddits,
+                                             Scope::Edit,
+                                             Scope::Flair,
+                                             Scope::History,
+                                             Scope::Identity,
+                                             Scope::LiveManage,
+                                             Scope::ModConfig,
+                                             Scope::ModContributors,
+                                             Scope::ModFlair,
+                                             Scope::ModLog,
+                                             Scope::ModMail,
+                                             Scope::ModOthers,
+                                             Scope::ModPosts,
+                                             Scope::ModSelf,
+                                             Scope::ModTraffic,
+                                             Scope::ModWiki,
+                                             Scope::MySubreddits,
+                                             Scope::PrivateMessages,
+                                             Scope::Read,
+                                             Scope::Report,
+                                             Scope::Save,
+                                             Scope::StructuredStyles,
+                                             Scope::Submit,
+                                             Scope::Subscribe,
+                                             Scope::Vote,
+                                             Scope::WikiEdit,
+                                             Scope::WikiRead];
+
+    /// Parses a scope name used by the API, such as `modposts`.
+    /// # Examples
+    /// ```
+    /// use new_rawr::auth::scope::Scope;
+    /// assert_eq!(Scope::from_name("modposts"), Some(Scope::ModPosts));
+    /// assert_eq!(Scope::from_name("*"), Some(Scope::All));
+    /// assert_eq!(Scope::from_name("unknown"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Scope> {
+        if name == "*" {
+            return Some(Scope::All);
+        }
+        Scope::SPECIFIC.iter().find(|scope| scope.to_string() == name).cloned()
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            Scope::All => "*",
+            Scope::Account => "account",
+            Scope::Creddits => "creddits",
+            Scope::Edit => "edit",
+            Scope::Flair => "flair",
+            Scope::History => "history",
+            Scope::Identity => "identity",
+            Scope::LiveManage => "livemanage",
+            Scope::ModConfig => "modconfig",
+            Scope::ModContributors => "modcontributors",
+            Scope::ModFlair => "modflair",
+            Scope::ModLog => "modlog",
+            Scope::ModMail => "modmail",
+            Scope::ModOthers => "modothers",
+            Scope::ModPosts => "modposts",
+            Scope::ModSelf => "modself",
+            Scope::ModTraffic => "modtraffic",
+            Scope::ModWiki => "modwiki",
+            Scope::MySubreddits => "mysubreddits",
+            Scope::PrivateMessages => "privatemessages",
+            Scope::Read => "read",
+            Scope::Report => "report",
+            Scope::Save => "save",
+            Scope::StructuredStyles => "structuredstyles",
+            Scope::Submit => "submit",
+            Scope::Subscribe => "subscribe",
+            Scope::Vote => "vote",
+            Scope::WikiEdit => "wikiedit",
+            Scope::WikiRead => "wikiread",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A set of OAuth scopes. A set containing `Scope::All` contains every scope.
+/// # Examples
+/// ```
+/// use new_rawr::auth::scope::{Scope, ScopeSet};
+/// let granted = ScopeSet::parse("identity read modposts");
+/// assert!(granted.contains(Scope::ModPosts));
+/// assert!(!granted.contains(Scope::Submit));
+/// assert!(ScopeSet::parse("*").contains(Scope::Submit));
+/// assert_eq!(granted.to_string(), "identity modposts read");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeSet {
+    scopes: BTreeSet<Scope>,
+}
+
+impl ScopeSet {
+    /// Creates an empty set.
+    pub fn new() -> ScopeSet {
+        ScopeSet::default()
+    }
+
+    /// Creates a set containing the specified scopes.
+    pub fn from_scopes(scopes: &[Scope]) -> ScopeSet {
+        ScopeSet { scopes: scopes.iter().cloned().collect() }
+    }
+
+    /// Parses a list of scope names separated by spaces or commas, as returned by Reddit.
+    /// Unknown scopes are ignored.
+    pub fn parse(scopes: &str) -> ScopeSet {
+        ScopeSet {
+            scopes: scopes.split([' ', ','])
+                .filter_map(Scope::from_name)
+                .collect(),
+        }
+    }
+
+    /// Adds a scope to the set.
+    pub fn insert(&mut self, scope: Scope) {
+        self.scopes.insert(scope);
+    }
+
+    /// `true` if the set contains the scope, or contains `Scope::All`.
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&Scope::All)
+    }
+
+    /// `true` if the set contains no scopes.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// The scopes in the set, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = &Scope> {
+        self.scopes.iter()
+    }
+}
+
+impl Display for ScopeSet {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let names = self.scopes.iter().map(|scope| scope.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", names.join(" "))
+    }
+}
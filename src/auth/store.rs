@@ -0,0 +1,144 @@
+//! Persistence of OAuth tokens between runs, so that restarting a bot reuses its token instead
+//! of logging in again. Attach a store to an authenticator with
+//! `Authenticator.set_token_store()` before creating the `RedditClient`.
+//!
+//! Tokens are only loaded on the first login. If the stored access token has expired, the
+//! authenticator logs in as usual (using the stored refresh token, if it has one) and saves the
+//! new token.
+//!
+//! By default, a `RedditClient` revokes its access token when it is dropped, which also removes
+//! it from the store. To reuse access tokens across restarts, disable this with
+//! `RedditClient.set_auto_logout(false)`.
+//! # Examples
+//! ```rust,no_run
+//! use new_rawr::auth::PasswordAuthenticator;
+//! use new_rawr::auth::store::FileTokenStore;
+//! use new_rawr::client::RedditClient;
+//! let auth = PasswordAuthenticator::new("a", "b", "c", "d");
+//! auth.lock().unwrap().set_token_store(Box::new(FileTokenStore::new("tokens.json")));
+//! let mut client = RedditClient::new("new_rawr", auth);
+//! client.set_auto_logout(false);
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use crate::errors::APIError;
+
+/// A token saved by a `TokenStore`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StoredToken {
+    /// The access token, or an empty string if it has been revoked.
+    pub access_token: String,
+    /// The refresh token, for authenticators that receive one.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// When the access token expires, as a UNIX timestamp in seconds.
+    pub expires_at: u64,
+    /// The scopes that the token grants, separated by spaces.
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// Somewhere to save tokens between runs, such as a file or a secrets manager.
+pub trait TokenStore {
+    /// Loads the saved token, if there is one. Errors (e.g. a corrupt file) should be treated
+    /// as if there were no token, so that the authenticator logs in again.
+    fn load(&self) -> Option<StoredToken>;
+    /// Saves a token, replacing any saved token.
+    fn save(&self, token: &StoredToken) -> Result<(), APIError>;
+}
+
+/// A `TokenStore` that saves the token as JSON in a file. On Unix, the file is only readable by
+/// its owner.
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Creates a store that saves to the specified file, which is created when a token is first
+    /// saved.
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileTokenStore {
+        FileTokenStore { path: path.into() }
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> Option<StoredToken> {
+        let text = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    fn save(&self, token: &StoredToken) -> Result<(), APIError> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        // The mode only applies to new files, so tighten an existing file's permissions before
+        // the token is written into it.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.set_len(0)?;
+        file.write_all(serde_json::to_string(token)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{FileTokenStore, StoredToken, TokenStore};
+
+    fn temp_file(test: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("new_rawr_tokens_{}_{}.json", test, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn token(access_token: &str) -> StoredToken {
+        StoredToken {
+            access_token: access_token.to_owned(),
+            expires_at: 100,
+            ..StoredToken::default()
+        }
+    }
+
+    #[test]
+    fn replaces_saved_token() {
+        let path = temp_file("replace");
+        let store = FileTokenStore::new(&path);
+        assert_eq!(store.load(), None);
+        store.save(&token("a much longer first token")).unwrap();
+        store.save(&token("second")).unwrap();
+        assert_eq!(store.load(), Some(token("second")));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restricts_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_file("permissions");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        FileTokenStore::new(&path).save(&token("secret")).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+//! A builder for `RedditClient`, for settings that must be chosen before the client logs in,
+//! such as the proxy to send requests through. Create one with `RedditClient::builder()`.
+//! # Examples
+//! ```rust,no_run
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::http::proxy::Proxy;
+//! let proxy = Proxy::new("http://proxy.example.com:3128").expect("Invalid proxy URL");
+//! let client = RedditClient::builder("new_rawr", AnonymousAuthenticator::new())
+//!     .proxy(proxy)
+//!     .build()
+//!     .expect("Could not log in");
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use crate::auth::Authenticator;
+use crate::client::RedditClient;
+use crate::endpoints::Endpoints;
+use crate::errors::APIError;
+use crate::http::HttpBackend;
+#[cfg(feature = "hyper-backend")]
+use crate::http::HyperBackend;
+#[cfg(feature = "hyper-backend")]
+use crate::http::proxy::Proxy;
+
+/// Configures and creates a `RedditClient`. See the module-level documentation for examples.
+pub struct RedditClientBuilder {
+    user_agent: String,
+    authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+    endpoints: Endpoints,
+    backend: Option<Box<dyn HttpBackend>>,
+    #[cfg(feature = "hyper-backend")]
+    proxy: Option<Proxy>,
+}
+
+impl RedditClientBuilder {
+    /// Internal method. Use `RedditClient::builder()` instead.
+    pub fn new(user_agent: &str,
+               authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>)
+               -> RedditClientBuilder {
+        RedditClientBuilder {
+            user_agent: user_agent.to_owned(),
+            authenticator,
+            endpoints: Endpoints::default(),
+            backend: None,
+            #[cfg(feature = "hyper-backend")]
+            proxy: None,
+        }
+    }
+
+    /// Sends all requests through the proxy, instead of the one set in the environment (see
+    /// `Proxy::from_env()`). Ignored if a backend is set with `backend()`, since the backend then
+    /// decides how to connect. Requires the `hyper-backend` feature.
+    #[cfg(feature = "hyper-backend")]
+    pub fn proxy(mut self, proxy: Proxy) -> RedditClientBuilder {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends requests to the specified hosts, and logs in at `endpoints.auth_host`. See
+    /// `RedditClient::with_endpoints()`.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> RedditClientBuilder {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Sends all requests through the `HttpBackend` instead of a `HyperBackend`. This is required
+    /// without the `hyper-backend` feature.
+    pub fn backend(mut self, backend: Box<dyn HttpBackend>) -> RedditClientBuilder {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Creates the client and logs in. Unlike `RedditClient::new()`, this returns an error
+    /// instead of panicking if logging in fails, and if the proxy set in the environment is
+    /// invalid (where `RedditClient::new()` would fail every request instead).
+    pub fn build(self) -> Result<RedditClient, APIError> {
+        let backend = match self.backend {
+            Some(backend) => backend,
+            None => default_backend(
+                #[cfg(feature = "hyper-backend")]
+                self.proxy,
+            )?,
+        };
+        RedditClient::connect(&self.user_agent, self.authenticator, backend, self.endpoints)
+    }
+}
+
+#[cfg(feature = "hyper-backend")]
+fn default_backend(proxy: Option<Proxy>) -> Result<Box<dyn HttpBackend>, APIError> {
+    let proxy = match proxy {
+        Some(proxy) => Some(proxy),
+        None => Proxy::from_env()?,
+    };
+    Ok(Box::new(proxy.map_or_else(HyperBackend::direct, HyperBackend::with_proxy)))
+}
+
+#[cfg(not(feature = "hyper-backend"))]
+fn default_backend() -> Result<Box<dyn HttpBackend>, APIError> {
+    Err(APIError::ValidationError(String::from("Set an HttpBackend with \
+                                                RedditClientBuilder.backend(), or enable the \
+                                                hyper-backend feature")))
+}
@@ -0,0 +1,305 @@
+//! The response caches of `RedditClient`.
+//!
+//! `ResponseCache` remembers the `ETag` and `Last-Modified` validators of responses, so that the
+//! client can send conditional `GET` requests and reuse the body it already has when Reddit
+//! answers HTTP 304 Not Modified. Polling loops such as `CommentStream` request the same URLs
+//! every few seconds, and the data has usually not changed. Every request still goes to Reddit,
+//! so cached bodies are never stale.
+//!
+//! `TtlCache` is an optional cache in front of `RedditClient.get_json()`, which answers repeated
+//! requests for the same URL from memory for a short time without sending them at all, so they
+//! do not count against the rate limit. It requires the `cache` feature.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+#[cfg(feature = "cache")]
+use std::time::{Duration, Instant};
+
+use http::{Method, StatusCode};
+use http::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+#[cfg(feature = "cache")]
+use crate::clock;
+use crate::http::{HttpRequest, HttpResponse};
+
+/// The number of URLs whose responses are kept unless changed with
+/// `RedditClient.set_cache_capacity()`.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// A response with the validators to send when requesting its URL again.
+#[derive(Clone)]
+pub struct CachedResponse {
+    response: HttpResponse,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    used: u64,
+}
+
+struct State {
+    capacity: usize,
+    entries: HashMap<String, CachedResponse>,
+    clock: u64,
+}
+
+/// The responses cached by one client. When it is full, the least recently used URL is
+/// forgotten.
+pub struct ResponseCache {
+    state: Mutex<State>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> ResponseCache {
+        ResponseCache {
+            state: Mutex::new(State {
+                capacity,
+                entries: HashMap::new(),
+                clock: 0,
+            }),
+        }
+    }
+
+    /// Sets the number of URLs to remember, forgetting all of them if it is 0.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = capacity;
+        while state.entries.len() > capacity {
+            evict(&mut state);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// Adds the validators of the cached response for the URL to a `GET` request, and returns
+    /// the cached response. Requests that already have conditional headers are left alone.
+    pub fn prepare(&self, request: &mut HttpRequest) -> Option<CachedResponse> {
+        let conditional = request.headers
+            .iter()
+            .any(|(name, _)| name == IF_NONE_MATCH || name == IF_MODIFIED_SINCE);
+        if request.method != Method::GET || conditional {
+            return None;
+        }
+        let cached = {
+            let mut state = self.state.lock().unwrap();
+            state.clock += 1;
+            let clock = state.clock;
+            let entry = state.entries.get_mut(&request.url)?;
+            entry.used = clock;
+            entry.clone()
+        };
+        if let Some(ref etag) = cached.etag {
+            request.headers.push((IF_NONE_MATCH, etag.to_owned()));
+        }
+        if let Some(ref last_modified) = cached.last_modified {
+            request.headers.push((IF_MODIFIED_SINCE, last_modified.to_owned()));
+        }
+        Some(cached)
+    }
+
+    /// Returns the cached response if Reddit answered 304 Not Modified, and otherwise caches
+    /// successful responses that have validators.
+    pub fn resolve(&self,
+                   request: &HttpRequest,
+                   cached: Option<CachedResponse>,
+                   response: HttpResponse)
+                   -> HttpResponse {
+        if request.method != Method::GET {
+            return response;
+        }
+        if response.status == StatusCode::NOT_MODIFIED {
+            return cached.map_or(response, |cached| cached.response);
+        }
+        let header = |name| {
+            response.headers.get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_owned())
+        };
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+        let mut state = self.state.lock().unwrap();
+        if !response.status.is_success() || (etag.is_none() && last_modified.is_none()) {
+            state.entries.remove(&request.url);
+            return response;
+        }
+        if state.capacity == 0 {
+            return response;
+        }
+        if !state.entries.contains_key(&request.url) && state.entries.len() >= state.capacity {
+            evict(&mut state);
+        }
+        state.clock += 1;
+        let entry = CachedResponse {
+            response: response.clone(),
+            etag,
+            last_modified,
+            used: state.clock,
+        };
+        state.entries.insert(request.url.to_owned(), entry);
+        response
+    }
+}
+
+/// Forgets the least recently used URL.
+fn evict(state: &mut State) {
+    let oldest = state.entries
+        .iter()
+        .min_by_key(|&(_, entry)| entry.used)
+        .map(|(url, _)| url.to_owned());
+    if let Some(url) = oldest {
+        state.entries.remove(&url);
+    }
+}
+
+/// A body fetched by `get_json()`, and when it was fetched.
+#[cfg(feature = "cache")]
+struct TtlEntry {
+    body: String,
+    fetched: Instant,
+}
+
+#[cfg(feature = "cache")]
+struct TtlState {
+    ttl: Duration,
+    capacity: usize,
+    entries: HashMap<String, TtlEntry>,
+}
+
+/// The bodies returned by `get_json()`, kept for a fixed time. Disabled (with a capacity of 0)
+/// unless enabled with `RedditClient.enable_get_cache()`. When it is full, the URL that was
+/// fetched the longest time ago is forgotten. Nothing is cached where the clock cannot be read
+/// (e.g. on wasm32), since the age of the bodies would be unknown.
+#[cfg(feature = "cache")]
+pub struct TtlCache {
+    state: Mutex<TtlState>,
+}
+
+#[cfg(feature = "cache")]
+impl TtlCache {
+    pub fn new() -> TtlCache {
+        TtlCache {
+            state: Mutex::new(TtlState {
+                ttl: Duration::from_secs(0),
+                capacity: 0,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Sets how long bodies are kept and how many URLs are remembered, forgetting every body if
+    /// `capacity` is 0.
+    pub fn configure(&self, ttl: Duration, capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.ttl = ttl;
+        state.capacity = capacity;
+        if capacity == 0 {
+            state.entries.clear();
+        }
+        while state.entries.len() > capacity {
+            evict_oldest(&mut state);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// The body for the URL, if it was fetched less than the TTL ago.
+    pub fn get(&self, url: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let ttl = state.ttl;
+        match state.entries.get(url) {
+            Some(entry) if is_fresh(entry, ttl) => Some(entry.body.to_owned()),
+            Some(_) => {
+                state.entries.remove(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, url: &str, body: &str) {
+        let fetched = match clock::now() {
+            Some(now) => now,
+            None => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+        let ttl = state.ttl;
+        state.entries.retain(|_, entry| is_fresh(entry, ttl));
+        if !state.entries.contains_key(url) && state.entries.len() >= state.capacity {
+            evict_oldest(&mut state);
+        }
+        let entry = TtlEntry {
+            body: body.to_owned(),
+            fetched,
+        };
+        state.entries.insert(url.to_owned(), entry);
+    }
+}
+
+#[cfg(feature = "cache")]
+fn is_fresh(entry: &TtlEntry, ttl: Duration) -> bool {
+    matches!(clock::elapsed(entry.fetched), Some(age) if age < ttl)
+}
+
+/// Forgets the URL that was fetched the longest time ago.
+#[cfg(feature = "cache")]
+fn evict_oldest(state: &mut TtlState) {
+    let oldest = state.entries
+        .iter()
+        .min_by_key(|&(_, entry)| entry.fetched)
+        .map(|(url, _)| url.to_owned());
+    if let Some(url) = oldest {
+        state.entries.remove(&url);
+    }
+}
+
+#[cfg(all(test, feature = "cache"))]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::TtlCache;
+
+    #[test]
+    fn disabled_by_default() {
+        let cache = TtlCache::new();
+        cache.insert("a", "1");
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn keeps_bodies_for_the_ttl() {
+        let cache = TtlCache::new();
+        cache.configure(Duration::from_millis(50), 10);
+        cache.insert("a", "1");
+        assert_eq!(cache.get("a"), Some(String::from("1")));
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn evicts_the_oldest() {
+        let cache = TtlCache::new();
+        cache.configure(Duration::from_secs(60), 2);
+        cache.insert("a", "1");
+        thread::sleep(Duration::from_millis(1));
+        cache.insert("b", "2");
+        cache.insert("c", "3");
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(String::from("2")));
+        assert_eq!(cache.get("c"), Some(String::from("3")));
+    }
+
+    #[test]
+    fn clears() {
+        let cache = TtlCache::new();
+        cache.configure(Duration::from_secs(60), 2);
+        cache.insert("a", "1");
+        cache.clear();
+        assert_eq!(cache.get("a"), None);
+        cache.insert("a", "1");
+        cache.configure(Duration::from_secs(60), 0);
+        assert_eq!(cache.get("a"), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Loading of client credentials from the environment or a `praw.ini` file, so that deployments
+//! can configure a bot without hardcoding credentials.
+//!
+//! Both sources use the same keys as PRAW: `client_id`, `client_secret`, `username`, `password`
+//! and `user_agent`. If all four credentials are present, the client logs in with a
+//! `PasswordAuthenticator`. If only the client ID and secret are present, it uses the OAuth API
+//! without a user via a `ClientCredentialsAuthenticator`. If none are present, it browses with
+//! an `AnonymousAuthenticator`.
+//! # Examples
+//! ```rust,no_run
+//! use new_rawr::client::RedditClient;
+//! let client = RedditClient::from_ini("praw.ini").expect("Could not load praw.ini!");
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::auth::{AnonymousAuthenticator, Authenticator, ClientCredentialsAuthenticator,
+                  PasswordAuthenticator};
+use crate::errors::APIError;
+
+/// The user agent used if the configuration does not specify one.
+pub const DEFAULT_USER_AGENT: &str = concat!("rust:new_rawr:v", env!("CARGO_PKG_VERSION"));
+
+/// The `praw.ini` section used when no site is specified.
+pub const DEFAULT_SITE: &str = "DEFAULT";
+
+/// Credentials and settings used to create a `RedditClient`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientConfig {
+    /// The OAuth client ID of the app.
+    pub client_id: Option<String>,
+    /// The OAuth client secret of the app.
+    pub client_secret: Option<String>,
+    /// The name of the account to log in as.
+    pub username: Option<String>,
+    /// The password of the account to log in as.
+    pub password: Option<String>,
+    /// The user agent to send. `DEFAULT_USER_AGENT` is used if this is not set.
+    pub user_agent: Option<String>,
+}
+
+impl ClientConfig {
+    /// Reads the configuration from the `REDDIT_CLIENT_ID`, `REDDIT_CLIENT_SECRET`,
+    /// `REDDIT_USERNAME`, `REDDIT_PASSWORD` and `REDDIT_USER_AGENT` environment variables. The
+    /// PRAW names (`praw_client_id` etc.) are used as a fallback.
+    pub fn from_env() -> ClientConfig {
+        let var = |key: &str| {
+            env::var(format!("REDDIT_{}", key.to_uppercase()))
+                .or_else(|_| env::var(format!("praw_{}", key)))
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+        ClientConfig {
+            client_id: var("client_id"),
+            client_secret: var("client_secret"),
+            username: var("username"),
+            password: var("password"),
+            user_agent: var("user_agent"),
+        }
+    }
+
+    /// Reads the configuration for a site (section) of a `praw.ini` file. As in PRAW, values in
+    /// the `[DEFAULT]` section apply to every site unless the site overrides them.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::config::ClientConfig;
+    /// let path = std::env::temp_dir().join("new_rawr_config_doctest.ini");
+    /// std::fs::write(&path, "[DEFAULT]\nuser_agent=linux:bot:v1\n\n\
+    ///                        [bot]\nclient_id=abc\nclient_secret=def\n\
+    ///                        username=bot\npassword=hunter2\n").unwrap();
+    /// let config = ClientConfig::from_ini(&path, "bot").unwrap();
+    /// assert_eq!(config.user_agent(), "linux:bot:v1");
+    /// assert!(config.has_credentials());
+    /// ```
+    pub fn from_ini<P: AsRef<Path>>(path: P, site: &str) -> Result<ClientConfig, APIError> {
+        let sections = parse_ini(&fs::read_to_string(path)?);
+        if !sections.contains_key(site) && site != DEFAULT_SITE {
+            return Err(APIError::ValidationError(format!("No [{}] section in praw.ini", site)));
+        }
+        let defaults = sections.get(DEFAULT_SITE);
+        let section = sections.get(site);
+        let value = |key: &str| {
+            section.and_then(|s| s.get(key))
+                .or_else(|| defaults.and_then(|s| s.get(key)))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        Ok(ClientConfig {
+            client_id: value("client_id"),
+            client_secret: value("client_secret"),
+            username: value("username"),
+            password: value("password"),
+            user_agent: value("user_agent"),
+        })
+    }
+
+    /// `true` if all credentials needed to log in are present.
+    pub fn has_credentials(&self) -> bool {
+        self.client_id.is_some() && self.client_secret.is_some() && self.username.is_some() &&
+        self.password.is_some()
+    }
+
+    /// The user agent to use, falling back to `DEFAULT_USER_AGENT`.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// Creates the authenticator for this configuration (see the module-level documentation).
+    /// Returns a `ValidationError` for any other combination of credentials, such as a username
+    /// without a password, since that is almost always a deployment mistake.
+    pub fn authenticator(&self) -> Result<Arc<Mutex<Box<dyn Authenticator + Send>>>, APIError> {
+        match (&self.client_id, &self.client_secret, &self.username, &self.password) {
+            (Some(id), Some(secret), Some(username), Some(password)) => {
+                Ok(PasswordAuthenticator::new(id, secret, username, password))
+            }
+            (Some(id), Some(secret), None, None) => Ok(ClientCredentialsAuthenticator::new(id, secret)),
+            (None, None, None, None) => Ok(AnonymousAuthenticator::new()),
+            _ => {
+                Err(APIError::ValidationError("client_id and client_secret must be set together, \
+                                               and username and password need both of them"
+                    .to_owned()))
+            }
+        }
+    }
+}
+
+/// Parses an INI file into sections of lowercased keys. Keys before the first section header
+/// belong to `[DEFAULT]`.
+fn parse_ini(text: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = DEFAULT_SITE.to_owned();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            current = line[1..line.len() - 1].trim().to_owned();
+            continue;
+        }
+        if let Some(pos) = line.find(['=', ':']) {
+            let (key, value) = line.split_at(pos);
+            sections.entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value[1..].trim().to_owned());
+        }
+    }
+    sections
+}
@@ -0,0 +1,72 @@
+//! Hooks that change every request just before it is sent, for deployments that must route
+//! Reddit traffic through an internal gateway, e.g. one that expects its own authentication
+//! header or an HMAC signature of each request. Add hooks with `RedditClient.add_request_hook()`.
+//!
+//! Hooks run in the order they were added, on every attempt at sending a request through
+//! `RedditClient.send()` (so retries are signed again, e.g. with a fresh timestamp). They run
+//! after the rate limit wait, right before the request is handed to the `HttpBackend`. If a hook
+//! fails, the request is not sent and its error is returned. Token requests (logging in and
+//! refreshing tokens) are sent by the authenticator straight to the backend, so if the gateway
+//! needs those signed too, sign them in a custom `HttpBackend` instead.
+//! # Examples
+//! ```
+//! use http::StatusCode;
+//! use http::header::HeaderName;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::errors::APIError;
+//! use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+//!
+//! /// A gateway that only forwards requests with the right key.
+//! struct Gateway;
+//!
+//! impl HttpBackend for Gateway {
+//!     fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+//!         let signed = request.headers
+//!             .iter()
+//!             .any(|(name, value)| name == "x-gateway-key" && value == "secret");
+//!         let status = if signed { StatusCode::OK } else { StatusCode::FORBIDDEN };
+//!         Ok(HttpResponse::new(status, "{}"))
+//!     }
+//! }
+//!
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(Gateway));
+//! assert!(client.get_json("/r/rust/about", false).is_err());
+//! client.add_request_hook(|request: &mut HttpRequest| {
+//!     request.headers.push((HeaderName::from_static("x-gateway-key"), String::from("secret")));
+//!     Ok(())
+//! });
+//! assert_eq!(client.get_json("/r/rust/about", false).unwrap(), "{}");
+//! ```
+
+use std::sync::Arc;
+
+use crate::errors::APIError;
+use crate::http::HttpRequest;
+
+/// Changes a request before it is sent, e.g. to add headers or sign it. Implemented for
+/// closures that take a `&mut HttpRequest`, so most hooks do not need their own type.
+pub trait RequestHook: Send + Sync {
+    /// Changes the request. Returning an error stops the request from being sent.
+    fn before_send(&self, request: &mut HttpRequest) -> Result<(), APIError>;
+}
+
+impl<F> RequestHook for F
+    where F: Fn(&mut HttpRequest) -> Result<(), APIError> + Send + Sync
+{
+    fn before_send(&self, request: &mut HttpRequest) -> Result<(), APIError> {
+        self(request)
+    }
+}
+
+/// Runs the hooks on the request, in order, stopping at the first error.
+pub(crate) fn apply(hooks: &[Arc<dyn RequestHook>],
+                    request: &mut HttpRequest)
+                    -> Result<(), APIError> {
+    for hook in hooks {
+        hook.before_send(request)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,394 @@
+//! Counters of the requests sent by a `RedditClient`, so that long-running bots can monitor how
+//! much of the API they use without wrapping every call. Every request sent through
+//! `RedditClient.send()` (which includes all of the structures in this crate) is counted, grouped
+//! by endpoint, with the names and IDs in the path replaced by placeholders (e.g.
+//! `/r/{subreddit}/about`), so that the number of groups stays small. Retries are counted as
+//! separate requests, because each of them counts against the rate limit. Requests to the token
+//! endpoints (logging in and refreshing tokens) are not counted.
+//!
+//! Use `RedditClient.metrics()` to take a `MetricsSnapshot`, and `RedditClient.reset_metrics()`
+//! to start counting from zero again (e.g. once per reporting interval).
+//! # Examples
+//! ```
+//! use http::{Method, StatusCode};
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::http::mock::MockBackend;
+//!
+//! let backend = MockBackend::new()
+//!     .on(Method::GET, "/r/rust/about", StatusCode::OK, r#"{"kind": "t5"}"#);
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(backend));
+//! client.get_json("/r/rust/about", false).unwrap();
+//! client.get_json("/r/rust/about", false).unwrap();
+//! assert!(client.get_json("/r/unknown/about", false).is_err());
+//!
+//! let metrics = client.metrics();
+//! assert_eq!(metrics.requests, 3);
+//! assert_eq!(metrics.errors, 1);
+//! assert_eq!(metrics.bytes_received, 28);
+//! assert_eq!(metrics.latency.count(), 3);
+//! let about = &metrics.endpoints["/r/{subreddit}/about"];
+//! assert_eq!(about.requests, 3);
+//! assert_eq!(about.statuses[&200], 2);
+//! assert_eq!(about.statuses[&404], 1);
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::clock;
+use crate::errors::APIError;
+use crate::http::{HttpRequest, HttpResponse};
+
+/// The upper bounds (in milliseconds) of the buckets in a `LatencyHistogram`. Slower requests
+/// are counted in a final bucket with no upper bound.
+pub const LATENCY_BUCKETS: [u64; 8] = [50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// How long requests took, counted in fixed buckets (see `LATENCY_BUCKETS`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Counts a request that took the specified time.
+    pub fn record(&mut self, latency: Duration) {
+        let millis = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS.iter()
+            .position(|&bound| millis <= bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// The number of requests counted.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Each bucket's upper bound (`None` for the final bucket) with the number of requests in
+    /// it.
+    pub fn buckets(&self) -> Vec<(Option<Duration>, u64)> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(index, &count)| {
+                (LATENCY_BUCKETS.get(index).map(|&bound| Duration::from_millis(bound)), count)
+            })
+            .collect()
+    }
+
+    /// The average time a request took, or `None` if no requests were counted.
+    pub fn mean(&self) -> Option<Duration> {
+        match self.count() {
+            0 => None,
+            count => Some(self.total / count as u32),
+        }
+    }
+
+    /// The longest time a request took.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// An estimate of the specified percentile (between 0 and 100), e.g. `percentile(95.0)`:
+    /// the upper bound of the bucket it falls in, or the longest time for the final bucket.
+    /// `None` if no requests were counted.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use new_rawr::client::metrics::LatencyHistogram;
+    /// let mut histogram = LatencyHistogram::default();
+    /// for millis in &[20, 30, 40, 400] {
+    ///     histogram.record(Duration::from_millis(*millis));
+    /// }
+    /// assert_eq!(histogram.percentile(50.0), Some(Duration::from_millis(50)));
+    /// assert_eq!(histogram.percentile(99.0), Some(Duration::from_millis(400)));
+    /// assert_eq!(histogram.mean(), Some(Duration::from_micros(122_500)));
+    /// ```
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bound, bucket) in self.buckets() {
+            seen += bucket;
+            if seen >= rank {
+                return Some(bound.map_or(self.max, |bound| bound.min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> LatencyHistogram {
+        LatencyHistogram {
+            counts: vec![0; LATENCY_BUCKETS.len() + 1],
+            total: Duration::from_secs(0),
+            max: Duration::from_secs(0),
+        }
+    }
+}
+
+/// The requests sent to one endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointMetrics {
+    /// The number of requests sent.
+    pub requests: u64,
+    /// The number of requests that could not be sent or received a response with an error
+    /// status code.
+    pub errors: u64,
+    /// The number of bytes sent in request bodies.
+    pub bytes_sent: u64,
+    /// The number of bytes received in response bodies.
+    pub bytes_received: u64,
+    /// The number of responses with each status code.
+    pub statuses: BTreeMap<u16, u64>,
+    /// How long the requests took.
+    pub latency: LatencyHistogram,
+}
+
+impl EndpointMetrics {
+    /// The fraction of requests that failed, between 0 and 1.
+    pub fn error_rate(&self) -> f64 {
+        error_rate(self.errors, self.requests)
+    }
+}
+
+/// A copy of the metrics of a `RedditClient`, as returned by `RedditClient.metrics()`. See the
+/// module-level documentation for examples.
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    /// When counting started, i.e. when the client was created or the metrics were last reset,
+    /// or `None` if the clock cannot be read (e.g. on wasm32).
+    pub since: Option<SystemTime>,
+    /// The number of requests sent, including retries.
+    pub requests: u64,
+    /// The number of requests that could not be sent or received a response with an error
+    /// status code.
+    pub errors: u64,
+    /// The number of bytes sent in request bodies.
+    pub bytes_sent: u64,
+    /// The number of bytes received in response bodies.
+    pub bytes_received: u64,
+    /// The number of times a request was sent again after a server or transport error.
+    pub retries: u64,
+    /// The number of times the client waited because of the rate limit, either before sending
+    /// a request or after receiving HTTP 429.
+    pub rate_limit_waits: u64,
+    /// The total time spent waiting because of the rate limit.
+    pub rate_limit_wait_time: Duration,
+    /// How long the requests took.
+    pub latency: LatencyHistogram,
+    /// The requests sent to each endpoint, keyed by the path of the URL without the host or
+    /// query string, with names and IDs replaced by placeholders (e.g. `/r/{subreddit}/about`
+    /// for `/r/rust/about`).
+    pub endpoints: BTreeMap<String, EndpointMetrics>,
+}
+
+impl MetricsSnapshot {
+    /// The fraction of requests that failed, between 0 and 1.
+    pub fn error_rate(&self) -> f64 {
+        error_rate(self.errors, self.requests)
+    }
+
+    /// The endpoints sorted by the number of requests sent to them, busiest first.
+    pub fn busiest_endpoints(&self) -> Vec<(&str, &EndpointMetrics)> {
+        let mut endpoints = self.endpoints
+            .iter()
+            .map(|(endpoint, metrics)| (endpoint.as_str(), metrics))
+            .collect::<Vec<_>>();
+        endpoints.sort_by_key(|&(_, metrics)| std::cmp::Reverse(metrics.requests));
+        endpoints
+    }
+}
+
+impl Default for MetricsSnapshot {
+    fn default() -> MetricsSnapshot {
+        MetricsSnapshot {
+            since: clock::system_now(),
+            requests: 0,
+            errors: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            retries: 0,
+            rate_limit_waits: 0,
+            rate_limit_wait_time: Duration::from_secs(0),
+            latency: LatencyHistogram::default(),
+            endpoints: BTreeMap::new(),
+        }
+    }
+}
+
+fn error_rate(errors: u64, requests: u64) -> f64 {
+    if requests == 0 {
+        0.0
+    } else {
+        errors as f64 / requests as f64
+    }
+}
+
+/// Collects the metrics of one client. Internal type. Use `RedditClient.metrics()` instead.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    state: Mutex<MetricsSnapshot>,
+}
+
+impl Metrics {
+    /// Counts one attempt at sending a request. The latency is `None` if the clock cannot be
+    /// read, in which case the request is not counted in the latency histograms.
+    pub fn record(&self,
+                  request: &HttpRequest,
+                  result: &Result<HttpResponse, APIError>,
+                  latency: Option<Duration>) {
+        let (status, received) = match *result {
+            Ok(ref response) => (Some(response.status.as_u16()), response.body.len() as u64),
+            Err(_) => (None, 0),
+        };
+        let failed = !matches!(status, Some(status) if status < 400);
+        let sent = request.body.len() as u64;
+
+        let mut state = self.state.lock().unwrap();
+        state.requests += 1;
+        state.errors += failed as u64;
+        state.bytes_sent += sent;
+        state.bytes_received += received;
+        if let Some(latency) = latency {
+            state.latency.record(latency);
+        }
+        let endpoint = state.endpoints.entry(endpoint(&request.url)).or_default();
+        endpoint.requests += 1;
+        endpoint.errors += failed as u64;
+        endpoint.bytes_sent += sent;
+        endpoint.bytes_received += received;
+        if let Some(latency) = latency {
+            endpoint.latency.record(latency);
+        }
+        if let Some(status) = status {
+            *endpoint.statuses.entry(status).or_insert(0) += 1;
+        }
+    }
+
+    /// Counts a retry after a server or transport error.
+    pub fn record_retry(&self) {
+        self.state.lock().unwrap().retries += 1;
+    }
+
+    /// Counts a wait because of the rate limit. Waits of zero are ignored.
+    pub fn record_rate_limit_wait(&self, wait: Duration) {
+        if wait > Duration::from_secs(0) {
+            let mut state = self.state.lock().unwrap();
+            state.rate_limit_waits += 1;
+            state.rate_limit_wait_time += wait;
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = MetricsSnapshot::default();
+    }
+}
+
+/// The path of a URL without the host or query string, with the names and IDs in it replaced
+/// by placeholders (e.g. `/comments/{id}` for `/comments/4uule8`), so that requests are grouped
+/// by endpoint and every subreddit, user or post does not create a new group.
+pub(crate) fn endpoint(url: &str) -> String {
+    let path = match url.find("://") {
+        Some(scheme) => {
+            let rest = &url[scheme + 3..];
+            rest.find('/').map_or("/", |start| &rest[start..])
+        }
+        None => url,
+    };
+    let segments = path.split('?').next().unwrap_or_default().split('/').collect::<Vec<_>>();
+    let mut template = Vec::with_capacity(segments.len());
+    let mut placeholders: &[&str] = &[];
+    for (index, &segment) in segments.iter().enumerate() {
+        if let Some((&placeholder, rest)) = placeholders.split_first() {
+            if !segment.is_empty() {
+                template.push(placeholder);
+                placeholders = rest;
+                continue;
+            }
+        }
+        placeholders = match segment {
+            "by_id" => &["{names}"],
+            "comments" => &["{id}", "{title}", "{id}"],
+            "m" => &["{multireddit}"],
+            "messages" => &["{id}"],
+            "r" | "structured_styles" | "style_asset_upload_s3" => &["{subreddit}"],
+            "u" | "user" => &["{user}"],
+            // `/api/v1/{subreddit}/post_requirements`
+            "v1" if segments.get(index + 2) == Some(&"post_requirements") => &["{subreddit}"],
+            // Wiki page names can contain slashes, e.g. `config/automoderator`.
+            "wiki" if template.last() == Some(&"{subreddit}") => {
+                template.extend(&["wiki", "{page}"]);
+                break;
+            }
+            _ => &[],
+        };
+        template.push(segment);
+    }
+    template.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Method, StatusCode};
+
+    use super::{endpoint, Metrics};
+    use crate::http::{HttpRequest, HttpResponse};
+
+    #[test]
+    fn strips_host_and_query() {
+        assert_eq!(endpoint("https://oauth.reddit.com/api/info?id=t3_a"), "/api/info");
+        assert_eq!(endpoint("https://oauth.reddit.com"), "/");
+        assert_eq!(endpoint("/api/v1/me"), "/api/v1/me");
+    }
+
+    #[test]
+    fn replaces_names_and_ids() {
+        let cases = [("/r/rust/about", "/r/{subreddit}/about"),
+                     ("/r/rust/hot", "/r/{subreddit}/hot"),
+                     ("/comments/4uule8", "/comments/{id}"),
+                     ("/comments/4uule8/_/d5rxz8b/", "/comments/{id}/{title}/{id}/"),
+                     ("/r/rust/comments/4uule8/title", "/r/{subreddit}/comments/{id}/{title}"),
+                     ("/by_id/t3_a,t3_b", "/by_id/{names}"),
+                     ("/user/spez/submitted", "/user/{user}/submitted"),
+                     ("/api/multi/user/spez/m/tech", "/api/multi/user/{user}/m/{multireddit}"),
+                     ("/message/messages/abc", "/message/messages/{id}"),
+                     ("/api/v1/rust/post_requirements", "/api/v1/{subreddit}/post_requirements"),
+                     ("/api/v1/structured_styles/rust", "/api/v1/structured_styles/{subreddit}"),
+                     ("/r/rust/wiki/config/automoderator", "/r/{subreddit}/wiki/{page}"),
+                     ("/r/rust/api/wiki/edit", "/r/{subreddit}/api/wiki/edit"),
+                     ("/api/v1/me/prefs", "/api/v1/me/prefs")];
+        for &(path, template) in &cases {
+            assert_eq!(endpoint(path), template, "{}", path);
+        }
+    }
+
+    #[test]
+    fn groups_ids_together() {
+        let metrics = Metrics::default();
+        for id in &["4uule8", "4uulf2"] {
+            let request = HttpRequest::new(Method::GET,
+                                           &format!("https://oauth.reddit.com/comments/{}", id));
+            metrics.record(&request, &Ok(HttpResponse::new(StatusCode::OK, "[]")), None);
+        }
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.endpoints.len(), 1);
+        assert_eq!(snapshot.endpoints["/comments/{id}"].requests, 2);
+    }
+}
@@ -27,61 +27,186 @@
 //! let client = RedditClient::new(agent, AnonymousAuthenticator::new());
 //! ```
 
-use std::borrow::Borrow;
-use std::error::Error;
-use std::io::Read;
-use std::panic::resume_unwind;
-use std::str::FromStr;
+use std::collections::HashMap;
+#[cfg(feature = "hyper-backend")]
+use std::env;
+#[cfg(feature = "hyper-backend")]
+use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
 
-use futures::AsyncReadExt;
-use hyper::{Body, Method, Request, StatusCode};
-use hyper::client::{Client, HttpConnector};
-use hyper::header::USER_AGENT;
-use hyper::http::request::Builder;
-use hyper::Uri;
-use hyper_tls::HttpsConnector;
-use serde::Deserialize;
-use serde_json::from_str;
+use http::{Method, StatusCode};
+use http::header::{ACCEPT_ENCODING, CONTENT_TYPE, USER_AGENT};
+use serde_json::{from_str, Value};
+
+pub mod builder;
+mod cache;
+pub mod config;
+pub mod hooks;
+pub mod metrics;
+pub mod ratelimit;
+pub mod retry;
+pub mod scheduler;
+#[cfg(feature = "tracing")]
+mod trace;
 
 use crate::auth::Authenticator;
+use crate::clock;
+use crate::auth::scope::{Scope, ScopeSet};
+#[cfg(feature = "hyper-backend")]
+use crate::client::config::{ClientConfig, DEFAULT_SITE};
+use crate::client::builder::RedditClientBuilder;
+use crate::client::cache::ResponseCache;
+#[cfg(feature = "cache")]
+use crate::client::cache::TtlCache;
+use crate::client::hooks::RequestHook;
+use crate::client::metrics::{Metrics, MetricsSnapshot};
+use crate::client::ratelimit::{RateLimitPolicy, RateLimitStatus, RateLimiter};
+use crate::client::retry::{ExponentialBackoff, RetryCause, RetryPolicy};
+use crate::client::scheduler::{Priority, Scheduler};
+#[cfg(feature = "tracing")]
+use crate::client::trace::RequestTrace;
+use crate::endpoints::{self, Endpoints};
 use crate::errors::APIError;
+use crate::http::{AuthHostBackend, HttpBackend, HttpRequest, HttpResponse};
+#[cfg(feature = "hyper-backend")]
+use crate::http::HyperBackend;
+use crate::responses::PostRequirements;
+use crate::responses::comment::CommentListing;
+use crate::responses::listing::SubredditRule;
+use crate::responses::trending::TrendingSearchesResponse;
+use crate::structures::blocks::BlockList;
+use crate::structures::network::Network;
+use crate::structures::preflight::PostChecker;
+use crate::structures::score_watch::ScoreWatcher;
 use crate::structures::messages::MessageInterface;
 use crate::structures::submission::LazySubmission;
 use crate::structures::subreddit::Subreddit;
+use crate::structures::trending::TrendingSearch;
+use crate::structures::streams::pause;
 use crate::structures::user::User;
 
 /// A client to connect to Reddit. See the module-level documentation for examples.
 pub struct RedditClient {
-    /// The internal HTTP client. You should not need to manually use this. If you do, file an
-    /// issue saying why the API does not support your use-case, and we'll try to add it.
-    pub client: Client<HttpsConnector<HttpConnector>>,
+    /// The HTTP backend used to send requests. You should not need to manually use this. If you
+    /// do, file an issue saying why the API does not support your use-case, and we'll try to add
+    /// it.
+    pub backend: Box<dyn HttpBackend>,
     user_agent: String,
     authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
     auto_logout: bool,
+    raw_json: bool,
+    compression: bool,
+    read_only: AtomicBool,
+    dry_run: AtomicBool,
+    dry_run_log: Mutex<Vec<HttpRequest>>,
+    endpoints: Endpoints,
+    scheduler: Scheduler,
+    rate_limiter: RateLimiter,
+    retry_policy: Mutex<Arc<dyn RetryPolicy>>,
+    request_hooks: Mutex<Vec<Arc<dyn RequestHook>>>,
+    cache: ResponseCache,
+    #[cfg(feature = "cache")]
+    get_cache: TtlCache,
+    post_requirements: Mutex<HashMap<String, PostRequirements>>,
+    report_rules: Mutex<HashMap<String, Option<Vec<SubredditRule>>>>,
+    metrics: Metrics,
 }
 
 
 impl RedditClient {
     /// Creates an instance of the `RedditClient` using the provided user agent.
+    #[cfg(feature = "hyper-backend")]
     pub fn new(user_agent: &str,
                authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>)
                -> RedditClient {
-        // Connection pooling is problematic if there are pauses/sleeps in the program, so we
-        // choose to disable it by using a non-pooling connector.
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, hyper::Body>(https);
+        RedditClient::with_backend(user_agent, authenticator, Box::new(HyperBackend::new()))
+    }
+
+    /// Creates a `RedditClientBuilder`, to choose settings such as the proxy before the client
+    /// logs in. `new()` already uses the proxy set in the `HTTPS_PROXY` or `ALL_PROXY`
+    /// environment variables, so the builder is only needed to choose one explicitly. See the
+    /// `builder` module for examples.
+    pub fn builder(user_agent: &str,
+                   authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>)
+                   -> RedditClientBuilder {
+        RedditClientBuilder::new(user_agent, authenticator)
+    }
+
+    /// Creates a `RedditClient` configured from environment variables (see
+    /// `ClientConfig::from_env()`). Logs in if credentials are set, and browses anonymously
+    /// otherwise.
+    #[cfg(feature = "hyper-backend")]
+    pub fn from_env() -> Result<RedditClient, APIError> {
+        let config = ClientConfig::from_env();
+        Ok(RedditClient::new(config.user_agent(), config.authenticator()?))
+    }
+
+    /// Creates a `RedditClient` configured from a `praw.ini` file. The site named by the
+    /// `praw_site` environment variable is used, or `[DEFAULT]` if it is not set. Use
+    /// `ClientConfig::from_ini()` to choose the site explicitly.
+    #[cfg(feature = "hyper-backend")]
+    pub fn from_ini<P: AsRef<Path>>(path: P) -> Result<RedditClient, APIError> {
+        let site = env::var("praw_site").unwrap_or_else(|_| DEFAULT_SITE.to_owned());
+        let config = ClientConfig::from_ini(path, &site)?;
+        Ok(RedditClient::new(config.user_agent(), config.authenticator()?))
+    }
+
+    /// Creates an instance of the `RedditClient` that sends all requests through the specified
+    /// `HttpBackend`, instead of the default `HyperBackend`. See the `http` module for examples.
+    pub fn with_backend(user_agent: &str,
+                        authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                        backend: Box<dyn HttpBackend>)
+                        -> RedditClient {
+        RedditClient::with_endpoints(user_agent, authenticator, backend, Endpoints::default())
+    }
+
+    /// Creates an instance of the `RedditClient` that sends requests to the specified hosts
+    /// instead of reddit.com, e.g. a local mock server, a proxy or a Reddit-compatible mirror.
+    /// The authenticator also logs in at `endpoints.auth_host`. See the `endpoints` module for
+    /// examples.
+    pub fn with_endpoints(user_agent: &str,
+                          authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                          backend: Box<dyn HttpBackend>,
+                          endpoints: Endpoints)
+                          -> RedditClient {
+        RedditClient::connect(user_agent, authenticator, backend, endpoints)
+            .expect("Authentication failed. Did you use the correct username/password?")
+    }
+
+    /// Creates a client and logs in, returning the error if logging in fails.
+    pub(crate) fn connect(user_agent: &str,
+                          authenticator: Arc<Mutex<Box<dyn Authenticator + Send>>>,
+                          backend: Box<dyn HttpBackend>,
+                          endpoints: Endpoints)
+                          -> Result<RedditClient, APIError> {
         let this = RedditClient {
-            client: client,
+            backend,
             user_agent: user_agent.to_owned(),
-            authenticator: authenticator,
+            authenticator,
             auto_logout: true,
+            raw_json: true,
+            compression: true,
+            read_only: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            dry_run_log: Mutex::new(Vec::new()),
+            endpoints,
+            scheduler: Scheduler::new(),
+            rate_limiter: RateLimiter::default(),
+            retry_policy: Mutex::new(Arc::new(ExponentialBackoff::default())),
+            request_hooks: Mutex::new(Vec::new()),
+            cache: ResponseCache::new(cache::DEFAULT_CAPACITY),
+            #[cfg(feature = "cache")]
+            get_cache: TtlCache::new(),
+            post_requirements: Mutex::new(HashMap::new()),
+            report_rules: Mutex::new(HashMap::new()),
+            metrics: Metrics::default(),
         };
 
-        this.get_authenticator()
-            .login(&this.client, &this.user_agent)
-            .expect("Authentication failed. Did you use the correct username/password?");
-        this
+        trace_span!("login");
+        this.get_authenticator().login(&this.auth_backend(), &this.user_agent)?;
+        Ok(this)
     }
 
     /// Disables the automatic logout that occurs when the client drops out of scope.
@@ -101,17 +226,355 @@ impl RedditClient {
         self.auto_logout = val;
     }
 
+    /// Logs out now, revoking the access token, and returns any error instead of ignoring it as
+    /// the automatic logout does. If `revoke_refresh_token` is `true`, the refresh token (if the
+    /// authenticator has one) is also revoked, so the user must approve the app again.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::CodeFlowAuthenticator;
+    /// let auth = CodeFlowAuthenticator::from_refresh_token("CLIENT_ID", "SECRET", "TOKEN");
+    /// let client = RedditClient::new("new_rawr", auth);
+    /// // The user disconnected their account from the app.
+    /// client.logout(true).expect("Could not revoke tokens");
+    /// ```
+    pub fn logout(mut self, revoke_refresh_token: bool) -> Result<(), APIError> {
+        trace_span!("logout", revoke_refresh_token = revoke_refresh_token);
+        self.auto_logout = false;
+        #[cfg(feature = "cache")]
+        self.get_cache.clear();
+        let mut authenticator = self.get_authenticator();
+        authenticator.logout(&self.auth_backend(), &self.user_agent)?;
+        if revoke_refresh_token {
+            authenticator.revoke_refresh_token(&self.auth_backend(), &self.user_agent)?;
+        }
+        Ok(())
+    }
+
+    /// Sets whether `raw_json=1` is sent with every request (enabled by default). With it,
+    /// Reddit returns text such as titles and comment bodies as-is; without it, `<`, `>` and `&`
+    /// are returned as HTML entities (e.g. `&amp;`), as in Reddit's legacy responses.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let mut client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// client.set_raw_json(false); // Titles will now contain HTML entities.
+    /// ```
+    pub fn set_raw_json(&mut self, val: bool) {
+        self.raw_json = val;
+    }
+
+    /// Sets whether responses are requested compressed with `Accept-Encoding: gzip, deflate`
+    /// (enabled by default). Listings and comment trees are several hundred KB of JSON, which
+    /// compresses to a fraction of that, so this saves bandwidth and time for bots that poll
+    /// often. Compressed responses are decoded by `send()`, whichever backend is used (see
+    /// `HttpResponse.decompress()`). The metrics count the bytes as they were received.
+    /// # Examples
+    /// ```
+    /// use http::{Method, StatusCode};
+    /// use http::header::ACCEPT_ENCODING;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::http::mock::MockBackend;
+    /// let backend = MockBackend::new()
+    ///     .on(Method::GET, "/r/rust/about", StatusCode::OK, r#"{"kind": "t5"}"#);
+    /// let mut client = RedditClient::with_backend("new_rawr",
+    ///                                             AnonymousAuthenticator::new(),
+    ///                                             Box::new(backend));
+    /// let request = client.request(Method::GET, "/r/rust/about", false).unwrap();
+    /// assert!(request.headers.iter().any(|(name, _)| name == ACCEPT_ENCODING));
+    /// client.set_compression(false);
+    /// let request = client.request(Method::GET, "/r/rust/about", false).unwrap();
+    /// assert!(!request.headers.iter().any(|(name, _)| name == ACCEPT_ENCODING));
+    /// ```
+    pub fn set_compression(&mut self, val: bool) {
+        self.compression = val;
+    }
+
+    /// Enables or disables read-only mode. While it is enabled, every request that would change
+    /// something on Reddit (voting, replying, editing, moderating etc.) fails with
+    /// `APIError::ReadOnlyMode` without being sent, while reads keep working. This acts as a
+    /// kill switch for bots during incidents. Changing the mode clears the bodies cached by
+    /// `get_json()` (see `enable_get_cache()`).
+    ///
+    /// Unlike the other settings, this only needs a shared reference, so it can be flipped from
+    /// another thread (e.g. when reloading configuration) while the client is in use.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::errors::APIError;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// client.set_read_only(true);
+    /// match client.post_success("/api/vote", "dir=1&id=t3_4uule8", false) {
+    ///     Err(APIError::ReadOnlyMode) => {}
+    ///     other => panic!("Expected ReadOnlyMode, got {:?}", other),
+    /// }
+    /// ```
+    pub fn set_read_only(&self, val: bool) {
+        self.read_only.store(val, Ordering::SeqCst);
+        #[cfg(feature = "cache")]
+        self.get_cache.clear();
+    }
+
+    /// `true` if the client is in read-only mode (see `set_read_only()`).
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Enables or disables dry-run mode. While it is enabled, every request that would change
+    /// something on Reddit is recorded instead of being sent, and succeeds with an empty
+    /// response, while reads keep working. Use it to test a bot's rules against live data
+    /// without actually voting, replying or removing anything, and check what it would have
+    /// done with `take_dry_run_requests()`. With the `tracing` feature, each skipped request is
+    /// also logged.
+    ///
+    /// Methods that only report success (e.g. `vote()` or `remove()`) work as usual, but
+    /// methods that return something Reddit created (e.g. the new comment from `reply()`) fail
+    /// with `APIError::JSONError`, because there is no real response to read it from. Read-only
+    /// mode takes precedence over dry-run mode.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// client.set_dry_run(true);
+    /// client.post_success("/api/vote", "dir=1&id=t3_4uule8", false).unwrap();
+    /// let skipped = client.take_dry_run_requests();
+    /// assert_eq!(skipped.len(), 1);
+    /// assert!(skipped[0].url.contains("/api/vote"));
+    /// assert_eq!(skipped[0].body, b"dir=1&id=t3_4uule8".to_vec());
+    /// ```
+    pub fn set_dry_run(&self, val: bool) {
+        self.dry_run.store(val, Ordering::SeqCst);
+    }
+
+    /// `true` if the client is in dry-run mode (see `set_dry_run()`).
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::SeqCst)
+    }
+
+    /// Returns the requests that were not sent because of dry-run mode, oldest first, and
+    /// forgets them.
+    pub fn take_dry_run_requests(&self) -> Vec<HttpRequest> {
+        std::mem::take(&mut *self.dry_run_log.lock().unwrap())
+    }
+
+    /// Sets how the client throttles requests based on the rate limit that Reddit reports
+    /// (`RateLimitPolicy::Block` by default). Like `set_read_only()`, this only needs a shared
+    /// reference. See the `ratelimit` module for examples.
+    pub fn set_rate_limit_policy(&self, policy: RateLimitPolicy) {
+        self.rate_limiter.set_policy(policy);
+    }
+
+    /// Sets how many times a request that receives HTTP 429 (Too Many Requests) is retried,
+    /// after sleeping for the wait that Reddit advertises. This is 0 by default, so the request
+    /// fails with `APIError::RateLimited` straight away.
+    /// # Examples
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use http::StatusCode;
+    /// use http::header::HeaderValue;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::errors::APIError;
+    /// use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+    ///
+    /// /// Answers HTTP 429 to the first request only.
+    /// struct Busy(AtomicUsize);
+    ///
+    /// impl HttpBackend for Busy {
+    ///     fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, APIError> {
+    ///         if self.0.fetch_add(1, Ordering::SeqCst) > 0 {
+    ///             return Ok(HttpResponse::new(StatusCode::OK, "{}"));
+    ///         }
+    ///         let mut response = HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, "");
+    ///         response.headers.insert("retry-after", HeaderValue::from_static("0"));
+    ///         Ok(response)
+    ///     }
+    /// }
+    ///
+    /// let client = RedditClient::with_backend("new_rawr",
+    ///                                         AnonymousAuthenticator::new(),
+    ///                                         Box::new(Busy(AtomicUsize::new(0))));
+    /// client.set_rate_limit_retries(3);
+    /// assert_eq!(client.get_json("/r/rust/about", false).unwrap(), "{}");
+    /// ```
+    pub fn set_rate_limit_retries(&self, retries: u32) {
+        self.rate_limiter.set_retries(retries);
+    }
+
+    /// Sets how requests that fail with a 5xx status code or a transport error are retried
+    /// (`ExponentialBackoff::default()` unless changed). Use `NoRetry` to turn retrying off. See
+    /// the `retry` module for examples.
+    pub fn set_retry_policy<P>(&self, policy: P)
+        where P: RetryPolicy + 'static
+    {
+        *self.retry_policy.lock().unwrap() = Arc::new(policy);
+    }
+
+    /// Adds a hook that changes every request just before it is sent, e.g. to add the headers
+    /// or signature that a corporate gateway requires. Closures taking a `&mut HttpRequest` can
+    /// be used as hooks. See the `hooks` module for examples.
+    pub fn add_request_hook<H>(&self, hook: H)
+        where H: RequestHook + 'static
+    {
+        self.request_hooks.lock().unwrap().push(Arc::new(hook));
+    }
+
+    /// Removes every hook added with `add_request_hook()`.
+    pub fn clear_request_hooks(&self) {
+        self.request_hooks.lock().unwrap().clear();
+    }
+
+    /// Sets how many URLs the client remembers responses for (256 by default), or turns caching
+    /// off if `capacity` is 0. When a `GET` response has an `ETag` or `Last-Modified` header,
+    /// the client sends it back with the next request to the same URL (as `If-None-Match` or
+    /// `If-Modified-Since`), and if Reddit answers HTTP 304 Not Modified, `send()` returns the
+    /// remembered response instead. This saves bandwidth for polling loops such as streams,
+    /// which usually get unchanged data. The request is still sent every time, so responses are
+    /// never stale.
+    /// # Examples
+    /// ```
+    /// use http::StatusCode;
+    /// use http::header::{ETAG, IF_NONE_MATCH, HeaderValue};
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::errors::APIError;
+    /// use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+    ///
+    /// /// Answers 304 if the client already has the current version.
+    /// struct Unchanged;
+    ///
+    /// impl HttpBackend for Unchanged {
+    ///     fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+    ///         if request.headers.iter().any(|(name, value)| name == IF_NONE_MATCH && value == "\"1\"") {
+    ///             return Ok(HttpResponse::new(StatusCode::NOT_MODIFIED, ""));
+    ///         }
+    ///         let mut response = HttpResponse::new(StatusCode::OK, r#"{"kind": "t5"}"#);
+    ///         response.headers.insert(ETAG, HeaderValue::from_static("\"1\""));
+    ///         Ok(response)
+    ///     }
+    /// }
+    ///
+    /// let client = RedditClient::with_backend("new_rawr",
+    ///                                         AnonymousAuthenticator::new(),
+    ///                                         Box::new(Unchanged));
+    /// let first = client.get_json("/r/rust/about", false).unwrap();
+    /// assert_eq!(client.get_json("/r/rust/about", false).unwrap(), first);
+    /// assert_eq!(client.metrics().endpoints["/r/{subreddit}/about"].statuses[&304], 1);
+    /// ```
+    pub fn set_cache_capacity(&self, capacity: usize) {
+        self.cache.set_capacity(capacity);
+    }
+
+    /// Forgets every cached response, both those remembered for conditional requests (see
+    /// `set_cache_capacity()`) and those kept by `get_json()` (see `enable_get_cache()`), and the
+    /// post requirements and report rules of subreddits (see `Subreddit.flair_required()` and
+    /// `Subreddit.report_reason()`).
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+        #[cfg(feature = "cache")]
+        self.get_cache.clear();
+        self.post_requirements.lock().unwrap().clear();
+        self.report_rules.lock().unwrap().clear();
+    }
+
+    /// Makes `get_json()` keep the bodies it returns for `ttl`, for up to `capacity` URLs, and
+    /// answer repeated requests for the same URL from memory without sending them. Requests
+    /// answered from memory do not count against the rate limit, which helps bots that look up
+    /// the same subreddit, user or flair options for every item they process. The cache is
+    /// disabled by default. Requires the `cache` feature.
+    ///
+    /// Everything fetched with `get_json()` is cached, including listings, so streams and
+    /// listings may return data up to `ttl` old: keep the TTL short (e.g. shorter than the
+    /// interval of any streams). The whole cache is cleared whenever the client sends a request
+    /// that changes something, so the bot sees its own changes, and when the client logs out or
+    /// read-only mode is turned on or off. Bodies are kept separately for each account the
+    /// authenticator is logged in as.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use http::{Method, StatusCode};
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::http::mock::MockBackend;
+    ///
+    /// let backend = MockBackend::new()
+    ///     .on(Method::GET, "/r/rust/about", StatusCode::OK, r#"{"kind": "t5"}"#);
+    /// let client = RedditClient::with_backend("new_rawr",
+    ///                                         AnonymousAuthenticator::new(),
+    ///                                         Box::new(backend));
+    /// client.enable_get_cache(Duration::from_secs(60), 100);
+    /// client.get_json("/r/rust/about", false).unwrap();
+    /// client.get_json("/r/rust/about", false).unwrap();
+    /// assert_eq!(client.metrics().requests, 1);
+    /// ```
+    #[cfg(feature = "cache")]
+    pub fn enable_get_cache(&self, ttl: Duration, capacity: usize) {
+        self.get_cache.configure(ttl, capacity);
+    }
+
+    /// Turns off the cache enabled with `enable_get_cache()`, forgetting what it holds.
+    #[cfg(feature = "cache")]
+    pub fn disable_get_cache(&self) {
+        self.get_cache.configure(Duration::from_secs(0), 0);
+    }
+
+    /// The rate limit reported in the most recent response, or `None` if Reddit has not
+    /// reported one yet.
+    pub fn rate_limit(&self) -> Option<RateLimitStatus> {
+        self.rate_limiter.status()
+    }
+
+    /// A snapshot of the requests this client has sent, e.g. to export to a monitoring system.
+    /// See the `metrics` module for examples.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Starts counting the metrics from zero again.
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// Gets the user agent that is sent with every request.
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// Overrides the hosts that API requests are sent to, e.g. to test against a mock server.
+    /// The client has already logged in by now, so use `with_endpoints()` instead if the
+    /// authenticator should log in at a different host too.
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = endpoints;
+    }
+
+    /// Gets the hosts that API requests are currently sent to.
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// The backend to give to the authenticator, which sends token requests to
+    /// `endpoints.auth_host`.
+    fn auth_backend(&self) -> AuthHostBackend<'_> {
+        AuthHostBackend {
+            backend: &*self.backend,
+            endpoints: &self.endpoints,
+        }
+    }
+
     /// Runs the lambda passed in. Refreshes the access token if it fails due to an HTTP 401
     /// Unauthorized error, then reruns the lambda. If the lambda fails twice, or fails due to
-    /// a different error, the error is returned.
+    /// a different error (including failing to refresh the token), the error is returned.
     pub fn ensure_authenticated<F, T>(&self, lambda: F) -> Result<T, APIError>
         where F: Fn() -> Result<T, APIError>
     {
         let res = lambda();
         match res {
             Err(APIError::HTTPError(StatusCode::UNAUTHORIZED)) => {
-                self.get_authenticator().refresh_token(&self.client, &self.user_agent).expect("Authentication failed. Did you use the correct username/password?");
-                ;
+                self.get_authenticator().refresh_token(&self.auth_backend(), &self.user_agent)?;
                 lambda()
             }
             _ => res,
@@ -120,18 +583,62 @@ impl RedditClient {
 
     /// Gets a mutable reference to the authenticator using a `&RedditClient`. Mainly used
     /// in the `ensure_authenticated` method to update tokens if necessary.
-    pub fn get_authenticator(&self) -> MutexGuard<Box<Authenticator + Send + 'static>> {
+    pub fn get_authenticator(&self) -> MutexGuard<'_, Box<dyn Authenticator + Send + 'static>> {
         self.authenticator.lock().unwrap()
     }
 
+    /// The OAuth scopes granted to the authenticator when it logged in. Anonymous clients only
+    /// have `Scope::Read`.
+    pub fn granted_scopes(&self) -> ScopeSet {
+        ScopeSet::parse(&self.get_authenticator().scopes().join(" "))
+    }
+
+    /// `true` if the authenticator was granted the scope, so that calls needing it should
+    /// succeed. See the `auth::scope` module for examples.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.granted_scopes().contains(scope)
+    }
+
+    /// When the current access token expires, or `None` for anonymous clients. The client
+    /// refreshes the token shortly before this, so it is mostly useful for monitoring.
+    pub fn token_expires_at(&self) -> Option<SystemTime> {
+        self.get_authenticator().token_expires_at()
+    }
+
+    /// How long until the current access token expires, or `None` for anonymous clients (or if
+    /// the clock cannot be read, e.g. on wasm32). This is zero if it has already expired.
+    pub fn token_expires_in(&self) -> Option<Duration> {
+        let expires_at = self.token_expires_at()?;
+        Some(expires_at.duration_since(clock::system_now()?).unwrap_or_default())
+    }
+
+    /// Revokes the current access token (using `/api/v1/revoke_token`) without logging out. The
+    /// next request logs in again and gets a new token, so this can be used to rotate
+    /// credentials, e.g. after a token may have leaked. Use `logout()` to stop using the client.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// println!("Token expires in {:?}, scopes: {}", client.token_expires_in(),
+    ///          client.granted_scopes());
+    /// client.revoke_token().expect("Could not revoke token");
+    /// // This request is sent with a new token.
+    /// client.subreddit("rust").about().expect("Could not get subreddit");
+    /// ```
+    pub fn revoke_token(&self) -> Result<(), APIError> {
+        trace_span!("revoke_token");
+        self.get_authenticator().revoke_token(&self.auth_backend(), &self.user_agent)
+    }
+
     /// Provides an interface to the specified subreddit which can be used to access
     /// subreddit-related API endpoints such as post listings.
-    pub fn subreddit(&self, name: &str) -> Subreddit {
+    pub fn subreddit(&self, name: &str) -> Subreddit<'_> {
         Subreddit::create_new(self, &self.url_escape(name.to_owned()))
     }
 
     /// Gets the specified user in order to get user-related data such as the 'about' page.
-    pub fn user(&self, name: &str) -> User {
+    pub fn user(&self, name: &str) -> User<'_> {
         User::new(self, &self.url_escape(name.to_owned()))
     }
 
@@ -139,96 +646,105 @@ impl RedditClient {
     pub fn build_url(&self,
                      dest: &str,
                      oauth_required: bool,
-                     authenticator: &mut MutexGuard<Box<Authenticator + Send + 'static>>)
+                     authenticator: &mut MutexGuard<Box<dyn Authenticator + Send + 'static>>)
                      -> String {
         let oauth_supported = authenticator.oauth();
-        let stem = if oauth_required || oauth_supported {
+        let use_oauth = oauth_required || oauth_supported;
+        if use_oauth {
             // All endpoints support OAuth, but some do not support the regular endpoint. If we are
             // required to use it or support it, we will use it.
             assert!(oauth_supported,
                     "OAuth is required to use this endpoint, but your authenticator does not \
                      support it.");
-            "https://oauth.reddit.com"
-        } else {
-            "https://api.reddit.com"
-        };
+        }
+        let stem = self.endpoints.host(use_oauth);
         format!("{}{}", stem, dest)
     }
 
-    /// Wrapper around the `get` function of `hyper::client::Client`, which sends a HTTP GET
-    /// request. The correct user agent header is also sent using this function, which is necessary
-    /// to prevent 403 errors.
-    pub fn get(&self, dest: &str, oauth_required: bool) -> Builder {
-        let mut authenticator = self.get_authenticator();
-        let url = self.build_url(dest, oauth_required, &mut authenticator);
-
-        let mut builder = (Builder::new());
-        let mut headers = authenticator.headers();
-        if headers.is_err() {
-            if headers.err().unwrap().to_string().eq("ExpiredToken") {
-                authenticator.login(&self.client, &*self.user_agent).expect("Authentication failed. Did you use the correct username/password?");
-            }
-        }
-        headers = authenticator.headers();
-        for x in headers.unwrap() {
-            builder = builder.header(x.0, x.1);
-        }
-        builder.method(Method::GET).uri(url).header(USER_AGENT, self.user_agent.to_owned())
+    /// Creates a HTTP GET request to the specified endpoint. The correct user agent header is
+    /// also sent using this function, which is necessary to prevent 403 errors.
+    pub fn get(&self, dest: &str, oauth_required: bool) -> Result<HttpRequest, APIError> {
+        self.request(Method::GET, dest, oauth_required)
     }
 
     /// Sends a GET request with the specified parameters, and returns the resulting
-    /// deserialized object.
+    /// deserialized object. If the cache is enabled (see `enable_get_cache()`), a body fetched
+    /// recently from the same URL is returned without sending the request.
     pub fn get_json(&self, dest: &str, oauth_required: bool) -> Result<String, APIError> {
         self.ensure_authenticated(|| {
-            let request = self.get(dest, oauth_required).body(Body::empty()).unwrap();
-
-            let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
-
-            let response = runtime.block_on(self.client.request(request)).unwrap();
-            if response.status().is_success() {
-                let value = runtime.block_on(hyper::body::to_bytes(response.into_body()));
-                Ok(String::from_utf8(value.unwrap().to_vec()).unwrap().parse().unwrap())
+            let request = self.get(dest, oauth_required)?;
+            #[cfg(feature = "cache")]
+            let key = self.get_cache_key(&request.url);
+            #[cfg(feature = "cache")]
+            {
+                if let Some(body) = self.get_cache.get(&key) {
+                    return Ok(body);
+                }
+            }
+            let response = self.send(request)?;
+            if response.status.is_success() {
+                let text = response.text()?;
+                #[cfg(feature = "cache")]
+                self.get_cache.insert(&key, &text);
+                Ok(text)
             } else {
-                Err(APIError::HTTPError(response.status()))
+                let body = String::from_utf8_lossy(&response.body);
+                Err(APIError::from_response(response.status, &body))
             }
         })
     }
 
-    /// Wrapper around the `post` function of `hyper::client::Client`, which sends a HTTP POST
-    /// request. The correct user agent header is also sent using this function, which is necessary
-    /// to prevent 403 errors.
-    pub fn post(&self, dest: &str, oauth_required: bool) -> Builder {
-        let mut authenticator = self.get_authenticator();
-        let url = self.build_url(dest, oauth_required, &mut authenticator);
-        let mut builder = Request::builder().method(Method::POST).uri(url);
-        let mut headers = authenticator.headers();
-        if headers.is_err() {
-            if headers.err().unwrap().to_string().eq("ExpiredToken") {
-                authenticator.login(&self.client, &*self.user_agent);
-            }
-        }
-        headers = authenticator.headers();
-        for x in headers.unwrap() {
-            builder = builder.header(x.0, x.1);
-        }
-        builder.header(USER_AGENT, self.user_agent.to_owned())
+    /// The key of a URL in the cache of `get_json()`. Bodies are kept per account, so that
+    /// responses fetched as one user are never returned to another (or to an anonymous client).
+    #[cfg(feature = "cache")]
+    fn get_cache_key(&self, url: &str) -> String {
+        let username = self.get_authenticator().username().unwrap_or_default();
+        format!("{} {}", username, url)
+    }
+
+    /// Creates a HTTP POST request to the specified endpoint. The correct user agent header is
+    /// also sent using this function, which is necessary to prevent 403 errors.
+    pub fn post(&self, dest: &str, oauth_required: bool) -> Result<HttpRequest, APIError> {
+        Ok(self.request(Method::POST, dest, oauth_required)?
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded"))
     }
 
     /// Sends a post request with the specified parameters, and converts the resulting JSON
     /// into a deserialized object.
     pub fn post_json(&self, dest: &str, body: &str, oauth_required: bool) -> Result<String, APIError> {
         self.ensure_authenticated(|| {
-            let request = self.post(dest, oauth_required).body(Body::from(body.to_string())).unwrap();
-
-            let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
+            let response = self.send(self.post(dest, oauth_required)?.body(body))?;
+            if response.status.is_success() {
+                let text = response.text()?;
+                APIError::check_errors(&text)?;
+                Ok(text)
+            } else {
+                let body = String::from_utf8_lossy(&response.body);
+                Err(APIError::from_response(response.status, &body))
+            }
+        })
+    }
 
-            let response = runtime.block_on(self.client.request(request)).unwrap();
-            let status = response.status();
-            if status.is_success() {
-                let value = runtime.block_on(hyper::body::to_bytes(response.into_body()));
-                Ok(String::from_utf8(value.unwrap().to_vec()).unwrap().parse().unwrap())
+    /// Sends a post request with a JSON body (used by some newer endpoints instead of form
+    /// parameters), and returns the response body.
+    pub fn post_json_body(&self,
+                          dest: &str,
+                          body: &Value,
+                          oauth_required: bool)
+                          -> Result<String, APIError> {
+        let body = body.to_string();
+        self.ensure_authenticated(|| {
+            let request = self.request(Method::POST, dest, oauth_required)?
+                .header(CONTENT_TYPE, "application/json")
+                .body(&body);
+            let response = self.send(request)?;
+            if response.status.is_success() {
+                let text = response.text()?;
+                APIError::check_errors(&text)?;
+                Ok(text)
             } else {
-                Err(APIError::HTTPError(status))
+                let body = String::from_utf8_lossy(&response.body);
+                Err(APIError::from_response(response.status, &body))
             }
         })
     }
@@ -241,19 +757,188 @@ impl RedditClient {
                         oauth_required: bool)
                         -> Result<(), APIError> {
         self.ensure_authenticated(|| {
-            let request = self.post(dest, oauth_required).body(Body::from(body.to_string())).unwrap();
-
-            let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
-
-            let response = runtime.block_on(self.client.request(request)).unwrap();
-            if response.status().is_success() {
-                Ok(())
+            let response = self.send(self.post(dest, oauth_required)?.body(body))?;
+            if response.status.is_success() {
+                APIError::check_errors(&String::from_utf8_lossy(&response.body))
             } else {
-                Err(APIError::HTTPError(response.status()))
+                let body = String::from_utf8_lossy(&response.body);
+                Err(APIError::from_response(response.status, &body))
             }
         })
     }
 
+    /// Sends a request through the `HttpBackend` of this client. If the client is shared between
+    /// threads, background requests wait for interactive requests to finish first (see
+    /// `with_priority`). Fails with `APIError::ReadOnlyMode` if the request would change something
+    /// while the client is in read-only mode, and records it without sending it in dry-run mode
+    /// (see `set_dry_run()`). The rate limit policy is applied before sending
+    /// (see `set_rate_limit_policy`), and HTTP 429 responses fail with `APIError::RateLimited`
+    /// unless retries are enabled (see `set_rate_limit_retries`). Server errors and transport
+    /// errors are retried according to the retry policy (see `set_retry_policy`). Compressed
+    /// responses are decoded (see `set_compression`). The request hooks run right before each
+    /// attempt (see `add_request_hook`). `GET` requests are sent as conditional requests when a
+    /// response for the URL is cached, and HTTP 304 returns the cached response (see
+    /// `set_cache_capacity`).
+    pub fn send(&self, mut request: HttpRequest) -> Result<HttpResponse, APIError> {
+        if self.is_read_only() && request.is_mutating() {
+            return Err(APIError::ReadOnlyMode);
+        }
+        if self.is_dry_run() && request.is_mutating() {
+            #[cfg(feature = "tracing")]
+            tracing::info!(method = %request.method,
+                           endpoint = %request.url,
+                           body = %String::from_utf8_lossy(&request.body),
+                           "dry run, request not sent");
+            self.dry_run_log.lock().unwrap().push(request);
+            return Ok(HttpResponse::new(StatusCode::OK, "{}"));
+        }
+        #[cfg(feature = "cache")]
+        {
+            if request.is_mutating() {
+                self.get_cache.clear();
+            }
+        }
+        let retry_policy = self.retry_policy.lock().unwrap().clone();
+        let request_hooks = self.request_hooks.lock().unwrap().clone();
+        let cached = self.cache.prepare(&mut request);
+        #[cfg(feature = "tracing")]
+        let trace = RequestTrace::start(&request);
+        let result = self.scheduler.run(scheduler::current_priority(), || {
+            let mut attempts = 0;
+            let mut failures = 0;
+            loop {
+                self.metrics.record_rate_limit_wait(self.rate_limiter.acquire()?);
+                let mut hooked = request.clone();
+                hooks::apply(&request_hooks, &mut hooked)?;
+                let started = clock::now();
+                let result = self.backend.execute(hooked);
+                self.metrics.record(&request, &result, started.and_then(clock::elapsed));
+                let response = match result.and_then(HttpResponse::decompress) {
+                    Ok(response) => response,
+                    Err(err) => {
+                        failures += 1;
+                        let cause = RetryCause::Transport(&err);
+                        match retry_policy.retry_delay(&request, &cause, failures) {
+                            Some(wait) if clock::can_pause() => {
+                                self.metrics.record_retry();
+                                #[cfg(feature = "tracing")]
+                                tracing::info!(wait_ms = wait.as_millis() as u64, error = ?err,
+                                               "request could not be sent, retrying");
+                                pause(wait)
+                            }
+                            _ => return Err(err),
+                        }
+                        continue;
+                    }
+                };
+                self.rate_limiter.update(&response.headers);
+                if response.status.is_server_error() {
+                    failures += 1;
+                    let cause = RetryCause::ServerError(response.status);
+                    match retry_policy.retry_delay(&request, &cause, failures) {
+                        Some(wait) if clock::can_pause() => {
+                            self.metrics.record_retry();
+                            #[cfg(feature = "tracing")]
+                            tracing::info!(wait_ms = wait.as_millis() as u64,
+                                           status = response.status.as_u16(),
+                                           "server error, retrying");
+                            pause(wait)
+                        }
+                        _ => return Ok(response),
+                    }
+                    continue;
+                }
+                if response.status != StatusCode::TOO_MANY_REQUESTS {
+                    return Ok(response);
+                }
+                let wait = self.rate_limiter.retry_after(&response.headers);
+                // Without a way to wait (e.g. on wasm32), retrying would only be rejected again.
+                if attempts >= self.rate_limiter.retries() || !clock::can_pause() {
+                    return Err(APIError::RateLimited { retry_after: wait });
+                }
+                attempts += 1;
+                #[cfg(feature = "tracing")]
+                tracing::info!(wait_ms = wait.as_millis() as u64, "rate limited, retrying");
+                self.metrics.record_rate_limit_wait(wait);
+                pause(wait);
+            }
+        });
+        #[cfg(feature = "tracing")]
+        trace.finish(&result, self.rate_limit());
+        result.map(|response| self.cache.resolve(&request, cached, response))
+    }
+
+    /// Runs the closure with every request it sends on this thread given the specified
+    /// priority. Streams already send their requests as `Priority::Background`; use this to
+    /// mark your own crawlers as background work so they yield to user-triggered actions.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::client::scheduler::Priority;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::options::ListingRequest;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let rust = client.subreddit("rust");
+    /// let crawled = client.with_priority(Priority::Background, || {
+    ///     rust.listing(ListingRequest::default()).unwrap().take(500).count()
+    /// });
+    /// ```
+    pub fn with_priority<F, T>(&self, priority: Priority, lambda: F) -> T
+        where F: FnOnce() -> T
+    {
+        scheduler::with_priority(priority, lambda)
+    }
+
+    /// Creates a request with any HTTP method (e.g. `PATCH`) to the specified endpoint, with the
+    /// authentication and user agent headers set. Prefer `get()` and `post()` where possible.
+    ///
+    /// If the access token has expired or is about to expire, it is refreshed first, so
+    /// long-running clients keep working after the token's lifetime (usually an hour). Fails if
+    /// the token cannot be refreshed, e.g. because the token endpoint could not be reached.
+    pub fn request(&self,
+                   method: Method,
+                   dest: &str,
+                   oauth_required: bool)
+                   -> Result<HttpRequest, APIError> {
+        let mut authenticator = self.get_authenticator();
+        let url = self.build_url(dest, oauth_required, &mut authenticator);
+        let url = RedditClient::with_raw_json(&url, self.raw_json);
+        let mut request = HttpRequest::new(method, &url);
+        let mut headers = authenticator.headers();
+        if let Err(APIError::ExpiredToken) = headers {
+            trace_span!("refresh_token");
+            authenticator.refresh_token(&self.auth_backend(), &self.user_agent)?;
+            headers = authenticator.headers();
+        }
+        for (name, value) in headers? {
+            request = request.header(name, &value);
+        }
+        if self.compression {
+            request = request.header(ACCEPT_ENCODING, "gzip, deflate");
+        }
+        Ok(request.header(USER_AGENT, &self.user_agent))
+    }
+
+    /// Removes any `raw_json` parameter from the URL's query string, then adds `raw_json=1` if
+    /// it is enabled, so the client-wide setting always wins over the individual endpoints.
+    pub(crate) fn with_raw_json(url: &str, raw_json: bool) -> String {
+        let mut parts = url.splitn(2, '?');
+        let path = parts.next().unwrap_or_default();
+        let mut params = parts.next()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|param| !param.is_empty() && !param.starts_with("raw_json="))
+            .collect::<Vec<&str>>();
+        if raw_json {
+            params.push("raw_json=1");
+        }
+        if params.is_empty() {
+            path.to_owned()
+        } else {
+            format!("{}?{}", path, params.join("&"))
+        }
+    }
+
     /// URL encodes the specified string so that it can be sent in GET and POST requests.
     ///
     /// This is only done when data is being sent that isn't from the API (we assume that API
@@ -268,19 +953,7 @@ impl RedditClient {
     /// assert_eq!(client.url_escape(String::from("\n")), String::from("%0A"))
     /// ```
     pub fn url_escape(&self, item: String) -> String {
-        let mut res = String::new();
-        for character in item.chars() {
-            match character {
-                ' ' => res.push('+'),
-                '*' | '-' | '.' | '0'...'9' | 'A'...'Z' | '_' | 'a'...'z' => res.push(character),
-                _ => {
-                    for val in character.to_string().as_bytes() {
-                        res = res + &format!("%{:02X}", val);
-                    }
-                }
-            }
-        }
-        res
+        url_escape(&item)
     }
 
     /// Gets a `LazySubmission` object which can be used to access the information/comments of a
@@ -294,10 +967,55 @@ impl RedditClient {
     /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
     /// assert_eq!(post.title(), "[C#] Abstract vs Interface");
     /// ```
-    pub fn get_by_id(&self, id: &str) -> LazySubmission {
+    pub fn get_by_id(&self, id: &str) -> LazySubmission<'_> {
         LazySubmission::new(self, &self.url_escape(id.to_owned()))
     }
 
+    /// Fetches the data of the comments and submissions with the specified full names from
+    /// `/api/info`, 100 per request. Items that no longer exist are left out.
+    pub(crate) fn get_info(&self, names: &[String]) -> Result<Vec<Value>, APIError> {
+        let mut items = Vec::new();
+        for chunk in names.chunks(100) {
+            let url = format!("{}?id={}&raw_json=1", endpoints::INFO, chunk.join(","));
+            let string = self.get_json(&url, false)?;
+            let listing: CommentListing = from_str(&string)?;
+            items.extend(listing.data.children.into_iter().map(|item| item.data));
+        }
+        Ok(items)
+    }
+
+    /// Fetches the post requirements of the subreddit, once per subreddit until the cache is
+    /// cleared with `clear_cache()`. This requires an OAuth-based authenticator.
+    pub(crate) fn post_requirements(&self, subreddit: &str) -> Result<PostRequirements, APIError> {
+        let key = subreddit.to_lowercase();
+        if let Some(requirements) = self.post_requirements.lock().unwrap().get(&key) {
+            return Ok(requirements.clone());
+        }
+        let url = endpoints::post_requirements(subreddit);
+        let requirements: PostRequirements = from_str(&self.get_json(&url, true)?)?;
+        self.post_requirements.lock().unwrap().insert(key, requirements.clone());
+        Ok(requirements)
+    }
+
+    /// Fetches the rules that reports in the subreddit must name, or `None` if the subreddit
+    /// allows free-form reports, once per subreddit until the cache is cleared with
+    /// `clear_cache()`.
+    pub(crate) fn report_rules(&self, subreddit: &str)
+                               -> Result<Option<Vec<SubredditRule>>, APIError> {
+        let key = subreddit.to_lowercase();
+        if let Some(rules) = self.report_rules.lock().unwrap().get(&key) {
+            return Ok(rules.clone());
+        }
+        let subreddit = self.subreddit(subreddit);
+        let rules = if subreddit.about()?.free_form_reports() {
+            None
+        } else {
+            Some(subreddit.rules()?)
+        };
+        self.report_rules.lock().unwrap().insert(key, rules.clone());
+        Ok(rules)
+    }
+
     /// Gets a `MessageInterface` object which allows access to the message listings (e.g. `inbox`,
     /// `unread`, etc.)
     /// # Examples
@@ -312,18 +1030,151 @@ impl RedditClient {
     ///
     /// }
     /// ```
-    pub fn messages(&self) -> MessageInterface {
+    pub fn messages(&self) -> MessageInterface<'_> {
         MessageInterface::new(self)
     }
+
+    /// Fetches the searches that are currently trending on Reddit, with the subreddits that the
+    /// matching posts come from.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// for trend in client.trending_searches().expect("Could not fetch trends") {
+    ///     println!("{} (in {})", trend.display, trend.subreddits.join(", "));
+    /// }
+    /// ```
+    pub fn trending_searches(&self) -> Result<Vec<TrendingSearch>, APIError> {
+        let string = self.get_json(endpoints::TRENDING_SEARCHES, false)?;
+        let result: TrendingSearchesResponse = serde_json::from_str(&string)?;
+        Ok(result.trending_searches.into_iter().map(TrendingSearch::new).collect())
+    }
+
+    /// Gets a `BlockList`, which caches the users that the logged-in account has blocked (or
+    /// that appear to have blocked it) so bots can avoid replying to them. See `BlockList` for
+    /// examples.
+    pub fn block_list(&self) -> BlockList<'_> {
+        BlockList::new(self)
+    }
+
+    /// Gets an empty `Network`, which collects the subreddits managed by one organization so
+    /// they can be moderated together. See `Network` for examples.
+    pub fn network(&self) -> Network<'_> {
+        Network::new(self)
+    }
+
+    /// Gets a `ScoreWatcher` with no thresholds, which reports when the scores of comments or
+    /// submissions (e.g. the bot's own replies) cross thresholds. See `ScoreWatcher` for
+    /// examples.
+    pub fn score_watcher(&self) -> ScoreWatcher<'_> {
+        ScoreWatcher::new(self)
+    }
+
+    /// Gets a `PostChecker`, which warns about posts that are likely to be rejected or removed
+    /// by a subreddit before they are submitted. See `PostChecker` for examples.
+    pub fn post_checker(&self) -> PostChecker<'_> {
+        PostChecker::new(self)
+    }
 }
 
 impl Drop for RedditClient {
     fn drop(&mut self) {
         if self.auto_logout {
-            let result = self.get_authenticator().logout(&self.client, &self.user_agent);
-            if result.is_err() {
-                println!("{}", result.err().unwrap());
+            let result = self.get_authenticator().logout(&self.auth_backend(), &self.user_agent);
+            #[cfg(feature = "tracing")]
+            {
+                if let Err(ref err) = result {
+                    tracing::warn!(error = %err, "automatic logout failed");
+                }
+            }
+            // There is no caller to return the error to; use `logout()` to handle it.
+            let _ = result;
+        }
+    }
+}
+
+/// URL encodes the string. See `RedditClient.url_escape()`.
+pub(crate) fn url_escape(item: &str) -> String {
+    let mut res = String::new();
+    for character in item.chars() {
+        match character {
+            ' ' => res.push('+'),
+            '*' | '-' | '.' | '0'..='9' | 'A'..='Z' | '_' | 'a'..='z' => res.push(character),
+            _ => {
+                for val in character.to_string().as_bytes() {
+                    res = res + &format!("%{:02X}", val);
+                }
             }
         }
     }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use http::{Method, StatusCode};
+    use http::header::HeaderName;
+
+    use super::RedditClient;
+    use crate::auth::Authenticator;
+    use crate::errors::APIError;
+    use crate::http::HttpBackend;
+    use crate::http::mock::MockBackend;
+
+    /// Logs in once, then reports its token as expired and fails to refresh it.
+    struct ExpiringAuthenticator {
+        logged_in: bool,
+    }
+
+    impl Authenticator for ExpiringAuthenticator {
+        fn login(&mut self, _: &dyn HttpBackend, _: &str) -> Result<(), APIError> {
+            if self.logged_in {
+                return Err(APIError::HTTPError(StatusCode::SERVICE_UNAVAILABLE));
+            }
+            self.logged_in = true;
+            Ok(())
+        }
+
+        fn logout(&mut self, _: &dyn HttpBackend, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+            Err(APIError::ExpiredToken)
+        }
+
+        fn oauth(&self) -> bool {
+            true
+        }
+    }
+
+    fn client() -> RedditClient {
+        let authenticator: Box<dyn Authenticator + Send> =
+            Box::new(ExpiringAuthenticator { logged_in: false });
+        let backend = MockBackend::new().on(Method::GET, "/r/rust/about", StatusCode::OK, "{}");
+        RedditClient::with_backend("new_rawr",
+                                   Arc::new(Mutex::new(authenticator)),
+                                   Box::new(backend))
+    }
+
+    #[test]
+    fn returns_refresh_errors() {
+        let client = client();
+        match client.request(Method::GET, "/r/rust/about", false) {
+            Err(APIError::HTTPError(StatusCode::SERVICE_UNAVAILABLE)) => {}
+            other => panic!("expected the refresh error, got {:?}", other.map(|_| ())),
+        }
+        match client.get_json("/r/rust/about", false) {
+            Err(APIError::HTTPError(StatusCode::SERVICE_UNAVAILABLE)) => {}
+            other => panic!("expected the refresh error, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,302 @@
+//! Client-side throttling driven by the `X-Ratelimit-Remaining`, `X-Ratelimit-Used` and
+//! `X-Ratelimit-Reset` headers that Reddit sends with every response. `RedditClient` reads them
+//! after each request, and before the next request applies a `RateLimitPolicy` so that heavy
+//! consumers (e.g. crawlers paging through listings) slow down instead of receiving HTTP 429.
+//!
+//! If Reddit does answer with HTTP 429 (Too Many Requests), the request fails with
+//! `APIError::RateLimited`, or is retried after the advertised wait if retries are enabled with
+//! `RedditClient.set_rate_limit_retries()`.
+//! # Examples
+//! ```
+//! use http::StatusCode;
+//! use http::header::HeaderValue;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::client::ratelimit::RateLimitPolicy;
+//! use new_rawr::errors::APIError;
+//! use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+//!
+//! struct Exhausted;
+//!
+//! impl HttpBackend for Exhausted {
+//!     fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, APIError> {
+//!         let mut response = HttpResponse::new(StatusCode::OK, "{}");
+//!         response.headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0.0"));
+//!         response.headers.insert("x-ratelimit-used", HeaderValue::from_static("600"));
+//!         response.headers.insert("x-ratelimit-reset", HeaderValue::from_static("30"));
+//!         Ok(response)
+//!     }
+//! }
+//!
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(Exhausted));
+//! client.set_rate_limit_policy(RateLimitPolicy::Error);
+//! client.get_json("/r/rust/about", false).unwrap();
+//! assert_eq!(client.rate_limit().unwrap().used, 600);
+//! match client.get_json("/r/rust/about", false) {
+//!     Err(APIError::RateLimited { retry_after }) => assert!(retry_after.as_secs() <= 30),
+//!     other => panic!("Expected RateLimited, got {:?}", other),
+//! }
+//! ```
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use http::HeaderMap;
+
+use crate::clock;
+use crate::errors::APIError;
+
+/// What `RedditClient` does before sending a request, based on the rate limit reported in the
+/// last response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    /// Sends requests immediately until none remain, then waits for the rate limit window to
+    /// reset. This is the default.
+    Block,
+    /// Spreads the remaining requests evenly over the rest of the window, so that the limit is
+    /// never reached. Best for long-running crawlers.
+    Delay,
+    /// Fails with `APIError::RateLimited` (with the time until the window resets) instead of
+    /// waiting when no requests remain.
+    Error,
+    /// Sends every request immediately, as if there were no rate limit.
+    Ignore,
+}
+
+/// The rate limit reported by Reddit in the most recent response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitStatus {
+    /// The number of requests that can be sent before the window resets.
+    pub remaining: f64,
+    /// The number of requests sent in the current window.
+    pub used: u64,
+    /// The time until the window resets.
+    pub reset: Duration,
+}
+
+struct State {
+    remaining: f64,
+    used: u64,
+    reset: Duration,
+    /// When the headers were received, or `None` if the clock cannot be read.
+    received: Option<Instant>,
+}
+
+impl State {
+    /// The time until the window resets, or `None` if it cannot be known because the clock
+    /// cannot be read.
+    fn reset_in(&self) -> Option<Duration> {
+        let elapsed = clock::elapsed(self.received?)?;
+        Some(self.reset.checked_sub(elapsed).unwrap_or_default())
+    }
+}
+
+/// Tracks the rate limit of one client and applies its `RateLimitPolicy`. See the module-level
+/// documentation.
+pub struct RateLimiter {
+    policy: Mutex<RateLimitPolicy>,
+    state: Mutex<Option<State>>,
+    retries: Mutex<u32>,
+}
+
+impl RateLimiter {
+    /// Creates a `RateLimiter` with the specified policy, which has not seen any responses yet.
+    pub fn new(policy: RateLimitPolicy) -> RateLimiter {
+        RateLimiter {
+            policy: Mutex::new(policy),
+            state: Mutex::new(None),
+            retries: Mutex::new(0),
+        }
+    }
+
+    /// The policy applied before each request.
+    pub fn policy(&self) -> RateLimitPolicy {
+        *self.policy.lock().unwrap()
+    }
+
+    /// Changes the policy applied before each request.
+    pub fn set_policy(&self, policy: RateLimitPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    /// How many times a request that receives HTTP 429 is retried before failing.
+    pub fn retries(&self) -> u32 {
+        *self.retries.lock().unwrap()
+    }
+
+    /// Changes how many times a request that receives HTTP 429 is retried before failing.
+    pub fn set_retries(&self, retries: u32) {
+        *self.retries.lock().unwrap() = retries;
+    }
+
+    /// How long to wait before retrying after HTTP 429, from the `Retry-After` header or, if
+    /// that is missing, the time until the rate limit window resets. Defaults to a minute.
+    pub fn retry_after(&self, headers: &HeaderMap) -> Duration {
+        headers.get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs)
+            .or_else(|| self.status().map(|status| status.reset))
+            .unwrap_or(Duration::from_secs(60))
+    }
+
+    /// The current rate limit, or `None` if no response with rate limit headers has been
+    /// received yet.
+    pub fn status(&self) -> Option<RateLimitStatus> {
+        self.state.lock().unwrap().as_ref().map(|state| {
+            RateLimitStatus {
+                remaining: state.remaining,
+                used: state.used,
+                reset: state.reset_in().unwrap_or(state.reset),
+            }
+        })
+    }
+
+    /// Called before sending a request. Waits (or fails) as required by the policy, and counts
+    /// the request against the remaining requests. Returns how long it waited. Where the clock
+    /// cannot be read (e.g. on wasm32), the window cannot be tracked, so requests are sent
+    /// immediately; where the thread cannot wait, a wait fails with `RateLimited` instead.
+    pub fn acquire(&self) -> Result<Duration, APIError> {
+        let policy = self.policy();
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let state = match *state {
+                Some(ref mut state) => state,
+                None => return Ok(Duration::from_secs(0)),
+            };
+            let reset_in = match state.reset_in() {
+                Some(reset_in) => reset_in,
+                None => return Ok(Duration::from_secs(0)),
+            };
+            if policy == RateLimitPolicy::Ignore || reset_in == Duration::from_secs(0) {
+                return Ok(Duration::from_secs(0));
+            }
+            let available = state.remaining;
+            if available < 1.0 && policy == RateLimitPolicy::Error {
+                return Err(APIError::RateLimited { retry_after: reset_in });
+            }
+            state.remaining -= 1.0;
+            match policy {
+                _ if available < 1.0 => reset_in,
+                RateLimitPolicy::Delay => reset_in.div_f64(available),
+                _ => Duration::from_secs(0),
+            }
+        };
+        if wait > Duration::from_secs(0) && !clock::can_pause() {
+            return Err(APIError::RateLimited { retry_after: wait });
+        }
+        clock::pause(wait);
+        Ok(wait)
+    }
+
+    /// Called with the headers of every response. Responses without rate limit headers (e.g.
+    /// from a custom `HttpBackend`) are ignored.
+    pub fn update(&self, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let remaining = header("x-ratelimit-remaining").and_then(|value| value.parse().ok());
+        let reset = header("x-ratelimit-reset").and_then(|value| value.parse().ok());
+        let used = header("x-ratelimit-used").and_then(|value| value.parse().ok());
+        if let (Some(remaining), Some(reset)) = (remaining, reset) {
+            *self.state.lock().unwrap() = Some(State {
+                remaining,
+                used: used.unwrap_or(0),
+                reset: Duration::from_secs(reset),
+                received: clock::now(),
+            });
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> RateLimiter {
+        RateLimiter::new(RateLimitPolicy::Block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::HeaderMap;
+    use http::header::HeaderValue;
+
+    use super::{RateLimitPolicy, RateLimiter};
+    use crate::errors::APIError;
+
+    fn headers(values: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for &(name, value) in values {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn limiter(policy: RateLimitPolicy, remaining: &'static str) -> RateLimiter {
+        let limiter = RateLimiter::new(policy);
+        limiter.update(&headers(&[("x-ratelimit-remaining", remaining),
+                                  ("x-ratelimit-reset", "300"),
+                                  ("x-ratelimit-used", "4")]));
+        limiter
+    }
+
+    #[test]
+    fn parses_headers() {
+        let status = limiter(RateLimitPolicy::Block, "595.0").status().unwrap();
+        assert_eq!(status.remaining, 595.0);
+        assert_eq!(status.used, 4);
+        assert!(status.reset <= Duration::from_secs(300));
+        assert!(status.reset > Duration::from_secs(290));
+    }
+
+    #[test]
+    fn ignores_incomplete_headers() {
+        let limiter = RateLimiter::default();
+        limiter.update(&headers(&[("x-ratelimit-remaining", "10")]));
+        limiter.update(&headers(&[("x-ratelimit-remaining", "many"), ("x-ratelimit-reset", "5")]));
+        assert!(limiter.status().is_none());
+        assert_eq!(limiter.acquire().unwrap(), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn retry_after() {
+        let fresh = RateLimiter::default();
+        assert_eq!(fresh.retry_after(&headers(&[("retry-after", " 7 ")])), Duration::from_secs(7));
+        assert_eq!(fresh.retry_after(&HeaderMap::new()), Duration::from_secs(60));
+        let limited = limiter(RateLimitPolicy::Block, "0");
+        assert!(limited.retry_after(&HeaderMap::new()) <= Duration::from_secs(300));
+    }
+
+    #[test]
+    fn counts_requests() {
+        let limiter = limiter(RateLimitPolicy::Block, "10");
+        assert_eq!(limiter.acquire().unwrap(), Duration::from_secs(0));
+        assert_eq!(limiter.status().unwrap().remaining, 9.0);
+    }
+
+    #[test]
+    fn error_policy() {
+        match limiter(RateLimitPolicy::Error, "0").acquire() {
+            Err(APIError::RateLimited { retry_after }) => {
+                assert!(retry_after <= Duration::from_secs(300));
+            }
+            other => panic!("Expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ignore_policy() {
+        let limiter = limiter(RateLimitPolicy::Ignore, "0");
+        assert_eq!(limiter.acquire().unwrap(), Duration::from_secs(0));
+        assert_eq!(limiter.status().unwrap().remaining, 0.0);
+    }
+
+    #[test]
+    fn delay_policy() {
+        // 300 seconds for 60000 requests spreads them 5ms apart.
+        let limiter = limiter(RateLimitPolicy::Delay, "60000");
+        let wait = limiter.acquire().unwrap();
+        assert!(wait > Duration::from_millis(4) && wait <= Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,170 @@
+//! Retrying of requests that fail because Reddit is briefly unavailable. `RedditClient` asks its
+//! `RetryPolicy` what to do whenever a response has a 5xx status code (e.g. 503 Service
+//! Unavailable) or the `HttpBackend` fails to send the request at all. The default policy is
+//! `ExponentialBackoff`; use `RedditClient.set_retry_policy()` to replace it, or `NoRetry` to
+//! turn retrying off.
+//!
+//! HTTP 429 (Too Many Requests) is handled separately by the `ratelimit` module.
+//! # Examples
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::time::Duration;
+//! use http::StatusCode;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::client::retry::ExponentialBackoff;
+//! use new_rawr::errors::APIError;
+//! use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+//!
+//! /// Answers HTTP 503 to the first two requests.
+//! struct Flaky(AtomicUsize);
+//!
+//! impl HttpBackend for Flaky {
+//!     fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, APIError> {
+//!         if self.0.fetch_add(1, Ordering::SeqCst) < 2 {
+//!             return Ok(HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""));
+//!         }
+//!         Ok(HttpResponse::new(StatusCode::OK, "{}"))
+//!     }
+//! }
+//!
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(Flaky(AtomicUsize::new(0))));
+//! client.set_retry_policy(ExponentialBackoff::new(Duration::from_millis(1), 3));
+//! assert_eq!(client.get_json("/r/rust/about", false).unwrap(), "{}");
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use http::StatusCode;
+
+use crate::errors::APIError;
+use crate::http::HttpRequest;
+
+/// Why a request failed, as passed to `RetryPolicy::retry_delay()`.
+#[derive(Debug)]
+pub enum RetryCause<'a> {
+    /// Reddit answered with a 5xx status code.
+    ServerError(StatusCode),
+    /// The `HttpBackend` could not send the request or read the response.
+    Transport(&'a APIError),
+}
+
+/// Decides whether, and after how long, a failed request is sent again.
+pub trait RetryPolicy: Send + Sync {
+    /// Returns how long to wait before sending the request again, or `None` to give up. `attempt`
+    /// is the number of times the request has failed so far, starting at 1. When the policy gives
+    /// up, the client returns the last response (for server errors) or error (for transport
+    /// errors) as if there had been no retries.
+    fn retry_delay(&self, request: &HttpRequest, cause: &RetryCause, attempt: u32)
+                   -> Option<Duration>;
+}
+
+/// Never retries requests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn retry_delay(&self, _request: &HttpRequest, _cause: &RetryCause, _attempt: u32)
+                   -> Option<Duration> {
+        None
+    }
+}
+
+/// Retries requests after a delay that doubles with every attempt (up to a maximum), with
+/// random jitter so that several clients do not all retry at the same moment. This is the
+/// default policy, and retries up to 3 times starting with a delay of 1 second.
+///
+/// Backends fail with `APIError::ValidationError` when they refuse a request (e.g. a request
+/// with an invalid URL), so these errors are never retried.
+///
+/// Requests that change something on Reddit (see `HttpRequest::is_mutating()`) are not retried
+/// by default, because Reddit sometimes carries out the action despite answering with an error
+/// (e.g. submitting a post twice). Use `with_mutating()` to retry them too.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    base: Duration,
+    max_delay: Duration,
+    max_retries: u32,
+    jitter: bool,
+    mutating: bool,
+}
+
+impl ExponentialBackoff {
+    /// Creates a policy that retries up to `max_retries` times, waiting around `base` before the
+    /// first retry.
+    pub fn new(base: Duration, max_retries: u32) -> ExponentialBackoff {
+        ExponentialBackoff {
+            base,
+            max_delay: Duration::from_secs(30),
+            max_retries,
+            jitter: true,
+            mutating: false,
+        }
+    }
+
+    /// Sets the longest delay between two attempts. The default is 30 seconds.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> ExponentialBackoff {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Turns the random jitter on or off. Without jitter, the delays are exactly `base`,
+    /// `2 * base`, `4 * base` and so on.
+    pub fn with_jitter(mut self, jitter: bool) -> ExponentialBackoff {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Also retries requests that change something on Reddit.
+    pub fn with_mutating(mut self, mutating: bool) -> ExponentialBackoff {
+        self.mutating = mutating;
+        self
+    }
+
+    /// The delay before the specified retry (starting at 1), without jitter.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use new_rawr::client::retry::ExponentialBackoff;
+    /// let backoff = ExponentialBackoff::new(Duration::from_secs(1), 10)
+    ///     .with_max_delay(Duration::from_secs(5));
+    /// assert_eq!(backoff.delay(1), Duration::from_secs(1));
+    /// assert_eq!(backoff.delay(3), Duration::from_secs(4));
+    /// assert_eq!(backoff.delay(4), Duration::from_secs(5));
+    /// ```
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base.checked_mul(factor).unwrap_or(self.max_delay).min(self.max_delay)
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> ExponentialBackoff {
+        ExponentialBackoff::new(Duration::from_secs(1), 3)
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry_delay(&self, request: &HttpRequest, cause: &RetryCause, attempt: u32)
+                   -> Option<Duration> {
+        if attempt > self.max_retries || (request.is_mutating() && !self.mutating) {
+            return None;
+        }
+        if let RetryCause::Transport(&APIError::ValidationError(_)) = *cause {
+            // The backend refused the request itself, so sending it again would not help.
+            return None;
+        }
+        let delay = self.delay(attempt);
+        if !self.jitter {
+            return Some(delay);
+        }
+        // Between half and all of the delay. `RandomState` is randomly seeded, which is enough
+        // to spread clients out without depending on a random number crate.
+        let random = RandomState::new().build_hasher().finish();
+        Some(delay.mul_f64(0.5 + (random as f64 / u64::MAX as f64) / 2.0))
+    }
+}
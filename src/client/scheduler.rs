@@ -0,0 +1,145 @@
+//! Scheduling of requests that share one `RedditClient`. Requests are split into two priority
+//! classes: interactive requests (triggered directly by a user, e.g. replying) and background
+//! requests (sent by streams and crawlers). Background requests wait while any interactive
+//! request is in flight, so long-running crawlers do not delay user-facing actions when the
+//! client is shared between threads.
+
+use std::cell::Cell;
+use std::sync::{Condvar, Mutex};
+
+/// The priority class of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// A request triggered by a user that should be sent as soon as possible. This is the
+    /// default for all requests.
+    Interactive,
+    /// A request that can wait, such as a stream polling for new items or a crawler fetching
+    /// the next page.
+    Background,
+}
+
+thread_local! {
+    static CURRENT: Cell<Priority> = const { Cell::new(Priority::Interactive) };
+}
+
+/// The priority of requests sent from the current thread. See `RedditClient.with_priority()`.
+pub fn current_priority() -> Priority {
+    CURRENT.with(|current| current.get())
+}
+
+/// Runs the closure with requests sent from the current thread set to the specified priority,
+/// restoring the previous priority afterwards.
+pub fn with_priority<F, T>(priority: Priority, lambda: F) -> T
+    where F: FnOnce() -> T
+{
+    let previous = CURRENT.with(|current| current.replace(priority));
+    let res = lambda();
+    CURRENT.with(|current| current.set(previous));
+    res
+}
+
+/// Orders requests by priority. Interactive requests always run immediately; background
+/// requests wait until no interactive requests are running.
+#[derive(Default)]
+pub struct Scheduler {
+    interactive: Mutex<usize>,
+    idle: Condvar,
+}
+
+impl Scheduler {
+    /// Creates a new `Scheduler` with no requests in flight.
+    pub fn new() -> Scheduler {
+        Scheduler::default()
+    }
+
+    /// Runs the closure (which should send one request) once the priority allows it.
+    pub fn run<F, T>(&self, priority: Priority, lambda: F) -> T
+        where F: FnOnce() -> T
+    {
+        match priority {
+            Priority::Interactive => {
+                *self.interactive.lock().unwrap() += 1;
+                let res = lambda();
+                let mut interactive = self.interactive.lock().unwrap();
+                *interactive -= 1;
+                if *interactive == 0 {
+                    self.idle.notify_all();
+                }
+                res
+            }
+            Priority::Background => {
+                let mut interactive = self.interactive.lock().unwrap();
+                while *interactive > 0 {
+                    interactive = self.idle.wait(interactive).unwrap();
+                }
+                drop(interactive);
+                lambda()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{current_priority, with_priority, Priority, Scheduler};
+
+    #[test]
+    fn restores_priority() {
+        assert_eq!(current_priority(), Priority::Interactive);
+        with_priority(Priority::Background, || {
+            assert_eq!(current_priority(), Priority::Background);
+            with_priority(Priority::Interactive, || {
+                assert_eq!(current_priority(), Priority::Interactive);
+            });
+            assert_eq!(current_priority(), Priority::Background);
+        });
+        assert_eq!(current_priority(), Priority::Interactive);
+    }
+
+    #[test]
+    fn runs_without_contention() {
+        let scheduler = Scheduler::new();
+        assert_eq!(scheduler.run(Priority::Interactive, || 1), 1);
+        assert_eq!(scheduler.run(Priority::Background, || 2), 2);
+    }
+
+    #[test]
+    fn background_waits_for_interactive() {
+        let scheduler = Arc::new(Scheduler::new());
+        let (started, wait_started) = channel();
+        let (release, wait_release) = channel::<()>();
+        let interactive = {
+            let scheduler = Arc::clone(&scheduler);
+            thread::spawn(move || {
+                scheduler.run(Priority::Interactive, || {
+                    started.send(()).unwrap();
+                    wait_release.recv().unwrap();
+                })
+            })
+        };
+        wait_started.recv().unwrap();
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let background = {
+            let (scheduler, ran) = (Arc::clone(&scheduler), Arc::clone(&ran));
+            thread::spawn(move || {
+                scheduler.run(Priority::Background, || ran.store(true, Ordering::SeqCst))
+            })
+        };
+        // Interactive requests are not held up by the waiting background request.
+        scheduler.run(Priority::Interactive, || {});
+        thread::sleep(Duration::from_millis(50));
+        assert!(!ran.load(Ordering::SeqCst));
+
+        release.send(()).unwrap();
+        interactive.join().unwrap();
+        background.join().unwrap();
+        assert!(ran.load(Ordering::SeqCst));
+    }
+}
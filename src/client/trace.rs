@@ -0,0 +1,62 @@
+//! `tracing` instrumentation of the requests sent by `RedditClient`. Only compiled with the
+//! `tracing` feature.
+
+use std::time::Instant;
+
+use tracing::field::Empty;
+use tracing::span::EnteredSpan;
+
+use crate::client::metrics::endpoint;
+use crate::clock;
+use crate::client::ratelimit::RateLimitStatus;
+use crate::errors::APIError;
+use crate::http::{HttpRequest, HttpResponse};
+
+/// A `reddit_request` span, which stays entered (so retries are recorded inside it) until the
+/// request has finished.
+pub(crate) struct RequestTrace {
+    span: EnteredSpan,
+    started: Option<Instant>,
+}
+
+impl RequestTrace {
+    /// Enters the span for a request that is about to be sent.
+    pub fn start(request: &HttpRequest) -> RequestTrace {
+        let span = tracing::debug_span!("reddit_request",
+                                        method = %request.method,
+                                        endpoint = %endpoint(&request.url),
+                                        status = Empty,
+                                        latency_ms = Empty,
+                                        ratelimit_remaining = Empty);
+        RequestTrace {
+            span: span.entered(),
+            started: clock::now(),
+        }
+    }
+
+    /// Records the outcome of the request and leaves the span.
+    pub fn finish(self,
+                  result: &Result<HttpResponse, APIError>,
+                  rate_limit: Option<RateLimitStatus>) {
+        // The latency is unknown (and recorded as 0) where the clock cannot be read.
+        let latency = self.started.and_then(clock::elapsed).map_or(0, |latency| latency.as_millis() as u64);
+        if self.started.is_some() {
+            self.span.record("latency_ms", latency);
+        }
+        if let Some(rate_limit) = rate_limit {
+            self.span.record("ratelimit_remaining", rate_limit.remaining);
+        }
+        match *result {
+            Ok(ref response) => {
+                self.span.record("status", response.status.as_u16());
+                if response.status.is_success() {
+                    tracing::debug!(latency_ms = latency, "request finished");
+                } else {
+                    tracing::warn!(latency_ms = latency, status = response.status.as_u16(),
+                                   "request failed");
+                }
+            }
+            Err(ref err) => tracing::warn!(latency_ms = latency, error = ?err, "request failed"),
+        }
+    }
+}
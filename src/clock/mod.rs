@@ -0,0 +1,60 @@
+//! Reading the clock and sleeping, which are not available on every target: on
+//! wasm32-unknown-unknown, `Instant::now()` and `SystemTime::now()` panic, and there are no
+//! threads to put to sleep. Every clock read in the crate goes through this module, which returns
+//! `None` (or an error) on such targets, so that timing is skipped there instead of panicking.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::errors::APIError;
+
+/// `true` on targets where reading the clock panics.
+const NO_CLOCK: bool = cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+/// The current instant, or `None` if the clock cannot be read.
+pub fn now() -> Option<Instant> {
+    if NO_CLOCK {
+        None
+    } else {
+        Some(Instant::now())
+    }
+}
+
+/// The time since `start`, or `None` if the clock cannot be read.
+pub fn elapsed(start: Instant) -> Option<Duration> {
+    now().map(|now| now.saturating_duration_since(start))
+}
+
+/// The current system time, or `None` if the clock cannot be read.
+pub fn system_now() -> Option<SystemTime> {
+    if NO_CLOCK {
+        None
+    } else {
+        Some(SystemTime::now())
+    }
+}
+
+/// The time since the UNIX epoch. Fails if the clock cannot be read, or is set before 1970.
+pub fn unix_time() -> Result<Duration, APIError> {
+    let now = system_now().ok_or_else(|| {
+        APIError::Unsupported(String::from("The system clock cannot be read on this target"))
+    })?;
+    now.duration_since(UNIX_EPOCH).map_err(|_| {
+        APIError::IOError(io::Error::other("The system clock is set before 1970"))
+    })
+}
+
+/// `true` if `pause()` can wait. Loops that would otherwise retry immediately (e.g. after HTTP
+/// 429) give up instead when it cannot.
+pub fn can_pause() -> bool {
+    cfg!(not(target_arch = "wasm32"))
+}
+
+/// Blocks the current thread for the duration, or returns immediately on targets without
+/// threads (see `can_pause()`).
+pub fn pause(duration: Duration) {
+    if can_pause() {
+        thread::sleep(duration);
+    }
+}
@@ -0,0 +1,321 @@
+//! A central registry of the hosts and paths used to access the Reddit API. Structures refer to
+//! the constants in this module instead of writing out paths inline, so that every endpoint
+//! `new_rawr` uses can be found (and audited) in one place. Paths that contain a subreddit, user
+//! or ID are built by the functions in this module, which return the path without a query
+//! string.
+//!
+//! The hosts that requests are sent to are configured with an `Endpoints` object, which can be
+//! passed to `RedditClient::with_endpoints()` to point the client at an alternative deployment
+//! (e.g. a mock server for testing, a proxy or a Reddit-compatible mirror). This includes the
+//! host that authenticators fetch tokens from, so the client can log in to the deployment too.
+//! # Examples
+//! ```
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::endpoints::Endpoints;
+//! use new_rawr::http::HyperBackend;
+//! let client = RedditClient::with_endpoints("new_rawr",
+//!                                           AnonymousAuthenticator::new(),
+//!                                           Box::new(HyperBackend::new()),
+//!                                           Endpoints::with_host("http://localhost:8080"));
+//! assert_eq!(client.endpoints().api_host, "http://localhost:8080");
+//! ```
+
+/// The hosts used to send API requests. Paths (such as `VOTE`) are appended to these.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoints {
+    /// The host used for the legacy (non-OAuth) API, e.g. by `AnonymousAuthenticator`.
+    pub api_host: String,
+    /// The host used for OAuth-authenticated requests.
+    pub oauth_host: String,
+    /// The host that access tokens are requested from and revoked at.
+    pub auth_host: String,
+}
+
+impl Default for Endpoints {
+    /// The standard Reddit hosts (`https://api.reddit.com`, `https://oauth.reddit.com` and
+    /// `https://www.reddit.com`).
+    fn default() -> Endpoints {
+        Endpoints {
+            api_host: String::from(API_HOST),
+            oauth_host: String::from(OAUTH_HOST),
+            auth_host: String::from(AUTH_HOST),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Sends all requests (OAuth, non-OAuth and token requests) to the specified host. The host
+    /// should include the scheme, and may include a path prefix (e.g. for a proxy). A trailing
+    /// slash is removed, e.g. `http://localhost:8080/` becomes `http://localhost:8080`.
+    pub fn with_host(host: &str) -> Endpoints {
+        let host = host.trim_end_matches('/');
+        Endpoints {
+            api_host: host.to_owned(),
+            oauth_host: host.to_owned(),
+            auth_host: host.to_owned(),
+        }
+    }
+
+    /// Gets the host to use for a request, depending on whether it is sent over OAuth.
+    pub fn host(&self, oauth: bool) -> &str {
+        if oauth {
+            &self.oauth_host
+        } else {
+            &self.api_host
+        }
+    }
+
+    /// Moves a URL on the default token host (such as `ACCESS_TOKEN`) to `auth_host`. Other
+    /// URLs are returned unchanged.
+    /// # Examples
+    /// ```
+    /// use new_rawr::endpoints::{self, Endpoints};
+    /// let local = Endpoints::with_host("http://localhost:8080/");
+    /// assert_eq!(local.auth_url(endpoints::ACCESS_TOKEN),
+    ///            "http://localhost:8080/api/v1/access_token");
+    /// assert_eq!(local.auth_url("https://example.com/x"), "https://example.com/x");
+    /// ```
+    pub fn auth_url(&self, url: &str) -> String {
+        match url.strip_prefix(AUTH_HOST) {
+            Some(path) if path.is_empty() || path.starts_with('/') => {
+                format!("{}{}", self.auth_host, path)
+            }
+            _ => url.to_owned(),
+        }
+    }
+}
+
+/// The default host for the legacy API.
+pub const API_HOST: &str = "https://api.reddit.com";
+/// The default host for the OAuth API.
+pub const OAUTH_HOST: &str = "https://oauth.reddit.com";
+/// The default host for token requests and the authorization page.
+pub const AUTH_HOST: &str = "https://www.reddit.com";
+/// The page where users grant an app access to their account in the authorization code flow.
+pub const AUTHORIZE: &str = "https://www.reddit.com/api/v1/authorize";
+/// Exchanges credentials for an OAuth access token.
+pub const ACCESS_TOKEN: &str = "https://www.reddit.com/api/v1/access_token";
+/// Revokes an OAuth access or refresh token.
+pub const REVOKE_TOKEN: &str = "https://www.reddit.com/api/v1/revoke_token";
+
+/// Approves a submission, comment or message.
+pub const APPROVE: &str = "/api/approve";
+/// Lists the users that the current user has blocked (OAuth only).
+pub const BLOCKED: &str = "/prefs/blocked";
+/// Replies to a submission, comment or message.
+pub const COMMENT: &str = "/api/comment";
+/// Sends a private message.
+pub const COMPOSE: &str = "/api/compose";
+/// Deletes a submission or comment.
+pub const DELETE: &str = "/api/del";
+/// Deletes a private message.
+pub const DELETE_MESSAGE: &str = "/api/del_msg";
+/// Distinguishes (or undistinguishes) a submission or comment.
+pub const DISTINGUISH: &str = "/api/distinguish";
+/// Edits the body of a submission, comment or message.
+pub const EDIT: &str = "/api/editusertext";
+/// Lists the post flairs that can be selected, and the current flair. The subreddit is
+/// prepended with `flair_selector()`.
+pub const FLAIR_SELECTOR: &str = "/api/flairselector";
+/// Hides a submission.
+pub const HIDE: &str = "/api/hide";
+/// Ignores reports on an item.
+pub const IGNORE_REPORTS: &str = "/api/ignore_reports";
+/// Lists the messages in the current user's inbox.
+pub const INBOX: &str = "/message/inbox";
+/// Fetches submissions, comments or subreddits by their full names.
+pub const INFO: &str = "/api/info";
+/// Locks a submission.
+pub const LOCK: &str = "/api/lock";
+/// Marks a submission as NSFW.
+pub const MARK_NSFW: &str = "/api/marknsfw";
+/// Creates or lists new modmail conversations (OAuth only).
+pub const MOD_CONVERSATIONS: &str = "/api/mod/conversations";
+/// Fetches the children of a 'more comments' item.
+pub const MORE_CHILDREN: &str = "/api/morechildren";
+/// Marks a message as read.
+pub const READ_MESSAGE: &str = "/api/read_message";
+/// Removes a submission, comment or message.
+pub const REMOVE: &str = "/api/remove";
+/// Sends a removal message for a removed comment (OAuth only).
+pub const REMOVAL_COMMENT_MESSAGE: &str = "/api/v1/modactions/removal_comment_message";
+/// Sends a removal message for a removed submission (OAuth only).
+pub const REMOVAL_LINK_MESSAGE: &str = "/api/v1/modactions/removal_link_message";
+/// Reports an item.
+pub const REPORT: &str = "/api/report";
+/// Gets or updates the structured styles ('community appearance') of a subreddit. The subreddit
+/// name is appended to this path.
+pub const STRUCTURED_STYLES: &str = "/api/v1/structured_styles";
+/// Requests an upload lease for a subreddit style image. The subreddit name is appended to
+/// this path.
+pub const STYLE_ASSET_UPLOAD: &str = "/api/v1/style_asset_upload_s3";
+/// Stickies (or unstickies) a submission.
+pub const SET_SUBREDDIT_STICKY: &str = "/api/set_subreddit_sticky";
+/// Submits a link or self post.
+pub const SUBMIT: &str = "/api/submit";
+/// Subscribes to (or unsubscribes from) a subreddit.
+pub const SUBSCRIBE: &str = "/api/subscribe";
+/// Lists the searches that are trending on Reddit.
+pub const TRENDING_SEARCHES: &str = "/api/trending_searches_v1";
+/// Unhides a submission.
+pub const UNHIDE: &str = "/api/unhide";
+/// Stops ignoring reports on an item.
+pub const UNIGNORE_REPORTS: &str = "/api/unignore_reports";
+/// Unlocks a submission.
+pub const UNLOCK: &str = "/api/unlock";
+/// Marks a submission as not NSFW.
+pub const UNMARK_NSFW: &str = "/api/unmarknsfw";
+/// Lists the unread messages in the current user's inbox.
+pub const UNREAD: &str = "/message/unread";
+/// Votes on a submission or comment.
+pub const VOTE: &str = "/api/vote";
+
+/// `POST` endpoints that only read data, so they are still allowed when the client is in
+/// read-only mode (see `RedditClient.set_read_only()`).
+pub const READ_ONLY_POSTS: &[&str] = &[MORE_CHILDREN, FLAIR_SELECTOR];
+
+/// Fetches submissions by their full names (e.g. `t3_4uule8`), separated by commas.
+pub fn by_id(names: &str) -> String {
+    format!("/by_id/{}", names)
+}
+
+/// Fetches a submission and its comments, by the ID of the submission (without `t3_`).
+pub fn comments(id: &str) -> String {
+    format!("/comments/{}", id)
+}
+
+/// Lists the approved submitters of a subreddit.
+pub fn contributors(subreddit: &str) -> String {
+    format!("/r/{}/about/contributors", subreddit)
+}
+
+/// Deletes the flair of a user in a subreddit.
+pub fn delete_flair(subreddit: &str) -> String {
+    format!("/r/{}/api/deleteflair", subreddit)
+}
+
+/// Sets the flair of several users in a subreddit at once, from CSV.
+pub fn flair_csv(subreddit: &str) -> String {
+    format!("/r/{}/api/flaircsv", subreddit)
+}
+
+/// Lists the user flairs of a subreddit.
+pub fn flair_list(subreddit: &str) -> String {
+    format!("/r/{}/api/flairlist", subreddit)
+}
+
+/// `FLAIR_SELECTOR` in a subreddit.
+pub fn flair_selector(subreddit: &str) -> String {
+    format!("/r/{}{}", subreddit, FLAIR_SELECTOR)
+}
+
+/// Adds a user to one of the lists of a subreddit (e.g. to invite a moderator).
+pub fn friend(subreddit: &str) -> String {
+    format!("/r/{}/api/friend", subreddit)
+}
+
+/// Lists the post flair templates of a subreddit.
+pub fn link_flair(subreddit: &str) -> String {
+    format!("/r/{}/api/link_flair_v2", subreddit)
+}
+
+/// Fetches a message and its replies, by the ID of the message (without `t4_`).
+pub fn message(id: &str) -> String {
+    format!("/message/messages/{}", id)
+}
+
+/// Lists the actions in the moderation log of a subreddit.
+pub fn mod_log(subreddit: &str) -> String {
+    format!("/r/{}/about/log", subreddit)
+}
+
+/// Lists the moderators of a subreddit.
+pub fn moderators(subreddit: &str) -> String {
+    format!("/r/{}/about/moderators", subreddit)
+}
+
+/// Fetches a multireddit of a user.
+pub fn multireddit(user: &str, name: &str) -> String {
+    format!("/api/multi/user/{}/m/{}", user, name)
+}
+
+/// Fetches the requirements that posts to a subreddit must meet (OAuth only).
+pub fn post_requirements(subreddit: &str) -> String {
+    format!("/api/v1/{}/post_requirements", subreddit)
+}
+
+/// Lists the users in one of the lists of a subreddit (e.g. `banned` or `muted`).
+pub fn relationships(subreddit: &str, list: &str) -> String {
+    format!("/r/{}/about/{}", subreddit, list)
+}
+
+/// Lists the rules of a subreddit.
+pub fn rules(subreddit: &str) -> String {
+    format!("/r/{}/about/rules", subreddit)
+}
+
+/// Selects the flair of a submission or user in a subreddit.
+pub fn select_flair(subreddit: &str) -> String {
+    format!("/r/{}/api/selectflair", subreddit)
+}
+
+/// Changes the permissions of a moderator of a subreddit.
+pub fn set_permissions(subreddit: &str) -> String {
+    format!("/r/{}/api/setpermissions", subreddit)
+}
+
+/// Fetches information about a subreddit.
+pub fn subreddit_about(subreddit: &str) -> String {
+    format!("/r/{}/about", subreddit)
+}
+
+/// Lists the submissions in a subreddit by a sort order (e.g. `hot` or `new`).
+pub fn subreddit_listing(subreddit: &str, sort: &str) -> String {
+    format!("/r/{}/{}", subreddit, sort)
+}
+
+/// Searches the submissions in a subreddit.
+pub fn subreddit_search(subreddit: &str) -> String {
+    format!("/r/{}/search", subreddit)
+}
+
+/// Removes a user from one of the lists of a subreddit (e.g. to remove a moderator).
+pub fn unfriend(subreddit: &str) -> String {
+    format!("/r/{}/api/unfriend", subreddit)
+}
+
+/// Fetches information about a user.
+pub fn user_about(user: &str) -> String {
+    format!("/user/{}/about", user)
+}
+
+/// Lists the comments of a user.
+pub fn user_comments(user: &str) -> String {
+    format!("/user/{}/comments", user)
+}
+
+/// Lists the items saved by a user.
+pub fn user_saved(user: &str) -> String {
+    format!("/user/{}/saved", user)
+}
+
+/// Lists the submissions of a user.
+pub fn user_submitted(user: &str) -> String {
+    format!("/user/{}/submitted", user)
+}
+
+/// Lists the widgets (e.g. the sidebar) of a subreddit.
+pub fn widgets(subreddit: &str) -> String {
+    format!("/r/{}/api/widgets", subreddit)
+}
+
+/// Edits a wiki page of a subreddit.
+pub fn wiki_edit(subreddit: &str) -> String {
+    format!("/r/{}/api/wiki/edit", subreddit)
+}
+
+/// Fetches a wiki page of a subreddit.
+pub fn wiki_page(subreddit: &str, page: &str) -> String {
+    format!("/r/{}/wiki/{}", subreddit, page)
+}
@@ -1,9 +1,11 @@
 use std::error::Error;
+use std::io;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
 
-use hyper;
-use hyper::StatusCode;
-use serde_json;
+use http::StatusCode;
+use serde::de::Error as DeError;
+use serde_json::{self, Value};
 
 /// Error type that occurs when an API request fails for some reason.
 #[derive(Debug)]
@@ -16,17 +18,425 @@ pub enum APIError {
     /// - 403 Forbidden - you are not allowed to access this, but your request was valid.
     HTTPError(StatusCode),
     /// Occurs if the HTTP response from Reddit was corrupt and Hyper could not parse it.
+    #[cfg(feature = "hyper-backend")]
     HyperError(hyper::Error),
     /// Occurs if JSON deserialization fails. This will always be a bug, so please report it
     /// if it does occur, but the error type is provided so you can fail gracefully.
     JSONError(serde_json::Error),
     ///The token has expired.
     ExpiredToken,
+    /// Occurs when reading or writing a local file fails, e.g. when saving a downloaded image,
+    /// or when a request times out (with `io::ErrorKind::TimedOut`).
+    IOError(io::Error),
+    /// Occurs when data is rejected by a client-side check before being sent to Reddit. The
+    /// string explains what was wrong.
+    ValidationError(String),
+    /// Occurs when Reddit refuses an action (e.g. posting or commenting) because the account
+    /// is doing it too often, or answers any request with HTTP 429 (Too Many Requests).
+    /// `retry_after` is how long to wait before trying again.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::thread;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::errors::APIError;
+    /// use new_rawr::traits::Commentable;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// loop {
+    ///     match post.reply("Hello!") {
+    ///         Err(APIError::RateLimited { retry_after }) => thread::sleep(retry_after),
+    ///         result => break result.map(|_| ()).expect("Could not reply"),
+    ///     }
+    /// }
+    /// ```
+    RateLimited {
+        /// How long to wait before trying again.
+        retry_after: Duration,
+    },
+    /// Occurs when a request that would change something on Reddit (e.g. voting, replying or
+    /// removing) is made while the client is in read-only mode. The request is not sent. See
+    /// `RedditClient.set_read_only()`.
+    ReadOnlyMode,
+    /// Occurs when replying to or voting on a post or comment that Reddit has archived (usually
+    /// after 6 months), which would always fail. The request is not sent if the archived status
+    /// is already known; otherwise this is Reddit's `TOO_OLD` error.
+    Archived,
+    /// Occurs when the action is not supported by Reddit's API (e.g. commenting as a subreddit),
+    /// so the request is not sent. The string explains what to do instead.
+    Unsupported(String),
+    /// Occurs when Reddit reports that a subreddit does not exist (`SUBREDDIT_NOEXIST`).
+    SubredditNotFound,
+    /// Occurs when replying to a post or comment that a moderator has locked (`THREAD_LOCKED`).
+    ThreadLocked,
+    /// Occurs when Reddit asks for a captcha to be solved (`BAD_CAPTCHA`), which this library
+    /// cannot do. This usually only happens to new accounts.
+    BadCaptcha,
+    /// Occurs when Reddit rejects a request with any other error code in its JSON response
+    /// (e.g. submitting a link that has already been posted). Rate limits are reported as
+    /// `RateLimited`, and the common errors above have their own variants.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::errors::{APIError, RedditErrorCode};
+    /// use new_rawr::traits::Commentable;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// match post.reply("Hello!").map(|_| ()) {
+    ///     Err(APIError::ThreadLocked) => println!("Locked"),
+    ///     Err(APIError::Archived) => println!("Archived"),
+    ///     Err(APIError::RedditError(ref details)) if details.code == RedditErrorCode::TooLong => {
+    ///         println!("Too long: {}", details.message)
+    ///     }
+    ///     result => result.expect("Could not reply"),
+    /// }
+    /// ```
+    RedditError(RedditErrorDetails),
+}
+
+/// An error reported by Reddit in the `errors` of an `api_type=json` response, as carried by
+/// `APIError::RedditError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedditErrorDetails {
+    /// The HTTP status code of the response. Reddit reports most of these errors with 200 OK.
+    pub status: StatusCode,
+    /// The error code.
+    pub code: RedditErrorCode,
+    /// Reddit's explanation of the error. This may be localized, so match on `code` instead.
+    pub message: String,
+    /// The request parameter that caused the error (e.g. `title`), if Reddit named one.
+    pub field: Option<String>,
+}
+
+/// The error codes that Reddit returns in the `errors` of `api_type=json` responses. Codes that
+/// are not listed here are kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RedditErrorCode {
+    /// `ALREADY_SUB`: the link has already been submitted to the subreddit.
+    AlreadySubmitted,
+    /// `BAD_CAPTCHA`: a captcha must be solved.
+    BadCaptcha,
+    /// `BAD_SR_NAME`: the subreddit name is invalid.
+    BadSubredditName,
+    /// `BAD_URL`: the URL is invalid.
+    BadUrl,
+    /// `DELETED_COMMENT`: the comment being replied to has been deleted.
+    DeletedComment,
+    /// `DELETED_LINK`: the post being replied to has been deleted.
+    DeletedLink,
+    /// `INVALID_OPTION`: a parameter had a value that is not allowed.
+    InvalidOption,
+    /// `NO_LINKS`: the subreddit only allows text posts.
+    NoLinks,
+    /// `NO_SELFS`: the subreddit does not allow text posts.
+    NoSelfs,
+    /// `NO_TEXT`: a required field (e.g. a title or comment body) was empty.
+    NoText,
+    /// `NOT_AUTHOR`: only the author can do this, e.g. edit a post.
+    NotAuthor,
+    /// `RATELIMIT`: the account is doing this too often.
+    RateLimit,
+    /// `SUBMIT_VALIDATION_FLAIR_REQUIRED`: the subreddit requires posts to have flair.
+    FlairRequired,
+    /// `SUBREDDIT_NOEXIST`: the subreddit does not exist.
+    SubredditNoExist,
+    /// `SUBREDDIT_NOTALLOWED`: the account is not allowed to post in the subreddit, e.g.
+    /// because it is private or restricted.
+    SubredditNotAllowed,
+    /// `THREAD_LOCKED`: the post or comment has been locked by a moderator.
+    ThreadLocked,
+    /// `TOO_LONG`: a field is longer than Reddit allows.
+    TooLong,
+    /// `TOO_OLD`: the post has been archived.
+    TooOld,
+    /// `USER_DOESNT_EXIST`: the user does not exist.
+    UserDoesntExist,
+    /// `USER_REQUIRED`: the request must be made while logged in.
+    UserRequired,
+    /// Any other code.
+    Other(String),
+}
+
+impl RedditErrorCode {
+    /// Converts a code string from Reddit, such as `THREAD_LOCKED`.
+    /// # Examples
+    /// ```
+    /// use new_rawr::errors::RedditErrorCode;
+    /// assert_eq!(RedditErrorCode::from_code("TOO_OLD"), RedditErrorCode::TooOld);
+    /// let other = RedditErrorCode::from_code("SOMETHING_NEW");
+    /// assert_eq!(other, RedditErrorCode::Other(String::from("SOMETHING_NEW")));
+    /// assert_eq!(other.code(), "SOMETHING_NEW");
+    /// ```
+    pub fn from_code(code: &str) -> RedditErrorCode {
+        match code {
+            "ALREADY_SUB" => RedditErrorCode::AlreadySubmitted,
+            "BAD_CAPTCHA" => RedditErrorCode::BadCaptcha,
+            "BAD_SR_NAME" => RedditErrorCode::BadSubredditName,
+            "BAD_URL" => RedditErrorCode::BadUrl,
+            "DELETED_COMMENT" => RedditErrorCode::DeletedComment,
+            "DELETED_LINK" => RedditErrorCode::DeletedLink,
+            "INVALID_OPTION" => RedditErrorCode::InvalidOption,
+            "NO_LINKS" => RedditErrorCode::NoLinks,
+            "NO_SELFS" => RedditErrorCode::NoSelfs,
+            "NO_TEXT" => RedditErrorCode::NoText,
+            "NOT_AUTHOR" => RedditErrorCode::NotAuthor,
+            "RATELIMIT" => RedditErrorCode::RateLimit,
+            "SUBMIT_VALIDATION_FLAIR_REQUIRED" => RedditErrorCode::FlairRequired,
+            "SUBREDDIT_NOEXIST" => RedditErrorCode::SubredditNoExist,
+            "SUBREDDIT_NOTALLOWED" => RedditErrorCode::SubredditNotAllowed,
+            "THREAD_LOCKED" => RedditErrorCode::ThreadLocked,
+            "TOO_LONG" => RedditErrorCode::TooLong,
+            "TOO_OLD" => RedditErrorCode::TooOld,
+            "USER_DOESNT_EXIST" => RedditErrorCode::UserDoesntExist,
+            "USER_REQUIRED" => RedditErrorCode::UserRequired,
+            other => RedditErrorCode::Other(other.to_owned()),
+        }
+    }
+
+    /// The code string used by Reddit.
+    pub fn code(&self) -> &str {
+        match *self {
+            RedditErrorCode::AlreadySubmitted => "ALREADY_SUB",
+            RedditErrorCode::BadCaptcha => "BAD_CAPTCHA",
+            RedditErrorCode::BadSubredditName => "BAD_SR_NAME",
+            RedditErrorCode::BadUrl => "BAD_URL",
+            RedditErrorCode::DeletedComment => "DELETED_COMMENT",
+            RedditErrorCode::DeletedLink => "DELETED_LINK",
+            RedditErrorCode::InvalidOption => "INVALID_OPTION",
+            RedditErrorCode::NoLinks => "NO_LINKS",
+            RedditErrorCode::NoSelfs => "NO_SELFS",
+            RedditErrorCode::NoText => "NO_TEXT",
+            RedditErrorCode::NotAuthor => "NOT_AUTHOR",
+            RedditErrorCode::RateLimit => "RATELIMIT",
+            RedditErrorCode::FlairRequired => "SUBMIT_VALIDATION_FLAIR_REQUIRED",
+            RedditErrorCode::SubredditNoExist => "SUBREDDIT_NOEXIST",
+            RedditErrorCode::SubredditNotAllowed => "SUBREDDIT_NOTALLOWED",
+            RedditErrorCode::ThreadLocked => "THREAD_LOCKED",
+            RedditErrorCode::TooLong => "TOO_LONG",
+            RedditErrorCode::TooOld => "TOO_OLD",
+            RedditErrorCode::UserDoesntExist => "USER_DOESNT_EXIST",
+            RedditErrorCode::UserRequired => "USER_REQUIRED",
+            RedditErrorCode::Other(ref code) => code,
+        }
+    }
+}
+
+impl APIError {
+    /// Checks a response from an `api_type=json` endpoint for a rate limit error, which Reddit
+    /// reports with a success status code. Responses that are not JSON envelopes pass.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use new_rawr::errors::APIError;
+    /// let body = r#"{"json": {"ratelimit": 540.5, "errors": [["RATELIMIT",
+    ///     "you are doing that too much. try again in 9 minutes.", "ratelimit"]]}}"#;
+    /// match APIError::check_ratelimit(body) {
+    ///     Err(APIError::RateLimited { retry_after }) => {
+    ///         assert_eq!(retry_after, Duration::from_millis(540500))
+    ///     }
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// let body = r#"{"json": {"errors": [["RATELIMIT",
+    ///     "Take a break for 2 minutes before trying again.", "ratelimit"]]}}"#;
+    /// match APIError::check_ratelimit(body) {
+    ///     Err(APIError::RateLimited { retry_after }) => {
+    ///         assert_eq!(retry_after, Duration::from_secs(120))
+    ///     }
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// assert!(APIError::check_ratelimit(r#"{"json": {"errors": []}}"#).is_ok());
+    /// ```
+    pub fn check_ratelimit(body: &str) -> Result<(), APIError> {
+        let value: Value = match serde_json::from_str(body) {
+            Ok(value) => value,
+            Err(_) => return Ok(()),
+        };
+        let json = &value["json"];
+        if let Some(seconds) = json["ratelimit"].as_f64() {
+            return Err(APIError::RateLimited {
+                retry_after: Duration::from_millis((seconds * 1000.0) as u64),
+            });
+        }
+        let errors = json["errors"].as_array().map(|e| e.as_slice()).unwrap_or(&[]);
+        for error in errors {
+            if error[0] == "RATELIMIT" {
+                let wait = error[1].as_str().and_then(parse_wait).unwrap_or(Duration::from_secs(60));
+                return Err(APIError::RateLimited { retry_after: wait });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl APIError {
+    /// Checks a response from an `api_type=json` endpoint for errors, which Reddit reports with
+    /// a success status code. Rate limits become `RateLimited` (see `check_ratelimit()`), and the
+    /// first other error becomes a typed variant (see `from_details()`). Responses that are not
+    /// JSON envelopes pass.
+    /// # Examples
+    /// ```
+    /// use new_rawr::errors::{APIError, RedditErrorCode};
+    /// let body = r#"{"json": {"errors": [["TOO_LONG",
+    ///     "this is too long (max: 300)", "title"]]}}"#;
+    /// match APIError::check_errors(body) {
+    ///     Err(APIError::RedditError(details)) => {
+    ///         assert_eq!(details.code, RedditErrorCode::TooLong);
+    ///         assert_eq!(details.field.as_ref().map(|f| f.as_str()), Some("title"));
+    ///     }
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// let locked = r#"{"json": {"errors": [["THREAD_LOCKED", "Comments are locked.", "parent"]]}}"#;
+    /// assert!(matches!(APIError::check_errors(locked), Err(APIError::ThreadLocked)));
+    /// assert!(APIError::check_errors(r#"{"json": {"errors": []}}"#).is_ok());
+    /// ```
+    pub fn check_errors(body: &str) -> Result<(), APIError> {
+        APIError::check_ratelimit(body)?;
+        match APIError::parse_errors(StatusCode::OK, body).into_iter().next() {
+            Some(details) => Err(APIError::from_details(details)),
+            None => Ok(()),
+        }
+    }
+
+    /// Converts a response with a non-success status code into an error. If the body has
+    /// Reddit's JSON `errors`, the first one becomes a typed variant (see `from_details()`);
+    /// otherwise this is `HTTPError`.
+    /// # Examples
+    /// ```
+    /// use http::StatusCode;
+    /// use new_rawr::errors::APIError;
+    /// let body = r#"{"json": {"errors": [["BAD_CAPTCHA", "care to try these again?", "captcha"]]}}"#;
+    /// let error = APIError::from_response(StatusCode::BAD_REQUEST, body);
+    /// assert!(matches!(error, APIError::BadCaptcha));
+    /// let error = APIError::from_response(StatusCode::FORBIDDEN, r#"{"message": "Forbidden"}"#);
+    /// assert!(matches!(error, APIError::HTTPError(StatusCode::FORBIDDEN)));
+    /// ```
+    pub fn from_response(status: StatusCode, body: &str) -> APIError {
+        if let Err(err @ APIError::RateLimited { .. }) = APIError::check_ratelimit(body) {
+            return err;
+        }
+        match APIError::parse_errors(status, body).into_iter().next() {
+            Some(details) => APIError::from_details(details),
+            None => APIError::HTTPError(status),
+        }
+    }
+
+    /// Converts an error reported by Reddit into the matching variant: `SubredditNotFound`,
+    /// `ThreadLocked`, `Archived`, `BadCaptcha`, `RateLimited`, or `RedditError` for any other
+    /// code.
+    pub fn from_details(details: RedditErrorDetails) -> APIError {
+        match details.code {
+            RedditErrorCode::SubredditNoExist => APIError::SubredditNotFound,
+            RedditErrorCode::ThreadLocked => APIError::ThreadLocked,
+            RedditErrorCode::TooOld => APIError::Archived,
+            RedditErrorCode::BadCaptcha => APIError::BadCaptcha,
+            RedditErrorCode::RateLimit => {
+                APIError::RateLimited {
+                    retry_after: parse_wait(&details.message).unwrap_or(Duration::from_secs(60)),
+                }
+            }
+            _ => APIError::RedditError(details),
+        }
+    }
+
+    /// Reads every error in the `json.errors` array of a response body. Each error is an array
+    /// of the code, the message and (optionally) the field that caused it. Returns an empty list
+    /// if the body is not a JSON envelope or has no errors.
+    pub fn parse_errors(status: StatusCode, body: &str) -> Vec<RedditErrorDetails> {
+        let value: Value = match serde_json::from_str(body) {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+        let errors = value["json"]["errors"].as_array().map(|e| e.as_slice()).unwrap_or(&[]);
+        errors.iter()
+            .filter_map(|error| {
+                let code = error[0].as_str()?;
+                Some(RedditErrorDetails {
+                    status,
+                    code: RedditErrorCode::from_code(code),
+                    message: error[1].as_str().unwrap_or("").to_owned(),
+                    field: error[2].as_str().map(|field| field.to_owned()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl APIError {
+    /// A `JSONError` for a response that was valid JSON, but did not contain something that it
+    /// always should (e.g. the comment that was just posted).
+    pub(crate) fn missing(what: &str) -> APIError {
+        APIError::JSONError(serde_json::Error::custom(format!("the response had no {}", what)))
+    }
+}
+
+/// Reads the wait from a message such as "try again in 9 minutes." or "Take a break for 2
+/// minutes before trying again.", for responses that do not include the `ratelimit` field.
+fn parse_wait(message: &str) -> Option<Duration> {
+    let words = message.split_whitespace().collect::<Vec<_>>();
+    words.windows(2).find_map(|pair| {
+        let amount: u64 = pair[0].parse().ok()?;
+        let unit = pair[1].to_lowercase();
+        if unit.starts_with("millisecond") {
+            Some(Duration::from_millis(amount))
+        } else if unit.starts_with("second") {
+            Some(Duration::from_secs(amount))
+        } else if unit.starts_with("minute") {
+            Some(Duration::from_secs(amount * 60))
+        } else if unit.starts_with("hour") {
+            Some(Duration::from_secs(amount * 60 * 60))
+        } else {
+            None
+        }
+    })
 }
 
 impl Display for APIError {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(f, "Error! {}. ({:?})", self, self)
+        match *self {
+            APIError::ExhaustedListing => write!(f, "The listing has no more results"),
+            APIError::HTTPError(status) => write!(f, "Reddit returned HTTP {}", status),
+            #[cfg(feature = "hyper-backend")]
+            APIError::HyperError(ref err) => {
+                write!(f, "The HTTP response could not be processed: {}", err)
+            }
+            APIError::JSONError(ref err) => {
+                write!(f,
+                       "The JSON sent by Reddit did not match what new_rawr was expecting: {}",
+                       err)
+            }
+            APIError::ExpiredToken => write!(f, "The access token has expired"),
+            APIError::IOError(ref err) => write!(f, "I/O error: {}", err),
+            APIError::ValidationError(ref message) => {
+                write!(f, "The data failed validation and was not sent: {}", message)
+            }
+            APIError::RateLimited { retry_after } => {
+                write!(f,
+                       "Reddit is rate limiting this action; try again in {} seconds",
+                       retry_after.as_secs())
+            }
+            APIError::ReadOnlyMode => {
+                write!(f, "The client is in read-only mode, so the request was not sent")
+            }
+            APIError::Archived => {
+                write!(f, "The content is archived, so it cannot be replied to or voted on")
+            }
+            APIError::Unsupported(ref message) => {
+                write!(f, "Reddit's API does not support this action: {}", message)
+            }
+            APIError::SubredditNotFound => write!(f, "The subreddit does not exist"),
+            APIError::ThreadLocked => {
+                write!(f, "The thread is locked, so it cannot be replied to")
+            }
+            APIError::BadCaptcha => write!(f, "Reddit requires a captcha to be solved"),
+            APIError::RedditError(ref details) => {
+                write!(f, "Reddit rejected the request: {}", details.message)?;
+                match details.field {
+                    Some(ref field) => write!(f, " ({:?}, field `{}`)", details.code, field),
+                    None => write!(f, " ({:?})", details.code),
+                }
+            }
+        }
     }
 }
 
@@ -34,6 +444,7 @@ impl Error for APIError {
     fn description(&self) -> &str {
         match *self {
             APIError::HTTPError(_) => "The API returned a non-success error code",
+            #[cfg(feature = "hyper-backend")]
             APIError::HyperError(_) => "An error occurred while processing the HTTP response",
             APIError::JSONError(_) => {
                 "The JSON sent by Reddit did not match what new_rawr was expecting"
@@ -41,19 +452,65 @@ impl Error for APIError {
             APIError::ExpiredToken => {
                 "ExpiredToken"
             }
+            APIError::ValidationError(_) => "The data failed validation and was not sent",
+            APIError::IOError(_) => "A local file could not be read or written, or a request timed out",
+            APIError::RateLimited { .. } => "Reddit is rate limiting this action",
+            APIError::ReadOnlyMode => "The client is in read-only mode, so the request was not sent",
+            APIError::Archived => "The content is archived, so it cannot be replied to or voted on",
+            APIError::Unsupported(_) => "Reddit's API does not support this action",
+            APIError::SubredditNotFound => "The subreddit does not exist",
+            APIError::ThreadLocked => "The thread is locked, so it cannot be replied to",
+            APIError::BadCaptcha => "Reddit requires a captcha to be solved",
+            APIError::RedditError(..) => "Reddit rejected the request",
             _ => "This error should not have occurred. Please file a bug",
         }
     }
 }
 
+#[cfg(feature = "hyper-backend")]
 impl From<hyper::Error> for APIError {
     fn from(err: hyper::Error) -> APIError {
         APIError::HyperError(err)
     }
 }
 
+impl From<io::Error> for APIError {
+    fn from(err: io::Error) -> APIError {
+        APIError::IOError(err)
+    }
+}
+
 impl From<serde_json::Error> for APIError {
     fn from(err: serde_json::Error) -> APIError {
         APIError::JSONError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::{APIError, RedditErrorCode, RedditErrorDetails};
+
+    #[test]
+    fn displays_variants() {
+        assert_eq!(APIError::HTTPError(StatusCode::FORBIDDEN).to_string(),
+                   "Reddit returned HTTP 403 Forbidden");
+        assert_eq!(APIError::ValidationError(String::from("Title is empty")).to_string(),
+                   "The data failed validation and was not sent: Title is empty");
+        assert_eq!(APIError::ReadOnlyMode.to_string(),
+                   "The client is in read-only mode, so the request was not sent");
+    }
+
+    #[test]
+    fn displays_reddit_errors() {
+        let details = RedditErrorDetails {
+            status: StatusCode::OK,
+            code: RedditErrorCode::TooLong,
+            message: String::from("this is too long"),
+            field: Some(String::from("title")),
+        };
+        assert_eq!(APIError::RedditError(details).to_string(),
+                   "Reddit rejected the request: this is too long (TooLong, field `title`)");
+    }
+}
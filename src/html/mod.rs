@@ -0,0 +1,124 @@
+//! Helpers for reading the HTML versions of text that Reddit returns (e.g. `body_html`), so
+//! that comments and posts can be indexed or displayed as plain text without an HTML parser.
+//! These are not general-purpose HTML parsers: they are written for the simple, well-formed
+//! HTML that Reddit generates from Markdown.
+//! # Examples
+//! ```
+//! use new_rawr::html;
+//! let body_html = "<div class=\"md\"><p>Fish &amp; chips</p>\n<ul>\n<li>one</li>\n\
+//!                  <li>two &lt;3</li>\n</ul>\n</div>";
+//! assert_eq!(html::to_plain_text(body_html), "Fish & chips\none\ntwo <3");
+//! ```
+
+/// Tags that start a new line in the plain text.
+const BLOCK_TAGS: &[&str] = &["blockquote", "br", "div", "h1", "h2", "h3", "h4", "h5", "h6", "hr",
+                              "li", "ol", "p", "pre", "table", "tr", "ul"];
+
+/// Tags whose content is not text, and is dropped entirely.
+const SKIPPED_TAGS: &[&str] = &["script", "style"];
+
+/// Decodes HTML entities, such as `&amp;`, `&#39;` and `&#x1F600;`. Unknown or malformed
+/// entities are left as they are.
+/// # Examples
+/// ```
+/// use new_rawr::html::decode_entities;
+/// assert_eq!(decode_entities("&lt;b&gt; &amp; &#39;q&#x27; &nbsp;&copy;"), "<b> & 'q' \u{a0}&copy;");
+/// ```
+pub fn decode_entities(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end]).map(|character| (character, end)));
+        match decoded {
+            Some((character, end)) => {
+                res.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Decodes the name of an entity (between `&` and `;`).
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ if name.starts_with("#x") || name.starts_with("#X") => {
+            u32::from_str_radix(&name[2..], 16).ok().and_then(std::char::from_u32)
+        }
+        _ if name.starts_with('#') => name[1..].parse().ok().and_then(std::char::from_u32),
+        _ => None,
+    }
+}
+
+/// Converts HTML to plain text: removes tags (and the content of `<script>` and `<style>`),
+/// decodes entities, starts a new line at each paragraph, list item or line break, and trims
+/// blank lines. HTML that is itself entity-encoded (as Reddit returns it without `raw_json=1`)
+/// is decoded first.
+/// # Examples
+/// ```
+/// use new_rawr::html::to_plain_text;
+/// let escaped = "&lt;div class=\"md\"&gt;&lt;p&gt;a &amp;amp; b&lt;/p&gt;&lt;/div&gt;";
+/// assert_eq!(to_plain_text(escaped), "a & b");
+/// assert_eq!(to_plain_text("<p>x<script>alert(1)</script>y</p><p>z</p>"), "xy\nz");
+/// ```
+pub fn to_plain_text(html: &str) -> String {
+    let decoded;
+    let mut rest = if html.trim_start().starts_with("&lt;") {
+        decoded = decode_entities(html);
+        decoded.as_str()
+    } else {
+        html
+    };
+    let mut text = String::with_capacity(rest.len());
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => {
+                // Not a tag after all.
+                text.push_str(&decode_entities(&rest[start..]));
+                rest = "";
+                break;
+            }
+        };
+        let opening = !rest[start + 1..end].starts_with('/');
+        let tag = tag_name(&rest[start + 1..end]);
+        rest = &rest[end + 1..];
+        if opening && SKIPPED_TAGS.contains(&tag.as_str()) {
+            let closing = format!("</{}", tag);
+            rest = match rest.to_ascii_lowercase().find(&closing) {
+                Some(close) => &rest[close..],
+                None => "",
+            };
+        } else if BLOCK_TAGS.contains(&tag.as_str()) && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    text.push_str(&decode_entities(rest));
+    let lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+/// The lowercase name of a tag, from the text between `<` and `>` (e.g. `/p` or `a href=".."`).
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('/')
+        .chars()
+        .take_while(|character| character.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
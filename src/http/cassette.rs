@@ -0,0 +1,368 @@
+//! Recording real responses from Reddit and replaying them later, so that tests can run offline
+//! and always see the same data. Record once with a `RecordingBackend` wrapped around a real
+//! backend, save the `Cassette` to a JSON file, and serve it back with a `ReplayBackend`.
+//!
+//! Requests to the token endpoints are recorded without their bodies (which contain passwords
+//! and client secrets), and tokens in their responses are replaced with `REDACTED`, as are the
+//! values of `Set-Cookie` headers (which can carry session cookies). Other responses are recorded
+//! as they are, so check fixtures before publishing them.
+//! # Examples
+//! Recording (this sends real requests):
+//!
+//! ```rust,no_run
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::http::HyperBackend;
+//! use new_rawr::http::cassette::RecordingBackend;
+//! let recorder = RecordingBackend::new(Box::new(HyperBackend::new()));
+//! let cassette = recorder.cassette();
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(recorder));
+//! client.get_json("/r/rust/about", false).expect("Could not fetch subreddit");
+//! cassette.save("tests/fixtures/rust_about.json").expect("Could not save cassette");
+//! ```
+//!
+//! Replaying:
+//!
+//! ```
+//! use http::{Method, StatusCode};
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::http::cassette::{Cassette, ReplayBackend};
+//! let cassette = Cassette::new();
+//! cassette.record(Method::GET, "https://api.reddit.com/r/rust/about?raw_json=1", "",
+//!                 StatusCode::OK, br#"{"kind": "t5"}"#);
+//! // Usually: let cassette = Cassette::load("tests/fixtures/rust_about.json")?;
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(ReplayBackend::new(cassette)));
+//! assert_eq!(client.get_json("/r/rust/about", false).unwrap(), r#"{"kind": "t5"}"#);
+//! assert!(client.get_json("/r/rust/hot", false).is_err());
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use http::{Method, StatusCode};
+use http::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::http::{HttpBackend, HttpRequest, HttpResponse};
+
+/// One request and the response that Reddit sent to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The full URL of the request.
+    pub url: String,
+    /// The request body (empty for token requests, which are not matched on their body).
+    pub request_body: String,
+    /// The status code of the response.
+    pub status: u16,
+    /// The response headers (e.g. the rate limit headers).
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// The response body. Bodies that are not UTF-8 (e.g. images) are base64-encoded.
+    pub body: String,
+    /// `true` if `body` is base64-encoded.
+    #[serde(default)]
+    pub binary: bool,
+}
+
+impl Interaction {
+    fn matches(&self, request: &HttpRequest) -> bool {
+        self.method == request.method.as_str() && self.url == request.url &&
+        (is_token_request(&request.url) || self.request_body.as_bytes() == &request.body[..])
+    }
+
+    fn response(&self) -> Result<HttpResponse, APIError> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|err| APIError::ValidationError(err.to_string()))?;
+        let mut response = HttpResponse::new(status, "");
+        response.body = if self.binary {
+            base64::decode(&self.body).map_err(|err| APIError::ValidationError(err.to_string()))?
+        } else {
+            self.body.as_bytes().to_vec()
+        };
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()),
+                                            HeaderValue::from_str(value)) {
+                response.headers.append(name, value);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Response headers whose values are replaced with `REDACTED` when they are recorded.
+const REDACTED_HEADERS: &[&str] = &["set-cookie"];
+
+/// `true` for requests to the token endpoints, on any host (see `Endpoints::auth_host`).
+fn is_token_request(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or_default();
+    [endpoints::ACCESS_TOKEN, endpoints::REVOKE_TOKEN]
+        .iter()
+        .any(|endpoint| path.ends_with(&endpoint[endpoints::AUTH_HOST.len()..]))
+}
+
+/// A list of recorded interactions, shared between the backend that records or replays them and
+/// the code that saves or loads them.
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Cassette {
+    /// Creates an empty cassette.
+    pub fn new() -> Cassette {
+        Cassette::default()
+    }
+
+    /// Loads a cassette saved with `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Cassette, APIError> {
+        let interactions: Vec<Interaction> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Cassette { interactions: Arc::new(Mutex::new(interactions)) })
+    }
+
+    /// Saves the interactions as pretty-printed JSON, so fixtures can be reviewed and diffed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), APIError> {
+        let json = serde_json::to_string_pretty(&*self.interactions.lock().unwrap())?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// A copy of the recorded interactions, oldest first.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    /// Adds an interaction without sending a request, e.g. to write fixtures by hand.
+    pub fn record(&self,
+                  method: Method,
+                  url: &str,
+                  request_body: &str,
+                  status: StatusCode,
+                  body: &[u8]) {
+        let mut response = HttpResponse::new(status, "");
+        response.body = body.to_vec();
+        let request = HttpRequest::new(method, url).body(request_body);
+        self.push(&request, &response);
+    }
+
+    fn push(&self, request: &HttpRequest, response: &HttpResponse) {
+        let token_request = is_token_request(&request.url);
+        let (body, binary) = match String::from_utf8(response.body.clone()) {
+            Ok(body) if token_request => (redact_tokens(&body), false),
+            Ok(body) => (body, false),
+            Err(_) => (base64::encode(&response.body), true),
+        };
+        let request_body = if token_request {
+            String::new()
+        } else {
+            String::from_utf8_lossy(&request.body).into_owned()
+        };
+        let headers = response.headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                    "REDACTED"
+                } else {
+                    value.to_str().ok()?
+                };
+                Some((name.as_str().to_owned(), value.to_owned()))
+            })
+            .collect();
+        self.interactions.lock().unwrap().push(Interaction {
+            method: request.method.as_str().to_owned(),
+            url: request.url.to_owned(),
+            request_body,
+            status: response.status.as_u16(),
+            headers,
+            body,
+            binary,
+        });
+    }
+}
+
+/// Replaces the tokens in a token endpoint response.
+fn redact_tokens(body: &str) -> String {
+    let mut value: Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(_) => return body.to_owned(),
+    };
+    if let Some(object) = value.as_object_mut() {
+        for key in &["access_token", "refresh_token"] {
+            if object.contains_key(*key) {
+                object.insert((*key).to_owned(), Value::from("REDACTED"));
+            }
+        }
+    }
+    value.to_string()
+}
+
+/// An `HttpBackend` that sends requests through another backend and records every request and
+/// response in a `Cassette`. See the module-level documentation for examples.
+pub struct RecordingBackend {
+    inner: Box<dyn HttpBackend>,
+    cassette: Cassette,
+}
+
+impl RecordingBackend {
+    /// Records the requests sent through `inner` in a new cassette.
+    pub fn new(inner: Box<dyn HttpBackend>) -> RecordingBackend {
+        RecordingBackend {
+            inner,
+            cassette: Cassette::new(),
+        }
+    }
+
+    /// A handle to the cassette that is being recorded, which can still be used (e.g. to save it)
+    /// after the backend has been given to a `RedditClient`.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.clone()
+    }
+}
+
+impl HttpBackend for RecordingBackend {
+    fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        // Compressed bodies are decoded, so that cassettes stay readable and can be edited.
+        let response = self.inner.execute(request.clone())?.decompress()?;
+        self.cassette.push(&request, &response);
+        Ok(response)
+    }
+}
+
+/// An `HttpBackend` that answers requests from a `Cassette` instead of sending them. See the
+/// module-level documentation for examples.
+///
+/// A request is answered with the first unused interaction with the same method, URL and body
+/// (token requests are matched without their body). Once every matching interaction has been
+/// used, the last one is repeated, so polling loops keep working. Requests that were never
+/// recorded fail with `APIError::ValidationError`.
+pub struct ReplayBackend {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayBackend {
+    /// Replays the interactions in the cassette.
+    pub fn new(cassette: Cassette) -> ReplayBackend {
+        let interactions = cassette.interactions();
+        ReplayBackend {
+            used: Mutex::new(vec![false; interactions.len()]),
+            interactions,
+        }
+    }
+
+    /// Replays the cassette saved at the path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ReplayBackend, APIError> {
+        Ok(ReplayBackend::new(Cassette::load(path)?))
+    }
+}
+
+impl HttpBackend for ReplayBackend {
+    fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        let mut used = self.used.lock().unwrap();
+        let matching = self.interactions
+            .iter()
+            .enumerate()
+            .filter(|&(_, interaction)| interaction.matches(&request))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let index = matching.iter()
+            .find(|&&index| !used[index])
+            .or_else(|| matching.last())
+            .cloned()
+            .ok_or_else(|| {
+                APIError::ValidationError(format!("No recorded response for {} {}",
+                                                  request.method,
+                                                  request.url))
+            })?;
+        used[index] = true;
+        self.interactions[index].response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Method, StatusCode};
+    use http::header::{HeaderValue, SET_COOKIE};
+
+    use super::{Cassette, RecordingBackend, ReplayBackend};
+    use crate::endpoints;
+    use crate::errors::APIError;
+    use crate::http::{HttpBackend, HttpRequest, HttpResponse};
+
+    /// Answers every request with a token response that sets a session cookie.
+    struct Reddit;
+
+    impl HttpBackend for Reddit {
+        fn execute(&self, _: HttpRequest) -> Result<HttpResponse, APIError> {
+            let body = r#"{"access_token": "secret", "expires_in": 3600}"#;
+            let mut response = HttpResponse::new(StatusCode::OK, body);
+            response.headers.append(SET_COOKIE, HeaderValue::from_static("reddit_session=abc"));
+            response.headers.append("x-ratelimit-remaining", HeaderValue::from_static("599"));
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn redacts_token_requests() {
+        let recorder = RecordingBackend::new(Box::new(Reddit));
+        let cassette = recorder.cassette();
+        let request = HttpRequest::new(Method::POST, endpoints::ACCESS_TOKEN)
+            .body("grant_type=password&username=a&password=hunter2");
+        recorder.execute(request).unwrap();
+        let interaction = &cassette.interactions()[0];
+        assert_eq!(interaction.request_body, "");
+        assert!(!interaction.body.contains("secret"));
+        assert!(interaction.body.contains(r#""access_token":"REDACTED""#));
+        assert!(interaction.body.contains(r#""expires_in":3600"#));
+    }
+
+    #[test]
+    fn redacts_cookies() {
+        let recorder = RecordingBackend::new(Box::new(Reddit));
+        let cassette = recorder.cassette();
+        let response = recorder.execute(HttpRequest::new(Method::GET, "/r/rust/about")).unwrap();
+        // The caller still sees the real cookie; only the recording is redacted.
+        assert_eq!(response.headers[SET_COOKIE], "reddit_session=abc");
+        let headers = &cassette.interactions()[0].headers;
+        assert!(headers.contains(&(String::from("set-cookie"), String::from("REDACTED"))));
+        assert!(headers.contains(&(String::from("x-ratelimit-remaining"), String::from("599"))));
+        assert!(!headers.iter().any(|(_, value)| value.contains("reddit_session")));
+    }
+
+    #[test]
+    fn keeps_other_bodies() {
+        let recorder = RecordingBackend::new(Box::new(Reddit));
+        let cassette = recorder.cassette();
+        let request = HttpRequest::new(Method::POST, "/api/vote").body("dir=1&id=t3_a");
+        recorder.execute(request).unwrap();
+        let interaction = &cassette.interactions()[0];
+        assert_eq!(interaction.request_body, "dir=1&id=t3_a");
+        assert!(interaction.body.contains("secret"));
+    }
+
+    #[test]
+    fn replays_in_order() {
+        let cassette = Cassette::new();
+        cassette.record(Method::GET, "/r/rust/new", "", StatusCode::OK, b"first");
+        cassette.record(Method::GET, "/r/rust/new", "", StatusCode::OK, b"second");
+        let replay = ReplayBackend::new(cassette);
+        let body = |replay: &ReplayBackend| {
+            let response = replay.execute(HttpRequest::new(Method::GET, "/r/rust/new")).unwrap();
+            String::from_utf8(response.body).unwrap()
+        };
+        assert_eq!(body(&replay), "first");
+        assert_eq!(body(&replay), "second");
+        assert_eq!(body(&replay), "second");
+        assert!(replay.execute(HttpRequest::new(Method::GET, "/r/rust/hot")).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! A fake `HttpBackend` for testing code that uses `new_rawr` without sending requests to
+//! Reddit. Register canned responses with `MockBackend::on()`, hand the backend to
+//! `RedditClient::with_backend`, and check the requests that were sent with `requests()`.
+//! # Examples
+//! ```
+//! use http::{Method, StatusCode};
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::http::mock::MockBackend;
+//!
+//! let backend = MockBackend::new()
+//!     .on(Method::GET, "/r/rust/about", StatusCode::OK, r#"{"kind": "t5"}"#)
+//!     .on(Method::POST, "/api/vote", StatusCode::FORBIDDEN, "");
+//! let requests = backend.recorder();
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(backend));
+//! assert_eq!(client.get_json("/r/rust/about", false).unwrap(), r#"{"kind": "t5"}"#);
+//! assert!(client.post_success("/api/vote", "dir=1&id=t3_4uule8", false).is_err());
+//! assert!(client.get_json("/r/unknown/about", false).is_err());
+//! let sent = requests.requests();
+//! assert_eq!(sent.len(), 3);
+//! assert_eq!(sent[1].body, b"dir=1&id=t3_4uule8".to_vec());
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use http::{Method, StatusCode};
+
+use crate::errors::APIError;
+use crate::http::{HttpBackend, HttpRequest, HttpResponse};
+
+struct Route {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    body: String,
+}
+
+/// An `HttpBackend` that answers requests with canned responses. See the module-level
+/// documentation for examples.
+///
+/// A request matches a response if it has the same method and its URL (without the query
+/// string) ends with the response's path, so paths work with both `https://www.reddit.com` and
+/// `https://oauth.reddit.com`. The most recently registered match wins, so later calls to `on()`
+/// override earlier ones. Requests without a match receive 404 Not Found.
+pub struct MockBackend {
+    routes: Vec<Route>,
+    recorder: RequestRecorder,
+}
+
+impl MockBackend {
+    /// Creates a backend with no responses, which answers every request with 404 Not Found.
+    pub fn new() -> MockBackend {
+        MockBackend {
+            routes: Vec::new(),
+            recorder: RequestRecorder::default(),
+        }
+    }
+
+    /// Answers requests with this method and path with the status and body.
+    pub fn on(mut self, method: Method, path: &str, status: StatusCode, body: &str) -> MockBackend {
+        self.routes.push(Route {
+            method,
+            path: path.to_owned(),
+            status,
+            body: body.to_owned(),
+        });
+        self
+    }
+
+    /// A handle to the requests that this backend receives, which can still be used after the
+    /// backend has been given to a `RedditClient`.
+    pub fn recorder(&self) -> RequestRecorder {
+        self.recorder.clone()
+    }
+}
+
+impl Default for MockBackend {
+    fn default() -> MockBackend {
+        MockBackend::new()
+    }
+}
+
+impl HttpBackend for MockBackend {
+    fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        let path = request.url.split('?').next().unwrap_or_default().to_owned();
+        let route = self.routes
+            .iter()
+            .rev()
+            .find(|route| route.method == request.method && path.ends_with(&route.path));
+        self.recorder.requests.lock().unwrap().push(request);
+        Ok(match route {
+            Some(route) => HttpResponse::new(route.status, &route.body),
+            None => HttpResponse::new(StatusCode::NOT_FOUND, ""),
+        })
+    }
+}
+
+/// The requests received by a `MockBackend`, in the order they were sent.
+#[derive(Clone, Default)]
+pub struct RequestRecorder {
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl RequestRecorder {
+    /// A copy of every request received so far.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Forgets the requests received so far.
+    pub fn clear(&self) {
+        self.requests.lock().unwrap().clear();
+    }
+}
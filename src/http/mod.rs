@@ -0,0 +1,407 @@
+//! The transport layer used to send requests to Reddit. All API modeling in `new_rawr` is
+//! independent of the HTTP stack: `RedditClient` and the authenticators only ever hand an
+//! `HttpRequest` to an `HttpBackend` and read the `HttpResponse` that comes back.
+//!
+//! By default, `HyperBackend` is used. If your application is tied to a different HTTP client
+//! (or you want to stub out Reddit entirely), implement `HttpBackend` and pass it to
+//! `RedditClient::with_backend`. For tests, the `mock` module provides a backend that answers
+//! with canned responses and records the requests it receives, and the `cassette` module records
+//! real responses to replay them offline.
+//! # WebAssembly
+//! `hyper` and `tokio` do not support `wasm32-unknown-unknown`, so building for the browser
+//! requires disabling the default `hyper-backend` feature:
+//!
+//! ```toml
+//! new-rawr = { version = "*", default-features = false }
+//! ```
+//!
+//! You then need to supply an `HttpBackend` built on the browser's `fetch` API. Streams do not
+//! sleep between polls on wasm (there are no threads to block), so drive them with
+//! `PollingStream::poll()` from your own timer instead of iterating over them.
+//! # Examples
+//! ```
+//! use http::StatusCode;
+//! use new_rawr::auth::AnonymousAuthenticator;
+//! use new_rawr::client::RedditClient;
+//! use new_rawr::errors::APIError;
+//! use new_rawr::http::{HttpBackend, HttpRequest, HttpResponse};
+//!
+//! struct AlwaysEmpty;
+//!
+//! impl HttpBackend for AlwaysEmpty {
+//!     fn execute(&self, _request: HttpRequest) -> Result<HttpResponse, APIError> {
+//!         Ok(HttpResponse::new(StatusCode::OK, "{}"))
+//!     }
+//! }
+//!
+//! let client = RedditClient::with_backend("new_rawr",
+//!                                         AnonymousAuthenticator::new(),
+//!                                         Box::new(AlwaysEmpty));
+//! assert_eq!(client.get_json("/r/rust/about", false).unwrap(), "{}");
+//! ```
+
+pub mod cassette;
+pub mod mock;
+#[cfg(feature = "hyper-backend")]
+pub mod proxy;
+
+#[cfg(feature = "hyper-backend")]
+use std::io;
+#[cfg(feature = "hyper-backend")]
+use std::time::Duration;
+
+use http::{HeaderMap, Method, StatusCode};
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, HeaderName};
+#[cfg(feature = "hyper-backend")]
+use hyper::{Body, Client, Request};
+#[cfg(feature = "hyper-backend")]
+use hyper::client::connect::Connect;
+#[cfg(feature = "hyper-backend")]
+use hyper_tls::HttpsConnector;
+#[cfg(feature = "hyper-backend")]
+use tokio::runtime::Runtime;
+
+use crate::endpoints::{self, Endpoints};
+use crate::errors::APIError;
+#[cfg(feature = "hyper-backend")]
+use crate::http::proxy::{Proxy, ProxyConnector};
+use crate::zlib;
+
+/// A transport that can send an `HttpRequest` and return the response. Implementations must
+/// not treat non-2xx status codes as errors: `RedditClient` inspects the status itself.
+pub trait HttpBackend: Send + Sync {
+    /// Sends the request, blocking until the whole response has been received.
+    fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError>;
+}
+
+/// Sends token requests to the `auth_host` of a client's `Endpoints` instead of Reddit.
+/// Authenticators are given this backend, because they build their URLs from the constants in
+/// `endpoints` and do not know which hosts the client uses.
+pub(crate) struct AuthHostBackend<'a> {
+    pub backend: &'a dyn HttpBackend,
+    pub endpoints: &'a Endpoints,
+}
+
+impl<'a> HttpBackend for AuthHostBackend<'a> {
+    fn execute(&self, mut request: HttpRequest) -> Result<HttpResponse, APIError> {
+        request.url = self.endpoints.auth_url(&request.url);
+        self.backend.execute(request)
+    }
+}
+
+/// A request to be sent by an `HttpBackend`.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The HTTP method (`GET` or `POST`).
+    pub method: Method,
+    /// The full URL, including the host.
+    pub url: String,
+    /// Headers to send with the request.
+    pub headers: Vec<(HeaderName, String)>,
+    /// The request body (usually URL-encoded form data), which is empty for `GET` requests.
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Creates a request with no headers and an empty body.
+    pub fn new(method: Method, url: &str) -> HttpRequest {
+        HttpRequest {
+            method,
+            url: url.to_owned(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the request.
+    pub fn header(mut self, name: HeaderName, value: &str) -> HttpRequest {
+        self.headers.push((name, value.to_owned()));
+        self
+    }
+
+    /// Sets the body of the request.
+    pub fn body(mut self, body: &str) -> HttpRequest {
+        self.body = body.as_bytes().to_vec();
+        self
+    }
+
+    /// Sets the body of the request to raw bytes, e.g. for uploading images.
+    pub fn bytes(mut self, body: Vec<u8>) -> HttpRequest {
+        self.body = body;
+        self
+    }
+
+    /// `true` if the request may change something on Reddit, i.e. it is not a `GET` or `HEAD`
+    /// request and not one of the read-only `POST` endpoints in `endpoints::READ_ONLY_POSTS`.
+    /// # Examples
+    /// ```
+    /// use http::Method;
+    /// use new_rawr::http::HttpRequest;
+    /// let vote = HttpRequest::new(Method::POST, "https://oauth.reddit.com/api/vote");
+    /// assert!(vote.is_mutating());
+    /// let more = HttpRequest::new(Method::POST, "https://api.reddit.com/api/morechildren?raw_json=1");
+    /// assert!(!more.is_mutating());
+    /// ```
+    pub fn is_mutating(&self) -> bool {
+        if self.method == Method::GET || self.method == Method::HEAD {
+            return false;
+        }
+        let path = self.url.split('?').next().unwrap_or_default();
+        !endpoints::READ_ONLY_POSTS.iter().any(|endpoint| path.ends_with(endpoint))
+    }
+}
+
+/// A response received by an `HttpBackend`.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The status code of the response.
+    pub status: StatusCode,
+    /// The response headers.
+    pub headers: HeaderMap,
+    /// The raw response body.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response with the specified status and body, and no headers.
+    pub fn new(status: StatusCode, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// The body decoded as UTF-8 text.
+    pub fn text(&self) -> Result<String, APIError> {
+        String::from_utf8(self.body.clone())
+            .map_err(|_| APIError::ValidationError(String::from("Response was not valid UTF-8")))
+    }
+
+    /// Decodes a body compressed with `gzip` or `deflate`, according to the `Content-Encoding`
+    /// header, and removes the header. Responses without the header are returned unchanged.
+    /// `RedditClient` calls this for every response, so backends only need to return the body
+    /// as it was received.
+    /// # Examples
+    /// ```
+    /// use http::StatusCode;
+    /// use http::header::{CONTENT_ENCODING, HeaderValue};
+    /// use new_rawr::http::HttpResponse;
+    /// let mut response = HttpResponse::new(StatusCode::OK, "");
+    /// response.headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    /// response.body = vec![0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56,
+    ///                      0xca, 0xce, 0xcc, 0x4b, 0x51, 0xb2, 0x52, 0x50, 0x2a, 0x31, 0x55, 0xaa,
+    ///                      0x05, 0x00, 0x75, 0x36, 0x20, 0x5f, 0x0e, 0x00, 0x00, 0x00];
+    /// let response = response.decompress().unwrap();
+    /// assert_eq!(response.text().unwrap(), r#"{"kind": "t5"}"#);
+    /// assert!(response.headers.get(CONTENT_ENCODING).is_none());
+    /// ```
+    pub fn decompress(mut self) -> Result<HttpResponse, APIError> {
+        let encoding = match self.headers.get(CONTENT_ENCODING).and_then(|value| value.to_str().ok()) {
+            Some(encoding) => encoding.trim().to_ascii_lowercase(),
+            None => return Ok(self),
+        };
+        let body = match encoding.as_str() {
+            "gzip" | "x-gzip" => zlib::gunzip(&self.body),
+            // Servers disagree on whether `deflate` means a zlib stream or raw deflate data.
+            "deflate" => zlib::decompress(&self.body).or_else(|_| zlib::inflate(&self.body)),
+            "identity" => Ok(self.body),
+            _ => Err(format!("unsupported encoding {}", encoding)),
+        };
+        self.body = body.map_err(|err| {
+            APIError::ValidationError(format!("Could not decompress response: {}", err))
+        })?;
+        self.headers.remove(CONTENT_ENCODING);
+        self.headers.remove(CONTENT_LENGTH);
+        Ok(self)
+    }
+}
+
+/// The default backend, which uses `hyper` with TLS support and runs each request to completion
+/// on an internal `tokio` runtime. Only available with the `hyper-backend` feature (enabled by
+/// default).
+///
+/// To share a connection pool with the rest of an application, or to use custom TLS roots or
+/// connector settings, build the `hyper::Client` yourself and pass it to `from_client()`.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use hyper::Client;
+/// use hyper::client::HttpConnector;
+/// use hyper_tls::HttpsConnector;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::http::HyperBackend;
+/// let mut connector = HttpConnector::new();
+/// connector.set_connect_timeout(Some(Duration::from_secs(5)));
+/// connector.enforce_http(false);
+/// let shared = Client::builder()
+///     .pool_max_idle_per_host(4)
+///     .build::<_, hyper::Body>(HttpsConnector::new_with_connector(connector));
+/// let backend = HyperBackend::from_client(shared.clone()).with_timeout(Duration::from_secs(30));
+/// let client = RedditClient::with_backend("new_rawr",
+///                                         AnonymousAuthenticator::new(),
+///                                         Box::new(backend));
+/// ```
+#[cfg(feature = "hyper-backend")]
+pub struct HyperBackend<C = HttpsConnector<ProxyConnector>> {
+    client: Client<C, Body>,
+    runtime: Runtime,
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "hyper-backend")]
+impl HyperBackend {
+    /// Creates a new `HyperBackend`, which uses the proxy set in the environment, if any (see
+    /// `Proxy::from_env()`). If the proxy set in the environment is invalid, every request fails
+    /// with an error, rather than silently connecting directly. Use `RedditClient::builder()` to
+    /// get the error when the client is created instead.
+    pub fn new() -> HyperBackend {
+        let connector = match Proxy::from_env() {
+            Ok(proxy) => ProxyConnector::new(proxy),
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %err, "invalid proxy URL in the environment");
+                ProxyConnector::failing(&format!("Invalid proxy URL in the environment: {}", err))
+            }
+        };
+        HyperBackend::with_connector(connector)
+    }
+
+    /// Creates a `HyperBackend` that sends requests through the proxy. See the `proxy` module for
+    /// examples.
+    pub fn with_proxy(proxy: Proxy) -> HyperBackend {
+        HyperBackend::with_connector(ProxyConnector::new(Some(proxy)))
+    }
+
+    /// Creates a `HyperBackend` that always connects directly, even if a proxy is set in the
+    /// environment.
+    pub fn direct() -> HyperBackend {
+        HyperBackend::with_connector(ProxyConnector::new(None))
+    }
+
+    fn with_connector(connector: ProxyConnector) -> HyperBackend {
+        let https = HttpsConnector::new_with_connector(connector);
+        HyperBackend::from_client(Client::builder().build::<_, Body>(https))
+    }
+}
+
+#[cfg(feature = "hyper-backend")]
+impl<C> HyperBackend<C> {
+    /// Creates a `HyperBackend` that sends requests with a `hyper::Client` created by the caller.
+    /// The client is cheap to clone, and clones share their connection pool. The connector must
+    /// support HTTPS, since Reddit's API is only available over HTTPS.
+    pub fn from_client(client: Client<C, Body>) -> HyperBackend<C> {
+        HyperBackend {
+            client,
+            runtime: Runtime::new().expect("Unable to create a runtime"),
+            timeout: None,
+        }
+    }
+
+    /// Fails requests that take longer than `timeout` (including reading the response body)
+    /// with an `APIError::IOError` of the kind `io::ErrorKind::TimedOut`. By default, requests
+    /// never time out.
+    pub fn with_timeout(mut self, timeout: Duration) -> HyperBackend<C> {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+#[cfg(feature = "hyper-backend")]
+impl Default for HyperBackend {
+    fn default() -> HyperBackend {
+        HyperBackend::new()
+    }
+}
+
+#[cfg(feature = "hyper-backend")]
+impl<C> HttpBackend for HyperBackend<C>
+    where C: Connect + Clone + Send + Sync + 'static
+{
+    fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        let mut builder = Request::builder().method(request.method).uri(request.url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        let request = builder.body(Body::from(request.body))
+            .map_err(|err| APIError::ValidationError(err.to_string()))?;
+        let send = async {
+            let response = self.client.request(request).await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+            Ok(HttpResponse {
+                status,
+                headers,
+                body: body.to_vec(),
+            })
+        };
+        match self.timeout {
+            Some(timeout) => {
+                // The timer must be created inside the runtime.
+                let send = async { tokio::time::timeout(timeout, send).await };
+                self.runtime.block_on(send).unwrap_or_else(|_| {
+                    let err = io::Error::new(io::ErrorKind::TimedOut, "The request timed out");
+                    Err(APIError::IOError(err))
+                })
+            }
+            None => self.runtime.block_on(send),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, HeaderValue};
+
+    use super::HttpResponse;
+    use crate::zlib;
+
+    fn encoded(encoding: &'static str, body: Vec<u8>) -> HttpResponse {
+        let mut response = HttpResponse::new(StatusCode::OK, "");
+        response.headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+        response.headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+        response.body = body;
+        response
+    }
+
+    #[test]
+    fn decodes_deflate() {
+        let zlib_body = zlib::compress(b"{}");
+        let raw_body = zlib_body[2..zlib_body.len() - 4].to_vec();
+        for body in [zlib_body, raw_body] {
+            let response = encoded("deflate", body).decompress().unwrap();
+            assert_eq!(response.body, b"{}");
+            assert!(response.headers.get(CONTENT_ENCODING).is_none());
+            assert!(response.headers.get(CONTENT_LENGTH).is_none());
+        }
+    }
+
+    #[test]
+    fn keeps_identity_and_plain_bodies() {
+        assert_eq!(encoded("identity", b"{}".to_vec()).decompress().unwrap().body, b"{}");
+        let plain = HttpResponse::new(StatusCode::OK, "{}");
+        assert_eq!(plain.decompress().unwrap().body, b"{}");
+    }
+
+    #[test]
+    fn rejects_bad_bodies() {
+        assert!(encoded("gzip", b"{}".to_vec()).decompress().is_err());
+        assert!(encoded("br", b"{}".to_vec()).decompress().is_err());
+    }
+
+    #[cfg(feature = "hyper-backend")]
+    #[test]
+    fn invalid_proxy_fails_requests() {
+        use http::Method;
+
+        use super::{HttpBackend, HttpRequest, HyperBackend};
+        use crate::http::proxy::ProxyConnector;
+
+        let backend = HyperBackend::with_connector(ProxyConnector::failing("Invalid proxy URL"));
+        let request = HttpRequest::new(Method::GET, "https://oauth.reddit.com/api/v1/me");
+        assert!(backend.execute(request).is_err());
+    }
+}
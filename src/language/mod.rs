@@ -0,0 +1,233 @@
+//! A small, dependency-free language detector for posts and comments, so that international
+//! subreddits can enforce language rules (see `Submission.detected_language()` and
+//! `PostStream.only_language()`). Only compiled with the `language` feature.
+//!
+//! Text in a distinctive script (e.g. Cyrillic, Greek or Japanese) is identified by its
+//! characters. Text in the Latin script is identified by counting common words (such as "the"
+//! and "und"), which works for a sentence or more, but not for single words or names. The
+//! detector gives up (returns `None`) rather than guess when the text is too short or
+//! ambiguous.
+//!
+//! Languages are identified by their ISO 639-1 codes, e.g. `en`. The supported languages are
+//! listed in `LANGUAGES`.
+//! # Examples
+//! ```
+//! use new_rawr::language::detect;
+//! assert_eq!(detect("What is the best way to learn this language?"), Some("en"));
+//! assert_eq!(detect("Wie kann ich das am besten lernen? Ich habe nicht viel Zeit."), Some("de"));
+//! assert_eq!(detect("Quelle est la meilleure façon de faire ça ? Je ne sais pas."), Some("fr"));
+//! assert_eq!(detect("¿Cuál es la mejor manera de aprender? No tengo mucho tiempo."), Some("es"));
+//! assert_eq!(detect("Как лучше всего выучить этот язык?"), Some("ru"));
+//! assert_eq!(detect("この言語を勉強する一番いい方法は何ですか"), Some("ja"));
+//! assert_eq!(detect("Rust"), None);
+//! ```
+
+/// The languages that `detect()` can recognize, by ISO 639-1 code.
+pub const LANGUAGES: &[&str] = &["ar", "de", "el", "en", "es", "fr", "he", "hi", "it", "ja", "ko",
+                                 "nl", "pl", "pt", "ru", "sv", "th", "uk", "zh"];
+
+/// Common words of the languages written in the Latin script. Words that are common in several
+/// of these languages (e.g. "de") count for each of them.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "are", "was", "were", "of", "to", "in", "that", "it", "this",
+             "with", "for", "you", "have", "not", "but", "what", "be", "they", "my", "just",
+             "would", "there", "about", "from", "how", "can", "any"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ich", "ein", "eine", "zu", "mit",
+             "sich", "auf", "für", "den", "dem", "auch", "es", "wie", "aber", "wenn", "noch",
+             "nur", "sind", "war", "habe", "kann", "oder", "viel", "kein"]),
+    ("fr", &["le", "la", "les", "et", "est", "un", "une", "des", "du", "que", "qui", "pas",
+             "pour", "dans", "ce", "il", "je", "sur", "avec", "mais", "sont", "ou", "nous",
+             "vous", "elle", "très", "au", "aux", "cette", "ne"]),
+    ("es", &["el", "la", "los", "las", "y", "es", "un", "una", "que", "de", "en", "no", "por",
+             "con", "para", "pero", "lo", "se", "su", "como", "más", "muy", "esta", "este",
+             "yo", "del", "al", "hay", "también", "tengo", "cuál"]),
+    ("it", &["il", "lo", "la", "gli", "le", "e", "è", "un", "una", "che", "di", "non", "per",
+             "con", "ma", "sono", "della", "del", "questo", "come", "anche", "più", "ho", "sei",
+             "mi", "ci", "nel", "alla", "perché"]),
+    ("pt", &["o", "a", "os", "as", "e", "é", "um", "uma", "que", "de", "não", "para", "com",
+             "mas", "por", "se", "do", "da", "dos", "em", "no", "na", "eu", "você", "muito",
+             "isso", "mais", "também", "são", "tenho"]),
+    ("nl", &["de", "het", "een", "en", "is", "van", "niet", "ik", "dat", "je", "op", "te",
+             "met", "voor", "zijn", "maar", "ook", "als", "er", "wat", "dit", "naar", "hij",
+             "wel", "nog", "heb", "kan", "geen"]),
+    ("sv", &["och", "att", "det", "som", "en", "är", "på", "av", "för", "med", "inte", "jag",
+             "till", "den", "har", "de", "om", "men", "ett", "så", "var", "vi", "kan", "eller",
+             "han", "hon", "mycket"]),
+    ("pl", &["w", "na", "nie", "z", "się", "to", "jest", "że", "do", "jak", "ale", "co", "tak",
+             "za", "od", "po", "mnie", "jestem", "czy", "już", "tylko", "ten", "być", "bardzo",
+             "ja", "mam"]),
+];
+
+/// The scripts that identify a language (or a group of languages) by themselves, in the order
+/// of `SCRIPTS`.
+#[derive(Clone, Copy, PartialEq)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+impl Script {
+    fn of(character: char) -> Option<Script> {
+        let script = match character as u32 {
+            _ if character.is_ascii_alphabetic() => Script::Latin,
+            0x00C0..=0x024F => Script::Latin,
+            0x0370..=0x03FF => Script::Greek,
+            0x0400..=0x04FF => Script::Cyrillic,
+            0x0590..=0x05FF => Script::Hebrew,
+            0x0600..=0x06FF | 0x0750..=0x077F => Script::Arabic,
+            0x0900..=0x097F => Script::Devanagari,
+            0x0E00..=0x0E7F => Script::Thai,
+            0x1100..=0x11FF | 0xAC00..=0xD7AF => Script::Hangul,
+            0x3040..=0x30FF => Script::Kana,
+            0x4E00..=0x9FFF => Script::Han,
+            _ => return None,
+        };
+        Some(script)
+    }
+}
+
+const SCRIPTS: [Script; 10] = [Script::Latin, Script::Cyrillic, Script::Greek, Script::Arabic,
+                               Script::Hebrew, Script::Devanagari, Script::Thai, Script::Hangul,
+                               Script::Kana, Script::Han];
+
+/// Detects the language of a text, returning its ISO 639-1 code (e.g. `en`), or `None` if the
+/// text is too short or ambiguous to tell. URLs, usernames and subreddit names are ignored. See
+/// the module-level documentation for examples.
+pub fn detect(text: &str) -> Option<&'static str> {
+    let words = text.split_whitespace()
+        .filter(|word| !word.contains("://") && !word.starts_with("www.") && !word.contains('/'))
+        .collect::<Vec<_>>();
+
+    let mut counts = [0usize; 10];
+    for character in words.iter().flat_map(|word| word.chars()) {
+        if let Some(script) = Script::of(character) {
+            counts[script as usize] += 1;
+        }
+    }
+    let count = |script: Script| counts[script as usize];
+    let total = counts.iter().sum::<usize>();
+    if total < 3 {
+        return None;
+    }
+
+    // Japanese mixes kana with Chinese characters, so any noticeable amount of kana means
+    // Japanese rather than Chinese.
+    let cjk = count(Script::Kana) + count(Script::Han);
+    if cjk * 2 > total {
+        return Some(if count(Script::Kana) * 10 >= cjk { "ja" } else { "zh" });
+    }
+    let (script, letters) = SCRIPTS.iter()
+        .map(|&script| (script, count(script)))
+        .max_by_key(|&(_, letters)| letters)
+        .unwrap();
+    if letters * 2 <= total {
+        return None;
+    }
+    match script {
+        Script::Latin => detect_latin(&words),
+        Script::Cyrillic => {
+            // Letters that are used in Ukrainian, but not in Russian.
+            let ukrainian = words.iter().any(|word| word.contains(['і', 'ї', 'є', 'ґ']));
+            Some(if ukrainian { "uk" } else { "ru" })
+        }
+        Script::Greek => Some("el"),
+        Script::Arabic => Some("ar"),
+        Script::Hebrew => Some("he"),
+        Script::Devanagari => Some("hi"),
+        Script::Thai => Some("th"),
+        Script::Hangul => Some("ko"),
+        Script::Kana => Some("ja"),
+        Script::Han => Some("zh"),
+    }
+}
+
+/// Picks the Latin-script language with the most common words in the text, if it has at least
+/// two and more than any other language.
+fn detect_latin(words: &[&str]) -> Option<&'static str> {
+    let words = words.iter()
+        .map(|word| word.trim_matches(|character: char| !character.is_alphanumeric()).to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let mut scores = STOPWORDS.iter()
+        .map(|&(language, stopwords)| {
+            (language, words.iter().filter(|word| stopwords.contains(&word.as_str())).count())
+        })
+        .collect::<Vec<_>>();
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    match (scores[0], scores[1]) {
+        ((language, best), (_, second)) if best >= 2 && best > second => Some(language),
+        _ => None,
+    }
+}
+
+/// `true` if the detected language is one of the languages, or if the list is empty or the
+/// language could not be detected. Used by the `only_language()` filters of streams.
+pub(crate) fn allows(languages: &[String], detected: Option<&str>) -> bool {
+    match detected {
+        Some(detected) if !languages.is_empty() => {
+            languages.iter().any(|language| language.eq_ignore_ascii_case(detected))
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allows, detect};
+
+    #[test]
+    fn latin_languages() {
+        assert_eq!(detect("Qual è il modo migliore per imparare questa lingua? Non ho molto tempo."),
+                   Some("it"));
+        assert_eq!(detect("Wat is de beste manier om deze taal te leren? Ik heb niet veel tijd."),
+                   Some("nl"));
+        assert_eq!(detect("Qual é a melhor maneira de aprender? Eu não tenho muito tempo."),
+                   Some("pt"));
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(detect("Як найкраще вивчити цю мову? У мене немає часу."), Some("uk"));
+        assert_eq!(detect("Ποιος είναι ο καλύτερος τρόπος;"), Some("el"));
+        assert_eq!(detect("ما هي أفضل طريقة لتعلم هذه اللغة؟"), Some("ar"));
+        assert_eq!(detect("מה הדרך הטובה ביותר ללמוד?"), Some("he"));
+        assert_eq!(detect("इस भाषा को सीखने का सबसे अच्छा तरीका क्या है?"), Some("hi"));
+        assert_eq!(detect("วิธีที่ดีที่สุดในการเรียนภาษานี้คืออะไร"), Some("th"));
+        assert_eq!(detect("이 언어를 배우는 가장 좋은 방법은 무엇입니까?"), Some("ko"));
+        assert_eq!(detect("学习这门语言最好的方法是什么"), Some("zh"));
+    }
+
+    #[test]
+    fn gives_up() {
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("ok"), None);
+        assert_eq!(detect("Rust Tokio Serde"), None);
+        // Only URLs and subreddit names, which are ignored.
+        assert_eq!(detect("https://www.reddit.com/r/rust /r/learnrust"), None);
+        // Half Latin, half Cyrillic.
+        assert_eq!(detect("abcdef абвгде"), None);
+    }
+
+    #[test]
+    fn ignores_links() {
+        assert_eq!(detect("Was ist das? https://example.com/the/and/of Ich weiß es nicht."),
+                   Some("de"));
+    }
+
+    #[test]
+    fn filters() {
+        let languages = vec![String::from("en"), String::from("DE")];
+        assert!(allows(&languages, Some("de")));
+        assert!(!allows(&languages, Some("fr")));
+        assert!(allows(&languages, None));
+        assert!(allows(&[], Some("fr")));
+    }
+}
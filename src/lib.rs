@@ -52,10 +52,10 @@
 //! ```rust,no_run
 //! # use new_rawr::client::RedditClient;
 //! # use new_rawr::auth::AnonymousAuthenticator;
-//! use new_rawr::options::ListingOptions;
+//! use new_rawr::options::ListingRequest;
 //! # let client = RedditClient::new("?:new_rawr:doc-tests", AnonymousAuthenticator::new());
 //! # let all = client.subreddit("all");
-//! let listing = all.hot(ListingOptions::default()).expect("Request unsuccessful");
+//! let listing = all.listing(ListingRequest::default()).expect("Request unsuccessful");
 //! ```
 //!
 //! *Why do I need the `expect` call?* Since it is possible for a request to the API to fail,
@@ -63,9 +63,9 @@
 //! API. This allows you to handle the error gracefully if you want, but for our examples
 //! we will always use `expect` to simplify the examples.
 //!
-//! The `ListingOptions` object is used to configure various options such as the amount of posts
-//! to fetch in each request and the anchor. For more information, see the documentation for
-//! `ListingOptions`.
+//! The `ListingRequest` object is used to configure various options such as the sort order, the
+//! amount of posts to fetch in each request and the anchor. For more information, see the
+//! documentation for `ListingRequest`.
 //!
 //! ### Using the Post Listing
 //! The `Listing` objects implement `Iterator`, which means it's really convenient to loop through
@@ -74,10 +74,10 @@
 //! ```rust,no_run
 //! # use new_rawr::client::RedditClient;
 //! # use new_rawr::auth::AnonymousAuthenticator;
-//! # use new_rawr::options::ListingOptions;
+//! # use new_rawr::options::ListingRequest;
 //! # let client = RedditClient::new("?:new_rawr:doc-tests", AnonymousAuthenticator::new());
 //! # let all = client.subreddit("all");
-//! let listing = all.hot(ListingOptions::default()).expect("Could not fetch posts");
+//! let listing = all.listing(ListingRequest::default()).expect("Could not fetch posts");
 //! for post in listing {
 //!     println!("{}", post.title());
 //! }
@@ -94,11 +94,11 @@
 //! ```rust,no_run
 //! # use new_rawr::auth::AnonymousAuthenticator;
 //! # use new_rawr::client::RedditClient;
-//! # use new_rawr::options::ListingOptions;
+//! # use new_rawr::options::ListingRequest;
 //! use new_rawr::traits::{Commentable, Content};
 //! # let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
 //! let all = client.subreddit("all");
-//! for post in all.hot(ListingOptions::default()).expect("Request failed") {
+//! for post in all.listing(ListingRequest::default()).expect("Request failed") {
 //!     if let Some(comment) = post.replies().expect("Could not get replies").next() {
 //!         println!("{}", comment.author().name);
 //!     }
@@ -121,11 +121,11 @@
 //! ```rust,no_run
 //! # use new_rawr::auth::AnonymousAuthenticator;
 //! # use new_rawr::client::RedditClient;
-//! # use new_rawr::options::ListingOptions;
+//! # use new_rawr::options::ListingRequest;
 //! # use new_rawr::traits::{Commentable, Content};
 //! # let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
 //! let all = client.subreddit("all");
-//! let mut listing = all.hot(ListingOptions::default()).expect("Request failed");
+//! let mut listing = all.listing(ListingRequest::default()).expect("Request failed");
 //! if let Some(top_post) = listing.next() {
 //!     top_post.reply("This is cool... I think. I'm a bot.").expect("Posting failed!");
 //! }
@@ -162,7 +162,6 @@
 //! ```rust,no_run
 //! # use new_rawr::auth::AnonymousAuthenticator;
 //! # use new_rawr::client::RedditClient;
-//! # use new_rawr::options::ListingOptions;
 //! # use new_rawr::traits::{Commentable, Content};
 //! use new_rawr::options::LinkPost;
 //! # let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
@@ -177,7 +176,6 @@
 //! ```rust,no_run
 //! # use new_rawr::auth::AnonymousAuthenticator;
 //! # use new_rawr::client::RedditClient;
-//! # use new_rawr::options::ListingOptions;
 //! # use new_rawr::traits::{Commentable, Content};
 //! use new_rawr::options::SelfPost;
 //! # let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
@@ -193,6 +191,28 @@
 //! - Setting post flairs (see `Submission.flair`)
 //! - Getting 'about' information (see `Subreddit.about()`)
 //!
+//! # Cargo Features
+//! Parts of the API that not every bot needs can be disabled to compile less code. Only
+//! `hyper-backend`, `async` and `tracing` add dependencies; the other features only gate code in
+//! this crate.
+//! - `hyper-backend` (default) - the default HTTP transport.
+//! - `streams` (default) - polling streams such as `Subreddit.new_stream()`.
+//! - `moderation` (default) - the moderation log, AutoModerator, moderator lists and removal
+//!   messages.
+//! - `media-upload` (default) - style image uploads and `Submission.download_preview()`.
+//! - `cache` (default) - `RedditClient.enable_get_cache()`, which answers repeated `get_json()`
+//!   requests from memory for a short time.
+//! - `wiki-diff` - `WikiPage.diff()`, unified diffs between wiki revisions.
+//! - `async` - `AsyncRedditClient`, a smaller client whose requests can be awaited (without rate
+//!   limiting, retries or request hooks; see the `async_client` module).
+//! - `webhooks` - delivering moderation log entries to webhooks.
+//! - `wayback` - archiving links with the Wayback Machine.
+//! - `tracing` - `tracing` spans for every API request (with the endpoint, status code, latency
+//!   and remaining rate limit) and for higher-level operations such as submitting, replying and
+//!   polling streams. Install a `tracing` subscriber in your application to record them.
+//! - `language` - detecting the language of posts and comments, and filtering streams by
+//!   language.
+//!
 //! # Not Yet Implemented
 //! - Multireddits
 //! - Live Threads
@@ -201,11 +221,29 @@
 
 extern crate serde;
 extern crate serde_json;
+#[cfg(feature = "hyper-backend")]
 extern crate hyper;
-extern crate futures;
 
+/// Enters a `tracing` span until the end of the enclosing block. Does nothing unless the
+/// `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        let _span = tracing::info_span!($($args)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($($args:tt)*) => {};
+}
+
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod auth;
 pub mod client;
+pub mod endpoints;
+pub mod http;
 /// Deserialized API responses.
 pub mod responses;
 /// Traits for common API functionality.
@@ -216,30 +254,38 @@ pub mod errors;
 pub mod structures;
 /// Configuration options for API requests.
 pub mod options;
+pub mod markdown;
+pub mod html;
+#[cfg(feature = "language")]
+pub mod language;
+#[cfg(feature = "hyper-backend")]
+pub mod simple;
+#[cfg_attr(not(feature = "moderation"), allow(dead_code))]
+mod zlib;
+mod clock;
 
 /// Basic `new_rawr` structures to import with `use new_rawr::prelude::*`;
-#[cfg(test)]
+#[cfg(all(test, feature = "hyper-backend"))]
 mod tests {
-    use hyper::Client;
     use crate::auth::{AnonymousAuthenticator, PasswordAuthenticator};
     use crate::client::RedditClient;
-    use crate::options::ListingOptions;
-    use crate::responses::listing::SubmissionData;
+    use crate::options::ListingRequest;
+    
     use crate::structures::submission::Submission;
 
     extern crate dotenv;
 
 
     use dotenv::dotenv;
-    use std::env;
+    
 
     #[test]
     fn hot_length() {
         let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
         let r_all = client.subreddit("all");
-        let hot = r_all.hot(ListingOptions::default()).expect("Request failed!");
+        let hot = r_all.listing(ListingRequest::default()).expect("Request failed!");
         let hot_list = hot.take(26).collect::<Vec<Submission>>();
-        assert_eq!(hot_list.len() as usize, 26);
+        assert_eq!(hot_list.len(), 26);
     }
     #[test]
     fn user_data_test() {
@@ -249,7 +295,7 @@ mod tests {
         let user = client.user("KingTuxWH");
 
         let take = user.submissions().unwrap().take(5).collect::<Vec<Submission>>();
-        let x = take.get(0).take().unwrap();
+        let x = take.first().unwrap();
         println!("{}", x.link_url().unwrap());
         let user = client.user("LordPenguin42");
         assert_eq!(user.about().unwrap().data.name, "LordPenguin42")
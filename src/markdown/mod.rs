@@ -0,0 +1,330 @@
+//! Helpers for formatting bot replies in Reddit-flavoured Markdown. Every function escapes the
+//! text passed to it, so user-provided content (titles, usernames, comment bodies) can be
+//! inserted safely without accidentally creating links, tables or formatting.
+//! # Examples
+//! ```
+//! use new_rawr::markdown::{self, Alignment, Table};
+//! let mut table = Table::new(&["Subreddit", "Posts"]);
+//! table.align(1, Alignment::Right);
+//! table.row(&["rust", "42"]);
+//! table.row(&["pics|memes", "7"]);
+//! let reply = format!("{}{}", table, markdown::footer("I am a bot."));
+//! assert!(reply.starts_with("Subreddit|Posts\n:--|--:\nrust|42\npics\\|memes|7\n"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Escapes all characters that Reddit would interpret as Markdown formatting, so the text is
+/// displayed literally. This includes HTML entities (`&amp;`), spoilers (`>!text!<`), and list
+/// markers (`-`, `+` and `1.`) at the start of a line.
+/// # Examples
+/// ```
+/// use new_rawr::markdown::escape;
+/// assert_eq!(escape("*not bold*"), "\\*not bold\\*");
+/// assert_eq!(escape("[a](b)"), "\\[a\\]\\(b\\)");
+/// assert_eq!(escape("- not a list"), "\\- not a list");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            res.push('\n');
+        }
+        let content = line.trim_start();
+        res.push_str(&line[..line.len() - content.len()]);
+        let digits = content.chars().take_while(|c| c.is_ascii_digit()).count();
+        let marker = if content.starts_with('-') || content.starts_with('+') {
+            Some(0)
+        } else if digits > 0 && (&content[digits..] == "." || content[digits..].starts_with(". ")) {
+            Some(digits)
+        } else {
+            None
+        };
+        for (j, character) in content.char_indices() {
+            match character {
+                '\\' | '`' | '*' | '_' | '~' | '^' | '[' | ']' | '(' | ')' | '|' | '>' | '<' |
+                '#' | '!' | '&' => res.push('\\'),
+                _ if marker == Some(j) => res.push('\\'),
+                _ => {}
+            }
+            res.push(character);
+        }
+    }
+    res
+}
+
+/// Hides the text behind a spoiler tag (`>!text!<`).
+/// # Examples
+/// ```
+/// use new_rawr::markdown::spoiler;
+/// assert_eq!(spoiler("Snape kills Dumbledore"), ">!Snape kills Dumbledore!<");
+/// ```
+pub fn spoiler(text: &str) -> String {
+    format!(">!{}!<", escape(text))
+}
+
+/// Formats the text as superscript (`^(text)`), which is usually used for small print.
+/// # Examples
+/// ```
+/// use new_rawr::markdown::superscript;
+/// assert_eq!(superscript("beep (boop)"), "^(beep \\(boop\\))");
+/// ```
+pub fn superscript(text: &str) -> String {
+    format!("^({})", escape(text))
+}
+
+/// Formats the text as a quote block. Multi-line text is quoted line by line.
+/// # Examples
+/// ```
+/// use new_rawr::markdown::quote;
+/// assert_eq!(quote("first\n\nsecond"), "> first\n>\n> second\n\n");
+/// ```
+pub fn quote(text: &str) -> String {
+    let mut res = String::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            res.push_str(">\n");
+        } else {
+            res.push_str(&format!("> {}\n", escape(line)));
+        }
+    }
+    res.push('\n');
+    res
+}
+
+/// Creates a footer separated from the rest of the reply by a horizontal rule, with the text
+/// in superscript. Bots conventionally use this to say that they are a bot and how to contact
+/// the owner.
+/// # Examples
+/// ```
+/// use new_rawr::markdown::footer;
+/// assert_eq!(footer("I am a bot."), "\n\n---\n\n^(I am a bot.)");
+/// ```
+pub fn footer(text: &str) -> String {
+    format!("\n\n---\n\n{}", superscript(text))
+}
+
+/// The alignment of a table column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment {
+    /// Left-aligned (the default).
+    Left,
+    /// Centred.
+    Center,
+    /// Right-aligned, which is usually best for numbers.
+    Right,
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            Alignment::Left => ":--",
+            Alignment::Center => ":-:",
+            Alignment::Right => "--:",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A Markdown table. Cells are escaped, and newlines in cells are replaced with spaces (since
+/// they would otherwise end the row). Use `to_string()` to get the Markdown.
+pub struct Table {
+    headers: Vec<String>,
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Creates a table with the specified column headers, and all columns left-aligned.
+    pub fn new(headers: &[&str]) -> Table {
+        Table {
+            headers: headers.iter().map(|header| Table::cell(header)).collect(),
+            alignments: vec![Alignment::Left; headers.len()],
+            rows: Vec::new(),
+        }
+    }
+
+    /// Sets the alignment of the column with the specified (zero-based) index.
+    pub fn align(&mut self, column: usize, alignment: Alignment) -> &mut Table {
+        if let Some(current) = self.alignments.get_mut(column) {
+            *current = alignment;
+        }
+        self
+    }
+
+    /// Adds a row to the table. Missing cells are left blank, and extra cells are ignored.
+    pub fn row(&mut self, cells: &[&str]) -> &mut Table {
+        let row = (0..self.headers.len())
+            .map(|i| cells.get(i).map(|cell| Table::cell(cell)).unwrap_or_default())
+            .collect();
+        self.rows.push(row);
+        self
+    }
+
+    fn cell(text: &str) -> String {
+        escape(&text.replace('\n', " "))
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        writeln!(f, "{}", self.headers.join("|"))?;
+        let alignments = self.alignments
+            .iter()
+            .map(|alignment| alignment.to_string())
+            .collect::<Vec<String>>();
+        writeln!(f, "{}", alignments.join("|"))?;
+        for row in &self.rows {
+            writeln!(f, "{}", row.join("|"))?;
+        }
+        Ok(())
+    }
+}
+
+/// A reply template with `{name}` placeholders, such as `Thanks for posting, {author}!`.
+/// Values are escaped as they are substituted, except `http` and `https` URLs, which would stop
+/// being links if escaped. A value only counts as a URL if it has a host and contains no
+/// whitespace, brackets, parentheses or angle brackets, so it cannot break out of a link. Use
+/// `{{` and `}}` for literal braces.
+/// Placeholders without a value are left unchanged.
+///
+/// `Commentable.reply_with_template()` fills in `author`, `title`, `permalink` and `subreddit`
+/// automatically.
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use new_rawr::markdown::ReplyTemplate;
+/// let template = ReplyTemplate::new("Hi {author}, your post [{title}]({permalink}) was removed.");
+/// let mut vars = HashMap::new();
+/// vars.insert(String::from("author"), String::from("some_user"));
+/// vars.insert(String::from("title"), String::from("[Meta] Rule 1"));
+/// vars.insert(String::from("permalink"), String::from("https://www.reddit.com/comments/4uule8"));
+/// assert_eq!(template.render(&vars),
+///            "Hi some\\_user, your post [\\[Meta\\] Rule 1](https://www.reddit.com/comments/4uule8) \
+///             was removed.");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplyTemplate {
+    template: String,
+}
+
+impl ReplyTemplate {
+    /// Creates a template from the specified text.
+    pub fn new(template: &str) -> ReplyTemplate {
+        ReplyTemplate { template: template.to_owned() }
+    }
+
+    /// Substitutes the variables into the template.
+    pub fn render(&self, vars: &HashMap<String, String>) -> String {
+        let mut res = String::with_capacity(self.template.len());
+        let mut rest = &self.template[..];
+        while let Some(pos) = rest.find(['{', '}']) {
+            res.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                res.push_str(&rest[..1]);
+                rest = &rest[2..];
+                continue;
+            }
+            let placeholder = if rest.starts_with('{') { rest.find('}') } else { None };
+            match placeholder.and_then(|end| vars.get(&rest[1..end]).map(|value| (end, value))) {
+                Some((end, value)) => {
+                    if is_url(value) {
+                        res.push_str(value);
+                    } else {
+                        res.push_str(&escape(value));
+                    }
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    res.push_str(&rest[..1]);
+                    rest = &rest[1..];
+                }
+            }
+        }
+        res.push_str(rest);
+        res
+    }
+}
+
+/// Returns `true` if the value is an `http` or `https` URL that can be inserted into Markdown
+/// unescaped.
+fn is_url(value: &str) -> bool {
+    let rest = match value.find("://") {
+        Some(pos) if value[..pos].eq_ignore_ascii_case("http") ||
+                     value[..pos].eq_ignore_ascii_case("https") => &value[pos + 3..],
+        _ => return false,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    !host.is_empty() &&
+    !value.contains(|c: char| c.is_whitespace() || c.is_control() || "[]()<>".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{escape, is_url, ReplyTemplate};
+
+    #[test]
+    fn escape_inline_formatting() {
+        assert_eq!(escape("*a* _b_ ~~c~~ ^d `e`"), "\\*a\\* \\_b\\_ \\~\\~c\\~\\~ \\^d \\`e\\`");
+        assert_eq!(escape("[a](b) | c"), "\\[a\\]\\(b\\) \\| c");
+        assert_eq!(escape("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn escape_html() {
+        assert_eq!(escape("&amp; <b>"), "\\&amp; \\<b\\>");
+    }
+
+    #[test]
+    fn escape_spoilers() {
+        assert_eq!(escape(">!secret!<"), "\\>\\!secret\\!\\<");
+        assert_eq!(escape("wow!"), "wow\\!");
+    }
+
+    #[test]
+    fn escape_headings_and_quotes() {
+        assert_eq!(escape("# title"), "\\# title");
+        assert_eq!(escape("> quote"), "\\> quote");
+    }
+
+    #[test]
+    fn escape_list_markers() {
+        assert_eq!(escape("- a"), "\\- a");
+        assert_eq!(escape("+ a"), "\\+ a");
+        assert_eq!(escape("1. a"), "1\\. a");
+        assert_eq!(escape("  42. a"), "  42\\. a");
+        assert_eq!(escape("a\n- b\n2. c"), "a\n\\- b\n2\\. c");
+    }
+
+    #[test]
+    fn escape_leaves_markers_inside_lines() {
+        assert_eq!(escape("well-known, 1+1 is 2."), "well-known, 1+1 is 2.");
+        assert_eq!(escape("1.5 million"), "1.5 million");
+        assert_eq!(escape("v2.0 - released"), "v2.0 - released");
+    }
+
+    #[test]
+    fn urls() {
+        assert!(is_url("https://www.reddit.com/comments/4uule8"));
+        assert!(is_url("HTTP://example.com"));
+        assert!(!is_url("javascript://alert(1)"));
+        assert!(!is_url("ftp://example.com/file"));
+        assert!(!is_url("https://"));
+        assert!(!is_url("https://example.com/a b"));
+        assert!(!is_url("https://example.com/)[click](https://evil.example"));
+    }
+
+    #[test]
+    fn template_escapes_values_that_are_not_urls() {
+        let template = ReplyTemplate::new("[link]({url})");
+        let mut vars = HashMap::new();
+        vars.insert(String::from("url"), String::from("x://y) **bold**"));
+        assert_eq!(template.render(&vars), "[link](x://y\\) \\*\\*bold\\*\\*)");
+        vars.insert(String::from("url"), String::from("https://example.com/a?b=c"));
+        assert_eq!(template.render(&vars), "[link](https://example.com/a?b=c)");
+    }
+}
@@ -1,5 +1,11 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
+use crate::client::url_escape;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::structures::subreddit::Subreddit;
+use crate::structures::user::User;
+
 /// Configures a paginated listing.
 pub struct ListingOptions {
     /// The maximum amount of posts to fetch in one request.
@@ -14,10 +20,10 @@ impl Display for ListingOptions {
     }
 }
 
-impl ListingOptions {
+impl Default for ListingOptions {
     /// Provides the default options (25 posts per page, starts at first post in listing). If
     /// you are unsure, this will act like the default display options on Reddit.
-    pub fn default() -> ListingOptions {
+    fn default() -> ListingOptions {
         ListingOptions {
             batch: 25,
             anchor: ListingAnchor::None,
@@ -72,6 +78,456 @@ impl Display for TimeFilter {
     }
 }
 
+/// The order of a listing requested with a `ListingRequest`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListingSort {
+    /// The hot feed, which is the default order on Reddit.
+    Hot,
+    /// The newest posts first.
+    New,
+    /// Posts that are quickly gaining votes. Not available for searches.
+    Rising,
+    /// The highest scoring posts in the time period.
+    Top,
+    /// The posts with the most even split of votes in the time period. Not available for
+    /// searches.
+    Controversial,
+    /// The best matches for the query. Only available for searches.
+    Relevance,
+    /// The posts with the most comments. Only available for searches.
+    Comments,
+}
+
+impl Display for ListingSort {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            ListingSort::Hot => "hot",
+            ListingSort::New => "new",
+            ListingSort::Rising => "rising",
+            ListingSort::Top => "top",
+            ListingSort::Controversial => "controversial",
+            ListingSort::Relevance => "relevance",
+            ListingSort::Comments => "comments",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Configures a listing of posts in a subreddit: the sort order, time filter, page size,
+/// anchor, and (for searches) the query. Pass it to `Subreddit.listing()`.
+/// # Examples
+/// ```
+/// use new_rawr::options::{ListingRequest, ListingSort, TimeFilter};
+/// let top = ListingRequest::new(ListingSort::Top)
+///     .time(TimeFilter::Week)
+///     .limit(100)
+///     .after("t3_4uule8")
+///     .sr_detail();
+/// assert_eq!(top.query_stem("rust").unwrap(),
+///            "/r/rust/top?limit=100&t=week&sr_detail=true&raw_json=1");
+///
+/// let search = ListingRequest::search("async traits").sort(ListingSort::New);
+/// assert_eq!(search.query_stem("rust").unwrap(),
+///            "/r/rust/search?q=async+traits&restrict_sr=on&sort=new&limit=25&raw_json=1");
+/// assert!(ListingRequest::new(ListingSort::Relevance).query_stem("rust").is_err());
+/// ```
+pub struct ListingRequest {
+    /// The order of the listing.
+    pub sort: ListingSort,
+    /// The time period for the top and controversial listings and for searches. Reddit
+    /// defaults to a day for top and controversial listings, and to all time for searches.
+    pub time: Option<TimeFilter>,
+    /// The maximum amount of posts to fetch in one request (up to 100).
+    pub limit: u8,
+    /// See `ListingAnchor` for explanation of this property.
+    pub anchor: ListingAnchor,
+    /// Includes details of each post's subreddit (`sr_detail`) in the response.
+    pub sr_detail: bool,
+    /// The search query, or `None` for a normal listing.
+    pub query: Option<String>,
+}
+
+impl ListingRequest {
+    /// Requests a listing in the specified order, with 25 posts per page and no anchor.
+    pub fn new(sort: ListingSort) -> ListingRequest {
+        ListingRequest {
+            sort,
+            time: None,
+            limit: 25,
+            anchor: ListingAnchor::None,
+            sr_detail: false,
+            query: None,
+        }
+    }
+
+    /// Requests the posts in the subreddit that match the query, best matches first. See
+    /// Reddit's search help for the query syntax (e.g. `author:spez`).
+    pub fn search(query: &str) -> ListingRequest {
+        let mut request = ListingRequest::new(ListingSort::Relevance);
+        request.query = Some(query.to_owned());
+        request
+    }
+
+    /// Sets the order of the listing.
+    pub fn sort(mut self, sort: ListingSort) -> ListingRequest {
+        self.sort = sort;
+        self
+    }
+
+    /// Sets the time period, e.g. for the top listing.
+    pub fn time(mut self, time: TimeFilter) -> ListingRequest {
+        self.time = Some(time);
+        self
+    }
+
+    /// Sets the maximum amount of posts to fetch in one request.
+    pub fn limit(mut self, limit: u8) -> ListingRequest {
+        self.limit = limit;
+        self
+    }
+
+    /// Starts the listing after the item with the specified full name.
+    pub fn after(mut self, name: &str) -> ListingRequest {
+        self.anchor = ListingAnchor::After(name.to_owned());
+        self
+    }
+
+    /// Starts the listing before the item with the specified full name.
+    pub fn before(mut self, name: &str) -> ListingRequest {
+        self.anchor = ListingAnchor::Before(name.to_owned());
+        self
+    }
+
+    /// Includes details of each post's subreddit in the response.
+    pub fn sr_detail(mut self) -> ListingRequest {
+        self.sr_detail = true;
+        self
+    }
+
+    /// The URL of the listing in the specified subreddit, without the anchor (which pagination
+    /// replaces). Fails if the sort order cannot be used with (or without) a search query.
+    pub fn query_stem(&self, subreddit: &str) -> Result<String, APIError> {
+        let mut stem = match (&self.query, self.sort) {
+            (&Some(_), ListingSort::Rising) | (&Some(_), ListingSort::Controversial) => {
+                return Err(APIError::ValidationError(format!("Searches cannot be sorted by {}",
+                                                             self.sort)));
+            }
+            (Some(query), sort) => {
+                format!("{}?q={}&restrict_sr=on&sort={}&limit={}",
+                        endpoints::subreddit_search(subreddit),
+                        url_escape(query),
+                        sort,
+                        self.limit)
+            }
+            (&None, ListingSort::Relevance) | (&None, ListingSort::Comments) => {
+                return Err(APIError::ValidationError(format!("Only searches can be sorted by {}",
+                                                             self.sort)));
+            }
+            (&None, sort) => {
+                format!("{}?limit={}",
+                        endpoints::subreddit_listing(subreddit, &sort.to_string()),
+                        self.limit)
+            }
+        };
+        if let Some(ref time) = self.time {
+            stem.push_str(&time.to_string());
+        }
+        if self.sr_detail {
+            stem.push_str("&sr_detail=true");
+        }
+        stem.push_str("&raw_json=1");
+        Ok(stem)
+    }
+}
+
+impl Default for ListingRequest {
+    fn default() -> ListingRequest {
+        ListingRequest::new(ListingSort::Hot)
+    }
+}
+
+/// Converts the options of the deprecated listing methods (e.g. `Subreddit.hot()`) into a
+/// request for the hot listing, keeping the page size and anchor.
+impl From<ListingOptions> for ListingRequest {
+    fn from(opts: ListingOptions) -> ListingRequest {
+        ListingRequest {
+            limit: opts.batch,
+            anchor: opts.anchor,
+            ..ListingRequest::default()
+        }
+    }
+}
+
+/// The reason sent when reporting an item. Use `Subreddit.report_reason()` to pick the correct
+/// kind of reason for a subreddit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportReason {
+    /// A free-form reason written by the reporter.
+    FreeForm(String),
+    /// The violation reason of one of the subreddit's rules.
+    Rule(String),
+}
+
+impl Display for ReportReason {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            ReportReason::FreeForm(ref reason) => write!(f, "reason={}", reason),
+            ReportReason::Rule(ref reason) => write!(f, "rule_reason={}", reason),
+        }
+    }
+}
+
+/// The recipient of a private message sent with `MessageInterface.compose()`. Messages to a
+/// subreddit are delivered to its moderators.
+///
+/// Strings are converted by their prefix: `/r/name` or `r/name` is a subreddit, and `/u/name`,
+/// `u/name` or a bare name is a user.
+/// # Examples
+/// ```
+/// use new_rawr::options::Recipient;
+/// assert_eq!(Recipient::from("/r/rust"), Recipient::Subreddit(String::from("rust")));
+/// assert_eq!(Recipient::from("u/Aurora0001"), Recipient::User(String::from("Aurora0001")));
+/// assert_eq!(Recipient::from("/r/rust").to_string(), "/r/rust");
+/// assert!(Recipient::from("not a user").validate().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recipient {
+    /// A user, by username (without `/u/`).
+    User(String),
+    /// The moderators of a subreddit, by subreddit name (without `/r/`).
+    Subreddit(String),
+}
+
+impl Recipient {
+    /// Checks that the name could be a valid username or subreddit name (letters, digits,
+    /// underscores and hyphens, up to 21 characters), so malformed recipients are rejected
+    /// before the message is sent.
+    pub fn validate(&self) -> Result<(), APIError> {
+        let name = match *self {
+            Recipient::User(ref name) | Recipient::Subreddit(ref name) => name,
+        };
+        let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if name.is_empty() || name.len() > 21 || !valid_chars {
+            return Err(APIError::ValidationError(format!("'{}' is not a valid message \
+                                                          recipient",
+                                                         self)));
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for Recipient {
+    fn from(name: &str) -> Recipient {
+        let trimmed = name.trim().trim_start_matches('/');
+        if let Some(subreddit) = trimmed.strip_prefix("r/") {
+            Recipient::Subreddit(subreddit.to_owned())
+        } else if let Some(user) = trimmed.strip_prefix("u/") {
+            Recipient::User(user.to_owned())
+        } else {
+            Recipient::User(trimmed.to_owned())
+        }
+    }
+}
+
+impl<'a, 'b> From<&'b User<'a>> for Recipient {
+    fn from(user: &User) -> Recipient {
+        Recipient::User(user.name.to_owned())
+    }
+}
+
+impl<'a, 'b> From<&'b Subreddit<'a>> for Recipient {
+    fn from(subreddit: &Subreddit) -> Recipient {
+        Recipient::Subreddit(subreddit.name.to_owned())
+    }
+}
+
+impl Display for Recipient {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Recipient::User(ref name) => write!(f, "{}", name),
+            Recipient::Subreddit(ref name) => write!(f, "/r/{}", name),
+        }
+    }
+}
+
+/// The kinds of image that can be uploaded to a subreddit's structured styles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StyleImage {
+    /// The large banner shown at the top of the subreddit.
+    BannerBackground,
+    /// An image positioned over the banner background.
+    BannerPositioned,
+    /// The banner shown on mobile devices.
+    MobileBanner,
+    /// The subreddit's icon.
+    CommunityIcon,
+}
+
+impl Display for StyleImage {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            StyleImage::BannerBackground => "bannerBackgroundImage",
+            StyleImage::BannerPositioned => "bannerPositionedImage",
+            StyleImage::MobileBanner => "mobileBannerImage",
+            StyleImage::CommunityIcon => "communityIcon",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Changes to make to a subreddit's structured styles. Only the fields that are set are sent,
+/// so everything else is left unchanged. Colours are hex strings such as `#FF4500`.
+/// # Examples
+/// ```
+/// use new_rawr::options::StyleUpdate;
+/// let update = StyleUpdate::new().primary_color("#FF4500").highlight_color("#0079D3");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StyleUpdate {
+    /// Style fields to set, as (API field name, value) pairs.
+    pub fields: Vec<(String, String)>,
+}
+
+impl StyleUpdate {
+    /// Creates an empty `StyleUpdate` which changes nothing.
+    pub fn new() -> StyleUpdate {
+        StyleUpdate::default()
+    }
+
+    /// Sets the primary (theme) colour.
+    pub fn primary_color(self, color: &str) -> StyleUpdate {
+        self.field("primaryColor", color)
+    }
+
+    /// Sets the highlight colour.
+    pub fn highlight_color(self, color: &str) -> StyleUpdate {
+        self.field("highlightColor", color)
+    }
+
+    /// Sets the banner background colour.
+    pub fn banner_background_color(self, color: &str) -> StyleUpdate {
+        self.field("bannerBackgroundColor", color)
+    }
+
+    /// Sets an image to the URL of a previously uploaded image.
+    pub fn image(self, kind: StyleImage, url: &str) -> StyleUpdate {
+        self.field(&kind.to_string(), url)
+    }
+
+    /// Sets any other style field by its API name (e.g. `bannerHeight`).
+    pub fn field(mut self, name: &str, value: &str) -> StyleUpdate {
+        self.fields.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+/// How a removal message is delivered to the author of a removed item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemovalMessageType {
+    /// A public, distinguished reply to the removed item.
+    Public,
+    /// A private modmail message to the author, sent from the subreddit.
+    Private,
+    /// A private modmail message to the author which shows the username of the moderator.
+    PrivateExposed,
+    /// A public, distinguished reply to the removed item from the subreddit's mod team account
+    /// (u/SUBREDDIT-ModTeam) instead of the moderator. This is the only way that the API allows
+    /// replying as the subreddit.
+    PublicAsSubreddit,
+}
+
+impl Display for RemovalMessageType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            RemovalMessageType::Public => "public",
+            RemovalMessageType::Private => "private",
+            RemovalMessageType::PrivateExposed => "private_exposed",
+            RemovalMessageType::PublicAsSubreddit => "public_as_subreddit",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Who a moderator's reply appears to be from. See `Subreddit.reply_as()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyIdentity {
+    /// The logged-in moderator, with a [M] distinguish.
+    Moderator,
+    /// The logged-in moderator, with a [M] distinguish, stickied to the top of the post. Only
+    /// top-level replies to submissions can be stickied.
+    StickiedModerator,
+    /// The subreddit itself (u/SUBREDDIT-ModTeam). Reddit only allows this for removal messages
+    /// (see `RemovalMessageType::PublicAsSubreddit`), so replies fail with
+    /// `APIError::Unsupported`.
+    Subreddit,
+}
+
+/// A permission that can be given to a moderator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModPermission {
+    /// Full permissions, including any added by Reddit in the future.
+    All,
+    /// Managing approved users and bans.
+    Access,
+    /// Managing chat channels.
+    ChatConfig,
+    /// Moderating chat channels.
+    ChatOperator,
+    /// Editing the subreddit's settings, styles and rules.
+    Config,
+    /// Managing user and post flair.
+    Flair,
+    /// Reading and replying to modmail.
+    Mail,
+    /// Approving, removing and locking posts and comments.
+    Posts,
+    /// Editing wiki pages and wiki settings.
+    Wiki,
+}
+
+impl ModPermission {
+    /// Every permission other than `All`.
+    pub const SPECIFIC: [ModPermission; 8] = [ModPermission::Access,
+                                              ModPermission::ChatConfig,
+                                              ModPermission::ChatOperator,
+                                              ModPermission::Config,
+                                              ModPermission::Flair,
+                                              ModPermission::Mail,
+                                              ModPermission::Posts,
+                                              ModPermission::Wiki];
+
+    /// Formats a set of permissions in the form used by the API (e.g. `+posts,-wiki,...`).
+    pub fn to_param(permissions: &[ModPermission]) -> String {
+        if permissions.contains(&ModPermission::All) {
+            return String::from("+all");
+        }
+        ModPermission::SPECIFIC.iter()
+            .map(|perm| {
+                let sign = if permissions.contains(perm) { '+' } else { '-' };
+                format!("{}{}", sign, perm)
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+impl Display for ModPermission {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            ModPermission::All => "all",
+            ModPermission::Access => "access",
+            ModPermission::ChatConfig => "chat_config",
+            ModPermission::ChatOperator => "chat_operator",
+            ModPermission::Config => "config",
+            ModPermission::Flair => "flair",
+            ModPermission::Mail => "mail",
+            ModPermission::Posts => "posts",
+            ModPermission::Wiki => "wiki",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// Options used when creating a link post. See `structures::subreddit` for examples of usage.
 pub struct LinkPost {
     /// The title of the link post to create
@@ -81,6 +537,10 @@ pub struct LinkPost {
     pub link: String,
     /// True if resubmitting this link is intended.
     pub resubmit: bool,
+    /// The ID of the post flair template to apply, if any.
+    pub flair_id: Option<String>,
+    /// Tags and other settings applied when the post is submitted.
+    pub options: SubmitOptions,
 }
 
 impl LinkPost {
@@ -91,9 +551,18 @@ impl LinkPost {
             title: title.to_owned(),
             link: link.to_owned(),
             resubmit: false,
+            flair_id: None,
+            options: SubmitOptions::default(),
         }
     }
 
+    /// Sets the post flair template to apply to this post. Some subreddits require a flair to be
+    /// set; see `Subreddit.link_flair_templates()` for the available templates.
+    pub fn flair(mut self, flair_id: &str) -> LinkPost {
+        self.flair_id = Some(flair_id.to_owned());
+        self
+    }
+
     /// Marks this post as a resubmission, so the API will accept it even if it has been submitted
     /// in this subreddit before.
     /// # Examples
@@ -105,6 +574,13 @@ impl LinkPost {
         self.resubmit = true;
         self
     }
+
+    /// Sets the tags and other settings to apply when the post is submitted. See
+    /// `SubmitOptions` for examples.
+    pub fn options(mut self, options: SubmitOptions) -> LinkPost {
+        self.options = options;
+        self
+    }
 }
 
 /// Options used when creating a self post. See `structures::subreddit` for examples of usage.
@@ -113,6 +589,10 @@ pub struct SelfPost {
     pub title: String,
     /// The markdown post body.
     pub text: String,
+    /// The ID of the post flair template to apply, if any.
+    pub flair_id: Option<String>,
+    /// Tags and other settings applied when the post is submitted.
+    pub options: SubmitOptions,
 }
 
 impl SelfPost {
@@ -122,6 +602,149 @@ impl SelfPost {
         SelfPost {
             title: title.to_owned(),
             text: text.to_owned(),
+            flair_id: None,
+            options: SubmitOptions::default(),
+        }
+    }
+
+    /// Sets the post flair template to apply to this post. Some subreddits require a flair to be
+    /// set; see `Subreddit.link_flair_templates()` for the available templates.
+    /// # Examples
+    /// ```
+    /// use new_rawr::options::SelfPost;
+    /// let post = SelfPost::new("Question about lifetimes", "...")
+    ///     .flair("e3cc2a4e-3a6b-11e7-bd5f-0e9ea2bcb0b8");
+    /// ```
+    pub fn flair(mut self, flair_id: &str) -> SelfPost {
+        self.flair_id = Some(flair_id.to_owned());
+        self
+    }
+
+    /// Sets the tags and other settings to apply when the post is submitted. See
+    /// `SubmitOptions` for examples.
+    pub fn options(mut self, options: SubmitOptions) -> SelfPost {
+        self.options = options;
+        self
+    }
+}
+
+/// Tags and settings that are applied to a `LinkPost` or `SelfPost` as part of the submission,
+/// instead of with separate requests after it has been posted. By default, no tags are set and
+/// replies are sent to the author's inbox.
+/// # Examples
+/// ```
+/// use new_rawr::options::{SelfPost, SubmitOptions};
+/// let options = SubmitOptions::new()
+///     .original_content()
+///     .spoiler()
+///     .send_replies(false)
+///     .event("2026-11-01T18:00:00", "2026-11-01T20:00:00", "Europe/London");
+/// assert_eq!(options.to_string(),
+///            "&sendreplies=false&original_content=true&spoiler=true\
+///             &event_start=2026-11-01T18%3A00%3A00&event_end=2026-11-01T20%3A00%3A00\
+///             &event_tz=Europe%2FLondon");
+/// let post = SelfPost::new("Meetup!", "See you there.").options(options);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmitOptions {
+    /// Marks the post as original content (OC). Only works in subreddits that allow it.
+    pub original_content: bool,
+    /// Marks the post as a spoiler, which blurs its preview.
+    pub spoiler: bool,
+    /// Marks the post as NSFW (not safe for work).
+    pub nsfw: bool,
+    /// Sends replies to the post to the author's inbox.
+    pub send_replies: bool,
+    /// The ID of a collection to add the post to (moderators only).
+    pub collection_id: Option<String>,
+    /// When the event that the post is about starts, e.g. `2026-11-01T18:00:00`.
+    pub event_start: Option<String>,
+    /// When the event that the post is about ends, in the same format as `event_start`.
+    pub event_end: Option<String>,
+    /// The time zone of `event_start` and `event_end`, e.g. `Europe/London`.
+    pub event_tz: Option<String>,
+}
+
+impl SubmitOptions {
+    /// Creates options with no tags set. Replies are sent to the author's inbox.
+    pub fn new() -> SubmitOptions {
+        SubmitOptions {
+            original_content: false,
+            spoiler: false,
+            nsfw: false,
+            send_replies: true,
+            collection_id: None,
+            event_start: None,
+            event_end: None,
+            event_tz: None,
+        }
+    }
+
+    /// Marks the post as original content (OC).
+    pub fn original_content(mut self) -> SubmitOptions {
+        self.original_content = true;
+        self
+    }
+
+    /// Marks the post as a spoiler.
+    pub fn spoiler(mut self) -> SubmitOptions {
+        self.spoiler = true;
+        self
+    }
+
+    /// Marks the post as NSFW.
+    pub fn nsfw(mut self) -> SubmitOptions {
+        self.nsfw = true;
+        self
+    }
+
+    /// Sets whether replies to the post are sent to the author's inbox.
+    pub fn send_replies(mut self, send_replies: bool) -> SubmitOptions {
+        self.send_replies = send_replies;
+        self
+    }
+
+    /// Adds the post to a collection. Only moderators can add posts to collections.
+    pub fn collection(mut self, collection_id: &str) -> SubmitOptions {
+        self.collection_id = Some(collection_id.to_owned());
+        self
+    }
+
+    /// Makes the post an event post. `start` and `end` are local times in the
+    /// `YYYY-MM-DDTHH:MM:SS` format, in the time zone `tz` (e.g. `America/New_York`).
+    pub fn event(mut self, start: &str, end: &str, tz: &str) -> SubmitOptions {
+        self.event_start = Some(start.to_owned());
+        self.event_end = Some(end.to_owned());
+        self.event_tz = Some(tz.to_owned());
+        self
+    }
+}
+
+impl Default for SubmitOptions {
+    fn default() -> SubmitOptions {
+        SubmitOptions::new()
+    }
+}
+
+/// Formats the options as parameters to append to a submission body, each starting with `&`.
+impl Display for SubmitOptions {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "&sendreplies={}", self.send_replies)?;
+        let flags = [("original_content", self.original_content),
+                     ("spoiler", self.spoiler),
+                     ("nsfw", self.nsfw)];
+        for &(name, set) in flags.iter().filter(|&&(_, set)| set) {
+            write!(f, "&{}={}", name, set)?;
+        }
+        let values = [("collection_id", &self.collection_id),
+                      ("event_start", &self.event_start),
+                      ("event_end", &self.event_end),
+                      ("event_tz", &self.event_tz)];
+        for &(name, value) in &values {
+            if let Some(ref value) = *value {
+                write!(f, "&{}={}", name, url_escape(value))?;
+            }
         }
+        Ok(())
     }
 }
@@ -6,6 +6,13 @@ pub use serde::Deserialize;
 pub struct TokenResponseData {
     pub access_token: String,
     pub expires_in: u64,
+    #[serde(default)]
     pub scope: String,
+    #[serde(default)]
     pub token_type: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Only returned for the installed client grant, as the device ID that was sent.
+    #[serde(default)]
+    pub device_id: Option<String>,
 }
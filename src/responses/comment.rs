@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 pub use serde::Deserialize;
-use crate::responses::{BasicThing, ThingList};
+use crate::responses::{BasicThing, Distinguish, ThingList};
 use crate::responses::listing::ListingData;
 
 /// The 'listing' format for comments.
@@ -92,14 +92,15 @@ pub struct CommentData {
     /// A timestamp of the time when the post was created, in **UTC**.
     pub created_utc: f64,
     /// Indicates whether the user has used a special flag for themselves, e.g. [M] or [A].
-    /// Possible values:
-    /// - None - Normal user
-    /// - Some("moderator") - [M]
-    /// - Some("admin") - [A]
-    /// - Some("special") - other special 'distinguishes' e.g. [Δ]
-    pub distinguished: Option<String>,
+    /// This is `None` for normal users.
+    pub distinguished: Option<Distinguish>,
     pub num_reports: Option<u64>, // TODO: skipped mod_reports
-    pub parent_id: String
+    pub parent_id: String,
+    /// The permalink of the comment, relative to reddit.com, if returned by the endpoint.
+    pub permalink: Option<String>,
+    /// The title of the parent submission. This is only returned by some listings (e.g. a
+    /// user's comments).
+    pub link_title: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
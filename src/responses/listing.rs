@@ -1,6 +1,7 @@
 use serde_json::Value;
 pub use serde::Deserialize;
-use crate::responses::BasicThing;
+use serde::de::DeserializeOwned;
+use crate::responses::{BasicThing, Distinguish};
 use crate::responses::comment::CommentListing;
 
 /// The 'listing' format returned by the Reddit API for post lists.
@@ -41,18 +42,82 @@ pub struct SubredditAboutData {
     pub submit_text_label: Option<String>,
     pub submit_link_label: Option<String>,
     pub comment_score_hide_mins: u64,
+    pub free_form_reports: Option<bool>,
     // CSS fields omitted
 }
 
-/// The contents of a call to a 'listing' endpoint.
+/// API response from /r/subreddit/about/rules
 #[derive(Deserialize, Debug)]
+pub struct SubredditRules {
+    pub rules: Vec<SubredditRule>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SubredditRule {
+    pub short_name: String,
+    pub description: String,
+    pub kind: String,
+    pub violation_reason: String,
+    pub priority: u64,
+    pub created_utc: f64,
+}
+
+/// The contents of a call to a 'listing' endpoint. Children which cannot be deserialized (e.g.
+/// promoted posts or new kinds of item) are left out of `children`, and the reason for each is
+/// recorded in `skipped` instead of failing the whole page.
+/// # Examples
+/// ```
+/// use new_rawr::responses::listing::{ListingData, SubredditRule};
+/// let json = r#"{"modhash": null, "before": null, "after": null, "children": [
+///     {"kind": "promo", "data": {}}
+/// ]}"#;
+/// let page: ListingData<SubredditRule> = serde_json::from_str(json).unwrap();
+/// assert!(page.children.is_empty());
+/// assert_eq!(page.skipped.len(), 1);
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(from = "RawListingData", bound = "T: DeserializeOwned")]
 pub struct ListingData<T> {
     /// A modhash (essentially a CSRF token) generated for this request. This is generally
     /// not required for any use-case, but is provided nevertheless.
     pub modhash: Option<String>,
     pub before: Option<String>,
     pub after: Option<String>,
+    /// The number of items in this page.
+    pub dist: Option<u64>,
     pub children: Vec<BasicThing<T>>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawListingData {
+    modhash: Option<String>,
+    before: Option<String>,
+    after: Option<String>,
+    dist: Option<u64>,
+    children: Vec<Value>,
+}
+
+impl<T: DeserializeOwned> From<RawListingData> for ListingData<T> {
+    fn from(raw: RawListingData) -> ListingData<T> {
+        let mut children = Vec::new();
+        let mut skipped = Vec::new();
+        for child in raw.children {
+            let kind = child.get("kind").and_then(Value::as_str).unwrap_or("?").to_owned();
+            match serde_json::from_value(child) {
+                Ok(child) => children.push(child),
+                Err(err) => skipped.push(format!("skipped '{}' item: {}", kind, err)),
+            }
+        }
+        ListingData {
+            modhash: raw.modhash,
+            before: raw.before,
+            after: raw.after,
+            dist: raw.dist,
+            children,
+            skipped,
+        }
+    }
 }
 
 /// API response from r/{subreddit}/about/contributors
@@ -63,6 +128,8 @@ pub struct UserListing {
     pub modhash: Option<String>,
     pub before: Option<String>,
     pub after: Option<String>,
+    /// The number of items in this page.
+    pub dist: Option<u64>,
     pub children: Vec<UserListingData>,
 }
 
@@ -100,6 +167,10 @@ pub struct SubmissionData {
     /// If this post is flaired, this set to `Some(FLAIR TEXT)`. Otherwise, it is `None`.
     /// Link flairs **can** be empty strings.
     pub link_flair_text: Option<String>,
+    /// The ID of the flair template used for the post's flair, if the flair was chosen from a
+    /// template.
+    #[serde(default)]
+    pub link_flair_template_id: Option<String>,
     /// The ID of the post in base-36 form, as used in Reddit's links.
     pub id: String,
     // skipped from_kind
@@ -153,6 +224,10 @@ pub struct SubmissionData {
     /// The reason for the post removal, if you are a moderator **and** this post has been
     /// removed.
     pub removal_reason: Option<String>,
+    /// Who removed the post (e.g. `moderator`, `automod_filtered` or `deleted`), if it has been
+    /// removed or deleted.
+    #[serde(default)]
+    pub removed_by_category: Option<String>,
     // TODO: skipped post_hint
     /// This is `true` if this submission is stickied (an 'annoucement' thread)
     pub stickied: bool,
@@ -189,17 +264,22 @@ pub struct SubmissionData {
     /// A timestamp of the time when the post was created, in **UTC**.
     pub created_utc: f64,
     /// Indicates whether the user has used a special flag for themselves, e.g. [M] or [A].
-    /// Possible values:
-    /// - None - Normal user
-    /// - Some("moderator") - [M]
-    /// - Some("admin") - [A]
-    /// - Some("special") - other special 'distinguishes' e.g. [Δ]
-    pub distinguished: Option<String>,
+    /// This is `None` for normal users.
+    pub distinguished: Option<Distinguish>,
     // TODO: skipped mod_reports
     /// This is `true` if the user has visited this link.
     pub visited: bool,
     /// The number of reports, if the user is a moderator of this subreddit.
     pub num_reports: Option<u64>,
+    /// The number of times this post has been viewed. Reddit only returns this to moderators
+    /// of the subreddit (and not for every post), so it is usually `None`.
+    pub view_count: Option<u64>,
+    /// The ratio of upvotes to total votes (e.g. `0.95`), if Reddit provided it.
+    pub upvote_ratio: Option<f64>,
+    /// The number of times this post has been crossposted, if Reddit provided it.
+    pub num_crossposts: Option<u64>,
+    /// Preview images generated by Reddit, if available.
+    pub preview: Option<Preview>,
 }
 
 /// Represents data responded in a user listing
@@ -215,3 +295,25 @@ pub struct UserListingData {
     pub id: String,
 }
 
+
+/// Preview images of a submission.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Preview {
+    pub images: Vec<PreviewImage>,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// A preview image, in its original size and several downscaled resolutions.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PreviewImage {
+    pub source: PreviewSource,
+    pub resolutions: Vec<PreviewSource>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PreviewSource {
+    pub url: String,
+    pub width: u64,
+    pub height: u64,
+}
@@ -3,12 +3,19 @@ pub mod auth;
 pub mod comment;
 pub mod listing;
 pub mod messages;
+pub mod modlog;
+pub mod styles;
+pub mod trending;
 pub mod user;
-pub use serde::Deserialize;
-
+pub mod wiki;
+pub use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
 use serde_json::Value;
 
+/// The name Reddit uses for the author of deleted content.
+pub(crate) const DELETED: &str = "[deleted]";
+
 /// A base structure that can represent both 'Thing' objects and 'Listing' objects, which both
 /// return a `kind` and `data`.
 #[derive(Deserialize, Debug)]
@@ -28,6 +35,55 @@ pub struct BasicThing<T> {
     pub data: T,
 }
 
+/// A special flag that a user has added to their submission or comment.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Distinguish {
+    /// Distinguished as a moderator - [M]
+    Moderator,
+    /// Distinguished as a Reddit admin - [A]
+    Admin,
+    /// Any other special distinguish, e.g. [Δ]
+    #[serde(other)]
+    Special,
+}
+
+impl Display for Distinguish {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            Distinguish::Moderator => "moderator",
+            Distinguish::Admin => "admin",
+            Distinguish::Special => "special",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The side of the username (or title) that a flair is displayed on.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlairPosition {
+    /// Shown to the left.
+    Left,
+    /// Shown to the right.
+    Right,
+    /// The position is not set (or is not recognised). Sent and displayed as an empty string,
+    /// as Reddit does.
+    #[serde(rename = "", other)]
+    Unset,
+}
+
+impl Display for FlairPosition {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            FlairPosition::Left => "left",
+            FlairPosition::Right => "right",
+            FlairPosition::Unset => "",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ThingList {
     pub things: Vec<BasicThing<Value>>
@@ -39,19 +95,150 @@ pub struct FlairSelectorResponse {
     pub choices: Vec<FlairChoice>
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct FlairChoice {
     pub flair_css_class: String,
     pub flair_template_id: String,
     pub flair_text: String,
-    pub flair_position: String,
+    pub flair_position: FlairPosition,
     pub flair_text_editable: bool
 }
 
+/// Allows a `FlairChoice` to be passed directly to `Flairable.flair()` in place of its template
+/// ID.
+impl AsRef<str> for FlairChoice {
+    fn as_ref(&self) -> &str {
+        &self.flair_template_id
+    }
+}
+
+/// API response from /api/v1/subreddit/post_requirements
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PostRequirements {
+    #[serde(default)]
+    pub is_flair_required: bool,
+    pub title_text_min_length: Option<u64>,
+    pub title_text_max_length: Option<u64>,
+    #[serde(default)]
+    pub title_required_strings: Vec<String>,
+    #[serde(default)]
+    pub title_blacklisted_strings: Vec<String>,
+    /// `required`, `notAllowed` or `none`.
+    pub body_restriction_policy: Option<String>,
+    pub body_text_min_length: Option<u64>,
+    pub body_text_max_length: Option<u64>,
+    #[serde(default)]
+    pub body_required_strings: Vec<String>,
+    #[serde(default)]
+    pub body_blacklisted_strings: Vec<String>,
+    /// `whitelist`, `blacklist` or `none`.
+    pub link_restriction_policy: Option<String>,
+    #[serde(default)]
+    pub domain_whitelist: Vec<String>,
+    #[serde(default)]
+    pub domain_blacklist: Vec<String>,
+    pub guidelines_text: Option<String>,
+}
+
+/// An item in the API response from /r/subreddit/api/link_flair_v2
+#[derive(Deserialize, Debug, Clone)]
+pub struct LinkFlairTemplate {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub text_editable: bool,
+    pub css_class: Option<String>,
+}
+
+/// API response from /r/subreddit/api/flairlist
+#[derive(Deserialize, Debug)]
+pub struct FlairListResponse {
+    pub users: Vec<UserFlair>,
+    pub next: Option<String>,
+}
+
+/// One row of the API response from /r/subreddit/api/flaircsv
+#[derive(Deserialize, Debug)]
+pub struct FlairCsvResult {
+    pub ok: bool,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub errors: Value,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserFlair {
+    pub user: String,
+    pub flair_text: Option<String>,
+    pub flair_css_class: Option<String>,
+}
+
+/// API response from /r/subreddit/about/moderators
+#[derive(Deserialize, Debug)]
+pub struct ModeratorListResponse {
+    pub data: ModeratorList,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ModeratorList {
+    pub children: Vec<ModeratorData>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModeratorData {
+    pub name: String,
+    #[serde(default)]
+    pub mod_permissions: Vec<String>,
+}
+
+/// API response from /r/subreddit/about/banned, /r/subreddit/about/muted and similar lists of
+/// users with a relationship to a subreddit.
+#[derive(Deserialize, Debug)]
+pub struct RelationshipListResponse {
+    pub data: RelationshipList,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RelationshipList {
+    pub children: Vec<RelationshipData>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelationshipData {
+    pub name: String,
+    /// The fullname of the user.
+    pub id: String,
+    /// When the relationship was created, as a UNIX timestamp.
+    pub date: f64,
+    /// The ban reason, for bans.
+    #[serde(default)]
+    pub note: Option<String>,
+    /// The number of days until a temporary ban ends.
+    #[serde(default)]
+    pub days_left: Option<u64>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CurrentFlairResponse {
     pub flair_css_class: Option<String>,
     pub flair_template_id: Option<String>,
     pub flair_text: Option<String>,
-    pub flair_position: Option<String>
+    pub flair_position: Option<FlairPosition>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlairPosition;
+
+    #[test]
+    fn flair_position_round_trips() {
+        assert_eq!(serde_json::to_string(&FlairPosition::Left).unwrap(), r#""left""#);
+        assert_eq!(serde_json::to_string(&FlairPosition::Unset).unwrap(), r#""""#);
+        assert_eq!(FlairPosition::Unset.to_string(), "");
+        let parse = |json| serde_json::from_str::<FlairPosition>(json).unwrap();
+        assert_eq!(parse(r#""right""#), FlairPosition::Right);
+        assert_eq!(parse(r#""""#), FlairPosition::Unset);
+        assert_eq!(parse(r#""center""#), FlairPosition::Unset);
+    }
 }
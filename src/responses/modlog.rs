@@ -0,0 +1,23 @@
+pub use serde::{Deserialize, Serialize};
+use crate::responses::BasicThing;
+use crate::responses::listing::ListingData;
+
+/// API response from /r/subreddit/about/log
+pub type ModLogListing = BasicThing<ListingData<ModActionData>>;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ModActionData {
+    pub id: String,
+    pub action: String,
+    #[serde(rename = "mod")]
+    pub moderator: String,
+    pub subreddit: String,
+    pub created_utc: f64,
+    pub details: Option<String>,
+    pub description: Option<String>,
+    pub target_author: Option<String>,
+    pub target_fullname: Option<String>,
+    pub target_permalink: Option<String>,
+    pub target_title: Option<String>,
+    pub target_body: Option<String>,
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+pub use serde::Deserialize;
+use serde_json::Value;
+
+/// API response from /api/v1/structured_styles/subreddit
+#[derive(Deserialize, Debug)]
+pub struct StructuredStylesResponse {
+    pub data: StructuredStylesData,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StructuredStylesData {
+    pub style: StyleData,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleData {
+    pub banner_background_image: Option<String>,
+    pub banner_background_color: Option<String>,
+    pub community_icon: Option<String>,
+    pub primary_color: Option<String>,
+    pub highlight_color: Option<String>,
+    pub mobile_banner_image: Option<String>,
+    /// All other style fields, which have not been typed yet.
+    #[serde(flatten)]
+    pub other: HashMap<String, Value>,
+}
+
+/// API response from /api/v1/style_asset_upload_s3/subreddit
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleUploadResponse {
+    pub s3_upload_lease: UploadLease,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UploadLease {
+    pub action: String,
+    pub fields: Vec<UploadLeaseField>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UploadLeaseField {
+    pub name: String,
+    pub value: String,
+}
@@ -0,0 +1,16 @@
+use crate::responses::listing::Listing;
+pub use serde::Deserialize;
+
+/// API response from /api/trending_searches_v1
+#[derive(Deserialize, Debug)]
+pub struct TrendingSearchesResponse {
+    pub trending_searches: Vec<TrendingSearchData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TrendingSearchData {
+    pub query_string: String,
+    pub display_string: String,
+    /// A sample of the posts that match the search.
+    pub results: Option<Listing>,
+}
@@ -0,0 +1,15 @@
+pub use serde::Deserialize;
+use crate::responses::BasicThing;
+
+/// API response from /r/subreddit/wiki/page
+pub type WikiPage = BasicThing<WikiPageData>;
+
+#[derive(Deserialize, Debug)]
+pub struct WikiPageData {
+    pub content_md: String,
+    pub content_html: Option<String>,
+    pub may_revise: bool,
+    pub reason: Option<String>,
+    pub revision_date: Option<f64>,
+    pub revision_id: Option<String>,
+}
@@ -0,0 +1,101 @@
+//! One-call helpers for quick scripts and examples. Each function creates a temporary
+//! `RedditClient`, makes its request and returns owned data, so there is no client to keep alive.
+//!
+//! Reads are made anonymously, unless the `REDDIT_CLIENT_ID`, `REDDIT_CLIENT_SECRET`,
+//! `REDDIT_USERNAME` and `REDDIT_PASSWORD` environment variables are all set, in which case the
+//! client logs in with them (see `ClientConfig::from_env()`). Functions that act as a user, such
+//! as `send_message`, require these variables.
+//!
+//! For anything beyond a handful of requests, create a `RedditClient` and reuse it instead.
+//! # Examples
+//! ```rust,no_run
+//! use new_rawr::simple;
+//! for post in simple::top_posts("rust", 5).expect("Could not fetch posts!") {
+//!     println!("{} ({})", post.title, post.score);
+//! }
+//! ```
+
+use serde_json;
+
+use crate::client::RedditClient;
+use crate::client::config::ClientConfig;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::options::{ListingRequest, ListingSort, TimeFilter};
+use crate::responses::listing;
+use crate::structures::snapshot::PostSnapshot;
+
+/// Creates the temporary client used by the helpers in this module.
+fn client(require_login: bool) -> Result<RedditClient, APIError> {
+    let config = ClientConfig::from_env();
+    if require_login && !config.has_credentials() {
+        return Err(APIError::ValidationError("REDDIT_CLIENT_ID, REDDIT_CLIENT_SECRET, \
+                                              REDDIT_USERNAME and REDDIT_PASSWORD must be set"
+            .to_owned()));
+    }
+    Ok(RedditClient::new(config.user_agent(), config.authenticator()?))
+}
+
+/// Extracts the post ID (without `t3_`) from a post URL, a `redd.it` short link, a full name or
+/// a bare ID.
+fn post_id(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let segments = path.split('/').collect::<Vec<_>>();
+    let id = if let Some(pos) = segments.iter().position(|s| *s == "comments") {
+        segments.get(pos + 1).cloned()
+    } else if path.contains("redd.it/") {
+        segments.last().cloned()
+    } else if !path.contains('/') {
+        Some(path.trim_start_matches("t3_"))
+    } else {
+        None
+    };
+    id.filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()))
+        .map(|id| id.to_owned())
+}
+
+/// Fetches a single post by URL (e.g. `https://www.reddit.com/r/rust/comments/4uule8/title/`),
+/// `redd.it` short link, full name (`t3_4uule8`) or ID.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::simple;
+/// let post = simple::fetch_post("https://redd.it/4uule8").expect("Could not fetch post!");
+/// println!("{} by {}", post.title, post.author);
+/// ```
+pub fn fetch_post(url: &str) -> Result<PostSnapshot, APIError> {
+    let id = post_id(url)
+        .ok_or_else(|| APIError::ValidationError(format!("'{}' is not a post URL", url)))?;
+    let client = client(false)?;
+    let body = client.get_json(&endpoints::by_id(&format!("t3_{}", id)), false)?;
+    let listing: listing::Listing = serde_json::from_str(&body)?;
+    listing.data
+        .children
+        .into_iter()
+        .next()
+        .map(|post| PostSnapshot::new(post.data))
+        .ok_or(APIError::ExhaustedListing)
+}
+
+/// Fetches the top `count` posts of the day in the specified subreddit (not including `/r/`).
+/// Fewer posts are returned if the subreddit does not have enough.
+pub fn top_posts(subreddit: &str, count: usize) -> Result<Vec<PostSnapshot>, APIError> {
+    let client = client(false)?;
+    let request = ListingRequest::new(ListingSort::Top)
+        .time(TimeFilter::Day)
+        .limit(count.clamp(1, 100) as u8);
+    let subreddit = client.subreddit(subreddit);
+    let posts = subreddit.listing(request)?;
+    Ok(posts.take(count).map(|post| PostSnapshot::new(post.data)).collect())
+}
+
+/// Sends a private message to a user (e.g. `spez`) or to a subreddit's moderators (e.g.
+/// `/r/rust`), logging in with the credentials from the environment.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::simple;
+/// simple::send_message("/u/spez", "Hello", "Sent from a script.").expect("Could not send!");
+/// ```
+pub fn send_message(recipient: &str, subject: &str, body: &str) -> Result<(), APIError> {
+    client(true)?.messages().compose(recipient, subject, body)
+}
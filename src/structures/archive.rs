@@ -0,0 +1,108 @@
+#[cfg(feature = "wayback")]
+use http::Method;
+
+#[cfg(feature = "wayback")]
+use crate::client::RedditClient;
+use crate::errors::APIError;
+#[cfg(feature = "wayback")]
+use crate::http::HttpRequest;
+use crate::structures::submission::Submission;
+use crate::traits::{Archiver, ItemProcessor};
+
+/// A processor which passes the link of every link post to an `Archiver`. Self posts are
+/// ignored. Archiving failures do not stop the stream; they are counted in `failures` and the
+/// most recent one is kept in `last_error`.
+/// # Examples
+/// ```rust,no_run
+/// # #[cfg(feature = "streams")] {
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::errors::APIError;
+/// use new_rawr::structures::archive::ArchivingProcessor;
+/// use new_rawr::traits::Archiver;
+/// // With the `wayback` feature, use `WaybackArchiver::new(&client)` instead.
+/// struct LogArchiver;
+/// impl Archiver for LogArchiver {
+///     fn archive(&mut self, url: &str) -> Result<(), APIError> {
+///         println!("Should archive {}", url);
+///         Ok(())
+///     }
+/// }
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let archiver = ArchivingProcessor::new(LogArchiver);
+/// let stream = client.subreddit("news").new_stream().with_processor(archiver);
+/// for post in stream {
+///     println!("Archived {}", post.title());
+/// }
+/// # }
+/// ```
+pub struct ArchivingProcessor<A: Archiver> {
+    archiver: A,
+    /// The number of links that have been archived.
+    pub archived: u64,
+    /// The number of links that could not be archived.
+    pub failures: u64,
+    /// The error from the most recent failure, if any.
+    pub last_error: Option<APIError>,
+}
+
+impl<A: Archiver> ArchivingProcessor<A> {
+    /// Creates a processor which archives links with the specified `Archiver`.
+    pub fn new(archiver: A) -> ArchivingProcessor<A> {
+        ArchivingProcessor {
+            archiver,
+            archived: 0,
+            failures: 0,
+            last_error: None,
+        }
+    }
+}
+
+impl<'a, A: Archiver> ItemProcessor<Submission<'a>> for ArchivingProcessor<A> {
+    fn process(&mut self, item: &Submission<'a>) {
+        if item.is_self_post() {
+            return;
+        }
+        if let Some(url) = item.link_url() {
+            match self.archiver.archive(&url) {
+                Ok(()) => self.archived += 1,
+                Err(err) => {
+                    self.failures += 1;
+                    self.last_error = Some(err);
+                }
+            }
+        }
+    }
+}
+
+/// An `Archiver` that submits pages to the Wayback Machine's save API
+/// (`https://web.archive.org/save/URL`). Requests are sent through the client's `HttpBackend`.
+/// Requires the `wayback` feature.
+#[cfg(feature = "wayback")]
+pub struct WaybackArchiver<'a> {
+    client: &'a RedditClient,
+}
+
+#[cfg(feature = "wayback")]
+impl<'a> WaybackArchiver<'a> {
+    /// Creates an archiver which sends requests with the client's backend and user agent.
+    pub fn new(client: &'a RedditClient) -> WaybackArchiver<'a> {
+        WaybackArchiver { client }
+    }
+}
+
+#[cfg(feature = "wayback")]
+impl<'a> Archiver for WaybackArchiver<'a> {
+    fn archive(&mut self, url: &str) -> Result<(), APIError> {
+        let request = HttpRequest::new(Method::GET, &format!("https://web.archive.org/save/{}", url))
+            .header(http::header::USER_AGENT, self.client.user_agent());
+        // Sent straight to the backend: the Wayback Machine has its own rate limit, and the
+        // client's hooks and OAuth headers are meant for Reddit.
+        let response = self.client.backend.execute(request)?;
+        if response.status.is_success() {
+            Ok(())
+        } else {
+            Err(APIError::HTTPError(response.status))
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::client::RedditClient;
+use crate::errors::APIError;
+use crate::structures::wiki::WikiPage;
+
+/// The wiki page that holds a subreddit's AutoModerator configuration.
+pub const AUTOMODERATOR_PAGE: &str = "config/automoderator";
+
+/// A check run on an AutoModerator configuration before it is sent to Reddit. Reddit will
+/// reject invalid YAML anyway, but validating first gives clearer errors and lets you enforce
+/// your own rules (e.g. requiring a comment on every rule).
+pub trait ConfigValidator {
+    /// Returns `Err(reason)` if the configuration should not be saved.
+    fn validate(&self, config: &str) -> Result<(), String>;
+}
+
+/// A basic validator that catches common YAML mistakes: tab indentation (which YAML forbids)
+/// and empty rules between `---` separators.
+/// # Examples
+/// ```
+/// use new_rawr::structures::automoderator::{BasicValidator, ConfigValidator};
+/// assert!(BasicValidator.validate("title: [spam]\naction: remove").is_ok());
+/// assert!(BasicValidator.validate("title: [spam]\n\taction: remove").is_err());
+/// ```
+pub struct BasicValidator;
+
+impl ConfigValidator for BasicValidator {
+    fn validate(&self, config: &str) -> Result<(), String> {
+        for (number, line) in config.lines().enumerate() {
+            if line.trim_start_matches(' ').starts_with('\t') {
+                return Err(format!("Line {} is indented with a tab", number + 1));
+            }
+        }
+        for (number, rule) in config.split("\n---").enumerate().skip(1) {
+            let empty = rule.lines()
+                .map(|line| line.trim())
+                .all(|line| line.is_empty() || line.starts_with('#') || line == "---");
+            if empty {
+                return Err(format!("Rule {} is empty", number + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A subreddit's AutoModerator configuration. Get one with `Subreddit.automoderator()`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::automoderator::BasicValidator;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut config = client.subreddit("new_rawr").automoderator().expect("Could not fetch");
+/// config.add_banned_words(&["spam", "scam"], "remove");
+/// config.save("Ban spam words", &BasicValidator).expect("Could not save");
+/// ```
+pub struct AutoModeratorConfig<'a> {
+    page: WikiPage<'a>,
+    /// The YAML configuration, which can be edited before calling `save()`.
+    pub config: String,
+}
+
+impl<'a> AutoModeratorConfig<'a> {
+    /// Internal method. Use `Subreddit.automoderator()` instead.
+    pub fn fetch(client: &'a RedditClient, subreddit: &str) -> Result<AutoModeratorConfig<'a>, APIError> {
+        let page = WikiPage::fetch(client, subreddit, AUTOMODERATOR_PAGE)?;
+        Ok(AutoModeratorConfig {
+            config: page.content().to_owned(),
+            page,
+        })
+    }
+
+    /// The ID of the revision that this configuration was fetched from.
+    pub fn revision_id(&self) -> Option<String> {
+        self.page.revision_id()
+    }
+
+    /// Appends a rule that applies `action` (e.g. `remove`, `filter` or `report`) to any
+    /// submission or comment containing one of the specified words.
+    pub fn add_banned_words(&mut self, words: &[&str], action: &str) {
+        let words = words.iter()
+            .map(|word| format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect::<Vec<String>>()
+            .join(", ");
+        if !self.config.trim().is_empty() {
+            self.config.push_str("\n---\n");
+        }
+        self.config.push_str(&format!("body+title (includes-word): [{}]\naction: {}\n", words, action));
+    }
+
+    /// Validates the configuration, then saves it to the wiki. Fails with
+    /// `APIError::ValidationError` if the validator rejects it, or
+    /// `APIError::HTTPError(Conflict)` if the configuration has been changed by someone else
+    /// since it was fetched.
+    pub fn save(&mut self, reason: &str, validator: &dyn ConfigValidator) -> Result<(), APIError> {
+        validator.validate(&self.config).map_err(APIError::ValidationError)?;
+        self.page.edit(&self.config, reason)
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::client::RedditClient;
+use crate::clock;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::BasicThing;
+use crate::responses::listing::UserListing;
+
+/// The name Reddit shows instead of the author when the author has blocked the logged-in user.
+pub const UNAVAILABLE_AUTHOR: &str = "[unavailable]";
+
+/// A cache of the users the logged-in account has blocked, and users who appear to have blocked
+/// it, used to avoid replying to people who will never see the reply. Get this with
+/// `RedditClient.block_list()`.
+///
+/// Reddit does not say who has blocked you, so this is a heuristic: items whose author shows as
+/// `[unavailable]` are from users who blocked you (see `Comment.is_from_blocked_user()` and
+/// `Message.is_from_blocked_user()`), and you can record other users with `mark_blocked_by()`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::options::ListingOptions;
+/// use new_rawr::traits::{Commentable, Content};
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut blocks = client.block_list();
+/// for message in client.messages().unread(ListingOptions::default()).unwrap() {
+///     let author = message.author().name;
+///     if !message.is_from_blocked_user() && blocks.should_reply(&author).unwrap() {
+///         message.reply("Thanks for your message!").unwrap();
+///     }
+/// }
+/// ```
+pub struct BlockList<'a> {
+    client: &'a RedditClient,
+    blocked: HashSet<String>,
+    blocked_by: HashSet<String>,
+    fetched_at: Option<Instant>,
+    ttl: Duration,
+}
+
+impl<'a> BlockList<'a> {
+    /// Internal method. Use `RedditClient.block_list()` instead.
+    pub fn new(client: &'a RedditClient) -> BlockList<'a> {
+        BlockList {
+            client,
+            blocked: HashSet::new(),
+            blocked_by: HashSet::new(),
+            fetched_at: None,
+            ttl: Duration::from_secs(600),
+        }
+    }
+
+    /// Sets how long the list of blocked users is cached before it is fetched again. The
+    /// default is 10 minutes.
+    pub fn with_ttl(mut self, ttl: Duration) -> BlockList<'a> {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Fetches the users that the logged-in account has blocked. This requires an OAuth-based
+    /// authenticator.
+    pub fn refresh(&mut self) -> Result<(), APIError> {
+        let string = self.client.get_json(endpoints::BLOCKED, true)?;
+        let result: BasicThing<UserListing> = serde_json::from_str(&string)?;
+        self.blocked = result.data
+            .children
+            .into_iter()
+            .map(|user| user.name.to_lowercase())
+            .collect();
+        self.fetched_at = clock::now();
+        Ok(())
+    }
+
+    /// Returns `true` if the logged-in account has blocked the user. The block list is fetched
+    /// if it has not been fetched yet, or the cached copy has expired.
+    pub fn has_blocked(&mut self, user: &str) -> Result<bool, APIError> {
+        let stale = match self.fetched_at.and_then(clock::elapsed) {
+            Some(age) => age > self.ttl,
+            None => true,
+        };
+        if stale {
+            self.refresh()?;
+        }
+        Ok(self.blocked.contains(&user.to_lowercase()))
+    }
+
+    /// Records that the user appears to have blocked the logged-in account.
+    pub fn mark_blocked_by(&mut self, user: &str) {
+        self.blocked_by.insert(user.to_lowercase());
+    }
+
+    /// Returns `true` if the user has been recorded with `mark_blocked_by()`.
+    pub fn is_blocked_by(&self, user: &str) -> bool {
+        self.blocked_by.contains(&user.to_lowercase())
+    }
+
+    /// Returns `true` unless the author is `[unavailable]`, the logged-in account has blocked
+    /// them, or they appear to have blocked the logged-in account.
+    pub fn should_reply(&mut self, author: &str) -> Result<bool, APIError> {
+        if author == UNAVAILABLE_AUTHOR || self.is_blocked_by(author) {
+            return Ok(false);
+        }
+        Ok(!self.has_blocked(author)?)
+    }
+}
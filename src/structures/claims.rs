@@ -0,0 +1,216 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::clock;
+
+/// A shared store that lets several bot instances claim items (e.g. reported posts in the mod
+/// queue) so that only one of them acts on each item. Items are identified by their full name
+/// (e.g. `t3_4uule8`).
+///
+/// `FileClaimStore` works for instances sharing a filesystem. For instances on different
+/// machines, implement this trait over a shared service such as Redis (`SET key worker NX PX
+/// ttl` is a suitable `claim()`).
+pub trait ClaimStore {
+    /// Attempts to claim the item. Returns `true` if this worker now holds the claim, or `false`
+    /// if another worker holds an unexpired claim. Claims older than `ttl` are treated as
+    /// abandoned (e.g. the worker holding them crashed) and can be taken over.
+    fn claim(&self, name: &str, ttl: Duration) -> io::Result<bool>;
+    /// Releases a claim held by this worker, so the item can be claimed again.
+    fn release(&self, name: &str) -> io::Result<()>;
+}
+
+/// A `ClaimStore` that records each claim as a file in a shared directory. Creating the file is
+/// atomic, so only one worker can succeed in claiming an item. To take over an expired claim, a
+/// worker must first create a lock file next to it, so only one worker can replace it.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use new_rawr::structures::claims::{ClaimStore, FileClaimStore};
+/// let dir = std::env::temp_dir().join(format!("new_rawr_claims_{}", std::process::id()));
+/// let first = FileClaimStore::new(&dir, "worker-1").unwrap();
+/// let second = FileClaimStore::new(&dir, "worker-2").unwrap();
+/// let ttl = Duration::from_secs(600);
+/// assert!(first.claim("t3_4uule8", ttl).unwrap());
+/// assert!(!second.claim("t3_4uule8", ttl).unwrap());
+/// first.release("t3_4uule8").unwrap();
+/// assert!(second.claim("t3_4uule8", ttl).unwrap());
+/// second.release("t3_4uule8").unwrap();
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub struct FileClaimStore {
+    dir: PathBuf,
+    worker: String,
+}
+
+impl FileClaimStore {
+    /// Creates a store in the specified directory (creating it if necessary). `worker` should be
+    /// unique to each bot instance, and is written into the claims that it makes.
+    pub fn new<P: Into<PathBuf>>(dir: P, worker: &str) -> io::Result<FileClaimStore> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FileClaimStore {
+            dir,
+            worker: worker.to_owned(),
+        })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        let name = name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "-");
+        self.dir.join(format!("{}.claim", name))
+    }
+
+    fn expired(&self, path: &Path, ttl: Duration) -> io::Result<bool> {
+        let modified = fs::metadata(path)?.modified()?;
+        let age = clock::system_now().and_then(|now| now.duration_since(modified).ok());
+        Ok(matches!(age, Some(age) if age > ttl))
+    }
+
+    /// Creates the claim file, returning `false` if it already exists.
+    fn create(&self, path: &Path) -> io::Result<bool> {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                file.write_all(self.worker.as_bytes())?;
+                Ok(true)
+            }
+            Err(ref err) if err.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Replaces an expired claim while holding its lock file. Returns `false` if another worker
+    /// holds the lock, or the claim was renewed in the meantime.
+    fn take_over(&self, path: &Path, ttl: Duration) -> io::Result<bool> {
+        let lock = path.with_extension("lock");
+        match OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(_) => {}
+            Err(ref err) if err.kind() == ErrorKind::AlreadyExists => {
+                // A lock this old was left behind by a worker that crashed while taking over.
+                if self.expired(&lock, ttl).unwrap_or(false) {
+                    let _ = fs::remove_file(&lock);
+                }
+                return Ok(false);
+            }
+            Err(err) => return Err(err),
+        }
+        let result = match self.expired(path, ttl) {
+            Ok(true) => {
+                match fs::remove_file(path) {
+                    Ok(()) => self.create(path),
+                    Err(ref err) if err.kind() == ErrorKind::NotFound => self.create(path),
+                    Err(err) => Err(err),
+                }
+            }
+            Ok(false) => Ok(false),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => self.create(path),
+            Err(err) => Err(err),
+        };
+        fs::remove_file(&lock)?;
+        result
+    }
+}
+
+impl ClaimStore for FileClaimStore {
+    fn claim(&self, name: &str, ttl: Duration) -> io::Result<bool> {
+        let path = self.path(name);
+        if self.create(&path)? {
+            return Ok(true);
+        }
+        match self.expired(&path, ttl) {
+            Ok(true) => self.take_over(&path, ttl),
+            Ok(false) => Ok(false),
+            // The claim was released in the meantime.
+            Err(ref err) if err.kind() == ErrorKind::NotFound => self.create(&path),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn release(&self, name: &str) -> io::Result<()> {
+        let path = self.path(name);
+        match fs::read_to_string(&path) {
+            Ok(ref owner) if *owner == self.worker => fs::remove_file(&path),
+            Ok(_) => Ok(()),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{ClaimStore, FileClaimStore};
+
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("new_rawr_claims_{}_{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn release_only_removes_own_claim() {
+        let dir = temp_dir("release");
+        let first = FileClaimStore::new(&dir, "worker-1").unwrap();
+        let second = FileClaimStore::new(&dir, "worker-2").unwrap();
+        let ttl = Duration::from_secs(600);
+        assert!(first.claim("t3_a", ttl).unwrap());
+        second.release("t3_a").unwrap();
+        assert!(!second.claim("t3_a", ttl).unwrap());
+        first.release("t3_a").unwrap();
+        assert!(second.claim("t3_a", ttl).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn expired_claim_is_taken_over() {
+        let dir = temp_dir("expired");
+        let first = FileClaimStore::new(&dir, "worker-1").unwrap();
+        let second = FileClaimStore::new(&dir, "worker-2").unwrap();
+        assert!(first.claim("t3_a", Duration::from_secs(0)).unwrap());
+        thread::sleep(Duration::from_millis(20));
+        assert!(second.claim("t3_a", Duration::from_millis(1)).unwrap());
+        assert_eq!(fs::read_to_string(dir.join("t3_a.claim")).unwrap(), "worker-2");
+        assert!(!dir.join("t3_a.lock").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn locked_claim_is_not_taken_over() {
+        let dir = temp_dir("locked");
+        let first = FileClaimStore::new(&dir, "worker-1").unwrap();
+        let second = FileClaimStore::new(&dir, "worker-2").unwrap();
+        assert!(first.claim("t3_a", Duration::from_secs(0)).unwrap());
+        thread::sleep(Duration::from_millis(20));
+        // Another worker is taking over the claim.
+        fs::write(dir.join("t3_a.lock"), "").unwrap();
+        assert!(!second.claim("t3_a", Duration::from_millis(1)).unwrap());
+        assert_eq!(fs::read_to_string(dir.join("t3_a.claim")).unwrap(), "worker-1");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn claims_are_exclusive_between_threads() {
+        let dir = temp_dir("threads");
+        let winners = (0..8)
+            .map(|i| {
+                let dir = dir.clone();
+                thread::spawn(move || {
+                    let store = FileClaimStore::new(&dir, &format!("worker-{}", i)).unwrap();
+                    store.claim("t3_a", Duration::from_secs(600)).unwrap()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|&won| won)
+            .count();
+        assert_eq!(winners, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
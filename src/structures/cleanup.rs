@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use serde_json::{self, from_value};
+
+use crate::client::RedditClient;
+use crate::clock;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::comment::{CommentData, CommentListing};
+use crate::structures::comment::Comment;
+use crate::structures::streams::run_every;
+use crate::traits::{Content, Votable};
+
+/// The full name of each comment that `CommentCleanup` tried to delete, with the result.
+pub type Deletions = Vec<(String, Result<(), APIError>)>;
+
+/// Revisits the logged-in account's recent comments and deletes any which scored below a
+/// threshold. Deleting poorly-received replies is a common courtesy for reply bots.
+///
+/// Comments are only checked once they are older than `min_age` (1 hour by default), so votes
+/// have time to settle, and are no longer checked once they are older than `max_age` (1 day by
+/// default).
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::cleanup::CommentCleanup;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let cleanup = CommentCleanup::new(&client, 0).expect("Not logged in!");
+/// cleanup.run(Duration::from_secs(600), |result| {
+///     match result {
+///         Ok(results) => {
+///             for (name, result) in results {
+///                 match result {
+///                     Ok(()) => println!("Deleted {}", name),
+///                     Err(err) => println!("Could not delete {}: {:?}", name, err),
+///                 }
+///             }
+///         }
+///         Err(err) => println!("Could not check comments: {:?}", err),
+///     }
+///     true
+/// });
+/// ```
+pub struct CommentCleanup<'a> {
+    client: &'a RedditClient,
+    username: String,
+    threshold: i64,
+    min_age: Duration,
+    max_age: Duration,
+}
+
+impl<'a> CommentCleanup<'a> {
+    /// Creates a checker that deletes comments with a score below `threshold`. Returns a
+    /// `ValidationError` if the client is not logged in as a user.
+    pub fn new(client: &'a RedditClient, threshold: i64) -> Result<CommentCleanup<'a>, APIError> {
+        let username = client.get_authenticator()
+            .username()
+            .ok_or_else(|| {
+                APIError::ValidationError("CommentCleanup requires a logged-in client".to_owned())
+            })?;
+        Ok(CommentCleanup {
+            client,
+            username,
+            threshold,
+            min_age: Duration::from_secs(60 * 60),
+            max_age: Duration::from_secs(24 * 60 * 60),
+        })
+    }
+
+    /// Sets the window of comment ages that are checked. Comments younger than `min_age` or
+    /// older than `max_age` are left alone.
+    pub fn with_window(mut self, min_age: Duration, max_age: Duration) -> CommentCleanup<'a> {
+        self.min_age = min_age;
+        self.max_age = max_age;
+        self
+    }
+
+    /// Checks the most recent 100 comments once, and returns the full name of each comment that
+    /// scored below the threshold with the result of deleting it. A failed deletion does not
+    /// stop the others; the comment is tried again in the next round. Fails only if the
+    /// comments could not be fetched.
+    pub fn run_once(&self) -> Result<Deletions, APIError> {
+        let url = format!("{}?sort=new&limit=100", endpoints::user_comments(&self.username));
+        let string = self.client.get_json(&url, false)?;
+        let listing: CommentListing = serde_json::from_str(&string)?;
+        let now = clock::unix_time()?.as_secs();
+        let mut results = Vec::new();
+        for item in listing.data.children {
+            let data = from_value::<CommentData>(item.data)?;
+            let age = now.saturating_sub(data.created_utc as u64);
+            if age > self.max_age.as_secs() {
+                // The listing is sorted newest first, so the rest are too old as well.
+                break;
+            }
+            if age < self.min_age.as_secs() {
+                continue;
+            }
+            let comment = Comment::new(self.client, data);
+            if comment.score() < self.threshold {
+                let name = comment.name().to_owned();
+                results.push((name, comment.delete()));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Calls `run_once()` every `interval`, passing each result to `on_round`. Stops when
+    /// `on_round` returns `false`.
+    pub fn run<F>(&self, interval: Duration, on_round: F)
+        where F: FnMut(Result<Deletions, APIError>) -> bool
+    {
+        run_every(interval, || self.run_once(), on_round);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use http::StatusCode;
+    use http::header::HeaderName;
+    use serde_json::json;
+
+    use super::CommentCleanup;
+    use crate::auth::Authenticator;
+    use crate::client::RedditClient;
+    use crate::errors::APIError;
+    use crate::http::{HttpBackend, HttpRequest, HttpResponse};
+
+    struct BotAuthenticator;
+
+    impl Authenticator for BotAuthenticator {
+        fn login(&mut self, _: &dyn HttpBackend, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn logout(&mut self, _: &dyn HttpBackend, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Result<HashMap<HeaderName, String>, APIError> {
+            Ok(HashMap::new())
+        }
+
+        fn oauth(&self) -> bool {
+            false
+        }
+
+        fn username(&self) -> Option<String> {
+            Some(String::from("bot"))
+        }
+    }
+
+    /// Lists two downvoted comments, and refuses to delete the first one.
+    struct Reddit;
+
+    fn comment(id: &str, created_utc: u64) -> serde_json::Value {
+        json!({
+            "kind": "t1",
+            "data": {
+                "subreddit_id": "t5_a", "link_id": "t3_a", "replies": "", "saved": false,
+                "id": id, "gilded": 0, "archived": false, "author": "bot", "score": -5,
+                "body": "", "edited": false, "downs": 0, "ups": 0, "body_html": "",
+                "subreddit": "rust", "name": format!("t1_{}", id), "score_hidden": false,
+                "stickied": false, "created": created_utc, "created_utc": created_utc,
+                "parent_id": "t3_a"
+            }
+        })
+    }
+
+    impl HttpBackend for Reddit {
+        fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+            if request.url.contains("/user/bot/comments") {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                let created = now - 2 * 60 * 60;
+                let listing = json!({
+                    "kind": "Listing",
+                    "data": {
+                        "children": [comment("a", created), comment("b", created)],
+                        "after": null,
+                        "before": null,
+                        "modhash": ""
+                    }
+                });
+                return Ok(HttpResponse::new(StatusCode::OK, &listing.to_string()));
+            }
+            let body = String::from_utf8_lossy(&request.body);
+            if body.contains("t1_a") {
+                Ok(HttpResponse::new(StatusCode::FORBIDDEN, "{}"))
+            } else {
+                Ok(HttpResponse::new(StatusCode::OK, "{}"))
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_going_after_a_failed_delete() {
+        let authenticator: Box<dyn Authenticator + Send> = Box::new(BotAuthenticator);
+        let client = RedditClient::with_backend("new_rawr",
+                                                Arc::new(Mutex::new(authenticator)),
+                                                Box::new(Reddit));
+        let results = CommentCleanup::new(&client, 0).unwrap().run_once().unwrap();
+        let names = results.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t1_a", "t1_b"]);
+        assert!(results[0].1.is_err());
+        assert!(results[1].1.is_ok());
+    }
+}
@@ -1,13 +1,24 @@
+use std::collections::HashMap;
+
 use serde_json;
 use serde_json::from_value;
 
+use crate::endpoints;
 use crate::client::RedditClient;
 use crate::structures::comment_list::CommentList;
 use crate::traits::{Votable, Created, Editable, Content, Commentable, Approvable, Stickable, Distinguishable, Reportable};
 use crate::errors::APIError;
+#[cfg(feature = "language")]
+use crate::language;
+use crate::responses::Distinguish;
 use crate::responses::comment::{CommentData};
 use crate::structures::user::User;
+use crate::structures::permalink::Permalink;
+#[cfg(feature = "moderation")]
+use crate::structures::removal::RemovalMessage;
+use crate::structures::blocks::UNAVAILABLE_AUTHOR;
 use crate::structures::subreddit::Subreddit;
+use crate::structures::submission::LazySubmission;
 use crate::responses::comment::{NewComment, CommentListing};
 
 /// Structure representing a comment and its associated data (e.g. replies)
@@ -22,6 +33,18 @@ impl<'a> Votable for Comment<'a> {
         self.data.score
     }
 
+    fn ups(&self) -> i64 {
+        self.data.ups
+    }
+
+    fn downs(&self) -> i64 {
+        self.data.downs
+    }
+
+    fn score_hidden(&self) -> bool {
+        self.data.score_hidden
+    }
+
     fn likes(&self) -> Option<bool> {
         self.data.likes
     }
@@ -51,7 +74,8 @@ impl<'a> Created for Comment<'a> {
 
 impl<'a> Editable for Comment<'a> {
     fn edited(&self) -> bool {
-        self.data.edited.as_bool().unwrap()
+        // `edited` is either `false` or the time of the edit.
+        self.data.edited.as_bool().unwrap_or(true)
     }
 
     fn edited_time(&self) -> Option<i64> {
@@ -59,10 +83,11 @@ impl<'a> Editable for Comment<'a> {
     }
 
     fn edit(&mut self, text: &str) -> Result<(), APIError> {
+        trace_span!("edit", thing = %self.data.name);
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.data.name);
-        let res = self.client.post_success("/api/editusertext", &body, false);
+        let res = self.client.post_success(endpoints::EDIT, &body, false);
         if let Ok(()) = res {
             // TODO: should we update body_html?
             self.data.body = text.to_owned();
@@ -80,7 +105,7 @@ impl<'a> Editable for Comment<'a> {
 }
 
 impl<'a> Content for Comment<'a> {
-    fn author(&self) -> User {
+    fn author(&self) -> User<'_> {
         User::new(self.client, &self.data.author)
     }
 
@@ -92,13 +117,13 @@ impl<'a> Content for Comment<'a> {
         self.data.author_flair_css_class.to_owned()
     }
 
-    fn subreddit(&self) -> Subreddit {
+    fn subreddit(&self) -> Subreddit<'_> {
         Subreddit::create_new(self.client, &self.data.subreddit)
     }
 
     fn delete(self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/del", &body, false)
+        self.client.post_success(endpoints::DELETE, &body, false)
     }
 
     fn name(&self) -> &str {
@@ -109,22 +134,22 @@ impl<'a> Content for Comment<'a> {
 impl<'a> Approvable for Comment<'a> {
     fn approve(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/approve", &body, false)
+        self.client.post_success(endpoints::APPROVE, &body, false)
     }
 
     fn remove(&self, spam: bool) -> Result<(), APIError> {
         let body = format!("id={}&spam={}", self.data.name, spam);
-        self.client.post_success("/api/remove", &body, false)
+        self.client.post_success(endpoints::REMOVE, &body, false)
     }
 
     fn ignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/ignore_reports", &body, false)
+        self.client.post_success(endpoints::IGNORE_REPORTS, &body, false)
     }
 
     fn unignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/unignore_reports", &body, false)
+        self.client.post_success(endpoints::UNIGNORE_REPORTS, &body, false)
     }
 }
 
@@ -134,39 +159,58 @@ impl<'a> Commentable<'a> for Comment<'a> {
                 to manually count with `replies().len()`, which may take some time.");
     }
 
-    fn reply(&self, text: &str) -> Result<Comment, APIError> {
+    fn reply(&self, text: &str) -> Result<Comment<'_>, APIError> {
+        trace_span!("reply", parent = %self.data.name);
+        if self.data.archived {
+            return Err(APIError::Archived);
+        }
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.name());
-        let result = self.client.post_json("/api/comment", &body, false).unwrap();
-        let result: NewComment = serde_json::from_str(&*result).unwrap();
-        Ok(Comment::new(self.client, result.json.data.things.into_iter().next().unwrap().data))
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        let thing = result.json.data.things.into_iter().next();
+        let thing = thing.ok_or_else(|| APIError::missing("new comment"))?;
+        Ok(Comment::new(self.client, thing.data))
 
     }
 
     fn replies(self) -> Result<CommentList<'a>, APIError> {
         Ok(self.replies)
     }
+
+    fn template_vars(&self) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert(String::from("author"), self.data.author.to_owned());
+        vars.insert(String::from("title"), self.data.link_title.to_owned().unwrap_or_default());
+        vars.insert(String::from("permalink"), self.permalink().to_string());
+        vars.insert(String::from("subreddit"), self.data.subreddit.to_owned());
+        vars
+    }
 }
 
 impl<'a> Comment<'a> {
     /// Internal method. Use `Submission.replies()` or `Comment.replies()` to get a listing, then
     /// select the desired comment instead.
-    pub fn new(client: &RedditClient, data: CommentData) -> Comment {
+    pub fn new(client: &RedditClient, data: CommentData) -> Comment<'_> {
         let comments = if data.replies.is_object() {
             // TODO: avoid cloning here
-            let listing = from_value::<CommentListing>(data.replies.clone()).unwrap();
-            CommentList::new(client,
-                             data.link_id.to_owned(),
-                             data.name.to_owned(),
-                             listing.data.children)
+            match from_value::<CommentListing>(data.replies.clone()) {
+                Ok(listing) => {
+                    CommentList::new(client,
+                                     data.link_id.to_owned(),
+                                     data.name.to_owned(),
+                                     listing.data.children)
+                }
+                Err(_) => CommentList::empty(client),
+            }
         } else {
             CommentList::empty(client)
         };
 
         Comment {
-            client: client,
-            data: data,
+            client,
+            data,
             replies: comments,
         }
     }
@@ -176,24 +220,163 @@ impl<'a> Comment<'a> {
         &self.data.parent_id
     }
 
+    /// Sends a removal message to the author explaining why this was removed. See
+    /// `RemovalMessage` for examples.
+    #[cfg(feature = "moderation")]
+    pub fn send_removal_message(&self, message: &RemovalMessage) -> Result<(), APIError> {
+        message.send(self.client, &self.data.name, &self.template_vars())
+    }
+
+    /// Gets a link to this comment.
+    pub fn permalink(&self) -> Permalink {
+        match self.data.permalink {
+            Some(ref permalink) => Permalink::new(permalink),
+            None => {
+                Permalink::new(&format!("/comments/{}/_/{}/",
+                                        self.data.link_id.trim_start_matches("t3_"),
+                                        self.data.id))
+            }
+        }
+    }
+
+    /// Gets a link to this comment which also shows the specified number of parent comments.
+    /// Call `no_participation()` on the result for an `np.reddit.com` link.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::Commentable;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let comment = post.replies().unwrap().next().unwrap();
+    /// let link = comment.permalink_with_context(3).no_participation();
+    /// println!("See the discussion [here]({}).", link);
+    /// ```
+    pub fn permalink_with_context(&self, parents: u32) -> Permalink {
+        self.permalink().context(parents)
+    }
+
+    /// Returns `true` if the author shows as `[unavailable]`, which means they have blocked the
+    /// logged-in user and will not see any reply.
+    pub fn is_from_blocked_user(&self) -> bool {
+        self.data.author == UNAVAILABLE_AUTHOR
+    }
+
+    /// Detects the language of the comment (e.g. `en`), or returns `None` if the comment is too
+    /// short to tell. See the `language` module for the supported languages.
+    #[cfg(feature = "language")]
+    pub fn detected_language(&self) -> Option<&'static str> {
+        language::detect(&self.data.body)
+    }
+
+    /// Gets the submission that this comment was posted on, as a `LazySubmission`. Use
+    /// `LazySubmission.get()` to fetch the post itself (e.g. its title or flair).
+    /// # Examples
+    /// ```rust,no_run
+    /// # #[cfg(feature = "streams")] {
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// for comment in post.reply_stream() {
+    ///     let post = comment.submission().get().expect("Could not get post.");
+    ///     println!("New comment on '{}'", post.title());
+    /// }
+    /// # }
+    /// ```
+    pub fn submission(&self) -> LazySubmission<'a> {
+        LazySubmission::new(self.client, &self.data.link_id)
+    }
+
+    /// Fetches the chain of comments above this one, ordered from the top-level comment down to
+    /// the direct parent of this comment. The chain is empty if this is a top-level comment.
+    ///
+    /// Reddit only reveals a comment's parent once the comment itself has been fetched, so one
+    /// `/api/info` request is sent for each level of the thread.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::{Commentable, Editable};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let reply = post.replies().unwrap().next().unwrap().replies().unwrap().next().unwrap();
+    /// for ancestor in reply.ancestors().expect("Could not fetch ancestors") {
+    ///     println!("> {}", ancestor.body().unwrap());
+    /// }
+    /// ```
+    pub fn ancestors(&self) -> Result<Vec<Comment<'a>>, APIError> {
+        let mut chain = Vec::new();
+        let mut parent = self.data.parent_id.to_owned();
+        while parent.starts_with("t1_") {
+            let url = format!("{}?id={}&raw_json=1", endpoints::INFO, parent);
+            let string = self.client.get_json(&url, false)?;
+            let listing: CommentListing = serde_json::from_str(&string)?;
+            let item = listing.data.children.into_iter().next().ok_or(APIError::ExhaustedListing)?;
+            let data = from_value::<CommentData>(item.data)?;
+            parent = data.parent_id.to_owned();
+            chain.push(Comment::new(self.client, data));
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Fetches the current score of this comment and deletes it if the score is below the
+    /// threshold. Returns `true` if the comment was deleted. Use `CommentCleanup` to check all of
+    /// the bot's recent comments on a schedule.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::traits::Commentable;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let comment = post.reply("Hello!").expect("Could not reply.");
+    /// // ... later ...
+    /// if comment.delete_if_score_below(0).expect("Could not check score.") {
+    ///     println!("Deleted a downvoted comment.");
+    /// }
+    /// ```
+    pub fn delete_if_score_below(self, threshold: i64) -> Result<bool, APIError> {
+        let url = format!("{}?id={}", endpoints::INFO, self.data.name);
+        let string = self.client.get_json(&url, false)?;
+        let listing: CommentListing = serde_json::from_str(&string)?;
+        let item = listing.data.children.into_iter().next().ok_or(APIError::ExhaustedListing)?;
+        let data = from_value::<CommentData>(item.data)?;
+        if data.score < threshold {
+            self.delete()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Adds a reply to this comment's reply list. This is an internal method - to make the client
     /// reply to this post, use `Comment.reply(MESSAGE)`.
     pub fn add_reply(&mut self, item: Comment<'a>) {
         self.replies.add_reply(item);
     }
 
+    /// The replies that have been loaded so far, without consuming the comment.
+    pub(crate) fn replies_mut(&mut self) -> &mut CommentList<'a> {
+        &mut self.replies
+    }
+
     fn vote(&self, dir: i8) -> Result<(), APIError> {
+        trace_span!("vote", thing = %self.data.name, dir = dir);
+        if self.data.archived {
+            return Err(APIError::Archived);
+        }
         let body = format!("dir={}&id={}", dir, self.data.name);
-        self.client.post_success("/api/vote", &body, false)
+        self.client.post_success(endpoints::VOTE, &body, false)
     }
 }
 
 impl<'a> Reportable for Comment<'a> {
     fn report(&self, reason: &str) -> Result<(), APIError> {
-        let body = format!("api_type=json&thing_id={}&reason={}",
-                           self.data.name,
-                           self.client.url_escape(reason.to_owned()));
-        self.client.post_success("/api/report", &body, false)
+        let reason = self.subreddit().report_reason(reason)?;
+        let body = format!("api_type=json&thing_id={}&{}", self.data.name, reason);
+        self.client.post_success(endpoints::REPORT, &body, false)
     }
 
     fn report_count(&self) -> Option<u64> {
@@ -208,7 +391,7 @@ impl<'a> Stickable for Comment<'a> {
 
     fn stick(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=yes&sticky=true&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
             self.data.stickied = true;
         }
@@ -217,7 +400,7 @@ impl<'a> Stickable for Comment<'a> {
 
     fn unstick(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=no&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
             self.data.stickied = false;
         }
@@ -226,22 +409,22 @@ impl<'a> Stickable for Comment<'a> {
 }
 
 impl<'a> Distinguishable for Comment<'a> {
-    fn distinguished(&self) -> Option<String> {
-        self.data.distinguished.to_owned()
+    fn distinguished(&self) -> Option<Distinguish> {
+        self.data.distinguished
     }
 
     fn distinguish(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=yes&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
-            self.data.distinguished = Some(String::from("moderator"));
+            self.data.distinguished = Some(Distinguish::Moderator);
         }
         res
     }
 
     fn undistinguish(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=no&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
             self.data.distinguished = None;
         }
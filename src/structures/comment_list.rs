@@ -1,19 +1,38 @@
+#[cfg(feature = "streams")]
 use std::vec::IntoIter;
+#[cfg(feature = "streams")]
 use std::collections::VecDeque;
-use std::thread;
+#[cfg(feature = "streams")]
 use std::time::Duration;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use crate::endpoints;
 use crate::client::RedditClient;
+#[cfg(all(feature = "language", feature = "streams"))]
+use crate::language;
+#[cfg(feature = "streams")]
+use crate::client::scheduler::Priority;
+#[cfg(feature = "streams")]
+use crate::structures::streams::{pause, AuthorFilter};
+#[cfg(feature = "streams")]
+use crate::structures::processors::NoopProcessor;
 use crate::structures::comment::Comment;
 use crate::responses::BasicThing;
+#[cfg(feature = "streams")]
 use crate::responses::listing;
 use crate::responses::comment::{CommentData, MoreData};
+#[cfg(feature = "streams")]
+use crate::responses::comment::CommentListing;
 use serde_json::{Value, from_value, from_str};
-use std::io::Read;
 use crate::errors::APIError;
 use crate::traits::Content;
-use hyper::Body;
+#[cfg(feature = "streams")]
+use crate::traits::{PollingStream, ItemProcessor};
 
 /// A list of comments that can be iterated through. Automatically fetches 'more' links when
 /// necessary until all comments have been consumed, which can lead to pauses while loading
@@ -21,12 +40,12 @@ use hyper::Body;
 /// # Examples
 /// ```
 /// use new_rawr::client::RedditClient;
-/// use new_rawr::options::ListingOptions;
+/// use new_rawr::options::ListingRequest;
 /// use new_rawr::traits::Commentable;
 /// use new_rawr::auth::AnonymousAuthenticator;
 /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
 /// let announcements = client.subreddit("announcements");
-/// let announcement = announcements.hot(ListingOptions::default())
+/// let announcement = announcements.listing(ListingRequest::default())
 ///     .expect("Could not fetch announcements")
 ///     .next().unwrap();
 /// // Usually less than 100 top-level comments are fetched at a time, but the CommentList
@@ -40,6 +59,36 @@ pub struct CommentList<'a> {
     more: Vec<MoreData>,
     link_id: String,
     parent: String,
+    progress: Option<Rc<RefCell<ProgressTracker<'a>>>>,
+    last_error: Option<APIError>,
+}
+
+/// Progress of fetching a comment tree, as reported to the callback of
+/// `Submission.replies_with_progress()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommentProgress {
+    /// The number of comments that have been loaded so far, including nested replies.
+    pub loaded: usize,
+    /// The number of 'load more comments' links that have been expanded.
+    pub expanded: usize,
+    /// The number of 'load more comments' links that have been found but not expanded yet.
+    pub pending: usize,
+    /// The number of comments that Reddit reports for the post, if known. This includes removed
+    /// comments, so `loaded` may never reach it.
+    pub expected: Option<u64>,
+}
+
+/// The progress callback and counts, shared by a `CommentList` and the reply lists of every
+/// comment in it.
+struct ProgressTracker<'a> {
+    progress: CommentProgress,
+    callback: Box<dyn FnMut(&CommentProgress) + 'a>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    fn report(&mut self) {
+        (self.callback)(&self.progress);
+    }
 }
 
 impl<'a> CommentList<'a> {
@@ -54,42 +103,97 @@ impl<'a> CommentList<'a> {
         let mut new_items = vec![];
         let mut new_mores = vec![];
         let mut hashes = HashMap::new();
+        // Items that do not parse (e.g. because Reddit changed their format) are skipped rather
+        // than losing the whole tree.
         for item in comment_list {
             if item.kind == "t1" {
-                let item = from_value::<CommentData>(item.data).unwrap();
-                let comment = Comment::new(client, item);
-                hashes.insert(comment.name().to_owned(), new_items.len());
-                new_items.push(comment);
+                if let Ok(item) = from_value::<CommentData>(item.data) {
+                    let comment = Comment::new(client, item);
+                    hashes.insert(comment.name().to_owned(), new_items.len());
+                    new_items.push(comment);
+                }
             } else if item.kind == "more" {
-                let item = from_value::<MoreData>(item.data).unwrap();
-                new_mores.push(item);
-            } else {
-                unreachable!();
+                if let Ok(item) = from_value::<MoreData>(item.data) {
+                    new_mores.push(item);
+                }
             }
         }
 
         CommentList {
-            client: client,
+            client,
             comments: new_items,
             more: new_mores,
             comment_hashes: hashes,
-            link_id: link_id,
-            parent: parent,
+            link_id,
+            parent,
+            progress: None,
+            last_error: None,
         }
     }
 
     /// Creates an empty listing, when there are no comments to show.
     pub fn empty(client: &'a RedditClient) -> CommentList<'a> {
         CommentList {
-            client: client,
+            client,
             link_id: String::new(),
             parent: String::new(),
             comments: Vec::new(),
             more: Vec::new(),
             comment_hashes: HashMap::new(),
+            progress: None,
+            last_error: None,
         }
     }
 
+    /// Calls `callback` with the loading progress now, and again every time a 'load more
+    /// comments' link is expanded anywhere in the tree. Internal method, use
+    /// `Submission.replies_with_progress()` instead.
+    pub fn with_progress<F>(mut self, expected: Option<u64>, callback: F) -> CommentList<'a>
+        where F: FnMut(&CommentProgress) + 'a
+    {
+        let tracker = Rc::new(RefCell::new(ProgressTracker {
+            progress: CommentProgress {
+                expected,
+                ..CommentProgress::default()
+            },
+            callback: Box::new(callback),
+        }));
+        let (loaded, pending) = self.track(&tracker);
+        {
+            let mut tracker = tracker.borrow_mut();
+            tracker.progress.loaded = loaded;
+            tracker.progress.pending = pending;
+            tracker.report();
+        }
+        self
+    }
+
+    /// The error that ended iteration, if a 'load more comments' link could not be expanded.
+    /// See `Listing.last_error()` for details.
+    pub fn last_error(&self) -> Option<&APIError> {
+        self.last_error.as_ref()
+    }
+
+    /// Clears the error from a failed expansion, so the next call to `next()` tries to expand
+    /// the link again. Until this is called, a list that failed keeps returning `None`.
+    pub fn retry(&mut self) {
+        self.last_error = None;
+    }
+
+    /// Attaches the tracker to this list and all nested reply lists, returning the number of
+    /// comments and 'more' links in them.
+    fn track(&mut self, tracker: &Rc<RefCell<ProgressTracker<'a>>>) -> (usize, usize) {
+        self.progress = Some(tracker.clone());
+        let mut loaded = self.comments.len();
+        let mut pending = self.more.len();
+        for comment in &mut self.comments {
+            let (nested_loaded, nested_pending) = comment.replies_mut().track(tracker);
+            loaded += nested_loaded;
+            pending += nested_pending;
+        }
+        (loaded, pending)
+    }
+
     /// Adds a (pre-existing) comment to the reply list. This is an internal method, and does not
     /// actually post a comment, just adds one that has already been fetched.
     pub fn add_reply(&mut self, item: Comment<'a>) {
@@ -97,48 +201,147 @@ impl<'a> CommentList<'a> {
         self.comments.push(item);
     }
 
-    fn fetch_more(&mut self, more_item: MoreData) -> CommentList<'a> {
-        let params = format!("api_type=json&raw_json=1&link_id={}&children={}",
-                             &self.link_id,
-                             &more_item.children.join(","));
-        let url = "/api/morechildren";
-        self.client
-            .ensure_authenticated(|| {
-                let request = self.client.post(url, false).body(Body::from(params.clone())).unwrap();
-
-                let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
-
-                let res = runtime.block_on(self.client.client.request(request)).unwrap();
-                if res.status().is_success() {
-                    // The "data" attribute is sometimes not present, so we have to unwrap it all
-                    // manually
-                    let value = runtime.block_on(hyper::body::to_bytes(res.into_body()));
-
-                    let value =String::from_utf8(value.unwrap().to_vec());;
-                    let mut new_listing: Value = from_str(value.unwrap().as_str()).unwrap();
-                    let new_listing = new_listing.as_object_mut().unwrap();
-                    let mut json = new_listing.remove("json").unwrap();
-                    let json = json.as_object_mut().unwrap();
-                    let data = json.remove("data");
-                    if let Some(mut data) = data {
-                        let things = data.as_object_mut().unwrap();
-                        let things = things.remove("things").unwrap();
-                        let things: Vec<BasicThing<Value>> = from_value(things).unwrap();
-                        Ok(CommentList::new(self.client,
-                                            self.link_id.to_owned(),
-                                            self.parent.to_owned(),
-                                            things))
-                    } else {
-                        Ok(CommentList::new(self.client,
-                                            self.link_id.to_owned(),
-                                            self.parent.to_owned(),
-                                            vec![]))
+    /// Expands every 'load more comments' link in this list and in the replies of its comments,
+    /// sending up to `concurrency` requests at the same time, so that the whole tree is loaded
+    /// before iterating. This is much faster than letting iteration expand the links one at a
+    /// time for large threads (e.g. megathreads with thousands of comments). The requests still
+    /// go through the client, so its rate limit policy applies to them.
+    ///
+    /// If a request fails, the other expansions are kept, the link that failed is put back (so
+    /// iterating will try it again) and the first error is returned.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::Commentable;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let mut comments = post.replies().expect("Could not get comments.");
+    /// comments.expand_all(8).expect("Could not expand comments.");
+    /// ```
+    pub fn expand_all(&mut self, concurrency: usize) -> Result<(), APIError> {
+        trace_span!("expand_all", concurrency = concurrency);
+        let mut first_error = None;
+        loop {
+            let mut jobs = Vec::new();
+            self.take_more(&mut Vec::new(), &mut jobs);
+            if jobs.is_empty() {
+                break;
+            }
+            let params = jobs.iter()
+                .map(|(_, more_item)| self.more_params(more_item))
+                .collect::<Vec<_>>();
+            let results = self.post_all(&params, concurrency);
+            let mut failed = false;
+            for ((path, more_item), result) in jobs.into_iter().zip(results) {
+                let list = self.list_at(&path);
+                match result.and_then(|value| list.parse_more(&value)) {
+                    Ok(new_listing) => list.absorb(new_listing),
+                    Err(err) => {
+                        list.more.push(more_item);
+                        failed = true;
+                        first_error = first_error.or(Some(err));
                     }
-                } else {
-                    Err(APIError::HTTPError(res.status()))
                 }
-            })
+            }
+            if failed {
+                break;
+            }
+        }
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the 'more' links from this list and all nested reply lists, along with the path
+    /// (of comment indices) to the list they came from. Links without children ('continue this
+    /// thread') cannot be expanded with `morechildren`, so they are dropped.
+    fn take_more(&mut self, path: &mut Vec<usize>, jobs: &mut Vec<(Vec<usize>, MoreData)>) {
+        for more_item in self.more.drain(..) {
+            if !more_item.children.is_empty() {
+                jobs.push((path.clone(), more_item));
+            }
+        }
+        for (index, comment) in self.comments.iter_mut().enumerate() {
+            path.push(index);
+            comment.replies_mut().take_more(path, jobs);
+            path.pop();
+        }
+    }
+
+    fn list_at(&mut self, path: &[usize]) -> &mut CommentList<'a> {
+        match path.split_first() {
+            Some((&index, rest)) => self.comments[index].replies_mut().list_at(rest),
+            None => self,
+        }
+    }
+
+    /// Sends the `morechildren` requests on up to `concurrency` threads, returning the results
+    /// in the same order as the parameters.
+    fn post_all(&self, params: &[String], concurrency: usize) -> Vec<Result<String, APIError>> {
+        let client = self.client;
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..params.len()).map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..concurrency.max(1).min(params.len()) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let params = match params.get(index) {
+                            Some(params) => params,
+                            None => break,
+                        };
+                        let result = client.post_json(endpoints::MORE_CHILDREN, params, false);
+                        results.lock().unwrap()[index] = Some(result);
+                    }
+                });
+            }
+        });
+        results.into_inner()
             .unwrap()
+            .into_iter()
+            .map(|result| result.expect("Every request should have a result"))
+            .collect()
+    }
+
+    /// Records an expanded 'more' link: updates the progress and merges the new comments.
+    fn absorb(&mut self, mut new_listing: CommentList<'a>) {
+        if let Some(tracker) = self.progress.clone() {
+            let (loaded, pending) = new_listing.track(&tracker);
+            let mut tracker = tracker.borrow_mut();
+            tracker.progress.loaded += loaded;
+            tracker.progress.expanded += 1;
+            tracker.progress.pending = (tracker.progress.pending + pending).saturating_sub(1);
+            tracker.report();
+        }
+        self.more.append(&mut new_listing.more);
+        self.merge_more_comments(new_listing);
+    }
+
+    fn more_params(&self, more_item: &MoreData) -> String {
+        format!("api_type=json&link_id={}&children={}",
+                &self.link_id,
+                &more_item.children.join(","))
+    }
+
+    fn fetch_more(&self, more_item: &MoreData) -> Result<CommentList<'a>, APIError> {
+        let params = self.more_params(more_item);
+        let value = self.client.post_json(endpoints::MORE_CHILDREN, &params, false)?;
+        self.parse_more(&value)
+    }
+
+    fn parse_more(&self, value: &str) -> Result<CommentList<'a>, APIError> {
+        // The "data" attribute is sometimes not present, in which case there are no comments.
+        let mut new_listing: Value = from_str(value)?;
+        let things = match new_listing.pointer_mut("/json/data/things") {
+            Some(things) => from_value::<Vec<BasicThing<Value>>>(things.take())?,
+            None => vec![],
+        };
+        Ok(CommentList::new(self.client,
+                            self.link_id.to_owned(),
+                            self.parent.to_owned(),
+                            things))
     }
 
     fn merge_more_comments(&mut self, list: CommentList<'a>) {
@@ -150,7 +353,7 @@ impl<'a> CommentList<'a> {
 
     fn merge_comment(&mut self,
                      mut item: Comment<'a>,
-                     mut orphanage: &mut HashMap<String, Vec<Comment<'a>>>) {
+                     orphanage: &mut HashMap<String, Vec<Comment<'a>>>) {
         {
             if item.parent() == self.parent {
                 self.add_reply(item);
@@ -169,7 +372,7 @@ impl<'a> CommentList<'a> {
                 for orphan in orphaned {
                     item.add_reply(orphan);
                 }
-                self.merge_comment(item, &mut orphanage);
+                self.merge_comment(item, orphanage);
             } else {
                 let name = item.name().to_owned();
                 if let Some(mut list) = orphanage.remove(&name) {
@@ -187,18 +390,25 @@ impl<'a> Iterator for CommentList<'a> {
     type Item = Comment<'a>;
     fn next(&mut self) -> Option<Comment<'a>> {
         if self.comments.is_empty() {
-            if self.more.is_empty() {
+            if self.more.is_empty() || self.last_error.is_some() {
                 None
             } else {
                 // XXX: This code is hideous (see the fetch_more etc.) but it does work.
                 // TODO: refactor (carefully!)
-                let more_item = self.more.drain(..1).next().unwrap();
-                let mut new_listing = self.fetch_more(more_item);
-                self.more.append(&mut new_listing.more);
-                // We've already consumed all of the items, so we can remove the mapping now.
-                self.comment_hashes = HashMap::new();
-                self.merge_more_comments(new_listing);
-                self.next()
+                match self.fetch_more(&self.more[0]) {
+                    Ok(new_listing) => {
+                        self.more.remove(0);
+                        // We've already consumed all of the items, so we can remove the mapping
+                        // now.
+                        self.comment_hashes = HashMap::new();
+                        self.absorb(new_listing);
+                        self.next()
+                    }
+                    Err(err) => {
+                        self.last_error = Some(err);
+                        None
+                    }
+                }
             }
         } else {
             // Draining breaks the comment_hashes map!
@@ -208,77 +418,185 @@ impl<'a> Iterator for CommentList<'a> {
     }
 }
 
-/// A stream of comments from oldest to newest that updates via polling every 5 seconds.
+#[cfg(feature = "streams")]
+/// Where a `CommentStream` fetches new comments from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentSource {
+    /// The replies to a submission.
+    Submission {
+        /// The full name of the submission (e.g. `t3_4uule8`).
+        link_name: String,
+        /// The ID of the submission (e.g. `4uule8`).
+        id: String,
+    },
+    /// The comments posted by a user, by their username.
+    User(String),
+}
+
+#[cfg(feature = "streams")]
+/// A stream of comments from oldest to newest that updates via polling every 5 seconds. Created
+/// with `Submission.reply_stream()` for the replies to a submission, or `User.comment_stream()`
+/// for the comments posted by a user.
 pub struct CommentStream<'a> {
     client: &'a RedditClient,
     set: VecDeque<String>,
     current_iter: Option<IntoIter<Comment<'a>>>,
-    id: String,
-    link_name: String,
+    processor: Box<dyn ItemProcessor<Comment<'a>> + 'a>,
+    authors: AuthorFilter,
+    #[cfg(feature = "language")]
+    languages: Vec<String>,
+    source: CommentSource,
 }
 
+#[cfg(feature = "streams")]
 impl<'a> CommentStream<'a> {
     /// Internal method. Use `Submission.reply_stream()` instead.
     pub fn new(client: &'a RedditClient, link_name: String, id: String) -> CommentStream<'a> {
+        let source = CommentSource::Submission {
+            link_name,
+            id,
+        };
+        CommentStream::with_source(client, source, AuthorFilter::for_client(client))
+    }
+
+    /// Internal method. Use `User.comment_stream()` instead.
+    pub fn for_user(client: &'a RedditClient, name: &str) -> CommentStream<'a> {
+        // The user may be the account the client is logged in as, so nobody is excluded.
+        let source = CommentSource::User(name.to_owned());
+        CommentStream::with_source(client, source, AuthorFilter::default())
+    }
+
+    fn with_source(client: &'a RedditClient,
+                   source: CommentSource,
+                   authors: AuthorFilter)
+                   -> CommentStream<'a> {
         CommentStream {
             set: VecDeque::new(),
             current_iter: None,
-            client: client,
-            link_name: link_name,
-            id: id,
+            processor: Box::new(NoopProcessor),
+            authors,
+            #[cfg(feature = "language")]
+            languages: Vec::new(),
+            client,
+            source,
+        }
+    }
+
+    /// Where this stream fetches new comments from.
+    pub fn source(&self) -> &CommentSource {
+        &self.source
+    }
+
+    /// Attaches an `ItemProcessor` which is run on every new item before it is yielded. This
+    /// replaces any processor that was previously attached.
+    pub fn with_processor<P>(mut self, processor: P) -> CommentStream<'a>
+        where P: ItemProcessor<Comment<'a>> + 'a
+    {
+        self.processor = Box::new(processor);
+        self
+    }
+
+    /// Skips items posted by any of the specified authors. In streams of the replies to a
+    /// submission, items by the account the client is logged in as are always skipped.
+    pub fn exclude_authors<S: AsRef<str>>(mut self, authors: &[S]) -> CommentStream<'a> {
+        self.authors.exclude(authors);
+        self
+    }
+
+    /// Only yields items posted by one of the specified authors.
+    pub fn only_authors<S: AsRef<str>>(mut self, authors: &[S]) -> CommentStream<'a> {
+        self.authors.only(authors);
+        self
+    }
+
+    /// Only yields items in the specified language (e.g. `en`), as detected by
+    /// `Comment.detected_language()`. Call this several times to allow several languages. Items
+    /// that are too short for their language to be detected are still yielded.
+    #[cfg(feature = "language")]
+    pub fn only_language(mut self, language: &str) -> CommentStream<'a> {
+        self.languages.push(language.to_owned());
+        self
+    }
+
+    /// Fetches the 5 newest comments from the source, newest first.
+    fn latest(&self) -> Result<Vec<Comment<'a>>, APIError> {
+        let client = self.client;
+        let url = match self.source {
+            CommentSource::Submission { ref id, .. } => {
+                format!("{}?sort=new&raw_json=1", endpoints::comments(id))
+            }
+            CommentSource::User(ref name) => {
+                format!("{}?sort=new&limit=5&raw_json=1", endpoints::user_comments(name))
+            }
+        };
+        let value = client.with_priority(Priority::Background, || client.get_json(&url, false))?;
+        match self.source {
+            CommentSource::Submission { ref link_name, .. } => {
+                let req: listing::CommentResponse = from_str(&value)?;
+                Ok(CommentList::new(client,
+                                    link_name.to_owned(),
+                                    link_name.to_owned(),
+                                    req.1.data.children)
+                    .take(5)
+                    .collect())
+            }
+            CommentSource::User(_) => {
+                let req: CommentListing = from_str(&value)?;
+                Ok(req.data
+                    .children
+                    .into_iter()
+                    .filter_map(|child| from_value::<CommentData>(child.data).ok())
+                    .map(|data| Comment::new(client, data))
+                    .collect())
+            }
         }
     }
 }
 
-impl<'a> Iterator for CommentStream<'a> {
-    type Item = Comment<'a>;
-    fn next(&mut self) -> Option<Comment<'a>> {
-        if self.current_iter.is_some() {
-            let mut iter = self.current_iter.take().unwrap();
-            let next_iter = iter.next();
-            if next_iter.is_some() {
-                let res = next_iter.unwrap();
-                let name = res.name().to_owned();
-                // VecDeque.contains is not stable yet!
-                let mut contains = false;
-                for item in &self.set {
-                    if item == &name {
-                        contains = true;
-                    }
+#[cfg(feature = "streams")]
+impl<'a> PollingStream for CommentStream<'a> {
+    fn poll(&mut self) -> Vec<Comment<'a>> {
+        trace_span!("poll_stream", source = ?self.source);
+        let latest = match self.latest() {
+            Ok(latest) => latest,
+            Err(_) => return Vec::new(),
+        };
+        let mut fresh = Vec::new();
+        for comment in latest.into_iter().rev() {
+            let name = comment.name().to_owned();
+            if !self.set.contains(&name) {
+                self.set.push_back(name);
+                if self.set.len() > 10 {
+                    self.set.pop_front();
                 }
-                if contains {
-                    self.current_iter = Some(iter);
-                    self.next()
-                } else {
-                    self.set.push_back(name);
-                    if self.set.len() > 10 {
-                        self.set.pop_front();
+                if !self.authors.allows(&comment.author().name) {
+                    continue;
+                }
+                #[cfg(feature = "language")]
+                {
+                    if !language::allows(&self.languages, comment.detected_language()) {
+                        continue;
                     }
-                    self.current_iter = Some(iter);
-                    Some(res)
                 }
-            } else {
-                self.next()
+                self.processor.process(&comment);
+                fresh.push(comment);
             }
-        } else {
-            thread::sleep(Duration::new(5, 0));
-            let url = format!("/comments/{}?sort=new&raw_json=1", self.id);
-            let value = self.client.get_json(&url, false);
-            if let Ok(value) = value {
-                let req: listing::CommentResponse= serde_json::from_str(&*value).unwrap();
-
-                let current_iter = CommentList::new(self.client,
-                                                    self.link_name.to_owned(),
-                                                    self.link_name.to_owned(),
-                                                    req.1.data.children)
-                    .take(5)
-                    .collect::<Vec<Comment>>()
-                    .into_iter()
-                    .rev()
-                    .collect::<Vec<Comment>>();
-                self.current_iter = Some(current_iter.into_iter());
+        }
+        fresh
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<'a> Iterator for CommentStream<'a> {
+    type Item = Comment<'a>;
+    fn next(&mut self) -> Option<Comment<'a>> {
+        if let Some(iter) = self.current_iter.as_mut() {
+            if let Some(res) = iter.next() {
+                return Some(res);
             }
-            self.next()
         }
+        pause(Duration::new(5, 0));
+        self.current_iter = Some(self.poll().into_iter());
+        self.next()
     }
 }
@@ -0,0 +1,223 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::options::TimeFilter;
+use crate::responses::listing;
+use crate::structures::streams::pause;
+use crate::structures::submission::Submission;
+use crate::traits::Content;
+
+/// The time filters crawled by `TopCrawl`, in order. Together they reach past the ~1000 item
+/// limit of a single listing.
+const FILTERS: [TimeFilter; 6] = [TimeFilter::AllTime,
+                                  TimeFilter::Year,
+                                  TimeFilter::Month,
+                                  TimeFilter::Week,
+                                  TimeFilter::Day,
+                                  TimeFilter::Hour];
+
+/// Somewhere to save the progress of a long-running crawl, so it can resume after a failure or
+/// restart.
+pub trait CheckpointStore {
+    /// Loads the value saved under the key, if any.
+    fn load(&self, key: &str) -> io::Result<Option<String>>;
+    /// Saves the value under the key, replacing any previous value.
+    fn save(&mut self, key: &str, value: &str) -> io::Result<()>;
+}
+
+/// A `CheckpointStore` that saves each key as a file in a directory.
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    /// Creates a store in the specified directory (creating it if necessary).
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<FileCheckpointStore> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FileCheckpointStore { dir })
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.dir.join(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save(&mut self, key: &str, value: &str) -> io::Result<()> {
+        // Write then rename, so a crash never leaves a half-written checkpoint.
+        let tmp = self.dir.join(format!("{}.tmp", key));
+        fs::write(&tmp, value)?;
+        fs::rename(tmp, self.dir.join(key))
+    }
+}
+
+/// A crawl of a subreddit's top posts across every time filter, which yields each post once.
+/// Once every post from a page has been yielded, the position (time filter and `after` cursor)
+/// and the names of the posts seen so far are saved to a `CheckpointStore`, so a crawl created with the same store resumes
+/// where the previous one stopped. Failed requests are retried with a growing delay; if they
+/// keep failing, iteration ends and the error is available from `last_error()`.
+///
+/// Create one with `Subreddit.top_all_time_crawl()`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::structures::crawl::FileCheckpointStore;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let store = FileCheckpointStore::new("checkpoints").expect("Could not create store");
+/// let sub = client.subreddit("rust");
+/// let mut crawl = sub.top_all_time_crawl(store);
+/// for post in crawl.by_ref() {
+///     println!("{}", post.title());
+/// }
+/// if let Some(err) = crawl.last_error() {
+///     println!("Crawl stopped early, run again to resume: {:?}", err);
+/// }
+/// ```
+pub struct TopCrawl<'a, S: CheckpointStore> {
+    client: &'a RedditClient,
+    store: S,
+    subreddit: String,
+    filter: usize,
+    after: Option<String>,
+    seen: HashSet<String>,
+    queue: VecDeque<Submission<'a>>,
+    retries: u32,
+    last_error: Option<APIError>,
+}
+
+impl<'a, S: CheckpointStore> TopCrawl<'a, S> {
+    /// Internal method. Use `Subreddit.top_all_time_crawl()` instead.
+    pub fn new(client: &'a RedditClient, subreddit: &str, store: S) -> TopCrawl<'a, S> {
+        let mut crawl = TopCrawl {
+            client,
+            store,
+            subreddit: subreddit.to_owned(),
+            filter: 0,
+            after: None,
+            seen: HashSet::new(),
+            queue: VecDeque::new(),
+            retries: 3,
+            last_error: None,
+        };
+        if let Err(err) = crawl.restore() {
+            crawl.last_error = Some(err.into());
+            crawl.filter = FILTERS.len();
+        }
+        crawl
+    }
+
+    /// Sets how many times a failed request is retried before the crawl stops. The default is 3.
+    pub fn with_retries(mut self, retries: u32) -> TopCrawl<'a, S> {
+        self.retries = retries;
+        self
+    }
+
+    /// The error that stopped the crawl, if it did not finish.
+    pub fn last_error(&self) -> Option<&APIError> {
+        self.last_error.as_ref()
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("top-{}-{}", self.subreddit, name)
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        if let Some(position) = self.store.load(&self.key("position"))? {
+            let mut parts = position.splitn(2, ' ');
+            self.filter = parts.next().and_then(|filter| filter.parse().ok()).unwrap_or(0);
+            self.after = parts.next().map(|after| after.to_owned()).filter(|a| !a.is_empty());
+        }
+        if let Some(seen) = self.store.load(&self.key("seen"))? {
+            self.seen = seen.lines().map(|name| name.to_owned()).collect();
+        }
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> io::Result<()> {
+        let position = format!("{} {}", self.filter, self.after.to_owned().unwrap_or_default());
+        let seen = self.seen.iter().cloned().collect::<Vec<String>>().join("\n");
+        let position_key = self.key("position");
+        let seen_key = self.key("seen");
+        self.store.save(&seen_key, &seen)?;
+        self.store.save(&position_key, &position)
+    }
+
+    fn fetch_page(&self) -> Result<listing::Listing, APIError> {
+        let mut url = format!("{}?limit=100{}",
+                              endpoints::subreddit_listing(&self.subreddit, "top"),
+                              FILTERS[self.filter]);
+        if let Some(ref after) = self.after {
+            url = format!("{}&after={}", url, after);
+        }
+        let string = self.client.get_json(&url, false)?;
+        Ok(serde_json::from_str(&string)?)
+    }
+
+    fn fetch_with_retries(&self) -> Result<listing::Listing, APIError> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_page() {
+                Ok(page) => return Ok(page),
+                Err(err) => {
+                    if attempt >= self.retries {
+                        return Err(err);
+                    }
+                    attempt += 1;
+                    pause(Duration::from_secs(2u64.pow(attempt)));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, S: CheckpointStore> Iterator for TopCrawl<'a, S> {
+    type Item = Submission<'a>;
+    fn next(&mut self) -> Option<Submission<'a>> {
+        while self.queue.is_empty() {
+            // Only checkpoint once every post from the previous page has been yielded, so
+            // stopping part-way through a page does not skip the rest of it on resume.
+            if self.last_error.is_none() {
+                if let Err(err) = self.checkpoint() {
+                    self.last_error = Some(err.into());
+                    return None;
+                }
+            }
+            if self.filter >= FILTERS.len() {
+                return None;
+            }
+            let page = match self.fetch_with_retries() {
+                Ok(page) => {
+                    self.last_error = None;
+                    page
+                }
+                Err(err) => {
+                    self.last_error = Some(err);
+                    return None;
+                }
+            };
+            for child in page.data.children {
+                let post = Submission::new(self.client, child.data);
+                if self.seen.insert(post.name().to_owned()) {
+                    self.queue.push_back(post);
+                }
+            }
+            self.after = page.data.after;
+            if self.after.is_none() {
+                self.filter += 1;
+            }
+        }
+        self.queue.pop_front()
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::errors::APIError;
+use crate::responses::DELETED;
+use crate::structures::comment::Comment;
+use crate::structures::submission::Submission;
+use crate::traits::{Commentable, Content, Created};
+
+/// A directed edge in an `InteractionGraph`: `from` replied to `to` one or more times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionEdge {
+    /// The user who replied.
+    pub from: String,
+    /// The user who was replied to.
+    pub to: String,
+    /// The number of replies.
+    pub count: u64,
+    /// The UTC timestamp of the first reply.
+    pub first: i64,
+    /// The UTC timestamp of the latest reply.
+    pub last: i64,
+}
+
+/// A graph of who replied to whom, built from the comment trees of one or more submissions.
+/// Top-level comments count as replies to the author of the submission.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::options::ListingRequest;
+/// use new_rawr::structures::graph::InteractionGraph;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let rust = client.subreddit("rust");
+/// let mut graph = InteractionGraph::new();
+/// for post in rust.listing(ListingRequest::default()).unwrap().take(10) {
+///     graph.add_submission(post).expect("Could not fetch comments");
+/// }
+/// println!("{}", graph.to_edge_list());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InteractionGraph {
+    edges: HashMap<(String, String), InteractionEdge>,
+}
+
+impl InteractionGraph {
+    /// Creates an empty graph.
+    pub fn new() -> InteractionGraph {
+        InteractionGraph::default()
+    }
+
+    /// Records that `from` replied to `to` at the specified UTC timestamp. Replies involving
+    /// deleted users are ignored.
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::graph::InteractionGraph;
+    /// let mut graph = InteractionGraph::new();
+    /// graph.add("alice", "bob", 100);
+    /// graph.add("alice", "bob", 200);
+    /// graph.add("bob", "[deleted]", 300);
+    /// assert_eq!(graph.to_edge_list(), "from,to,count,first,last\nalice,bob,2,100,200\n");
+    /// ```
+    pub fn add(&mut self, from: &str, to: &str, timestamp: i64) {
+        if from == DELETED || to == DELETED {
+            return;
+        }
+        let edge = self.edges
+            .entry((from.to_owned(), to.to_owned()))
+            .or_insert(InteractionEdge {
+                from: from.to_owned(),
+                to: to.to_owned(),
+                count: 0,
+                first: timestamp,
+                last: timestamp,
+            });
+        edge.count += 1;
+        edge.first = edge.first.min(timestamp);
+        edge.last = edge.last.max(timestamp);
+    }
+
+    /// Fetches the whole comment tree of the submission and adds every reply to the graph.
+    /// This may need several requests for large threads.
+    pub fn add_submission(&mut self, post: Submission) -> Result<(), APIError> {
+        let mut authors = HashMap::new();
+        authors.insert(post.name().to_owned(), post.author().name);
+        for comment in post.replies()? {
+            self.add_comment(comment, &mut authors)?;
+        }
+        Ok(())
+    }
+
+    fn add_comment(&mut self,
+                   comment: Comment,
+                   authors: &mut HashMap<String, String>)
+                   -> Result<(), APIError> {
+        let author = comment.author().name;
+        if let Some(parent) = authors.get(comment.parent()) {
+            self.add(&author, parent, comment.created_utc());
+        }
+        authors.insert(comment.name().to_owned(), author);
+        for reply in comment.replies()? {
+            self.add_comment(reply, authors)?;
+        }
+        Ok(())
+    }
+
+    /// All edges in the graph, ordered by `from` and then `to`.
+    pub fn edges(&self) -> Vec<InteractionEdge> {
+        let mut edges = self.edges.values().cloned().collect::<Vec<InteractionEdge>>();
+        edges.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.to.cmp(&b.to)));
+        edges
+    }
+
+    /// Exports the graph as a CSV edge list with a `from,to,count,first,last` header, which can
+    /// be loaded by most network analysis tools.
+    pub fn to_edge_list(&self) -> String {
+        let mut res = String::from("from,to,count,first,last\n");
+        for edge in self.edges() {
+            res.push_str(&format!("{},{},{},{},{}\n",
+                                  edge.from,
+                                  edge.to,
+                                  edge.count,
+                                  edge.first,
+                                  edge.last));
+        }
+        res
+    }
+}
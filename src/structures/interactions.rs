@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::client::RedditClient;
+use crate::errors::APIError;
+use crate::responses::DELETED;
+use crate::responses::comment::CommentData;
+
+/// A comment in which one of the two users replied directly to the other, either to one of
+/// their comments or to their submission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionReply {
+    /// The user who replied.
+    pub from: String,
+    /// The user who was replied to.
+    pub to: String,
+    /// The full name of the reply (e.g. `t1_d5t5ebn`).
+    pub comment: String,
+    /// The full name of the comment or submission that was replied to.
+    pub parent: String,
+    /// The UTC timestamp of the reply.
+    pub created_utc: i64,
+    /// The permalink of the reply, if Reddit returned one.
+    pub permalink: Option<String>,
+}
+
+/// A thread in which two users replied to each other, as returned by
+/// `User.interactions_with()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionThread {
+    /// The full name of the submission (e.g. `t3_4uule8`).
+    pub link_id: String,
+    /// The subreddit the submission was posted in.
+    pub subreddit: String,
+    /// The title of the submission, if Reddit returned it.
+    pub title: Option<String>,
+    /// The replies between the two users in this thread, oldest first.
+    pub replies: Vec<InteractionReply>,
+}
+
+impl InteractionThread {
+    /// `true` if both users replied to each other in this thread, rather than only one of them
+    /// replying to the other.
+    pub fn is_mutual(&self) -> bool {
+        match self.replies.first() {
+            Some(first) => self.replies.iter().any(|reply| reply.from != first.from),
+            None => false,
+        }
+    }
+
+    /// The UTC timestamp of the latest reply in this thread.
+    pub fn last_reply(&self) -> i64 {
+        self.replies.iter().map(|reply| reply.created_utc).max().unwrap_or(0)
+    }
+}
+
+/// Finds the threads in which `first` and `second` replied to each other, from the recent
+/// comments of both users. Internal method. Use `User.interactions_with()` instead.
+pub fn find_interactions(client: &RedditClient,
+                         first: &str,
+                         second: &str,
+                         comments: Vec<CommentData>)
+                         -> Result<Vec<InteractionThread>, APIError> {
+    let mut parents = comments.iter().map(|comment| comment.parent_id.to_owned()).collect::<Vec<_>>();
+    parents.sort();
+    parents.dedup();
+    let authors = fetch_authors(client, &parents)?;
+
+    let mut threads: HashMap<String, InteractionThread> = HashMap::new();
+    for comment in comments {
+        let to = match authors.get(&comment.parent_id) {
+            Some(to) => to,
+            None => continue,
+        };
+        let other = if comment.author.eq_ignore_ascii_case(first) {
+            second
+        } else if comment.author.eq_ignore_ascii_case(second) {
+            first
+        } else {
+            continue;
+        };
+        if !to.eq_ignore_ascii_case(other) {
+            continue;
+        }
+        let thread = threads.entry(comment.link_id.to_owned()).or_insert_with(|| {
+            InteractionThread {
+                link_id: comment.link_id.to_owned(),
+                subreddit: comment.subreddit.to_owned(),
+                title: comment.link_title.to_owned(),
+                replies: Vec::new(),
+            }
+        });
+        thread.replies.push(InteractionReply {
+            from: comment.author,
+            to: to.to_owned(),
+            comment: comment.name,
+            parent: comment.parent_id,
+            created_utc: comment.created_utc as i64,
+            permalink: comment.permalink,
+        });
+    }
+
+    let mut threads = threads.into_values().collect::<Vec<_>>();
+    for thread in &mut threads {
+        thread.replies.sort_by_key(|reply| reply.created_utc);
+    }
+    threads.sort_by_key(|thread| -thread.last_reply());
+    Ok(threads)
+}
+
+/// Looks up the authors of the comments and submissions with the specified full names. Deleted
+/// items are left out.
+fn fetch_authors(client: &RedditClient, names: &[String]) -> Result<HashMap<String, String>, APIError> {
+    let mut authors = HashMap::new();
+    for item in client.get_info(names)? {
+        let name = item["name"].as_str();
+        let author = item["author"].as_str();
+        if let (Some(name), Some(author)) = (name, author) {
+            if author != DELETED {
+                authors.insert(name.to_owned(), author.to_owned());
+            }
+        }
+    }
+    Ok(authors)
+}
@@ -1,12 +1,24 @@
+#[cfg(feature = "streams")]
 use std::vec::IntoIter;
+#[cfg(feature = "streams")]
 use std::collections::VecDeque;
-use std::thread;
+#[cfg(feature = "streams")]
 use std::time::Duration;
 
 use crate::responses::listing;
 use crate::client::RedditClient;
+#[cfg(all(feature = "language", feature = "streams"))]
+use crate::language;
+#[cfg(feature = "streams")]
+use crate::client::scheduler::Priority;
+#[cfg(feature = "streams")]
+use crate::structures::streams::{pause, AuthorFilter};
+#[cfg(feature = "streams")]
+use crate::structures::processors::NoopProcessor;
 use crate::structures::submission::Submission;
-use crate::traits::{Content, PageListing};
+use crate::traits::PageListing;
+#[cfg(feature = "streams")]
+use crate::traits::{Content, PollingStream, ItemProcessor};
 use crate::errors::APIError;
 
 /// A paginated listing of posts that can be iterated through. Posts are fetched lazily
@@ -14,11 +26,11 @@ use crate::errors::APIError;
 /// # Examples
 /// ```rust,no_run
 /// use new_rawr::client::RedditClient;
-/// use new_rawr::options::ListingOptions;
+/// use new_rawr::options::ListingRequest;
 /// use new_rawr::auth::AnonymousAuthenticator;
 /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
 /// let sub = client.subreddit("redditdev");
-/// let mut hot = sub.hot(ListingOptions::default()).expect("Could not get hot posts");
+/// let mut hot = sub.listing(ListingRequest::default()).expect("Could not get hot posts");
 /// for post in hot.take(500) {
 ///     // Do something with each post here
 /// }
@@ -32,26 +44,42 @@ use crate::errors::APIError;
 ///
 /// ## Improving Performance
 /// By default, new_rawr paginates using the same `limit` parameter as you
-/// (`ListingOptions::default()` sets it to 25), so by default you can only fetch 25 posts
-/// at a time. Create a `ListingOptions` object with a batch size of 100 to reduce the amount of
-/// requests that are needed, like this:
+/// (`ListingRequest::default()` sets it to 25), so by default you can only fetch 25 posts
+/// at a time. Set the limit to 100 to reduce the amount of requests that are needed, like this:
 ///
 /// ```
-/// # use new_rawr::options::ListingOptions;
-/// use new_rawr::options::ListingAnchor;
-/// ListingOptions {
-///     batch: 100,
-///     anchor: ListingAnchor::None
-/// };
+/// use new_rawr::options::ListingRequest;
+/// ListingRequest::default().limit(100);
 /// ```
 ///
 /// Keep in mind that if you only want 5 or 10 items, you might save bandwidth and get a quicker
 /// response by using a smaller batch size (and the Reddit admins would love it if you didn't
 /// waste bandwidth!)
+///
+/// ## Page metadata
+/// The `PageListing` trait exposes the metadata of the most recently fetched page, which can be
+/// used to show result counts or to build your own pagination:
+///
+/// ```rust,no_run
+/// # use new_rawr::client::RedditClient;
+/// # use new_rawr::options::ListingRequest;
+/// # use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::traits::PageListing;
+/// # let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let sub = client.subreddit("redditdev");
+/// let hot = sub.listing(ListingRequest::default()).expect("Could not get hot posts");
+/// println!("{:?} posts on this page, next page after {:?}", hot.dist(), hot.after());
+/// ```
 pub struct Listing<'a> {
     client: &'a RedditClient,
     query_stem: String,
     data: listing::ListingData<listing::SubmissionData>,
+    last_error: Option<APIError>,
+    warnings: Vec<String>,
+    pages: usize,
+    max_pages: Option<usize>,
+    stop_at: Option<String>,
+    finished: bool,
 }
 
 impl<'a> Listing<'a> {
@@ -59,11 +87,17 @@ impl<'a> Listing<'a> {
     pub fn new(client: &RedditClient,
                query_stem: String,
                data: listing::ListingData<listing::SubmissionData>)
-               -> Listing {
+               -> Listing<'_> {
         Listing {
-            client: client,
-            query_stem: query_stem,
-            data: data,
+            client,
+            query_stem,
+            warnings: data.skipped.to_owned(),
+            data,
+            last_error: None,
+            pages: 1,
+            max_pages: None,
+            stop_at: None,
+            finished: false,
         }
     }
 }
@@ -80,16 +114,114 @@ impl<'a> PageListing for Listing<'a> {
     fn modhash(&self) -> Option<String> {
         self.data.modhash.to_owned()
     }
+
+    fn dist(&self) -> Option<u64> {
+        self.data.dist
+    }
 }
 
 impl<'a> Listing<'a> {
+    /// The error that ended iteration, if the last page could not be fetched. When the iterator
+    /// returns `None`, this distinguishes the end of the listing (`None`) from a failed request
+    /// (`Some(error)`). After a failure, call `retry()` to resume iteration.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::{ListingRequest, ListingSort};
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let rust = client.subreddit("rust");
+    /// let mut new = rust.listing(ListingRequest::new(ListingSort::New)).unwrap();
+    /// loop {
+    ///     while let Some(post) = new.next() {
+    ///         println!("{}", post.title());
+    ///     }
+    ///     match new.last_error() {
+    ///         Some(err) => println!("Fetch failed, retrying: {:?}", err),
+    ///         None => break,
+    ///     }
+    ///     new.retry();
+    /// }
+    /// ```
+    pub fn last_error(&self) -> Option<&APIError> {
+        self.last_error.as_ref()
+    }
+
+    /// Clears the error from a failed page fetch, so the next call to `next()` tries to fetch the
+    /// page again. Until this is called, a listing that failed keeps returning `None`. Because
+    /// of this, `Listing` is not a `FusedIterator`: it can return items after `None`.
+    pub fn retry(&mut self) {
+        self.last_error = None;
+    }
+
+    /// Limits the listing to the specified number of pages in total, including the page that
+    /// has already been fetched. Unlike `take(n)`, this bounds the number of requests rather than
+    /// the number of items.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::{ListingRequest, ListingSort};
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let sub = client.subreddit("rust");
+    /// let posts = sub.listing(ListingRequest::new(ListingSort::New)).unwrap().take_pages(3).collect::<Vec<_>>();
+    /// assert!(posts.len() <= 75);
+    /// ```
+    pub fn take_pages(mut self, pages: usize) -> Listing<'a> {
+        self.max_pages = Some(pages);
+        if pages == 0 {
+            self.finished = true;
+        }
+        self
+    }
+
+    /// Stops the listing when the item with the specified full name (e.g. `t3_4uule8`) is
+    /// reached. The item itself is not yielded. This is useful for fetching only the posts that
+    /// are newer than the last one processed.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::{ListingRequest, ListingSort};
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::Content;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let sub = client.subreddit("rust");
+    /// let last_seen = "t3_4uule8";
+    /// for post in sub.listing(ListingRequest::new(ListingSort::New)).unwrap().until_before(last_seen) {
+    ///     println!("New since last run: {}", post.name());
+    /// }
+    /// ```
+    pub fn until_before(mut self, fullname: &str) -> Listing<'a> {
+        self.stop_at = Some(fullname.to_owned());
+        self
+    }
+
+    /// `true` if no more pages will be fetched, because the listing is exhausted, the page limit
+    /// was reached or the last fetch failed.
+    fn last_page(&self) -> bool {
+        self.after().is_none() || self.last_error.is_some() ||
+        self.max_pages.is_some_and(|max| self.pages >= max)
+    }
+
+    /// The number of items that have been left out of this listing so far because they could not
+    /// be parsed (for example, promoted posts or new kinds of item). Skipped items do not stop
+    /// iteration.
+    pub fn skipped(&self) -> usize {
+        self.warnings.len()
+    }
+
+    /// A description of each item that has been skipped so far, including the parsing error.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     fn fetch_after(&mut self) -> Result<Listing<'a>, APIError> {
         match self.after() {
             Some(after_id) => {
                 let url = format!("{}&after={}", self.query_stem, after_id);
                 let string = self.client
-                    .get_json(&url, false).unwrap();
-                let string :listing::Listing= serde_json::from_str(&*string).unwrap();
+                    .get_json(&url, false)?;
+                let string :listing::Listing= serde_json::from_str(&string)?;
                 Ok(Listing::new(self.client, self.query_stem.to_owned(), string.data))
 
             }
@@ -101,20 +233,50 @@ impl<'a> Listing<'a> {
 impl<'a> Iterator for Listing<'a> {
     type Item = Submission<'a>;
     fn next(&mut self) -> Option<Submission<'a>> {
-        if self.data.children.is_empty() {
-            if self.after().is_none() {
+        if self.finished {
+            None
+        } else if self.data.children.is_empty() {
+            if self.last_page() {
                 None
             } else {
-                let mut new_listing = self.fetch_after().expect("After does not exist!");
-                self.data.children.append(&mut new_listing.data.children);
-                self.data.after = new_listing.data.after;
-                self.next()
+                match self.fetch_after() {
+                    Ok(mut new_listing) => {
+                        self.pages += 1;
+                        self.warnings.append(&mut new_listing.warnings);
+                        self.data.children.append(&mut new_listing.data.children);
+                        self.data.after = new_listing.data.after;
+                        self.data.before = new_listing.data.before;
+                        self.data.dist = new_listing.data.dist;
+                        self.data.modhash = new_listing.data.modhash;
+                        self.next()
+                    }
+                    Err(err) => {
+                        self.last_error = Some(err);
+                        None
+                    }
+                }
             }
         } else {
             let child = self.data.children.drain(..1).next().unwrap();
+            if self.stop_at.as_ref() == Some(&child.data.name) {
+                self.finished = true;
+                return None;
+            }
             Some(Submission::new(self.client, child.data))
         }
     }
+
+    /// The lower bound is the number of items left from the current page (at most `dist`), and
+    /// the upper bound is only known once no more pages will be fetched.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            return (0, Some(0));
+        }
+        let remaining = self.data.children.len();
+        let lower = if self.stop_at.is_some() { 0 } else { remaining };
+        let upper = if self.last_page() { Some(remaining) } else { None };
+        (lower, upper)
+    }
 }
 
 /// An infinite stream of posts which updates as posts are received. Stores a list of seen posts
@@ -125,73 +287,138 @@ impl<'a> Iterator for Listing<'a> {
 ///
 /// On extremely popular subreddits where more than 5 posts per second are being made, some may be
 /// missed. If this is problematic for your use-case, file an issue on GitHub.
+///
+/// Posts made by the account the client is logged in as are never yielded. Other authors can be
+/// filtered with `exclude_authors()` and `only_authors()`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::traits::Content;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let stream = client.subreddit("rust").new_stream().exclude_authors(&["AutoModerator"]);
+/// for post in stream {
+///     println!("{} by {}", post.title(), post.author().name);
+/// }
+/// ```
+#[cfg(feature = "streams")]
 pub struct PostStream<'a> {
     client: &'a RedditClient,
     set: VecDeque<String>,
     current_iter: Option<IntoIter<Submission<'a>>>,
+    processor: Box<dyn ItemProcessor<Submission<'a>> + 'a>,
+    authors: AuthorFilter,
+    #[cfg(feature = "language")]
+    languages: Vec<String>,
     url: String,
 }
 
+#[cfg(feature = "streams")]
 impl<'a> PostStream<'a> {
     /// Internal method. Use `Subreddit.new_stream()` instead.
     pub fn new(client: &'a RedditClient, url: String) -> PostStream<'a> {
         PostStream {
             set: VecDeque::new(),
             current_iter: None,
-            client: client,
-            url: url,
+            processor: Box::new(NoopProcessor),
+            authors: AuthorFilter::for_client(client),
+            #[cfg(feature = "language")]
+            languages: Vec::new(),
+            client,
+            url,
         }
     }
+
+    /// Attaches an `ItemProcessor` which is run on every new item before it is yielded. This
+    /// replaces any processor that was previously attached.
+    pub fn with_processor<P>(mut self, processor: P) -> PostStream<'a>
+        where P: ItemProcessor<Submission<'a>> + 'a
+    {
+        self.processor = Box::new(processor);
+        self
+    }
+
+    /// Skips items posted by any of the specified authors. Items by the account the client is
+    /// logged in as are always skipped.
+    pub fn exclude_authors<S: AsRef<str>>(mut self, authors: &[S]) -> PostStream<'a> {
+        self.authors.exclude(authors);
+        self
+    }
+
+    /// Only yields items posted by one of the specified authors.
+    pub fn only_authors<S: AsRef<str>>(mut self, authors: &[S]) -> PostStream<'a> {
+        self.authors.only(authors);
+        self
+    }
+
+    /// Only yields items in the specified language (e.g. `en`), as detected by
+    /// `Submission.detected_language()`. Call this several times to allow several languages. Items
+    /// that are too short for their language to be detected are still yielded.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::Content;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// for post in client.subreddit("de").new_stream().only_language("de") {
+    ///     println!("{} by {}", post.title(), post.author().name);
+    /// }
+    /// ```
+    #[cfg(feature = "language")]
+    pub fn only_language(mut self, language: &str) -> PostStream<'a> {
+        self.languages.push(language.to_owned());
+        self
+    }
 }
 
-impl<'a> Iterator for PostStream<'a> {
-    type Item = Submission<'a>;
-    fn next(&mut self) -> Option<Submission<'a>> {
-        if self.current_iter.is_some() {
-            let mut iter = self.current_iter.take().unwrap();
-            let next_iter = iter.next();
-            if next_iter.is_some() {
-                let res = next_iter.unwrap();
-                let name = res.name().to_owned();
-                // VecDeque.contains is not stable yet!
-                let mut contains = false;
-                for item in &self.set {
-                    if item == &name {
-                        contains = true;
-                    }
+#[cfg(feature = "streams")]
+impl<'a> PollingStream for PostStream<'a> {
+    fn poll(&mut self) -> Vec<Submission<'a>> {
+        trace_span!("poll_stream", url = %self.url);
+        let req = self.client.with_priority(Priority::Background, || {
+            self.client.get_json(&self.url, false)
+        });
+        let req: listing::Listing = match req.map(|req| serde_json::from_str(&req)) {
+            Ok(Ok(req)) => req,
+            _ => return Vec::new(),
+        };
+        let mut fresh = Vec::new();
+        for child in req.data.children.into_iter().rev() {
+            let post = Submission::new(self.client, child.data);
+            let name = post.name().to_owned();
+            if !self.set.contains(&name) {
+                self.set.push_back(name);
+                if self.set.len() > 10 {
+                    self.set.pop_front();
                 }
-                if contains {
-                    self.current_iter = Some(iter);
-                    self.next()
-                } else {
-                    self.set.push_back(name);
-                    if self.set.len() > 10 {
-                        self.set.pop_front();
+                if !self.authors.allows(&post.author().name) {
+                    continue;
+                }
+                #[cfg(feature = "language")]
+                {
+                    if !language::allows(&self.languages, post.detected_language()) {
+                        continue;
                     }
-                    self.current_iter = Some(iter);
-                    Some(res)
                 }
-            } else {
-                self.next()
+                self.processor.process(&post);
+                fresh.push(post);
+            }
+        }
+        fresh
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<'a> Iterator for PostStream<'a> {
+    type Item = Submission<'a>;
+    fn next(&mut self) -> Option<Submission<'a>> {
+        if let Some(iter) = self.current_iter.as_mut() {
+            if let Some(res) = iter.next() {
+                return Some(res);
             }
-        } else {
-            thread::sleep(Duration::new(5, 0));
-            let req= self.client.get_json(&self.url, false);
-            let current_iter = if let Ok(req) = req {
-                let req : listing::Listing=serde_json::from_str(&*req).unwrap();
-
-                Some(req.data
-                    .children
-                    .into_iter()
-                    .map(|i| Submission::new(self.client, i.data))
-                    .rev()
-                    .collect::<Vec<Submission<'a>>>()
-                    .into_iter())
-            } else {
-                None
-            };
-            self.current_iter = current_iter;
-            self.next()
         }
+        pause(Duration::new(5, 0));
+        self.current_iter = Some(self.poll().into_iter());
+        self.next()
     }
 }
@@ -1,18 +1,26 @@
+use std::collections::HashMap;
+#[cfg(feature = "streams")]
 use std::vec::IntoIter;
-use std::thread;
+#[cfg(feature = "streams")]
 use std::time::Duration;
-use serde_json;
+use serde_json::{self, Value};
 
 
+use crate::endpoints;
 use crate::client::RedditClient;
+#[cfg(feature = "streams")]
+use crate::client::scheduler::Priority;
+#[cfg(feature = "streams")]
+use crate::structures::streams::pause;
 use crate::traits::{Created, Content, Approvable, PageListing, Editable, Commentable};
 use crate::structures::user::User;
+use crate::structures::blocks::UNAVAILABLE_AUTHOR;
 use crate::structures::comment::Comment;
 use crate::responses::comment::NewComment;
 use crate::structures::comment_list::CommentList;
 use crate::errors::APIError;
 use crate::structures::subreddit::Subreddit;
-use crate::options::ListingOptions;
+use crate::options::{ListingOptions, Recipient};
 use crate::responses::listing;
 use crate::responses::messages::{MessageData, MessageListingData};
 
@@ -25,10 +33,10 @@ pub struct Message<'a> {
 impl<'a> Message<'a> {
     /// Internal method. Use `RedditClient.messages().inbox()` or `unread()` instead to get
     /// message listings and individual messages.
-    pub fn new(client: &RedditClient, data: MessageData) -> Message {
+    pub fn new(client: &RedditClient, data: MessageData) -> Message<'_> {
         Message {
-            client: client,
-            data: data,
+            client,
+            data,
         }
     }
 
@@ -37,10 +45,73 @@ impl<'a> Message<'a> {
         self.data.parent_id.to_owned()
     }
 
+    /// Returns `true` if the author shows as `[unavailable]`, which means they have blocked the
+    /// logged-in user and will not see any reply.
+    pub fn is_from_blocked_user(&self) -> bool {
+        self.data.author.as_ref().map(|author| author == UNAVAILABLE_AUTHOR).unwrap_or(false)
+    }
+
     /// Marks this message as read, so it will not show in the unread queue.
     pub fn mark_read(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.name());
-        self.client.post_success("/api/read_message", &body, false)
+        self.client.post_success(endpoints::READ_MESSAGE, &body, false)
+    }
+
+    /// The replies to this message that were included when it was fetched, including replies
+    /// to those replies, in the order Reddit sent them. This does not send a request; use
+    /// `Message.thread()` to fetch the whole conversation.
+    pub fn loaded_replies(&self) -> Vec<Message<'a>> {
+        let mut replies = Vec::new();
+        collect_replies(&self.data.replies, &mut replies);
+        replies.into_iter().map(|data| Message::new(self.client, data)).collect()
+    }
+
+    /// Fetches the whole conversation that this message belongs to, starting with the first
+    /// message and ending with the most recent reply.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::options::ListingOptions;
+    /// use new_rawr::traits::{Content, Editable};
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let message = client.messages().inbox(ListingOptions::default()).unwrap().next().unwrap();
+    /// for message in message.thread().expect("Could not fetch conversation") {
+    ///     println!("{}: {}", message.author().name, message.body().unwrap());
+    /// }
+    /// ```
+    pub fn thread(&self) -> Result<Vec<Message<'a>>, APIError> {
+        let first = self.data.first_message_name.as_ref().unwrap_or(&self.data.name);
+        let id = first.trim_start_matches("t4_");
+        let url = format!("{}?raw_json=1", endpoints::message(id));
+        let string = self.client.get_json(&url, false)?;
+        let listing: MessageListingData = serde_json::from_str(&string)?;
+        let mut thread = Vec::new();
+        for child in listing.data.children {
+            let mut replies = Vec::new();
+            collect_replies(&child.data.replies, &mut replies);
+            thread.push(child.data);
+            thread.append(&mut replies);
+        }
+        thread.sort_by(|a, b| {
+            a.created_utc.partial_cmp(&b.created_utc).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(thread.into_iter().map(|data| Message::new(self.client, data)).collect())
+    }
+}
+
+/// Parses the `replies` of a message (an empty string, or a listing of messages which may have
+/// replies of their own) into a flat list.
+fn collect_replies(replies: &Value, out: &mut Vec<MessageData>) {
+    if !replies.is_object() {
+        return;
+    }
+    if let Ok(listing) = serde_json::from_value::<MessageListingData>(replies.clone()) {
+        for child in listing.data.children {
+            let nested = child.data.replies.clone();
+            out.push(child.data);
+            collect_replies(&nested, out);
+        }
     }
 }
 
@@ -55,16 +126,36 @@ impl<'a> Commentable<'a> for Message<'a> {
                 function is unavailable.");
     }
 
-    fn reply(&self, text: &str) -> Result<Comment, APIError> {
+    fn reply(&self, text: &str) -> Result<Comment<'_>, APIError> {
+        trace_span!("reply", parent = %self.data.name);
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.name());
-        let result = self.client.post_json("/api/comment", &body, false).unwrap();
-        let result :NewComment = serde_json::from_str(&*result).unwrap();
-        Ok(Comment::new(self.client, result.json.data.things.into_iter().next().unwrap().data))
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        let thing = result.json.data.things.into_iter().next();
+        let thing = thing.ok_or_else(|| APIError::missing("new comment"))?;
+        Ok(Comment::new(self.client, thing.data))
 
 
     }
+
+    fn template_vars(&self) -> HashMap<String, String> {
+        let permalink = if self.data.context.is_empty() {
+            format!("https://www.reddit.com/message/messages/{}",
+                    self.data.name.trim_start_matches("t4_"))
+        } else {
+            format!("https://www.reddit.com{}", self.data.context)
+        };
+        let title = self.data.link_title.to_owned().unwrap_or_else(|| self.data.subject.to_owned());
+        let mut vars = HashMap::new();
+        vars.insert(String::from("author"),
+                    self.data.author.to_owned().unwrap_or_else(|| String::from("reddit")));
+        vars.insert(String::from("title"), title);
+        vars.insert(String::from("permalink"), permalink);
+        vars.insert(String::from("subreddit"), self.data.subreddit.to_owned().unwrap_or_default());
+        vars
+    }
 }
 
 impl<'a> Created for Message<'a> {
@@ -78,7 +169,7 @@ impl<'a> Created for Message<'a> {
 }
 
 impl<'a> Content for Message<'a> {
-    fn author(&self) -> User {
+    fn author(&self) -> User<'_> {
         let author = self.data.author.to_owned().unwrap_or(String::from("reddit"));
         User::new(self.client, &author)
     }
@@ -91,14 +182,14 @@ impl<'a> Content for Message<'a> {
         None
     }
 
-    fn subreddit(&self) -> Subreddit {
+    fn subreddit(&self) -> Subreddit<'_> {
         let subreddit = self.data.subreddit.to_owned().unwrap_or(String::from("all"));
         Subreddit::create_new(self.client, &subreddit)
     }
 
     fn delete(self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/del_msg", &body, false)
+        self.client.post_success(endpoints::DELETE_MESSAGE, &body, false)
     }
 
     fn name(&self) -> &str {
@@ -109,22 +200,22 @@ impl<'a> Content for Message<'a> {
 impl<'a> Approvable for Message<'a> {
     fn approve(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/approve", &body, false)
+        self.client.post_success(endpoints::APPROVE, &body, false)
     }
 
     fn remove(&self, spam: bool) -> Result<(), APIError> {
         let body = format!("id={}&spam={}", self.data.name, spam);
-        self.client.post_success("/api/remove", &body, false)
+        self.client.post_success(endpoints::REMOVE, &body, false)
     }
 
     fn ignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/ignore_reports", &body, false)
+        self.client.post_success(endpoints::IGNORE_REPORTS, &body, false)
     }
 
     fn unignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/unignore_reports", &body, false)
+        self.client.post_success(endpoints::UNIGNORE_REPORTS, &body, false)
     }
 }
 
@@ -138,10 +229,11 @@ impl<'a> Editable for Message<'a> {
     }
 
     fn edit(&mut self, text: &str) -> Result<(), APIError> {
+        trace_span!("edit", thing = %self.data.name);
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.data.name);
-        let res = self.client.post_success("/api/editusertext", &body, false);
+        let res = self.client.post_success(endpoints::EDIT, &body, false);
         if let Ok(()) = res {
             // TODO: should we update body_html?
             self.data.body = text.to_owned();
@@ -165,39 +257,48 @@ pub struct MessageInterface<'a> {
 
 impl<'a> MessageInterface<'a> {
     /// Internal method. Use `RedditClient.messages()` instead.
-    pub fn new(client: &RedditClient) -> MessageInterface {
-        MessageInterface { client: client }
+    pub fn new(client: &RedditClient) -> MessageInterface<'_> {
+        MessageInterface { client }
     }
 
-    /// Composes a private message to send to a user.
+    /// Composes a private message to send to a user, or to the moderators of a subreddit. The
+    /// recipient can be a `User`, a `Subreddit` or a string (see `Recipient`), and is validated
+    /// before the message is sent.
     /// # Examples
-    /// ```ignore
-    ///
-    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
-    /// client.messages().compose("Aurora0001", "Test", "Hi!");
-    // ```
-    pub fn compose(&self, recipient: &str, subject: &str, body: &str) -> Result<(), APIError> {
-        let body = format!("api_type=json&subject={}&text={}&to={}", subject, body, recipient);
-        self.client.post_success("/api/compose", &body, false)
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// client.messages().compose("Aurora0001", "Test", "Hi!").unwrap();
+    /// client.messages().compose(&client.subreddit("rust"), "Question", "Hi mods!").unwrap();
+    /// ```
+    pub fn compose<R>(&self, recipient: R, subject: &str, body: &str) -> Result<(), APIError>
+        where R: Into<Recipient>
+    {
+        let recipient = recipient.into();
+        recipient.validate()?;
+        let body = format!("api_type=json&subject={}&text={}&to={}",
+                           self.client.url_escape(subject.to_owned()),
+                           self.client.url_escape(body.to_owned()),
+                           self.client.url_escape(recipient.to_string()));
+        self.client.post_success(endpoints::COMPOSE, &body, false)
     }
 
     /// Gets a list of all received messages that have not been deleted.
     pub fn inbox(&self, opts: ListingOptions) -> Result<MessageListing<'a>, APIError> {
-        let uri = format!("/message/inbox?raw_json=1&limit={}", opts.batch);
+        let uri = format!("{}?raw_json=1&limit={}", endpoints::INBOX, opts.batch);
         let full_uri = format!("{}&{}", uri, opts.anchor);
-        let result = self.client
-            .get_json(&full_uri, false).unwrap();
-        let result :MessageListingData = serde_json::from_str(&*result).unwrap();
+        let result = self.client.get_json(&full_uri, false)?;
+        let result: MessageListingData = serde_json::from_str(&result)?;
         Ok(MessageListing::new(self.client, uri, result.data))
     }
 
     /// Gets all messages that have **not** been marked as read.
     pub fn unread(&self, opts: ListingOptions) -> Result<MessageListing<'a>, APIError> {
-        let uri = format!("/message/unread?raw_json=1&limit={}", opts.batch);
+        let uri = format!("{}?raw_json=1&limit={}", endpoints::UNREAD, opts.batch);
         let full_uri = format!("{}&{}", uri, opts.anchor);
-        let result = self.client
-            .get_json(&full_uri, false).unwrap();
-        let result :MessageListingData = serde_json::from_str(&*result).unwrap();
+        let result = self.client.get_json(&full_uri, false)?;
+        let result: MessageListingData = serde_json::from_str(&result)?;
         Ok(MessageListing::new(self.client, uri, result.data))
 
     }
@@ -215,8 +316,9 @@ impl<'a> MessageInterface<'a> {
     ///     println!("New message received.");
     /// }
     /// ```
+    #[cfg(feature = "streams")]
     pub fn unread_stream(self) -> MessageStream<'a> {
-        MessageStream::new(&self.client, String::from("/message/unread?limit=5"))
+        MessageStream::new(self.client, format!("{}?limit=5", endpoints::UNREAD))
     }
 }
 
@@ -228,6 +330,7 @@ pub struct MessageListing<'a> {
     client: &'a RedditClient,
     query_stem: String,
     data: listing::ListingData<MessageData>,
+    last_error: Option<APIError>,
 }
 
 impl<'a> MessageListing<'a> {
@@ -236,11 +339,12 @@ impl<'a> MessageListing<'a> {
     pub fn new(client: &RedditClient,
                query_stem: String,
                data: listing::ListingData<MessageData>)
-               -> MessageListing {
+               -> MessageListing<'_> {
         MessageListing {
-            client: client,
-            query_stem: query_stem,
-            data: data,
+            client,
+            query_stem,
+            data,
+            last_error: None,
         }
     }
 }
@@ -257,16 +361,26 @@ impl<'a> PageListing for MessageListing<'a> {
     fn modhash(&self) -> Option<String> {
         self.data.modhash.to_owned()
     }
+
+    fn dist(&self) -> Option<u64> {
+        self.data.dist
+    }
 }
 
 impl<'a> MessageListing<'a> {
+    /// The error that ended iteration, if the last page could not be fetched. See
+    /// `Listing.last_error()` for details.
+    pub fn last_error(&self) -> Option<&APIError> {
+        self.last_error.as_ref()
+    }
+
     fn fetch_after(&mut self) -> Result<MessageListing<'a>, APIError> {
         match self.after() {
             Some(after_id) => {
                 let url = format!("{}&after={}", self.query_stem, after_id);
                 let string = self.client
-                    .get_json(&url, false).unwrap();
-                let string:MessageListingData = serde_json::from_str(&*string).unwrap();
+                    .get_json(&url, false)?;
+                let string:MessageListingData = serde_json::from_str(&string)?;
                 Ok(MessageListing::new(self.client, self.query_stem.to_owned(), string.data))
             }
             None => Err(APIError::ExhaustedListing),
@@ -281,10 +395,21 @@ impl<'a> Iterator for MessageListing<'a> {
             if self.after().is_none() {
                 None
             } else {
-                let mut new_listing = self.fetch_after().expect("After does not exist!");
-                self.data.children.append(&mut new_listing.data.children);
-                self.data.after = new_listing.data.after;
-                self.next()
+                match self.fetch_after() {
+                    Ok(mut new_listing) => {
+                        self.last_error = None;
+                        self.data.children.append(&mut new_listing.data.children);
+                        self.data.after = new_listing.data.after;
+                        self.data.before = new_listing.data.before;
+                        self.data.dist = new_listing.data.dist;
+                        self.data.modhash = new_listing.data.modhash;
+                        self.next()
+                    }
+                    Err(err) => {
+                        self.last_error = Some(err);
+                        None
+                    }
+                }
             }
         } else {
             let child = self.data.children.drain(..1).next().unwrap();
@@ -295,35 +420,36 @@ impl<'a> Iterator for MessageListing<'a> {
 
 /// A stream of unread messages from oldest to newest. Before being yielded from this iterator,
 /// each message will be marked as read (and will not show up in the unread queue again).
+#[cfg(feature = "streams")]
 pub struct MessageStream<'a> {
     client: &'a RedditClient,
     current_iter: Option<IntoIter<Message<'a>>>,
     url: String,
 }
 
+#[cfg(feature = "streams")]
 impl<'a> MessageStream<'a> {
     /// Internal method. Use `Subreddit.new_stream()` instead.
     pub fn new(client: &'a RedditClient, url: String) -> MessageStream<'a> {
         MessageStream {
             current_iter: None,
-            client: client,
-            url: url,
+            client,
+            url,
         }
     }
 }
 
+#[cfg(feature = "streams")]
 impl<'a> Iterator for MessageStream<'a> {
     type Item = Message<'a>;
     fn next(&mut self) -> Option<Message<'a>> {
         if self.current_iter.is_some() {
             let mut iter = self.current_iter.take().unwrap();
-            let next_iter = iter.next();
-            if next_iter.is_some() {
-                let res = next_iter.unwrap();
+            if let Some(res) = iter.next() {
                 loop {
                     // Loops until post is marked as read.
                     if res.mark_read().is_ok() {
-                        thread::sleep(Duration::new(5, 0));
+                        pause(Duration::new(5, 0));
                         break;
                     }
                 }
@@ -333,10 +459,14 @@ impl<'a> Iterator for MessageStream<'a> {
                 self.next()
             }
         } else {
-            thread::sleep(Duration::new(5, 0));
-            let req: Result<String, APIError> = self.client.get_json(&self.url, false);
-            let current_iter = if let Ok(res) = req {
-                let req :MessageListingData = serde_json::from_str(&*res).unwrap();
+            pause(Duration::new(5, 0));
+            let req: Result<String, APIError> = self.client.with_priority(Priority::Background, || {
+                self.client.get_json(&self.url, false)
+            });
+            let parsed = req.and_then(|res| {
+                Ok(serde_json::from_str::<MessageListingData>(&res)?)
+            });
+            let current_iter = if let Ok(req) = parsed {
                 Some(req.data
                     .children
                     .into_iter()
@@ -12,3 +12,65 @@ pub mod subreddit;
 pub mod user;
 /// Structures for private messages.
 pub mod messages;
+/// Combinators for consuming several streams from a single loop. The streams themselves require
+/// the `streams` feature.
+pub mod streams;
+/// Processors that can be attached to streams to inspect or enrich items.
+pub mod processors;
+/// Subreddit wiki pages.
+pub mod wiki;
+/// Typed access to a subreddit's AutoModerator configuration.
+#[cfg(feature = "moderation")]
+pub mod automoderator;
+/// Aggregated statistics about the posts in a subreddit.
+pub mod stats;
+/// Subreddit moderation log entries and streams.
+#[cfg(feature = "moderation")]
+pub mod modlog;
+/// Graphs of interactions between users.
+pub mod graph;
+/// Threads in which two specific users replied to each other.
+pub mod interactions;
+/// Structured styles ('community appearance') of subreddits.
+pub mod styles;
+/// Serializable snapshots of a submission and its comment tree.
+pub mod snapshot;
+/// Coordination between several bot instances, so items are only actioned once.
+pub mod claims;
+/// Queues of replies that are sent gradually.
+pub mod reply_queue;
+/// Checks for blocked users, so bots do not reply to people who cannot see them.
+pub mod blocks;
+/// Archiving of links posted to Reddit.
+pub mod archive;
+/// Searches that are trending on Reddit.
+pub mod trending;
+/// Links to submissions and comments.
+pub mod permalink;
+/// Messages explaining why an item was removed.
+#[cfg(feature = "moderation")]
+pub mod removal;
+/// Resumable crawls of large listings.
+pub mod crawl;
+/// Scheduled deletion of the bot's own poorly-received comments.
+pub mod cleanup;
+/// Moderator lists and syncing them to a desired state.
+#[cfg(feature = "moderation")]
+pub mod moderators;
+/// Reading and writing Toolbox user notes.
+#[cfg(feature = "moderation")]
+pub mod usernotes;
+/// Submitting posts on a schedule, such as weekly threads.
+pub mod scheduled;
+/// Rotating a subreddit's sticky posts through a queue of scheduled announcements.
+pub mod sticky;
+/// Tracking of submissions' positions in a subreddit's hot listing.
+pub mod ranking;
+/// Notifications when the scores of comments or submissions cross thresholds.
+pub mod score_watch;
+/// Checks of posts against a subreddit's posting rules before they are submitted.
+pub mod preflight;
+/// Vote counts and scores of submissions and comments.
+pub mod score;
+/// Sets of related subreddits, for moderating a network of communities together.
+pub mod network;
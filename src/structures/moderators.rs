@@ -0,0 +1,139 @@
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::options::ModPermission;
+use crate::responses::ModeratorData;
+
+/// A change to a subreddit's moderator list, produced by `plan_moderator_sync()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeratorChange {
+    /// Invites a user to become a moderator with the specified permissions.
+    Invite {
+        /// The user to invite.
+        name: String,
+        /// The permissions to offer.
+        permissions: Vec<ModPermission>,
+    },
+    /// Changes the permissions of an existing moderator.
+    SetPermissions {
+        /// The moderator to change.
+        name: String,
+        /// The new permissions.
+        permissions: Vec<ModPermission>,
+    },
+    /// Removes a moderator.
+    Remove {
+        /// The moderator to remove.
+        name: String,
+    },
+}
+
+impl ModeratorChange {
+    /// Sends this change to the API for the specified subreddit (without `/r/`).
+    pub fn apply(&self, client: &RedditClient, subreddit: &str) -> Result<(), APIError> {
+        let (path, body) = match *self {
+            ModeratorChange::Invite { ref name, ref permissions } => {
+                (endpoints::friend(subreddit),
+                 format!("type=moderator_invite&name={}&permissions={}",
+                         name,
+                         client.url_escape(ModPermission::to_param(permissions))))
+            }
+            ModeratorChange::SetPermissions { ref name, ref permissions } => {
+                (endpoints::set_permissions(subreddit),
+                 format!("type=moderator&name={}&permissions={}",
+                         name,
+                         client.url_escape(ModPermission::to_param(permissions))))
+            }
+            ModeratorChange::Remove { ref name } => {
+                (endpoints::unfriend(subreddit), format!("type=moderator&name={}", name))
+            }
+        };
+        client.post_success(&path, &body, false)
+    }
+}
+
+/// The result of `Subreddit.sync_moderators()`.
+#[derive(Debug, Default)]
+pub struct ModSyncReport {
+    /// The changes that were made successfully.
+    pub applied: Vec<ModeratorChange>,
+    /// The changes that failed, with the error for each. Other changes are still attempted after
+    /// a failure.
+    pub failed: Vec<(ModeratorChange, APIError)>,
+}
+
+/// Normalizes permissions for comparison, so `[All]` and `["all"]` compare equal and order does
+/// not matter.
+fn normalize<S: ToString>(permissions: &[S]) -> Vec<String> {
+    let mut names = permissions.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+    if names.iter().any(|name| name == "all") {
+        return vec![String::from("all")];
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Compares the current moderator list with the desired one and returns the changes needed to
+/// reach the desired state. Names are compared case-insensitively. The `protected` user (usually
+/// the logged-in account) is never changed or removed, so the bot cannot lock itself out.
+///
+/// Pending invites are not part of the moderator list, so users who have been invited but have
+/// not accepted yet are invited again.
+/// # Examples
+/// ```
+/// use new_rawr::options::ModPermission;
+/// use new_rawr::responses::ModeratorData;
+/// use new_rawr::structures::moderators::{plan_moderator_sync, ModeratorChange};
+/// let current = vec![ModeratorData { name: "bot".into(), mod_permissions: vec!["all".into()] },
+///                    ModeratorData { name: "alice".into(), mod_permissions: vec!["posts".into()] },
+///                    ModeratorData { name: "mallory".into(), mod_permissions: vec!["all".into()] }];
+/// let desired: Vec<(&str, &[ModPermission])> = vec![
+///     ("Alice", &[ModPermission::Posts, ModPermission::Wiki]),
+///     ("bob", &[ModPermission::Mail]),
+/// ];
+/// let changes = plan_moderator_sync(&current, &desired, Some("bot"));
+/// assert_eq!(changes, vec![
+///     ModeratorChange::SetPermissions {
+///         name: "alice".into(),
+///         permissions: vec![ModPermission::Posts, ModPermission::Wiki],
+///     },
+///     ModeratorChange::Invite { name: "bob".into(), permissions: vec![ModPermission::Mail] },
+///     ModeratorChange::Remove { name: "mallory".into() },
+/// ]);
+/// ```
+pub fn plan_moderator_sync(current: &[ModeratorData],
+                           desired: &[(&str, &[ModPermission])],
+                           protected: Option<&str>)
+                           -> Vec<ModeratorChange> {
+    let is_protected = |name: &str| protected.is_some_and(|p| p.eq_ignore_ascii_case(name));
+    let mut changes = Vec::new();
+    for &(name, permissions) in desired {
+        if is_protected(name) {
+            continue;
+        }
+        match current.iter().find(|m| m.name.eq_ignore_ascii_case(name)) {
+            Some(existing) => {
+                if normalize(&existing.mod_permissions) != normalize(permissions) {
+                    changes.push(ModeratorChange::SetPermissions {
+                        name: existing.name.to_owned(),
+                        permissions: permissions.to_vec(),
+                    });
+                }
+            }
+            None => {
+                changes.push(ModeratorChange::Invite {
+                    name: name.to_owned(),
+                    permissions: permissions.to_vec(),
+                });
+            }
+        }
+    }
+    for existing in current {
+        let wanted = desired.iter().any(|&(name, _)| existing.name.eq_ignore_ascii_case(name));
+        if !wanted && !is_protected(&existing.name) {
+            changes.push(ModeratorChange::Remove { name: existing.name.to_owned() });
+        }
+    }
+    changes
+}
@@ -0,0 +1,214 @@
+#[cfg(feature = "streams")]
+use std::collections::VecDeque;
+#[cfg(feature = "streams")]
+use std::time::Duration;
+#[cfg(feature = "streams")]
+use std::vec::IntoIter;
+
+#[cfg(any(feature = "streams", feature = "webhooks"))]
+use crate::client::RedditClient;
+#[cfg(feature = "streams")]
+use crate::client::scheduler::Priority;
+use crate::responses::modlog::ModActionData;
+#[cfg(feature = "streams")]
+use crate::responses::modlog::ModLogListing;
+#[cfg(feature = "streams")]
+use crate::structures::streams::pause;
+use crate::traits::Created;
+#[cfg(feature = "streams")]
+use crate::traits::PollingStream;
+#[cfg(feature = "webhooks")]
+use crate::errors::APIError;
+#[cfg(feature = "webhooks")]
+use crate::http::HttpRequest;
+
+/// An entry in a subreddit's moderation log, e.g. a removal or a ban.
+#[derive(Debug, Clone)]
+pub struct ModAction {
+    /// The raw data of the entry, which can be serialized to JSON.
+    pub data: ModActionData,
+}
+
+impl ModAction {
+    /// The ID of this entry.
+    pub fn id(&self) -> &str {
+        &self.data.id
+    }
+
+    /// The type of action, e.g. `removelink`, `banuser` or `approvecomment`.
+    pub fn action(&self) -> &str {
+        &self.data.action
+    }
+
+    /// The name of the moderator who performed the action.
+    pub fn moderator(&self) -> &str {
+        &self.data.moderator
+    }
+
+    /// The full name of the item that was acted upon, if any.
+    pub fn target(&self) -> Option<String> {
+        self.data.target_fullname.to_owned()
+    }
+}
+
+impl Created for ModAction {
+    fn created(&self) -> i64 {
+        self.data.created_utc as i64
+    }
+
+    fn created_utc(&self) -> i64 {
+        self.data.created_utc as i64
+    }
+}
+
+/// An infinite stream of new moderation log entries, from oldest to newest, which polls the
+/// log every 5 seconds. Each entry is only yielded once. You must be a moderator of the
+/// subreddit to use this.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::client::RedditClient;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// for action in client.subreddit("new_rawr").mod_log_stream() {
+///     println!("{} performed {}", action.moderator(), action.action());
+/// }
+/// ```
+#[cfg(feature = "streams")]
+pub struct ModLogStream<'a> {
+    client: &'a RedditClient,
+    set: VecDeque<String>,
+    current_iter: Option<IntoIter<ModAction>>,
+    url: String,
+}
+
+#[cfg(feature = "streams")]
+impl<'a> ModLogStream<'a> {
+    /// Internal method. Use `Subreddit.mod_log_stream()` instead.
+    pub fn new(client: &'a RedditClient, url: String) -> ModLogStream<'a> {
+        ModLogStream {
+            client,
+            set: VecDeque::new(),
+            current_iter: None,
+            url,
+        }
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<'a> PollingStream for ModLogStream<'a> {
+    fn poll(&mut self) -> Vec<ModAction> {
+        trace_span!("poll_stream", url = %self.url);
+        let req = self.client.with_priority(Priority::Background, || {
+            self.client.get_json(&self.url, false)
+        });
+        let req: ModLogListing = match req.map(|req| serde_json::from_str(&req)) {
+            Ok(Ok(req)) => req,
+            _ => return Vec::new(),
+        };
+        let mut fresh = Vec::new();
+        for child in req.data.children.into_iter().rev() {
+            if !self.set.contains(&child.data.id) {
+                self.set.push_back(child.data.id.to_owned());
+                if self.set.len() > 100 {
+                    self.set.pop_front();
+                }
+                fresh.push(ModAction { data: child.data });
+            }
+        }
+        fresh
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<'a> Iterator for ModLogStream<'a> {
+    type Item = ModAction;
+    fn next(&mut self) -> Option<ModAction> {
+        if let Some(iter) = self.current_iter.as_mut() {
+            if let Some(res) = iter.next() {
+                return Some(res);
+            }
+        }
+        pause(Duration::new(5, 0));
+        self.current_iter = Some(self.poll().into_iter());
+        self.next()
+    }
+}
+
+/// The JSON format used when delivering entries to a webhook.
+#[cfg(feature = "webhooks")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookFormat {
+    /// The raw mod log entry, serialized as JSON.
+    Raw,
+    /// A Slack incoming webhook message (`{"text": ...}`).
+    Slack,
+    /// A Discord webhook message (`{"content": ...}`).
+    Discord,
+}
+
+/// Delivers moderation log entries to an HTTP endpoint by sending a JSON `POST` request, e.g.
+/// to bridge moderation activity into a Discord or Slack channel. Requires the `webhooks`
+/// feature.
+/// # Examples
+/// ```rust,ignore
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::structures::modlog::{ModLogWebhook, WebhookFormat};
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let webhook = ModLogWebhook::new("https://discord.com/api/webhooks/...", WebhookFormat::Discord);
+/// for action in client.subreddit("new_rawr").mod_log_stream() {
+///     webhook.deliver(&client, &action).expect("Delivery failed");
+/// }
+/// ```
+#[cfg(feature = "webhooks")]
+pub struct ModLogWebhook {
+    /// The URL that entries are posted to.
+    pub url: String,
+    /// The format of the JSON body.
+    pub format: WebhookFormat,
+}
+
+#[cfg(feature = "webhooks")]
+impl ModLogWebhook {
+    /// Creates a webhook that posts to the specified URL.
+    pub fn new(url: &str, format: WebhookFormat) -> ModLogWebhook {
+        ModLogWebhook {
+            url: url.to_owned(),
+            format,
+        }
+    }
+
+    /// Formats the entry as the JSON body that would be sent to the webhook.
+    pub fn payload(&self, action: &ModAction) -> Result<String, APIError> {
+        let summary = format!("/u/{} performed {} in /r/{}{}",
+                              action.data.moderator,
+                              action.data.action,
+                              action.data.subreddit,
+                              action.data
+                                  .target_permalink
+                                  .as_ref()
+                                  .map(|link| format!(" (https://www.reddit.com{})", link))
+                                  .unwrap_or_default());
+        let payload = match self.format {
+            WebhookFormat::Raw => serde_json::to_string(&action.data)?,
+            WebhookFormat::Slack => serde_json::json!({ "text": summary }).to_string(),
+            WebhookFormat::Discord => serde_json::json!({ "content": summary }).to_string(),
+        };
+        Ok(payload)
+    }
+
+    /// Sends the entry to the webhook using the client's `HttpBackend`, bypassing the Reddit rate
+    /// limit, request hooks and read-only mode of the client, which only apply to requests to
+    /// Reddit. Fails with `APIError::HTTPError` if the endpoint does not return a success status.
+    pub fn deliver(&self, client: &RedditClient, action: &ModAction) -> Result<(), APIError> {
+        let request = HttpRequest::new(http::Method::POST, &self.url)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(&self.payload(action)?);
+        let response = client.backend.execute(request)?;
+        if response.status.is_success() {
+            Ok(())
+        } else {
+            Err(APIError::HTTPError(response.status))
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::Value;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::structures::subreddit::Subreddit;
+
+/// A set of subreddits managed by the same organization or moderator team, gathered from the
+/// community list widgets of a 'hub' subreddit, from multireddits, or added by name. Use it to
+/// run a moderation task (e.g. `Subreddit.sync_moderators()`) on every subreddit at once.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut network = client.network();
+/// network.add_community_lists("rust").expect("Could not fetch widgets");
+/// network.add_multireddit("new_rawr", "network").expect("Could not fetch multireddit");
+/// for (name, result) in network.each(|sub| sub.clear_user_flair("spammer")) {
+///     if let Err(err) = result {
+///         println!("Could not clear the flair in /r/{}: {:?}", name, err);
+///     }
+/// }
+/// ```
+pub struct Network<'a> {
+    client: &'a RedditClient,
+    names: Vec<String>,
+}
+
+impl<'a> Network<'a> {
+    /// Internal method. Use `RedditClient.network()` instead.
+    pub fn new(client: &'a RedditClient) -> Network<'a> {
+        Network {
+            client,
+            names: Vec::new(),
+        }
+    }
+
+    /// Adds a subreddit by name (without `/r/`). Subreddits are only listed once, ignoring case.
+    pub fn add_subreddit(&mut self, name: &str) {
+        let name = name.trim_start_matches("/r/").trim_start_matches("r/");
+        if !name.is_empty() && !self.contains(name) {
+            self.names.push(name.to_owned());
+        }
+    }
+
+    /// Adds the subreddits listed in the community list widgets of the subreddit (the
+    /// 'Related communities' boxes in its sidebar), but not the subreddit itself.
+    pub fn add_community_lists(&mut self, subreddit: &str) -> Result<(), APIError> {
+        let url = format!("{}?raw_json=1", endpoints::widgets(subreddit));
+        let string = self.client.get_json(&url, false)?;
+        for name in parse_community_lists(&serde_json::from_str(&string)?) {
+            self.add_subreddit(&name);
+        }
+        Ok(())
+    }
+
+    /// Adds the subreddits in a user's multireddit.
+    pub fn add_multireddit(&mut self, user: &str, name: &str) -> Result<(), APIError> {
+        let url = format!("{}?raw_json=1", endpoints::multireddit(user, name));
+        let string = self.client.get_json(&url, false)?;
+        for name in parse_multireddit(&serde_json::from_str(&string)?) {
+            self.add_subreddit(&name);
+        }
+        Ok(())
+    }
+
+    /// `true` if the subreddit is part of the network (ignoring case).
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.iter().any(|existing| existing.eq_ignore_ascii_case(name))
+    }
+
+    /// The names of the subreddits in the network, in the order they were added.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The subreddits in the network, in the order they were added.
+    pub fn subreddits(&self) -> Vec<Subreddit<'a>> {
+        self.names.iter().map(|name| self.client.subreddit(name)).collect()
+    }
+
+    /// Runs the action on every subreddit in the network, and returns each subreddit's name with
+    /// the result. Unlike a loop with `?`, a failure in one subreddit (e.g. because the account
+    /// is not a moderator there) does not stop the action in the others.
+    pub fn each<T, F>(&self, mut action: F) -> Vec<(String, Result<T, APIError>)>
+        where F: FnMut(&Subreddit<'a>) -> Result<T, APIError>
+    {
+        self.subreddits()
+            .into_iter()
+            .zip(self.names.iter())
+            .map(|(subreddit, name)| (name.to_owned(), action(&subreddit)))
+            .collect()
+    }
+}
+
+/// Gets the names of the subreddits in every community list widget in a response from
+/// `/r/subreddit/api/widgets`.
+/// # Examples
+/// ```
+/// use new_rawr::structures::network::parse_community_lists;
+/// let json = serde_json::json!({
+///     "items": {
+///         "widget_1": {"kind": "community-list", "data": [{"name": "rust"}, {"name": "r/cpp"}]},
+///         "widget_2": {"kind": "textarea", "text": "Welcome!"}
+///     }
+/// });
+/// let mut names = parse_community_lists(&json);
+/// names.sort();
+/// assert_eq!(names, vec!["cpp", "rust"]);
+/// ```
+pub fn parse_community_lists(widgets: &Value) -> Vec<String> {
+    let items = match widgets["items"].as_object() {
+        Some(items) => items,
+        None => return Vec::new(),
+    };
+    items.values()
+        .filter(|widget| widget["kind"] == "community-list")
+        .filter_map(|widget| widget["data"].as_array())
+        .flat_map(|communities| communities.iter())
+        .filter_map(|community| community["name"].as_str())
+        .map(|name| name.trim_start_matches("r/").to_owned())
+        .collect()
+}
+
+/// Gets the names of the subreddits in a response from `/api/multi/user/USER/m/NAME`.
+/// # Examples
+/// ```
+/// use new_rawr::structures::network::parse_multireddit;
+/// let json = serde_json::json!({
+///     "kind": "LabeledMulti",
+///     "data": {"name": "network", "subreddits": [{"name": "rust"}, {"name": "learnrust"}]}
+/// });
+/// assert_eq!(parse_multireddit(&json), vec!["rust", "learnrust"]);
+/// ```
+pub fn parse_multireddit(multi: &Value) -> Vec<String> {
+    multi["data"]["subreddits"]
+        .as_array()
+        .map(|subreddits| {
+            subreddits.iter()
+                .filter_map(|subreddit| subreddit["name"].as_str())
+                .map(|name| name.to_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,53 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// A link to a submission or comment on Reddit. Use `to_string()` to get the URL.
+/// # Examples
+/// ```
+/// use new_rawr::structures::permalink::Permalink;
+/// let link = Permalink::new("/r/rust/comments/4uule8/_/d5tai4u/");
+/// assert_eq!(link.to_string(), "https://www.reddit.com/r/rust/comments/4uule8/_/d5tai4u/");
+/// let link = link.context(3).no_participation();
+/// assert_eq!(link.to_string(),
+///            "https://np.reddit.com/r/rust/comments/4uule8/_/d5tai4u/?context=3");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permalink {
+    path: String,
+    context: Option<u32>,
+    no_participation: bool,
+}
+
+impl Permalink {
+    /// Creates a link from a path relative to reddit.com, as returned by the API.
+    pub fn new(path: &str) -> Permalink {
+        Permalink {
+            path: path.to_owned(),
+            context: None,
+            no_participation: false,
+        }
+    }
+
+    /// Shows the specified number of parent comments above a linked comment.
+    pub fn context(mut self, parents: u32) -> Permalink {
+        self.context = Some(parents);
+        self
+    }
+
+    /// Links to `np.reddit.com` ('no participation'), which many subreddits require when bots
+    /// link to discussions elsewhere, to discourage brigading.
+    pub fn no_participation(mut self) -> Permalink {
+        self.no_participation = true;
+        self
+    }
+}
+
+impl Display for Permalink {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let host = if self.no_participation { "np" } else { "www" };
+        write!(f, "https://{}.reddit.com{}", host, self.path)?;
+        if let Some(context) = self.context {
+            write!(f, "?context={}", context)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde_json;
+
+use crate::client::RedditClient;
+use crate::clock;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::options::{LinkPost, SelfPost};
+use crate::responses::PostRequirements;
+use crate::responses::listing;
+use crate::structures::user::UserAbout;
+use crate::traits::Created;
+
+/// A reason that a post is likely to be rejected or removed, found by `PostChecker`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostWarning {
+    /// The subreddit requires post flair, but none was chosen.
+    FlairRequired,
+    /// The title is shorter than the minimum length.
+    TitleTooShort(u64),
+    /// The title is longer than the maximum length.
+    TitleTooLong(u64),
+    /// The title must contain one of these strings.
+    TitleMissingString(Vec<String>),
+    /// The title contains a banned string.
+    TitleBlacklisted(String),
+    /// The subreddit requires text in the body of text posts.
+    BodyRequired,
+    /// The subreddit does not allow text in the body of text posts.
+    BodyNotAllowed,
+    /// The body is shorter than the minimum length.
+    BodyTooShort(u64),
+    /// The body is longer than the maximum length.
+    BodyTooLong(u64),
+    /// The body must contain one of these strings.
+    BodyMissingString(Vec<String>),
+    /// The body contains a banned string.
+    BodyBlacklisted(String),
+    /// Links to this domain are not allowed.
+    DomainBlacklisted(String),
+    /// Only links to certain domains are allowed, and this is not one of them.
+    DomainNotWhitelisted(String),
+    /// The account has less karma (link and comment karma combined) than the minimum set with
+    /// `PostChecker::with_min_karma()`.
+    LowKarma {
+        /// The karma of the account.
+        karma: i64,
+        /// The minimum karma.
+        required: i64,
+    },
+    /// The account is younger than the minimum set with `PostChecker::with_min_account_age()`.
+    NewAccount {
+        /// The age of the account.
+        age: Duration,
+        /// The minimum age.
+        required: Duration,
+    },
+    /// Some of the account's recent posts in the subreddit were removed by moderators,
+    /// AutoModerator or Reddit, so this one may be too.
+    RecentRemovals {
+        /// The number of recent posts in the subreddit that were removed.
+        removed: usize,
+        /// The number of recent posts in the subreddit that were checked.
+        checked: usize,
+    },
+}
+
+/// Checks a post against the posting rules that a subreddit declares in its post requirements
+/// (e.g. title length or banned domains). `body` is `None` for link posts, and `link` is `None`
+/// for text posts. Use `PostChecker` to fetch the requirements and check the account as well.
+/// # Examples
+/// ```
+/// use new_rawr::responses::PostRequirements;
+/// use new_rawr::structures::preflight::{check_requirements, PostWarning};
+/// let requirements = PostRequirements {
+///     title_text_min_length: Some(10),
+///     domain_blacklist: vec![String::from("example.com")],
+///     ..PostRequirements::default()
+/// };
+/// let warnings = check_requirements(&requirements, "Short", None,
+///                                   Some("https://www.example.com/page"), false);
+/// assert_eq!(warnings, vec![PostWarning::TitleTooShort(10),
+///                           PostWarning::DomainBlacklisted(String::from("example.com"))]);
+/// ```
+pub fn check_requirements(requirements: &PostRequirements,
+                          title: &str,
+                          body: Option<&str>,
+                          link: Option<&str>,
+                          has_flair: bool)
+                          -> Vec<PostWarning> {
+    let mut warnings = Vec::new();
+    if requirements.is_flair_required && !has_flair {
+        warnings.push(PostWarning::FlairRequired);
+    }
+    let length = title.chars().count() as u64;
+    if let Some(min) = requirements.title_text_min_length.filter(|&min| length < min) {
+        warnings.push(PostWarning::TitleTooShort(min));
+    }
+    if let Some(max) = requirements.title_text_max_length.filter(|&max| length > max) {
+        warnings.push(PostWarning::TitleTooLong(max));
+    }
+    if !contains_any(title, &requirements.title_required_strings) {
+        warnings.push(PostWarning::TitleMissingString(requirements.title_required_strings.clone()));
+    }
+    if let Some(banned) = find_any(title, &requirements.title_blacklisted_strings) {
+        warnings.push(PostWarning::TitleBlacklisted(banned));
+    }
+    if let Some(body) = body {
+        let length = body.trim().chars().count() as u64;
+        match requirements.body_restriction_policy.as_deref() {
+            Some("required") if length == 0 => warnings.push(PostWarning::BodyRequired),
+            Some("notAllowed") if length > 0 => warnings.push(PostWarning::BodyNotAllowed),
+            _ => {}
+        }
+        if let Some(min) = requirements.body_text_min_length.filter(|&min| length < min) {
+            warnings.push(PostWarning::BodyTooShort(min));
+        }
+        if let Some(max) = requirements.body_text_max_length.filter(|&max| length > max) {
+            warnings.push(PostWarning::BodyTooLong(max));
+        }
+        if !contains_any(body, &requirements.body_required_strings) {
+            warnings.push(PostWarning::BodyMissingString(requirements.body_required_strings
+                .clone()));
+        }
+        if let Some(banned) = find_any(body, &requirements.body_blacklisted_strings) {
+            warnings.push(PostWarning::BodyBlacklisted(banned));
+        }
+    }
+    if let Some(link) = link {
+        let domain = domain_of(link);
+        let matches = |list: &[String]| {
+            list.iter().any(|allowed| {
+                let allowed = allowed.to_lowercase();
+                domain == allowed || domain.ends_with(&format!(".{}", allowed))
+            })
+        };
+        match requirements.link_restriction_policy.as_deref() {
+            Some("whitelist") if !matches(&requirements.domain_whitelist) => {
+                warnings.push(PostWarning::DomainNotWhitelisted(domain.to_owned()))
+            }
+            _ if matches(&requirements.domain_blacklist) => {
+                warnings.push(PostWarning::DomainBlacklisted(domain.to_owned()))
+            }
+            _ => {}
+        }
+    }
+    warnings
+}
+
+/// `true` if the text contains one of the strings (case-insensitively), or there are none.
+fn contains_any(text: &str, strings: &[String]) -> bool {
+    strings.is_empty() || find_any(text, strings).is_some()
+}
+
+fn find_any(text: &str, strings: &[String]) -> Option<String> {
+    let text = text.to_lowercase();
+    strings.iter().find(|string| text.contains(&string.to_lowercase())).cloned()
+}
+
+/// The domain of a URL, without any `www.` prefix.
+fn domain_of(url: &str) -> String {
+    let rest = url.splitn(2, "://").last().unwrap_or(url);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit('@').next().unwrap_or(host).split(':').next().unwrap_or(host);
+    host.trim_start_matches("www.").to_lowercase()
+}
+
+/// Warns about posts that are likely to be rejected or removed before they are submitted. Each
+/// check combines the subreddit's declared post requirements (cached for an hour by default),
+/// the minimum karma and account age that you know the subreddit's AutoModerator enforces
+/// (Reddit does not publish these), and whether the account's recent posts in the subreddit
+/// were removed. Get one with `RedditClient.post_checker()`.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::options::SelfPost;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut checker = client.post_checker()
+///     .with_min_karma(50)
+///     .with_min_account_age(Duration::from_secs(7 * 24 * 60 * 60));
+/// let post = SelfPost::new("Weekly discussion", "What are you working on?");
+/// let warnings = checker.check_text("rust", &post).expect("Could not check post");
+/// if warnings.is_empty() {
+///     client.subreddit("rust").submit_text(post).expect("Could not submit");
+/// } else {
+///     println!("Not posting: {:?}", warnings);
+/// }
+/// ```
+pub struct PostChecker<'a> {
+    client: &'a RedditClient,
+    requirements: HashMap<String, (Option<Instant>, PostRequirements)>,
+    ttl: Duration,
+    min_karma: Option<i64>,
+    min_account_age: Option<Duration>,
+}
+
+impl<'a> PostChecker<'a> {
+    /// Internal method. Use `RedditClient.post_checker()` instead.
+    pub fn new(client: &'a RedditClient) -> PostChecker<'a> {
+        PostChecker {
+            client,
+            requirements: HashMap::new(),
+            ttl: Duration::from_secs(60 * 60),
+            min_karma: None,
+            min_account_age: None,
+        }
+    }
+
+    /// Sets how long each subreddit's post requirements are cached. The default is 1 hour.
+    pub fn with_ttl(mut self, ttl: Duration) -> PostChecker<'a> {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Warns if the account has less karma (link and comment karma combined) than this.
+    pub fn with_min_karma(mut self, karma: i64) -> PostChecker<'a> {
+        self.min_karma = Some(karma);
+        self
+    }
+
+    /// Warns if the account is younger than this.
+    pub fn with_min_account_age(mut self, age: Duration) -> PostChecker<'a> {
+        self.min_account_age = Some(age);
+        self
+    }
+
+    /// The post requirements of a subreddit, fetched if they are not cached or the cached copy
+    /// has expired. This requires an OAuth-based authenticator.
+    pub fn requirements(&mut self, subreddit: &str) -> Result<PostRequirements, APIError> {
+        let key = subreddit.to_lowercase();
+        if let Some(&(fetched_at, ref requirements)) = self.requirements.get(&key) {
+            if matches!(fetched_at.and_then(clock::elapsed), Some(age) if age < self.ttl) {
+                return Ok(requirements.clone());
+            }
+        }
+        let url = endpoints::post_requirements(subreddit);
+        let string = self.client.get_json(&url, true)?;
+        let requirements: PostRequirements = serde_json::from_str(&string)?;
+        self.requirements.insert(key, (clock::now(), requirements.clone()));
+        Ok(requirements)
+    }
+
+    /// Checks a text post that is about to be submitted to the subreddit.
+    pub fn check_text(&mut self, subreddit: &str, post: &SelfPost)
+                      -> Result<Vec<PostWarning>, APIError> {
+        let requirements = self.requirements(subreddit)?;
+        let mut warnings = check_requirements(&requirements,
+                                              &post.title,
+                                              Some(&post.text),
+                                              None,
+                                              post.flair_id.is_some());
+        warnings.extend(self.check_account(subreddit)?);
+        Ok(warnings)
+    }
+
+    /// Checks a link post that is about to be submitted to the subreddit.
+    pub fn check_link(&mut self, subreddit: &str, post: &LinkPost)
+                      -> Result<Vec<PostWarning>, APIError> {
+        let requirements = self.requirements(subreddit)?;
+        let mut warnings = check_requirements(&requirements,
+                                              &post.title,
+                                              None,
+                                              Some(&post.link),
+                                              post.flair_id.is_some());
+        warnings.extend(self.check_account(subreddit)?);
+        Ok(warnings)
+    }
+
+    /// Checks the karma, age and recent removals of the logged-in account. Does nothing for
+    /// clients that are not logged in as a user.
+    fn check_account(&self, subreddit: &str) -> Result<Vec<PostWarning>, APIError> {
+        let mut warnings = Vec::new();
+        let username = match self.client.get_authenticator().username() {
+            Some(username) => username,
+            None => return Ok(warnings),
+        };
+        if self.min_karma.is_some() || self.min_account_age.is_some() {
+            let about = UserAbout::new(self.client, username.to_owned())?;
+            let karma = about.link_karma() + about.comment_karma();
+            if let Some(required) = self.min_karma.filter(|&required| karma < required) {
+                warnings.push(PostWarning::LowKarma {
+                    karma,
+                    required,
+                });
+            }
+            let age = about.age();
+            if let Some(required) = self.min_account_age.filter(|&required| age < required) {
+                warnings.push(PostWarning::NewAccount {
+                    age,
+                    required,
+                });
+            }
+        }
+        let url = format!("{}?sort=new&limit=100", endpoints::user_submitted(&username));
+        let string = self.client.get_json(&url, false)?;
+        let page: listing::Listing = serde_json::from_str(&string)?;
+        let posts = page.data
+            .children
+            .into_iter()
+            .filter(|child| child.data.subreddit.eq_ignore_ascii_case(subreddit))
+            .collect::<Vec<_>>();
+        let removed = posts.iter()
+            .filter(|child| {
+                match child.data.removed_by_category.as_deref() {
+                    None | Some("deleted") => false,
+                    Some(_) => true,
+                }
+            })
+            .count();
+        if removed > 0 {
+            warnings.push(PostWarning::RecentRemovals {
+                removed,
+                checked: posts.len(),
+            });
+        }
+        Ok(warnings)
+    }
+}
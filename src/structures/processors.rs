@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use crate::traits::{Editable, ItemProcessor};
+
+/// A processor that does nothing. This is the default processor used by streams.
+pub struct NoopProcessor;
+
+impl<T> ItemProcessor<T> for NoopProcessor {
+    fn process(&mut self, _item: &T) {}
+}
+
+/// An example processor that counts the words in the body of every item it sees. Items without
+/// a body (e.g. link posts) are counted as having no words.
+///
+/// Streams take ownership of their processor, so wrap it in an `Arc<Mutex<_>>` if you need to
+/// read the results while the stream is running.
+/// # Examples
+/// ```rust,no_run
+/// # #[cfg(feature = "streams")] {
+/// use std::sync::{Arc, Mutex};
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::structures::processors::WordCount;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let counter = Arc::new(Mutex::new(WordCount::new()));
+/// let stream = client.subreddit("rust").new_stream().with_processor(counter.clone());
+/// for _ in stream.take(10) {
+///     println!("Average words: {}", counter.lock().unwrap().average());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct WordCount {
+    /// The number of items processed so far.
+    pub items: u64,
+    /// The total number of words in all processed items.
+    pub words: u64,
+    /// The number of words in the most recently processed item.
+    pub last: u64,
+}
+
+impl WordCount {
+    /// Creates a new `WordCount` with all counts set to zero.
+    pub fn new() -> WordCount {
+        WordCount::default()
+    }
+
+    /// The average number of words per item, or `0.0` if no items have been processed.
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::processors::WordCount;
+    /// let count = WordCount { items: 4, words: 10, last: 2 };
+    /// assert_eq!(count.average(), 2.5);
+    /// ```
+    pub fn average(&self) -> f64 {
+        if self.items == 0 {
+            0.0
+        } else {
+            self.words as f64 / self.items as f64
+        }
+    }
+}
+
+impl<T: Editable> ItemProcessor<T> for WordCount {
+    fn process(&mut self, item: &T) {
+        let words = item.body().map(|body| body.split_whitespace().count() as u64).unwrap_or(0);
+        self.items += 1;
+        self.words += words;
+        self.last = words;
+    }
+}
+
+impl<T, P: ItemProcessor<T>> ItemProcessor<T> for Arc<Mutex<P>> {
+    fn process(&mut self, item: &T) {
+        self.lock().unwrap().process(item);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde_json;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::listing;
+use crate::structures::streams::run_every;
+
+/// A change in the position of a submission in a subreddit's hot listing, as reported by
+/// `RankTracker`. Ranks start at 1 for the top post, and do not count stickied posts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RankEvent {
+    /// The submission (identified by its full name, e.g. `t3_4uule8`) entered the top N.
+    Entered {
+        /// The full name of the submission.
+        name: String,
+        /// The rank it entered at.
+        rank: usize,
+    },
+    /// The submission moved to a different rank within the top N.
+    Moved {
+        /// The full name of the submission.
+        name: String,
+        /// The previous rank.
+        from: usize,
+        /// The new rank.
+        to: usize,
+    },
+    /// The submission dropped out of the top N (or was removed).
+    Left {
+        /// The full name of the submission.
+        name: String,
+        /// The last rank it was seen at.
+        rank: usize,
+    },
+}
+
+/// Samples the top N posts of a subreddit's hot listing and reports when submissions enter,
+/// leave or move within it. Useful for monitoring how promoted posts perform. By default,
+/// every submission is tracked; use `with_tracked()` to only report on specific submissions.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// use new_rawr::structures::ranking::{RankEvent, RankTracker};
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let mut tracker = RankTracker::new(&client, "rust", 10).with_tracked(&["t3_4uule8"]);
+/// tracker.run(Duration::from_secs(300), |result| {
+///     for event in result.unwrap_or_default() {
+///         if let RankEvent::Entered { name, rank } = event {
+///             println!("{} is now #{} on /r/rust", name, rank);
+///         }
+///     }
+///     true
+/// });
+/// ```
+pub struct RankTracker<'a> {
+    client: &'a RedditClient,
+    subreddit: String,
+    top: usize,
+    tracked: Option<HashSet<String>>,
+    ranks: HashMap<String, usize>,
+}
+
+impl<'a> RankTracker<'a> {
+    /// Creates a tracker for the top `top` posts (at most 100) of the subreddit's hot listing.
+    pub fn new(client: &'a RedditClient, subreddit: &str, top: usize) -> RankTracker<'a> {
+        RankTracker {
+            client,
+            subreddit: subreddit.to_owned(),
+            top: top.min(100),
+            tracked: None,
+            ranks: HashMap::new(),
+        }
+    }
+
+    /// Only reports events for the submissions with these full names (e.g. `t3_4uule8`).
+    pub fn with_tracked(mut self, names: &[&str]) -> RankTracker<'a> {
+        self.tracked = Some(names.iter().map(|name| (*name).to_owned()).collect());
+        self
+    }
+
+    /// The rank of a submission in the last sample, or `None` if it was not in the top N.
+    pub fn rank(&self, name: &str) -> Option<usize> {
+        self.ranks.get(name).cloned()
+    }
+
+    /// Fetches the hot listing once, and returns what changed since the previous sample. The
+    /// first sample reports every tracked submission in the top N as `Entered`.
+    pub fn sample(&mut self) -> Result<Vec<RankEvent>, APIError> {
+        // Stickied posts are not ranked, so ask for a few extra to make up for them.
+        let url = format!("{}?limit={}",
+                          endpoints::subreddit_listing(&self.subreddit, "hot"),
+                          (self.top + 2).min(100));
+        let string = self.client.get_json(&url, false)?;
+        let page: listing::Listing = serde_json::from_str(&string)?;
+        let names = page.data
+            .children
+            .into_iter()
+            .filter(|child| !child.data.stickied)
+            .map(|child| child.data.name)
+            .collect::<Vec<_>>();
+        Ok(self.update(&names))
+    }
+
+    /// Records a new ranking (full names, highest first) and returns what changed. Only the
+    /// first N names are counted. `sample()` calls this with the hot listing, but it can be
+    /// used with rankings fetched in other ways.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::structures::ranking::{RankEvent, RankTracker};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let mut tracker = RankTracker::new(&client, "rust", 2);
+    /// let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    /// tracker.update(&names(&["t3_a", "t3_b", "t3_c"]));
+    /// let events = tracker.update(&names(&["t3_b", "t3_c"]));
+    /// assert_eq!(events, vec![
+    ///     RankEvent::Moved { name: String::from("t3_b"), from: 2, to: 1 },
+    ///     RankEvent::Entered { name: String::from("t3_c"), rank: 2 },
+    ///     RankEvent::Left { name: String::from("t3_a"), rank: 1 },
+    /// ]);
+    /// ```
+    pub fn update(&mut self, names: &[String]) -> Vec<RankEvent> {
+        let ranks = names.iter()
+            .take(self.top)
+            .enumerate()
+            .map(|(index, name)| (name.to_owned(), index + 1))
+            .collect::<HashMap<_, _>>();
+        let mut events = Vec::new();
+        for (index, name) in names.iter().take(self.top).enumerate() {
+            if !self.is_tracked(name) {
+                continue;
+            }
+            match self.ranks.get(name) {
+                None => {
+                    events.push(RankEvent::Entered {
+                        name: name.to_owned(),
+                        rank: index + 1,
+                    })
+                }
+                Some(&from) if from != index + 1 => {
+                    events.push(RankEvent::Moved {
+                        name: name.to_owned(),
+                        from,
+                        to: index + 1,
+                    })
+                }
+                _ => {}
+            }
+        }
+        let mut left = self.ranks
+            .iter()
+            .filter(|&(name, _)| !ranks.contains_key(name) && self.is_tracked(name))
+            .map(|(name, &rank)| (rank, name.to_owned()))
+            .collect::<Vec<_>>();
+        left.sort();
+        events.extend(left.into_iter().map(|(rank, name)| {
+            RankEvent::Left {
+                name,
+                rank,
+            }
+        }));
+        self.ranks = ranks;
+        events
+    }
+
+    /// Calls `sample()` every `interval`, passing each result to `on_sample`. Stops when
+    /// `on_sample` returns `false`.
+    pub fn run<F>(&mut self, interval: Duration, on_sample: F)
+        where F: FnMut(Result<Vec<RankEvent>, APIError>) -> bool
+    {
+        run_every(interval, || self.sample(), on_sample);
+    }
+
+    fn is_tracked(&self, name: &str) -> bool {
+        self.tracked.as_ref().is_none_or(|tracked| tracked.contains(name))
+    }
+}
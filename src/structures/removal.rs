@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::markdown::ReplyTemplate;
+use crate::options::RemovalMessageType;
+
+/// A removal message that explains to an author why their submission or comment was removed.
+/// The message is a `ReplyTemplate`, so it can refer to `{author}`, `{title}`, `{permalink}`
+/// and `{subreddit}`. Send it with `Submission.send_removal_message()` or
+/// `Comment.send_removal_message()` after removing the item. This requires an OAuth-based
+/// authenticator and moderator permissions.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::options::RemovalMessageType;
+/// use new_rawr::structures::removal::RemovalMessage;
+/// use new_rawr::traits::Approvable;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let rule_1 = RemovalMessage::new("Hi {author}, your post '{title}' was removed because \
+///                                   it is off-topic for /r/{subreddit}.",
+///                                  RemovalMessageType::Private)
+///     .title("Your post was removed");
+/// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+/// post.remove(false).expect("Could not remove post.");
+/// post.send_removal_message(&rule_1).expect("Could not send removal message.");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovalMessage {
+    template: ReplyTemplate,
+    title: String,
+    kind: RemovalMessageType,
+}
+
+impl RemovalMessage {
+    /// Creates a removal message from the template text, delivered in the specified way.
+    pub fn new(template: &str, kind: RemovalMessageType) -> RemovalMessage {
+        RemovalMessage {
+            template: ReplyTemplate::new(template),
+            title: String::from("Your submission was removed"),
+            kind,
+        }
+    }
+
+    /// Sets the subject of private removal messages. This is ignored for public messages.
+    pub fn title(mut self, title: &str) -> RemovalMessage {
+        self.title = title.to_owned();
+        self
+    }
+
+    /// Internal method. Use `Submission.send_removal_message()` or
+    /// `Comment.send_removal_message()` instead.
+    pub fn send(&self,
+                client: &RedditClient,
+                item_id: &str,
+                vars: &HashMap<String, String>)
+                -> Result<(), APIError> {
+        let endpoint = if item_id.starts_with("t1_") {
+            endpoints::REMOVAL_COMMENT_MESSAGE
+        } else {
+            endpoints::REMOVAL_LINK_MESSAGE
+        };
+        let body = json!({
+            "item_id": [item_id],
+            "message": self.template.render(vars),
+            "title": self.title,
+            "type": self.kind.to_string(),
+        });
+        client.post_json_body(endpoint, &body, true)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::comment::NewComment;
+use crate::structures::comment::Comment;
+use crate::structures::streams::pause;
+
+/// A queue of replies waiting to be sent. Bots usually find many items to reply to in a burst
+/// (e.g. when a stream polls), so queueing the replies and sending them with `flush()` spaces
+/// them out instead of tripping Reddit's rate limits.
+/// # Examples
+/// ```rust,no_run
+/// # #[cfg(feature = "streams")] {
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::markdown::ReplyTemplate;
+/// use new_rawr::structures::reply_queue::ReplyQueue;
+/// use new_rawr::traits::Commentable;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let template = ReplyTemplate::new("Thanks for posting, {author}!");
+/// let mut queue = ReplyQueue::new(&client);
+/// let sub = client.subreddit("new_rawr");
+/// for post in sub.new_stream() {
+///     post.reply_with_template(&template, &mut queue);
+///     for result in queue.flush() {
+///         if let Err(err) = result {
+///             println!("Could not reply: {:?}", err);
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub struct ReplyQueue<'a> {
+    client: &'a RedditClient,
+    pending: VecDeque<(String, String)>,
+    delay: Duration,
+}
+
+impl<'a> ReplyQueue<'a> {
+    /// Creates an empty queue which waits 2 seconds between replies.
+    pub fn new(client: &'a RedditClient) -> ReplyQueue<'a> {
+        ReplyQueue {
+            client,
+            pending: VecDeque::new(),
+            delay: Duration::new(2, 0),
+        }
+    }
+
+    /// Sets the time to wait between sending replies.
+    pub fn with_delay(mut self, delay: Duration) -> ReplyQueue<'a> {
+        self.delay = delay;
+        self
+    }
+
+    /// Queues a reply with the specified body to the item with the specified full name (e.g.
+    /// `t3_4uule8`).
+    pub fn push(&mut self, thing_id: &str, text: &str) {
+        self.pending.push_back((thing_id.to_owned(), text.to_owned()));
+    }
+
+    /// The number of replies waiting to be sent.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// `true` if there are no replies waiting to be sent.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Sends the oldest queued reply, returning `None` if the queue is empty.
+    pub fn send_next(&mut self) -> Option<Result<Comment<'a>, APIError>> {
+        self.pending.pop_front().map(|(thing_id, text)| self.send(&thing_id, &text))
+    }
+
+    /// Sends every queued reply in order, waiting between each one. The results are returned in
+    /// the same order; a failed reply does not stop the others being sent.
+    pub fn flush(&mut self) -> Vec<Result<Comment<'a>, APIError>> {
+        let mut results = Vec::new();
+        while let Some(result) = self.send_next() {
+            results.push(result);
+            if !self.pending.is_empty() {
+                pause(self.delay);
+            }
+        }
+        results
+    }
+
+    fn send(&self, thing_id: &str, text: &str) -> Result<Comment<'a>, APIError> {
+        let body = format!("api_type=json&text={}&thing_id={}",
+                           self.client.url_escape(text.to_owned()),
+                           thing_id);
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        match result.json.data.things.into_iter().next() {
+            Some(thing) => Ok(Comment::new(self.client, thing.data)),
+            None => Err(APIError::ExhaustedListing),
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use crate::client::RedditClient;
+use crate::clock;
+use crate::errors::APIError;
+use crate::options::SelfPost;
+use crate::structures::streams::run_every;
+use crate::structures::wiki::WikiPage;
+
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP",
+                                 "OCT", "NOV", "DEC"];
+
+/// The source of the current time for a `ScheduledPoster`. Any `Fn() -> i64` returning a UNIX
+/// timestamp in seconds is a clock, so tests can use a fixed time.
+pub trait Clock {
+    /// The current time, as a UNIX timestamp in seconds.
+    fn now(&self) -> i64;
+}
+
+/// A `Clock` that reads the system time. It returns 0 where the clock cannot be read (e.g. on
+/// wasm32), so supply your own `Clock` there.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        clock::unix_time().unwrap_or_default().as_secs() as i64
+    }
+}
+
+impl<F> Clock for F
+    where F: Fn() -> i64
+{
+    fn now(&self) -> i64 {
+        self()
+    }
+}
+
+/// Converts a UNIX timestamp to the UTC (year, month, day, hour, minute, weekday), with Sunday
+/// as weekday 0.
+fn civil_time(time: i64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = time.div_euclid(86400);
+    let seconds = time.rem_euclid(86400);
+    let weekday = (days + 4).rem_euclid(7) as u32;
+    // Howard Hinnant's days-to-civil algorithm.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, (seconds / 3600) as u32, (seconds % 3600 / 60) as u32, weekday)
+}
+
+/// Parses one cron field into a bit mask of the allowed values.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let upper = text.to_uppercase();
+        if let Some(pos) = names.iter().position(|name| *name == upper) {
+            return Ok(pos as u32 + min);
+        }
+        text.parse::<u32>()
+            .ok()
+            .filter(|number| *number >= min && *number <= max)
+            .ok_or_else(|| format!("'{}' is not between {} and {}", text, min, max))
+    };
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(pos) => {
+                let step = part[pos + 1..]
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("'{}' has an invalid step", part))?;
+                (&part[..pos], step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some(pos) = range.find('-') {
+            (value(&range[..pos])?, value(&range[pos + 1..])?)
+        } else {
+            let start = value(range)?;
+            (start, if step > 1 { max } else { start })
+        };
+        for number in (start..=end).step_by(step as usize) {
+            mask |= 1 << number;
+        }
+    }
+    Ok(mask)
+}
+
+/// When a scheduled post is made, as a cron expression with five fields: minute, hour, day of
+/// month, month and day of week (e.g. `0 9 * * MON` for 9:00 UTC every Monday). Fields accept
+/// `*`, numbers, names (`MON`, `JAN`), lists (`1,15`), ranges (`MON-FRI`) and steps (`*/15`).
+/// As in cron, if both the day of month and day of week are restricted, either may match.
+/// Times are in UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// Parses a cron expression. Returns a `ValidationError` describing the first invalid field.
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::scheduled::Schedule;
+    /// let schedule = Schedule::parse("30 9 * * MON").unwrap();
+    /// assert!(schedule.matches(1500888600)); // Monday 24 July 2017, 09:30 UTC
+    /// assert!(!schedule.matches(1500975000)); // Tuesday 25 July 2017, 09:30 UTC
+    /// assert!(Schedule::parse("60 * * * *").is_err());
+    /// ```
+    pub fn parse(expression: &str) -> Result<Schedule, APIError> {
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return Err(APIError::ValidationError(format!("'{}' should have 5 fields", expression)));
+        }
+        let parse = |index: usize, min, max, names: &[&str]| {
+            parse_field(fields[index], min, max, names)
+                .map_err(|err| APIError::ValidationError(format!("In '{}': {}", expression, err)))
+        };
+        Ok(Schedule {
+            minutes: parse(0, 0, 59, &[])?,
+            hours: parse(1, 0, 23, &[])?,
+            days: parse(2, 1, 31, &[])?,
+            months: parse(3, 1, 12, &MONTH_NAMES)?,
+            // Both 0 and 7 mean Sunday, as in most cron implementations.
+            weekdays: parse(4, 0, 7, &WEEKDAY_NAMES).map(|mask| mask | (mask >> 7) & 1)?,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    /// `true` if the schedule includes the minute containing this UNIX timestamp.
+    pub fn matches(&self, time: i64) -> bool {
+        let (_, month, day, hour, minute, weekday) = civil_time(time);
+        let day_matches = self.days & (1 << day) != 0;
+        let weekday_matches = self.weekdays & (1 << weekday) != 0;
+        let day_ok = if !self.any_day && !self.any_weekday {
+            day_matches || weekday_matches
+        } else {
+            day_matches && weekday_matches
+        };
+        self.minutes & (1 << minute) != 0 && self.hours & (1 << hour) != 0 &&
+        self.months & (1 << month) != 0 && day_ok
+    }
+}
+
+/// A text post that is submitted on a schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledPost {
+    /// When the post is submitted.
+    pub schedule: Schedule,
+    /// The title template. See `ScheduledPost.render()`.
+    pub title: String,
+    /// The body template. See `ScheduledPost.render()`.
+    pub body: String,
+    /// The flair template ID to submit the post with, if any.
+    pub flair_id: Option<String>,
+}
+
+impl ScheduledPost {
+    /// Creates the post to submit at the specified time. `{date}` (e.g. `2017-07-24`), `{year}`,
+    /// `{month}` and `{day}` in the title and body are replaced with the UTC date at that time.
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::scheduled::{parse_schedule, ScheduledPost};
+    /// let posts = parse_schedule("0 9 * * MON | Weekly thread ({date}) | Chat here!").unwrap();
+    /// let post = posts[0].render(1500886800);
+    /// assert_eq!(post.title, "Weekly thread (2017-07-24)");
+    /// ```
+    pub fn render(&self, time: i64) -> SelfPost {
+        let (year, month, day, _, _, _) = civil_time(time);
+        let fill = |template: &str| {
+            template.replace("{date}", &format!("{:04}-{:02}-{:02}", year, month, day))
+                .replace("{year}", &year.to_string())
+                .replace("{month}", &format!("{:02}", month))
+                .replace("{day}", &format!("{:02}", day))
+        };
+        let post = SelfPost::new(&fill(&self.title), &fill(&self.body));
+        match self.flair_id {
+            Some(ref flair_id) => post.flair(flair_id),
+            None => post,
+        }
+    }
+}
+
+/// Parses a schedule definition, with one post per line in the form
+/// `SCHEDULE | TITLE | BODY` or `SCHEDULE | TITLE | BODY | FLAIR_ID`. `\n` in the body is
+/// replaced with a line break. Blank lines and lines starting with `#` are ignored.
+/// # Examples
+/// ```
+/// use new_rawr::structures::scheduled::parse_schedule;
+/// let definition = "# Weekly threads\n\
+///                   0 9 * * MON | Weekly discussion ({date}) | What are you working on?\n\
+///                   0 12 1 * * | Monthly feedback | Tell the mods what you think.\\n\\nThanks!";
+/// let posts = parse_schedule(definition).unwrap();
+/// assert_eq!(posts.len(), 2);
+/// assert_eq!(posts[1].body, "Tell the mods what you think.\n\nThanks!");
+/// ```
+pub fn parse_schedule(definition: &str) -> Result<Vec<ScheduledPost>, APIError> {
+    let mut posts = Vec::new();
+    for (number, line) in definition.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts = line.split('|').map(|part| part.trim()).collect::<Vec<_>>();
+        if parts.len() < 3 || parts.len() > 4 || parts[1].is_empty() {
+            return Err(APIError::ValidationError(format!("Line {} should be 'SCHEDULE | TITLE | \
+                                                          BODY [| FLAIR_ID]'",
+                                                         number + 1)));
+        }
+        posts.push(ScheduledPost {
+            schedule: Schedule::parse(parts[0])?,
+            title: parts[1].to_owned(),
+            body: parts[2].replace("\\n", "\n"),
+            flair_id: parts.get(3).filter(|id| !id.is_empty()).map(|id| id.to_string()),
+        });
+    }
+    Ok(posts)
+}
+
+/// Submits text posts to a subreddit on a schedule, such as weekly discussion threads. The
+/// schedule can be kept in a wiki page so that moderators can change it without redeploying
+/// the bot (see `parse_schedule()` for the format).
+///
+/// The poster only submits posts while it is running: call `run_pending()` at least once a
+/// minute (or use `run()`). Each post is submitted at most once per call, and minutes between
+/// calls are caught up on, so a late check still makes the post.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::scheduled::ScheduledPoster;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut poster = ScheduledPoster::from_wiki(&client, "new_rawr", "bot/schedule")
+///     .expect("Could not load schedule");
+/// poster.run(Duration::from_secs(30), |title, result| {
+///     match result {
+///         Ok(()) => println!("Posted {}", title),
+///         Err(err) => println!("Could not post {}: {:?}", title, err),
+///     }
+///     true
+/// });
+/// ```
+pub struct ScheduledPoster<'a> {
+    client: &'a RedditClient,
+    subreddit: String,
+    /// The posts to submit.
+    pub posts: Vec<ScheduledPost>,
+    clock: Box<dyn Clock + 'a>,
+    last_checked: Option<i64>,
+}
+
+impl<'a> ScheduledPoster<'a> {
+    /// Creates a poster that submits the posts to the subreddit (without `/r/`).
+    pub fn new(client: &'a RedditClient,
+               subreddit: &str,
+               posts: Vec<ScheduledPost>)
+               -> ScheduledPoster<'a> {
+        ScheduledPoster {
+            client,
+            subreddit: subreddit.to_owned(),
+            posts,
+            clock: Box::new(SystemClock),
+            last_checked: None,
+        }
+    }
+
+    /// Creates a poster with the schedule definition in a wiki page of the subreddit.
+    pub fn from_wiki(client: &'a RedditClient,
+                     subreddit: &str,
+                     page: &str)
+                     -> Result<ScheduledPoster<'a>, APIError> {
+        let page = WikiPage::fetch(client, subreddit, page)?;
+        Ok(ScheduledPoster::new(client, subreddit, parse_schedule(page.content())?))
+    }
+
+    /// Replaces the clock (by default, `SystemClock`).
+    pub fn with_clock<C>(mut self, clock: C) -> ScheduledPoster<'a>
+        where C: Clock + 'a
+    {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The posts that are due since the last call (or in the current minute, on the first
+    /// call), with the time each one was scheduled for. Calling this marks them as handled.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::structures::scheduled::{parse_schedule, ScheduledPoster};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let posts = parse_schedule("0 9 * * MON | Weekly thread | Hello!").unwrap();
+    /// // Monday 24 July 2017, 09:00:30 UTC
+    /// let mut poster = ScheduledPoster::new(&client, "new_rawr", posts).with_clock(|| 1500886830);
+    /// assert_eq!(poster.due().len(), 1);
+    /// assert_eq!(poster.due().len(), 0);
+    /// ```
+    pub fn due(&mut self) -> Vec<(&ScheduledPost, i64)> {
+        let now = self.clock.now().div_euclid(60);
+        // Do not catch up on more than a day, e.g. after the bot was down.
+        let start = self.last_checked.map_or(now, |last| (last + 1).max(now - 24 * 60));
+        self.last_checked = Some(now);
+        self.posts
+            .iter()
+            .filter_map(|post| {
+                (start..=now)
+                    .rev()
+                    .find(|minute| post.schedule.matches(minute * 60))
+                    .map(|minute| (post, minute * 60))
+            })
+            .collect()
+    }
+
+    /// Submits the posts that are due, returning the title and result of each submission.
+    pub fn run_pending(&mut self) -> Vec<(String, Result<(), APIError>)> {
+        let due = self.due()
+            .into_iter()
+            .map(|(post, time)| post.render(time))
+            .collect::<Vec<SelfPost>>();
+        let subreddit = self.client.subreddit(&self.subreddit);
+        due.into_iter()
+            .map(|post| (post.title.to_owned(), subreddit.submit_text(post)))
+            .collect()
+    }
+
+    /// Calls `run_pending()` every `interval` (which should be under a minute), passing each
+    /// submission to `on_post`. Stops when `on_post` returns `false`.
+    pub fn run<F>(&mut self, interval: Duration, mut on_post: F)
+        where F: FnMut(&str, Result<(), APIError>) -> bool
+    {
+        run_every(interval, || self.run_pending(), |results| {
+            results.into_iter().all(|(title, result)| on_post(&title, result))
+        });
+    }
+}
@@ -0,0 +1,61 @@
+/// Every vote-related value that Reddit reports for a submission or comment, as returned by
+/// `Votable::score_details()`.
+/// ## Notes
+/// Reddit fuzzes all of these values to stop vote manipulation, so they are only estimates.
+/// `ups` is usually close to `score` and `downs` is usually 0, because Reddit no longer reports
+/// real downvote counts. For submissions, `estimated_upvotes()` and `estimated_downvotes()`
+/// derive more realistic counts from the upvote ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// The (fuzzed) points score, i.e. upvotes - downvotes.
+    pub score: i64,
+    /// The number of upvotes reported by Reddit (fuzzed).
+    pub ups: i64,
+    /// The number of downvotes reported by Reddit (fuzzed, and usually 0).
+    pub downs: i64,
+    /// The ratio of upvotes to total votes (e.g. `0.95`). Only submissions have this.
+    pub upvote_ratio: Option<f64>,
+    /// `true` if the subreddit is currently hiding the score, in which case the other values
+    /// may be placeholders.
+    pub hidden: bool,
+}
+
+impl Score {
+    /// Estimates the number of upvotes from the score and upvote ratio. Returns `None` if there is
+    /// no upvote ratio, or the ratio is exactly 0.5 (so the votes cancel out and the total cannot
+    /// be worked out).
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::score::Score;
+    /// let score = Score { score: 90, ups: 90, downs: 0, upvote_ratio: Some(0.95), hidden: false };
+    /// assert_eq!(score.estimated_upvotes(), Some(95));
+    /// assert_eq!(score.estimated_downvotes(), Some(5));
+    /// assert_eq!(score.estimated_total_votes(), Some(100));
+    /// ```
+    pub fn estimated_upvotes(&self) -> Option<u64> {
+        let ratio = self.upvote_ratio?;
+        if (ratio - 0.5).abs() < f64::EPSILON {
+            return None;
+        }
+        // upvotes - downvotes = score and upvotes / (upvotes + downvotes) = ratio.
+        let upvotes = self.score as f64 * ratio / (2.0 * ratio - 1.0);
+        if upvotes.is_finite() && upvotes >= 0.0 {
+            Some(upvotes.round() as u64)
+        } else {
+            None
+        }
+    }
+
+    /// Estimates the number of downvotes from the score and upvote ratio. See
+    /// `estimated_upvotes()`.
+    pub fn estimated_downvotes(&self) -> Option<u64> {
+        let upvotes = self.estimated_upvotes()? as i64;
+        Some((upvotes - self.score).max(0) as u64)
+    }
+
+    /// Estimates the total number of votes from the score and upvote ratio. See
+    /// `estimated_upvotes()`.
+    pub fn estimated_total_votes(&self) -> Option<u64> {
+        Some(self.estimated_upvotes()? + self.estimated_downvotes()?)
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde_json::{self, from_value};
+
+use crate::client::RedditClient;
+use crate::client::scheduler::Priority;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::DELETED;
+use crate::responses::comment::{CommentData, CommentListing};
+use crate::structures::streams::run_every;
+
+/// A score that a `ScoreWatcher` reports reaching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreThreshold {
+    /// The score rose to this value or higher (e.g. `AtLeast(100)` to log popular comments).
+    AtLeast(i64),
+    /// The score fell to this value or lower (e.g. `AtMost(-3)` to delete downvoted comments).
+    AtMost(i64),
+}
+
+impl ScoreThreshold {
+    /// `true` if the score is at or beyond the threshold.
+    pub fn reached_by(&self, score: i64) -> bool {
+        match *self {
+            ScoreThreshold::AtLeast(threshold) => score >= threshold,
+            ScoreThreshold::AtMost(threshold) => score <= threshold,
+        }
+    }
+}
+
+/// A comment or submission whose score crossed a threshold, as reported by `ScoreWatcher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreEvent {
+    /// The full name of the comment or submission (e.g. `t1_d5t5ebn`).
+    pub name: String,
+    /// The threshold that was crossed.
+    pub threshold: ScoreThreshold,
+    /// The new score.
+    pub score: i64,
+    /// The score in the previous sample, or `None` if this is the first sample of this item.
+    pub previous: Option<i64>,
+}
+
+/// Samples the scores of a set of comments or submissions (e.g. the bot's own replies) and
+/// reports when a score crosses one of the configured thresholds. Each threshold is reported
+/// once when it is reached, and again only if the score moves back and then reaches it again.
+///
+/// The scores are fetched from `/api/info`, 100 items per request, as background requests (see
+/// `RedditClient.with_priority()`), so sampling does not hold up the bot's interactive work.
+/// Items that have been deleted are no longer watched.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::score_watch::ScoreThreshold;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut watcher = client.score_watcher()
+///     .threshold(ScoreThreshold::AtMost(-3))
+///     .threshold(ScoreThreshold::AtLeast(100));
+/// watcher.add_user_comments("new_rawr", 100).expect("Could not fetch comments");
+/// watcher.run(Duration::from_secs(600), |result| {
+///     for event in result.unwrap_or_default() {
+///         match event.threshold {
+///             ScoreThreshold::AtMost(_) => println!("{} is downvoted, deleting", event.name),
+///             ScoreThreshold::AtLeast(_) => println!("{} reached {}", event.name, event.score),
+///         }
+///     }
+///     true
+/// });
+/// ```
+pub struct ScoreWatcher<'a> {
+    client: &'a RedditClient,
+    thresholds: Vec<ScoreThreshold>,
+    names: Vec<String>,
+    scores: HashMap<String, i64>,
+}
+
+impl<'a> ScoreWatcher<'a> {
+    /// Internal method. Use `RedditClient.score_watcher()` instead.
+    pub fn new(client: &'a RedditClient) -> ScoreWatcher<'a> {
+        ScoreWatcher {
+            client,
+            thresholds: Vec::new(),
+            names: Vec::new(),
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Adds a threshold to report.
+    pub fn threshold(mut self, threshold: ScoreThreshold) -> ScoreWatcher<'a> {
+        self.thresholds.push(threshold);
+        self
+    }
+
+    /// Starts watching the comment or submission with the specified full name (e.g.
+    /// `t1_d5t5ebn`), e.g. right after the bot has posted it.
+    pub fn watch(&mut self, name: &str) {
+        if !self.names.iter().any(|existing| existing == name) {
+            self.names.push(name.to_owned());
+        }
+    }
+
+    /// Stops watching the comment or submission with the specified full name.
+    pub fn unwatch(&mut self, name: &str) {
+        self.names.retain(|existing| existing != name);
+        self.scores.remove(name);
+    }
+
+    /// Starts watching the `limit` most recent comments of a user (at most 100), such as the
+    /// account the bot is logged in as.
+    pub fn add_user_comments(&mut self, user: &str, limit: usize) -> Result<(), APIError> {
+        let url = format!("{}?sort=new&limit={}&raw_json=1",
+                          endpoints::user_comments(user),
+                          limit.min(100));
+        let string = self.client.get_json(&url, false)?;
+        let listing: CommentListing = serde_json::from_str(&string)?;
+        for item in listing.data.children {
+            let data = from_value::<CommentData>(item.data)?;
+            self.watch(&data.name);
+        }
+        Ok(())
+    }
+
+    /// The full names of the watched items.
+    pub fn watched(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The score of an item in the last sample, or `None` if it has not been sampled yet.
+    pub fn score(&self, name: &str) -> Option<i64> {
+        self.scores.get(name).cloned()
+    }
+
+    /// Fetches the scores of the watched items once, and returns the thresholds that were
+    /// crossed since the previous sample.
+    pub fn sample(&mut self) -> Result<Vec<ScoreEvent>, APIError> {
+        let items = self.client.with_priority(Priority::Background, || {
+            self.client.get_info(&self.names)
+        })?;
+        let scores = items.iter()
+            .filter(|item| item["author"] != DELETED)
+            .filter_map(|item| Some((item["name"].as_str()?.to_owned(), item["score"].as_i64()?)))
+            .collect::<Vec<_>>();
+        // Items that were not returned, or were deleted, cannot change any more.
+        let alive = scores.iter().map(|(name, _)| name.as_str()).collect::<HashSet<_>>();
+        self.names.retain(|name| alive.contains(name.as_str()));
+        self.scores.retain(|name, _| alive.contains(name.as_str()));
+        Ok(self.update(&scores))
+    }
+
+    /// Records new scores (full names with scores) and returns the thresholds that were
+    /// crossed. `sample()` calls this with the scores from Reddit, but it can be used with
+    /// scores fetched in other ways.
+    /// # Examples
+    /// ```
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::structures::score_watch::{ScoreEvent, ScoreThreshold};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let mut watcher = client.score_watcher().threshold(ScoreThreshold::AtMost(-3));
+    /// assert!(watcher.update(&[(String::from("t1_a"), 1)]).is_empty());
+    /// let events = watcher.update(&[(String::from("t1_a"), -4)]);
+    /// assert_eq!(events, vec![ScoreEvent {
+    ///     name: String::from("t1_a"),
+    ///     threshold: ScoreThreshold::AtMost(-3),
+    ///     score: -4,
+    ///     previous: Some(1),
+    /// }]);
+    /// // Already reported, so falling further does not report it again.
+    /// assert!(watcher.update(&[(String::from("t1_a"), -6)]).is_empty());
+    /// ```
+    pub fn update(&mut self, scores: &[(String, i64)]) -> Vec<ScoreEvent> {
+        let mut events = Vec::new();
+        for &(ref name, score) in scores {
+            let previous = self.scores.insert(name.to_owned(), score);
+            for &threshold in &self.thresholds {
+                let was_reached = matches!(previous, Some(previous) if threshold.reached_by(previous));
+                if threshold.reached_by(score) && !was_reached {
+                    events.push(ScoreEvent {
+                        name: name.to_owned(),
+                        threshold,
+                        score,
+                        previous,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// Calls `sample()` every `interval`, passing each result to `on_sample`. Stops when
+    /// `on_sample` returns `false`.
+    pub fn run<F>(&mut self, interval: Duration, on_sample: F)
+        where F: FnMut(Result<Vec<ScoreEvent>, APIError>) -> bool
+    {
+        run_every(interval, || self.sample(), on_sample);
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::{BasicThing, Distinguish};
+use crate::responses::comment::{CommentData, CommentListing, MoreData};
+use crate::responses::listing::{CommentResponse, SubmissionData};
+
+/// The version of the `ThreadSnapshot` schema. This is increased whenever a field is renamed or
+/// removed, so archived snapshots can be migrated.
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// The maximum number of comment IDs that Reddit will expand in one `/api/morechildren` call.
+const MORE_CHILDREN_LIMIT: usize = 100;
+
+/// A submission and its comment tree captured as a single serializable document. Created with
+/// `Submission.snapshot()`. The field names form a stable schema (see `SNAPSHOT_SCHEMA_VERSION`)
+/// and do not follow changes to the raw API responses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ThreadSnapshot {
+    /// The schema version that this snapshot was written with.
+    pub schema_version: u32,
+    /// The submission itself.
+    pub post: PostSnapshot,
+    /// The top-level comments, each containing its replies.
+    pub comments: Vec<CommentSnapshot>,
+    /// `false` if some 'more comments' links were not expanded because the request budget was
+    /// used up, or because Reddit did not return them.
+    pub complete: bool,
+}
+
+/// The submission in a `ThreadSnapshot`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PostSnapshot {
+    /// The full name of the post (e.g. `t3_4uule8`).
+    pub name: String,
+    /// The subreddit that the post was made in (not including `/r/`).
+    pub subreddit: String,
+    /// The author of the post (not including `/u/`).
+    pub author: String,
+    /// The title of the post.
+    pub title: String,
+    /// The text of a self post, or an empty string for link posts.
+    pub selftext: String,
+    /// The URL linked to by a link post.
+    pub url: Option<String>,
+    /// The permalink of the post, relative to reddit.com.
+    pub permalink: String,
+    /// The (fuzzed) points score of the post.
+    pub score: i64,
+    /// The ratio of upvotes to total votes, if available.
+    pub upvote_ratio: Option<f64>,
+    /// The number of comments that Reddit reports for the post.
+    pub num_comments: u64,
+    /// `true` if the post is marked NSFW.
+    pub over_18: bool,
+    /// `true` if the post is locked.
+    pub locked: bool,
+    /// The UTC timestamp at which the post was created.
+    pub created_utc: f64,
+    /// The UTC timestamp at which the post was last edited, if it has been edited.
+    pub edited_utc: Option<f64>,
+}
+
+impl PostSnapshot {
+    /// Creates a `PostSnapshot` from the raw submission data.
+    pub fn new(data: SubmissionData) -> PostSnapshot {
+        PostSnapshot {
+            edited_utc: data.edited.as_f64(),
+            name: data.name,
+            subreddit: data.subreddit,
+            author: data.author,
+            title: data.title,
+            selftext: data.selftext,
+            url: data.url,
+            permalink: data.permalink,
+            score: data.score,
+            upvote_ratio: data.upvote_ratio,
+            num_comments: data.num_comments,
+            over_18: data.over_18,
+            locked: data.locked,
+            created_utc: data.created_utc,
+        }
+    }
+}
+
+/// A comment in a `ThreadSnapshot`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommentSnapshot {
+    /// The full name of the comment (e.g. `t1_d5tai4u`).
+    pub name: String,
+    /// The full name of the parent comment or post.
+    pub parent_id: String,
+    /// The author of the comment (not including `/u/`).
+    pub author: String,
+    /// The Markdown text of the comment.
+    pub body: String,
+    /// The (fuzzed) points score of the comment.
+    pub score: i64,
+    /// The UTC timestamp at which the comment was created.
+    pub created_utc: f64,
+    /// The UTC timestamp at which the comment was last edited, if it has been edited.
+    pub edited_utc: Option<f64>,
+    /// Whether the comment is distinguished, and by whom.
+    pub distinguished: Option<Distinguish>,
+    /// `true` if the comment is stickied.
+    pub stickied: bool,
+    /// The replies to this comment.
+    pub replies: Vec<CommentSnapshot>,
+}
+
+impl CommentSnapshot {
+    /// Creates a `CommentSnapshot` (with no replies) from the raw comment data.
+    pub fn new(data: CommentData) -> CommentSnapshot {
+        CommentSnapshot {
+            edited_utc: data.edited.as_f64(),
+            name: data.name,
+            parent_id: data.parent_id,
+            author: data.author,
+            body: data.body,
+            score: data.score,
+            created_utc: data.created_utc,
+            distinguished: data.distinguished,
+            stickied: data.stickied,
+            replies: Vec::new(),
+        }
+    }
+}
+
+impl ThreadSnapshot {
+    /// Internal method. Use `Submission.snapshot()` instead.
+    pub fn fetch(client: &RedditClient,
+                 id: &str,
+                 budget: usize)
+                 -> Result<ThreadSnapshot, APIError> {
+        let url = format!("{}?raw_json=1&limit=500", endpoints::comments(id));
+        let string = client.get_json(&url, false)?;
+        let response: CommentResponse = serde_json::from_str(&string)?;
+        let post = match response.0.data.children.into_iter().next() {
+            Some(item) => item.data,
+            None => return Err(APIError::ExhaustedListing),
+        };
+
+        let mut comments = Vec::new();
+        let mut mores = Vec::new();
+        flatten(response.1.data.children, &mut comments, &mut mores)?;
+
+        let mut remaining = budget;
+        let mut complete = true;
+        while let Some(mut more) = mores.pop() {
+            if more.children.is_empty() {
+                // 'Continue this thread' links cannot be expanded with /api/morechildren.
+                complete = false;
+                continue;
+            }
+            if remaining == 0 {
+                complete = false;
+                break;
+            }
+            remaining -= 1;
+            if more.children.len() > MORE_CHILDREN_LIMIT {
+                let rest = more.children.split_off(MORE_CHILDREN_LIMIT);
+                mores.push(MoreData {
+                    count: more.count,
+                    parent_id: more.parent_id.to_owned(),
+                    children: rest,
+                });
+            }
+            let body = format!("api_type=json&link_id={}&children={}",
+                               post.name,
+                               more.children.join(","));
+            let string = client.post_json(endpoints::MORE_CHILDREN, &body, false)?;
+            let value: Value = serde_json::from_str(&string)?;
+            let things = value.pointer("/json/data/things").cloned().unwrap_or(Value::Null);
+            let things: Vec<BasicThing<Value>> = match things {
+                Value::Array(_) => serde_json::from_value(things)?,
+                _ => Vec::new(),
+            };
+            flatten(things, &mut comments, &mut mores)?;
+        }
+
+        let mut by_parent: HashMap<String, Vec<CommentSnapshot>> = HashMap::new();
+        for comment in comments {
+            by_parent.entry(comment.parent_id.to_owned()).or_default().push(comment);
+        }
+        let post = PostSnapshot::new(post);
+        let comments = build_tree(&post.name, &mut by_parent);
+        Ok(ThreadSnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            post,
+            comments,
+            complete,
+        })
+    }
+}
+
+/// Collects the comments in a (possibly nested) list of things into `comments`, and any 'more
+/// comments' links into `mores`.
+fn flatten(things: Vec<BasicThing<Value>>,
+           comments: &mut Vec<CommentSnapshot>,
+           mores: &mut Vec<MoreData>)
+           -> Result<(), APIError> {
+    for thing in things {
+        if thing.kind == "t1" {
+            let mut data: CommentData = serde_json::from_value(thing.data)?;
+            let replies = data.replies.take();
+            comments.push(CommentSnapshot::new(data));
+            if replies.is_object() {
+                let listing: CommentListing = serde_json::from_value(replies)?;
+                flatten(listing.data.children, comments, mores)?;
+            }
+        } else if thing.kind == "more" {
+            mores.push(serde_json::from_value(thing.data)?);
+        }
+    }
+    Ok(())
+}
+
+fn build_tree(parent: &str,
+              by_parent: &mut HashMap<String, Vec<CommentSnapshot>>)
+              -> Vec<CommentSnapshot> {
+    let mut children = by_parent.remove(parent).unwrap_or_default();
+    for child in &mut children {
+        child.replies = build_tree(&child.name, by_parent);
+    }
+    children
+}
@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "moderation")]
+use crate::structures::modlog::ModAction;
+use crate::structures::submission::Submission;
+use crate::traits::{Content, Created, Flairable, Votable};
+
+/// The number of seconds in a day, used to bucket posts by UTC day.
+const DAY: i64 = 86400;
+
+/// A report aggregated from the submissions in a subreddit over a time window. Create one with
+/// `Subreddit.stats(DAYS)`, or from any iterator of submissions with `SubredditStats::collect`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let stats = client.subreddit("rust").stats(7).expect("Could not crawl /r/rust");
+/// println!("{} posts, average score {}", stats.total_posts, stats.average_score);
+/// for (author, posts) in stats.top_authors.iter().take(5) {
+///     println!("/u/{}: {}", author, posts);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubredditStats {
+    /// The UTC timestamp of the start of the window.
+    pub since: i64,
+    /// The total number of posts in the window.
+    pub total_posts: u64,
+    /// The number of posts made on each day, keyed by the UTC timestamp of the start of the day.
+    pub posts_per_day: BTreeMap<i64, u64>,
+    /// Authors and their number of posts, most active first.
+    pub top_authors: Vec<(String, u64)>,
+    /// The number of posts with each link flair. Unflaired posts are not counted.
+    pub flairs: HashMap<String, u64>,
+    /// The mean (fuzzed) score of the posts, or `0.0` if there were none.
+    pub average_score: f64,
+}
+
+impl SubredditStats {
+    /// Aggregates the submissions from an iterator, stopping at the first one created before
+    /// `since` (a UTC timestamp). The iterator should yield the newest posts first, like
+    /// `Subreddit.new()`.
+    pub fn collect<'a, I>(posts: I, since: i64) -> SubredditStats
+        where I: Iterator<Item = Submission<'a>>
+    {
+        let mut stats = SubredditStats {
+            since,
+            total_posts: 0,
+            posts_per_day: BTreeMap::new(),
+            top_authors: Vec::new(),
+            flairs: HashMap::new(),
+            average_score: 0.0,
+        };
+        let mut authors: HashMap<String, u64> = HashMap::new();
+        let mut total_score = 0;
+        for post in posts.take_while(|post| post.created_utc() >= since) {
+            stats.total_posts += 1;
+            total_score += post.score();
+            let day = post.created_utc() - post.created_utc().rem_euclid(DAY);
+            *stats.posts_per_day.entry(day).or_insert(0) += 1;
+            *authors.entry(post.author().name).or_insert(0) += 1;
+            if let Some(flair) = post.get_flair_text() {
+                *stats.flairs.entry(flair).or_insert(0) += 1;
+            }
+        }
+        if stats.total_posts > 0 {
+            stats.average_score = total_score as f64 / stats.total_posts as f64;
+        }
+        stats.top_authors = authors.into_iter().collect();
+        stats.top_authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats
+    }
+}
+
+/// A summary of a user's recent activity, useful for vetting users or spotting spam accounts.
+/// Create one with `User.activity_summary(LIMIT)`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::AnonymousAuthenticator;
+/// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+/// let summary = client.user("Aurora0001").activity_summary(100).expect("Could not crawl user");
+/// println!("Average score: {}", summary.average_score());
+/// for (subreddit, items) in summary.top_subreddits(5) {
+///     println!("/r/{}: {}", subreddit, items);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserActivitySummary {
+    /// The name of the user.
+    pub user: String,
+    /// The number of submissions included in the summary.
+    pub submissions: u64,
+    /// The number of comments included in the summary.
+    pub comments: u64,
+    /// The number of submissions and comments made in each subreddit.
+    pub subreddits: HashMap<String, u64>,
+    /// The number of submissions and comments made in each hour of the day (UTC).
+    pub posting_hours: [u64; 24],
+    /// The sum of the (fuzzed) scores of all submissions and comments.
+    pub total_score: i64,
+}
+
+impl UserActivitySummary {
+    /// Creates an empty summary for the specified user.
+    pub fn new(user: &str) -> UserActivitySummary {
+        UserActivitySummary {
+            user: user.to_owned(),
+            submissions: 0,
+            comments: 0,
+            subreddits: HashMap::new(),
+            posting_hours: [0; 24],
+            total_score: 0,
+        }
+    }
+
+    /// Adds a submission or comment to the summary.
+    pub fn add(&mut self, is_comment: bool, subreddit: &str, created_utc: i64, score: i64) {
+        if is_comment {
+            self.comments += 1;
+        } else {
+            self.submissions += 1;
+        }
+        *self.subreddits.entry(subreddit.to_owned()).or_insert(0) += 1;
+        self.posting_hours[(created_utc.rem_euclid(DAY) / 3600) as usize] += 1;
+        self.total_score += score;
+    }
+
+    /// The subreddits with the most activity, most active first. At most `n` are returned.
+    pub fn top_subreddits(&self, n: usize) -> Vec<(String, u64)> {
+        let mut subreddits = self.subreddits
+            .iter()
+            .map(|(name, count)| (name.to_owned(), *count))
+            .collect::<Vec<_>>();
+        subreddits.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        subreddits.truncate(n);
+        subreddits
+    }
+
+    /// The mean (fuzzed) score of all submissions and comments, or `0.0` if there were none.
+    pub fn average_score(&self) -> f64 {
+        let items = self.submissions + self.comments;
+        if items == 0 {
+            0.0
+        } else {
+            self.total_score as f64 / items as f64
+        }
+    }
+}
+
+/// A report of the actions taken by each moderator of a subreddit over a time window, from the
+/// moderation log. Create one with `Subreddit.mod_activity(DAYS)`, or from any iterator of
+/// `ModAction`s with `ModActivityReport::collect`.
+/// # Examples
+/// ```rust,no_run
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let report = client.subreddit("new_rawr").mod_activity(30).expect("Could not crawl mod log");
+/// for (moderator, total) in report.totals() {
+///     println!("/u/{}: {} actions {:?}", moderator, total, report.moderators[&moderator]);
+/// }
+/// ```
+#[cfg(feature = "moderation")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModActivityReport {
+    /// The UTC timestamp of the start of the window.
+    pub since: i64,
+    /// The total number of actions in the window.
+    pub total_actions: u64,
+    /// The number of actions of each type (e.g. `removelink`), keyed by moderator name.
+    pub moderators: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+#[cfg(feature = "moderation")]
+impl ModActivityReport {
+    /// Aggregates the entries from an iterator, stopping at the first one created before `since`
+    /// (a UTC timestamp). The iterator should yield the newest entries first, like the
+    /// moderation log.
+    pub fn collect<I>(actions: I, since: i64) -> ModActivityReport
+        where I: Iterator<Item = ModAction>
+    {
+        let mut report = ModActivityReport {
+            since,
+            total_actions: 0,
+            moderators: BTreeMap::new(),
+        };
+        for action in actions.take_while(|action| action.created_utc() >= since) {
+            report.total_actions += 1;
+            *report.moderators
+                .entry(action.moderator().to_owned())
+                .or_default()
+                .entry(action.action().to_owned())
+                .or_insert(0) += 1;
+        }
+        report
+    }
+
+    /// The total number of actions by each moderator, most active first.
+    pub fn totals(&self) -> Vec<(String, u64)> {
+        let mut totals = self.moderators
+            .iter()
+            .map(|(moderator, actions)| (moderator.to_owned(), actions.values().sum()))
+            .collect::<Vec<(String, u64)>>();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        totals
+    }
+}
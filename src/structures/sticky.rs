@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use serde_json::{self, Value};
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::structures::scheduled::{Clock, SystemClock};
+use crate::structures::streams::run_every;
+
+/// The number of sticky slots Reddit gives each subreddit.
+const SLOTS: usize = 2;
+
+/// A post to sticky for a period of time, e.g. an announcement or a weekly thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StickyEntry {
+    /// The full name of the submission (e.g. `t3_4uule8`).
+    pub name: String,
+    /// When the post should be stickied, as a UNIX timestamp in seconds.
+    pub start: i64,
+    /// When the post should be unstickied again, as a UNIX timestamp in seconds.
+    pub end: i64,
+}
+
+impl StickyEntry {
+    /// Creates an entry that stickies the post at `start` (a UNIX timestamp in seconds) for
+    /// `duration`.
+    pub fn new(name: &str, start: i64, duration: Duration) -> StickyEntry {
+        StickyEntry {
+            name: name.to_owned(),
+            start,
+            end: start + duration.as_secs() as i64,
+        }
+    }
+}
+
+/// A change made (or to be made) by a `StickyRotator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StickyAction {
+    /// The post was stickied (and distinguished, unless turned off).
+    Stick(String),
+    /// The post's time was up, so it was unstickied (and undistinguished, unless turned off).
+    Unstick(String),
+    /// The post's time was up, but it was not stickied (e.g. because no slot was free, or a
+    /// moderator already unstickied it), so it was only removed from the queue.
+    Drop(String),
+}
+
+/// Rotates the sticky posts ('announcements') of a subreddit through a queue of posts, each with
+/// the period it should be stickied for. Each call to `rotate()` unstickies the queued posts
+/// whose time is up and stickies the posts whose time has come, as long as a slot is free.
+///
+/// Reddit gives each subreddit two sticky slots. The rotator only ever unstickies posts from its
+/// queue, so posts stickied by hand keep their slot, and `slots()` limits how many slots the
+/// rotator uses at once (e.g. to keep one for the moderators). When more posts are due than
+/// there are free slots, the one scheduled first is stickied first. Posts are distinguished
+/// when they are stickied and undistinguished when they are unstickied, unless turned off with
+/// `distinguish()`.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::sticky::{StickyEntry, StickyRotator};
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let week = Duration::from_secs(7 * 24 * 60 * 60);
+/// let mut rotator = StickyRotator::new(&client, "new_rawr").slots(1);
+/// rotator.schedule(StickyEntry::new("t3_4uule8", 1500886800, week));
+/// rotator.schedule(StickyEntry::new("t3_4uulf2", 1501491600, week));
+/// rotator.run(Duration::from_secs(60), |result| {
+///     match result {
+///         Ok(actions) => actions.iter().for_each(|action| println!("{:?}", action)),
+///         Err(err) => println!("Could not rotate the stickies: {:?}", err),
+///     }
+///     true
+/// });
+/// ```
+pub struct StickyRotator<'a> {
+    client: &'a RedditClient,
+    subreddit: String,
+    queue: Vec<StickyEntry>,
+    slots: usize,
+    distinguish: bool,
+    clock: Box<dyn Clock + 'a>,
+}
+
+impl<'a> StickyRotator<'a> {
+    /// Creates a rotator with an empty queue for the subreddit (without `/r/`).
+    pub fn new(client: &'a RedditClient, subreddit: &str) -> StickyRotator<'a> {
+        StickyRotator {
+            client,
+            subreddit: subreddit.to_owned(),
+            queue: Vec::new(),
+            slots: SLOTS,
+            distinguish: true,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    /// Sets how many of the subreddit's two sticky slots the rotator may use at once (2 by
+    /// default).
+    pub fn slots(mut self, slots: usize) -> StickyRotator<'a> {
+        self.slots = slots.min(SLOTS);
+        self
+    }
+
+    /// Sets whether posts are distinguished while they are stickied (enabled by default).
+    pub fn distinguish(mut self, distinguish: bool) -> StickyRotator<'a> {
+        self.distinguish = distinguish;
+        self
+    }
+
+    /// Replaces the clock (by default, `SystemClock`).
+    pub fn with_clock<C>(mut self, clock: C) -> StickyRotator<'a>
+        where C: Clock + 'a
+    {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Adds a post to the queue.
+    pub fn schedule(&mut self, entry: StickyEntry) {
+        self.queue.push(entry);
+    }
+
+    /// The posts in the queue, including the ones that are stickied now. Posts are removed once
+    /// their time is up.
+    pub fn queue(&self) -> &[StickyEntry] {
+        &self.queue
+    }
+
+    /// The full names of the posts that are stickied in the subreddit now.
+    pub fn stickied(&self) -> Result<Vec<String>, APIError> {
+        // Sticky posts always come first in the hot listing.
+        let url = format!("{}?limit={}&raw_json=1",
+                          endpoints::subreddit_listing(&self.subreddit, "hot"),
+                          SLOTS);
+        let listing: Value = serde_json::from_str(&self.client.get_json(&url, false)?)?;
+        Ok(listing["data"]["children"]
+            .as_array()
+            .map(|children| {
+                children.iter()
+                    .map(|child| &child["data"])
+                    .filter(|data| data["stickied"] == true)
+                    .filter_map(|data| data["name"].as_str())
+                    .map(|name| name.to_owned())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Decides what to change at the time `now` (a UNIX timestamp in seconds), given the posts
+    /// that are stickied now. `rotate()` calls this and then makes the changes.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::structures::sticky::{StickyAction, StickyEntry, StickyRotator};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let day = Duration::from_secs(24 * 60 * 60);
+    /// let mut rotator = StickyRotator::new(&client, "new_rawr");
+    /// rotator.schedule(StickyEntry::new("t3_a", 0, day));
+    /// rotator.schedule(StickyEntry::new("t3_b", 86400, day));
+    /// rotator.schedule(StickyEntry::new("t3_c", 86400, day));
+    ///
+    /// // One slot is taken by a post stickied by hand, which is left alone.
+    /// let stickied = vec![String::from("t3_a"), String::from("t3_rules")];
+    /// assert_eq!(rotator.plan(90000, &stickied),
+    ///            vec![StickyAction::Unstick(String::from("t3_a")),
+    ///                 StickyAction::Stick(String::from("t3_b"))]);
+    /// ```
+    pub fn plan(&self, now: i64, stickied: &[String]) -> Vec<StickyAction> {
+        let is_stickied = |name: &str| stickied.iter().any(|sticky| sticky == name);
+        let mut actions = Vec::new();
+        let mut in_use = stickied.len();
+        let mut managed = 0;
+        for entry in &self.queue {
+            if entry.end <= now {
+                if is_stickied(&entry.name) {
+                    actions.push(StickyAction::Unstick(entry.name.to_owned()));
+                    in_use = in_use.saturating_sub(1);
+                } else {
+                    actions.push(StickyAction::Drop(entry.name.to_owned()));
+                }
+            } else if is_stickied(&entry.name) {
+                managed += 1;
+            }
+        }
+
+        let mut due = self.queue
+            .iter()
+            .filter(|entry| entry.start <= now && now < entry.end && !is_stickied(&entry.name))
+            .collect::<Vec<_>>();
+        due.sort_by_key(|entry| entry.start);
+        let free = SLOTS.saturating_sub(in_use).min(self.slots.saturating_sub(managed));
+        actions.extend(due.into_iter()
+            .take(free)
+            .map(|entry| StickyAction::Stick(entry.name.to_owned())));
+        actions
+    }
+
+    /// Unstickies the posts whose time is up and stickies the posts that are due, returning the
+    /// changes that were made. Posts whose time is up are removed from the queue once every
+    /// change has been made, so a failed rotation is completed by the next call.
+    pub fn rotate(&mut self) -> Result<Vec<StickyAction>, APIError> {
+        let now = self.clock.now();
+        let actions = self.plan(now, &self.stickied()?);
+        for action in &actions {
+            match *action {
+                StickyAction::Stick(ref name) => {
+                    if self.distinguish {
+                        self.set_distinguished(name, true)?;
+                    }
+                    self.set_stickied(name, true)?;
+                }
+                StickyAction::Unstick(ref name) => {
+                    self.set_stickied(name, false)?;
+                    if self.distinguish {
+                        self.set_distinguished(name, false)?;
+                    }
+                }
+                StickyAction::Drop(_) => {}
+            }
+        }
+        self.queue.retain(|entry| entry.end > now);
+        Ok(actions)
+    }
+
+    /// Calls `rotate()` every `interval`, passing each result to `on_rotate`. Stops when
+    /// `on_rotate` returns `false`.
+    pub fn run<F>(&mut self, interval: Duration, on_rotate: F)
+        where F: FnMut(Result<Vec<StickyAction>, APIError>) -> bool
+    {
+        run_every(interval, || self.rotate(), on_rotate);
+    }
+
+    fn set_stickied(&self, name: &str, state: bool) -> Result<(), APIError> {
+        let body = format!("api_type=json&id={}&state={}", name, state);
+        self.client.post_success(endpoints::SET_SUBREDDIT_STICKY, &body, false)
+    }
+
+    fn set_distinguished(&self, name: &str, state: bool) -> Result<(), APIError> {
+        let how = if state { "yes" } else { "no" };
+        let body = format!("api_type=json&how={}&id={}", how, name);
+        self.client.post_success(endpoints::DISTINGUISH, &body, false)
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(feature = "streams")]
+use std::vec::IntoIter;
+use std::time::Duration;
+
+use crate::clock;
+#[cfg(feature = "streams")]
+use crate::client::RedditClient;
+#[cfg(feature = "streams")]
+use crate::traits::{Created, PollingStream};
+
+/// Blocks the current thread between polls of a stream. On wasm32 targets there are no threads
+/// to block, so this returns immediately and streams should be driven with
+/// `PollingStream::poll()` instead.
+pub fn pause(duration: Duration) {
+    clock::pause(duration);
+}
+
+/// Calls `round` every `interval`, passing each result to `callback`, until `callback` returns
+/// `false`. This is the loop behind the `run()` methods of the polling helpers.
+pub(crate) fn run_every<T, R, F>(interval: Duration, mut round: R, mut callback: F)
+    where R: FnMut() -> T,
+          F: FnMut(T) -> bool
+{
+    while callback(round()) {
+        pause(interval);
+    }
+}
+
+#[cfg(feature = "streams")]
+/// Allow/deny lists of authors used by `PostStream` and `CommentStream` to drop items before
+/// they are yielded. Names are compared case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorFilter {
+    excluded: Vec<String>,
+    only: Option<Vec<String>>,
+}
+
+#[cfg(feature = "streams")]
+impl AuthorFilter {
+    /// Creates a filter which excludes the account the client is logged in as (if any), so bots
+    /// do not respond to their own posts and comments.
+    pub fn for_client(client: &RedditClient) -> AuthorFilter {
+        let mut filter = AuthorFilter::default();
+        if let Some(name) = client.get_authenticator().username() {
+            filter.exclude(&[name]);
+        }
+        filter
+    }
+
+    /// Adds the specified authors to the deny list.
+    pub fn exclude<S: AsRef<str>>(&mut self, authors: &[S]) {
+        self.excluded.extend(authors.iter().map(|a| a.as_ref().to_lowercase()));
+    }
+
+    /// Restricts the filter to only allow the specified authors. Calling this again adds to the
+    /// allow list.
+    pub fn only<S: AsRef<str>>(&mut self, authors: &[S]) {
+        self.only
+            .get_or_insert_with(Vec::new)
+            .extend(authors.iter().map(|a| a.as_ref().to_lowercase()));
+    }
+
+    /// `true` if items by this author should be yielded.
+    pub fn allows(&self, author: &str) -> bool {
+        let author = author.to_lowercase();
+        if self.excluded.contains(&author) {
+            return false;
+        }
+        match self.only {
+            Some(ref only) => only.contains(&author),
+            None => true,
+        }
+    }
+}
+
+#[cfg(feature = "streams")]
+/// Helper for combining several streams so they can be consumed from a single loop.
+pub struct Streams;
+
+#[cfg(feature = "streams")]
+impl Streams {
+    /// Merges multiple streams (e.g. several `PostStream`s from different subreddits) into a
+    /// single `MergedStream`. Every stream is polled once per cycle, and the new items are
+    /// yielded in the order they were created, so one loop can replace a thread per stream.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::structures::streams::Streams;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let streams = vec![client.subreddit("rust").new_stream(),
+    ///                    client.subreddit("programming").new_stream()];
+    /// for post in Streams::merge(streams) {
+    ///     println!("{}", post.title());
+    /// }
+    /// ```
+    pub fn merge<S>(streams: Vec<S>) -> MergedStream<S>
+        where S: PollingStream,
+              S::Item: Created
+    {
+        MergedStream {
+            streams,
+            current_iter: None,
+        }
+    }
+}
+
+#[cfg(feature = "streams")]
+/// An infinite stream that yields items from several underlying streams in creation order.
+/// Created with `Streams::merge()`.
+pub struct MergedStream<S: PollingStream> {
+    streams: Vec<S>,
+    current_iter: Option<IntoIter<S::Item>>,
+}
+
+#[cfg(feature = "streams")]
+impl<S> PollingStream for MergedStream<S>
+    where S: PollingStream,
+          S::Item: Created
+{
+    fn poll(&mut self) -> Vec<S::Item> {
+        let mut fresh = Vec::new();
+        for stream in &mut self.streams {
+            fresh.append(&mut stream.poll());
+        }
+        fresh.sort_by_key(|item| item.created_utc());
+        fresh
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<S> Iterator for MergedStream<S>
+    where S: PollingStream,
+          S::Item: Created
+{
+    type Item = S::Item;
+    fn next(&mut self) -> Option<S::Item> {
+        if self.streams.is_empty() {
+            return None;
+        }
+        if let Some(iter) = self.current_iter.as_mut() {
+            if let Some(res) = iter.next() {
+                return Some(res);
+            }
+        }
+        pause(Duration::new(5, 0));
+        self.current_iter = Some(self.poll().into_iter());
+        self.next()
+    }
+}
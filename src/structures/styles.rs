@@ -0,0 +1,140 @@
+use http::Method;
+use http::header::CONTENT_TYPE;
+
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+#[cfg(feature = "media-upload")]
+use crate::http::HttpRequest;
+use crate::options::StyleUpdate;
+#[cfg(feature = "media-upload")]
+use crate::options::StyleImage;
+use crate::responses::styles::{StructuredStylesResponse, StyleData};
+#[cfg(feature = "media-upload")]
+use crate::responses::styles::StyleUploadResponse;
+
+/// Access to the structured styles ('community appearance' on new Reddit) of a subreddit. Get
+/// this with `Subreddit.styles()`. Updating styles requires an OAuth-based authenticator and
+/// moderator permissions.
+/// # Examples
+/// ```rust,no_run
+/// # #[cfg(feature = "media-upload")] {
+/// use std::fs;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::options::{StyleImage, StyleUpdate};
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let sub = client.subreddit("new_rawr");
+/// let styles = sub.styles();
+/// let banner = fs::read("banner.png").unwrap();
+/// styles.upload_image(StyleImage::BannerBackground, "banner.png", "image/png", banner)
+///     .expect("Upload failed");
+/// styles.update(StyleUpdate::new().primary_color("#FF4500")).expect("Update failed");
+/// # }
+/// ```
+pub struct SubredditStyles<'a> {
+    client: &'a RedditClient,
+    subreddit: String,
+}
+
+impl<'a> SubredditStyles<'a> {
+    /// Internal method. Use `Subreddit.styles()` instead.
+    pub fn new(client: &'a RedditClient, subreddit: &str) -> SubredditStyles<'a> {
+        SubredditStyles {
+            client,
+            subreddit: subreddit.to_owned(),
+        }
+    }
+
+    /// Fetches the current structured styles of the subreddit.
+    pub fn get(&self) -> Result<StyleData, APIError> {
+        let url = format!("{}/{}", endpoints::STRUCTURED_STYLES, self.subreddit);
+        let string = self.client.get_json(&url, true)?;
+        let result: StructuredStylesResponse = serde_json::from_str(&string)?;
+        Ok(result.data.style)
+    }
+
+    /// Applies the changes in the `StyleUpdate` to the subreddit.
+    pub fn update(&self, update: StyleUpdate) -> Result<(), APIError> {
+        let url = format!("{}/{}", endpoints::STRUCTURED_STYLES, self.subreddit);
+        let body = update.fields
+            .iter()
+            .map(|(name, value)| {
+                format!("{}={}", name, self.client.url_escape(value.to_owned()))
+            })
+            .collect::<Vec<String>>()
+            .join("&");
+        self.client.ensure_authenticated(|| {
+            let request = self.client
+                .request(Method::PATCH, &url, true)?
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(&body);
+            let response = self.client.send(request)?;
+            if response.status.is_success() {
+                Ok(())
+            } else {
+                Err(APIError::HTTPError(response.status))
+            }
+        })
+    }
+
+    /// Uploads an image (e.g. a PNG or JPEG file's contents) and sets it as the specified
+    /// style image. Returns the URL of the uploaded image. Requires the `media-upload` feature.
+    #[cfg(feature = "media-upload")]
+    pub fn upload_image(&self,
+                        kind: StyleImage,
+                        filename: &str,
+                        mimetype: &str,
+                        image: Vec<u8>)
+                        -> Result<String, APIError> {
+        let url = format!("{}/{}", endpoints::STYLE_ASSET_UPLOAD, self.subreddit);
+        let body = format!("filepath={}&mimetype={}&imagetype={}",
+                           self.client.url_escape(filename.to_owned()),
+                           self.client.url_escape(mimetype.to_owned()),
+                           kind);
+        let string = self.client.post_json(&url, &body, true)?;
+        let lease: StyleUploadResponse = serde_json::from_str(&string)?;
+        let lease = lease.s3_upload_lease;
+
+        let boundary = "------------------------new_rawr-upload";
+        let mut multipart = Vec::new();
+        let mut key = String::new();
+        for field in &lease.fields {
+            if field.name == "key" {
+                key = field.value.to_owned();
+            }
+            multipart.extend(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                                     boundary,
+                                     field.name,
+                                     field.value)
+                .into_bytes());
+        }
+        multipart.extend(format!("--{}\r\nContent-Disposition: form-data; name=\"file\"; \
+                                  filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                                 boundary,
+                                 filename,
+                                 mimetype)
+            .into_bytes());
+        multipart.extend(image);
+        multipart.extend(format!("\r\n--{}--\r\n", boundary).into_bytes());
+
+        let action = if lease.action.starts_with("//") {
+            format!("https:{}", lease.action)
+        } else {
+            lease.action.to_owned()
+        };
+        let request = HttpRequest::new(Method::POST, &action)
+            .header(CONTENT_TYPE, &format!("multipart/form-data; boundary={}", boundary))
+            .bytes(multipart);
+        // The lease points at an S3 bucket, not at Reddit, so the request skips the client's
+        // rate limiter and hooks.
+        let response = self.client.backend.execute(request)?;
+        if !response.status.is_success() {
+            return Err(APIError::HTTPError(response.status));
+        }
+
+        let image_url = format!("{}/{}", action, key);
+        self.update(StyleUpdate::new().image(kind, &image_url))?;
+        Ok(image_url)
+    }
+}
@@ -1,17 +1,42 @@
+use std::collections::HashMap;
+use std::slice::Iter;
+#[cfg(feature = "media-upload")]
+use std::fs;
+#[cfg(feature = "media-upload")]
+use std::path::Path;
+
+#[cfg(feature = "media-upload")]
+use http::Method;
+use http::StatusCode;
+#[cfg(feature = "media-upload")]
+use http::header::USER_AGENT;
+
 use serde_json;
 
 
+use crate::endpoints;
 use crate::responses::{listing, FlairSelectorResponse, FlairChoice};
 use crate::client::RedditClient;
 use crate::traits::{Votable, Editable, Created, Content, Approvable, Commentable, Stickable, Lockable, Reportable, Distinguishable, Flairable, Visible};
 use crate::errors::APIError;
+#[cfg(feature = "language")]
+use crate::language;
+use crate::responses::Distinguish;
 use crate::structures::user::User;
 use crate::structures::subreddit::Subreddit;
-use crate::responses::comment::{CommentData, NewComment};
-use crate::structures::comment_list::{CommentList, CommentStream};
+use crate::responses::comment::NewComment;
+use crate::structures::comment_list::{CommentList, CommentProgress};
+#[cfg(feature = "streams")]
+use crate::structures::comment_list::CommentStream;
 use crate::structures::listing::Listing;
 use crate::structures::comment::Comment;
-use crate::responses::listing::CommentResponse;
+use crate::structures::permalink::Permalink;
+#[cfg(feature = "moderation")]
+use crate::structures::removal::RemovalMessage;
+use crate::structures::snapshot::ThreadSnapshot;
+use crate::responses::listing::PreviewSource;
+#[cfg(feature = "media-upload")]
+use crate::http::HttpRequest;
 
 /// Structure representing a link post or self post (a submission) on Reddit.
 pub struct Submission<'a> {
@@ -32,6 +57,22 @@ impl<'a> Votable for Submission<'a> {
         self.data.score
     }
 
+    fn ups(&self) -> i64 {
+        self.data.ups
+    }
+
+    fn downs(&self) -> i64 {
+        self.data.downs
+    }
+
+    fn upvote_ratio(&self) -> Option<f64> {
+        self.data.upvote_ratio
+    }
+
+    fn score_hidden(&self) -> bool {
+        self.data.hide_score
+    }
+
     fn likes(&self) -> Option<bool> {
         self.data.likes
     }
@@ -61,7 +102,8 @@ impl<'a> Created for Submission<'a> {
 
 impl<'a> Editable for Submission<'a> {
     fn edited(&self) -> bool {
-        self.data.edited.as_bool().unwrap()
+        // `edited` is either `false` or the time of the edit.
+        self.data.edited.as_bool().unwrap_or(true)
     }
 
     fn edited_time(&self) -> Option<i64> {
@@ -69,10 +111,11 @@ impl<'a> Editable for Submission<'a> {
     }
 
     fn edit(&mut self, text: &str) -> Result<(), APIError> {
+        trace_span!("edit", thing = %self.data.name);
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.data.name);
-        let res = self.client.post_success("/api/editusertext", &body, false);
+        let res = self.client.post_success(endpoints::EDIT, &body, false);
         if let Ok(()) = res {
             // TODO: should we update selftext_html?
             self.data.selftext = text.to_owned();
@@ -95,7 +138,7 @@ impl<'a> Editable for Submission<'a> {
 }
 
 impl<'a> Content for Submission<'a> {
-    fn author(&self) -> User {
+    fn author(&self) -> User<'_> {
         User::new(self.client, &self.data.author)
     }
 
@@ -107,13 +150,13 @@ impl<'a> Content for Submission<'a> {
         self.data.author_flair_css_class.to_owned()
     }
 
-    fn subreddit(&self) -> Subreddit {
+    fn subreddit(&self) -> Subreddit<'_> {
         Subreddit::create_new(self.client, &self.data.subreddit)
     }
 
     fn delete(self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/del", &body, false)
+        self.client.post_success(endpoints::DELETE, &body, false)
     }
     fn name(&self) -> &str {
         &self.data.name
@@ -123,22 +166,22 @@ impl<'a> Content for Submission<'a> {
 impl<'a> Approvable for Submission<'a> {
     fn approve(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/approve", &body, false)
+        self.client.post_success(endpoints::APPROVE, &body, false)
     }
 
     fn remove(&self, spam: bool) -> Result<(), APIError> {
         let body = format!("id={}&spam={}", self.data.name, spam);
-        self.client.post_success("/api/remove", &body, false)
+        self.client.post_success(endpoints::REMOVE, &body, false)
     }
 
     fn ignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/ignore_reports", &body, false)
+        self.client.post_success(endpoints::IGNORE_REPORTS, &body, false)
     }
 
     fn unignore_reports(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        self.client.post_success("/api/unignore_reports", &body, false)
+        self.client.post_success(endpoints::UNIGNORE_REPORTS, &body, false)
     }
 }
 
@@ -147,36 +190,50 @@ impl<'a> Commentable<'a> for Submission<'a> {
         self.data.num_comments
     }
 
-    fn reply(&self, text: &str) -> Result<Comment, APIError> {
+    fn reply(&self, text: &str) -> Result<Comment<'_>, APIError> {
+        trace_span!("reply", parent = %self.data.name);
+        if self.data.archived {
+            return Err(APIError::Archived);
+        }
         let body = format!("api_type=json&text={}&thing_id={}",
                            self.client.url_escape(text.to_owned()),
                            self.name());
         //
-        let result = self.client.post_json("/api/comment", &body, false).unwrap();
-        let result: NewComment = serde_json::from_str(&*result).unwrap();
-
-        Ok(Comment::new(self.client, result.json.data.things.into_iter().next().unwrap().data))
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        let thing = result.json.data.things.into_iter().next();
+        let thing = thing.ok_or_else(|| APIError::missing("new comment"))?;
+        Ok(Comment::new(self.client, thing.data))
     }
 
     fn replies(self) -> Result<CommentList<'a>, APIError> {
         // TODO: sort type
-        let url = format!("/comments/{}", self.data.id);
-        let result = self.client.get_json(&url, false).unwrap();
-        let result: listing::CommentResponse = serde_json::from_str(&*result).unwrap();
+        let url = endpoints::comments(&self.data.id);
+        let result = self.client.get_json(&url, false)?;
+        let result: listing::CommentResponse = serde_json::from_str(&result)?;
 
         Ok(CommentList::new(self.client,
                             self.data.name.to_owned(),
                             self.data.name.to_owned(),
                             result.1.data.children))
     }
+
+    fn template_vars(&self) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert(String::from("author"), self.data.author.to_owned());
+        vars.insert(String::from("title"), self.data.title.to_owned());
+        vars.insert(String::from("permalink"), self.permalink().to_string());
+        vars.insert(String::from("subreddit"), self.data.subreddit.to_owned());
+        vars
+    }
 }
 
 impl<'a> Submission<'a> {
     /// Internal method. Get submissions from a listing instead (see `Subreddit.hot()` etc.)
-    pub fn new(client: &RedditClient, data: listing::SubmissionData) -> Submission {
+    pub fn new(client: &RedditClient, data: listing::SubmissionData) -> Submission<'_> {
         Submission {
-            client: client,
-            data: data,
+            client,
+            data,
         }
     }
 
@@ -188,11 +245,12 @@ impl<'a> Submission<'a> {
     ///
     /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
     /// let sub = client.subreddit("all");
-    /// let mut listing = sub.hot(ListingOptions::default()).expect("Could not fetch listing!");
+    /// let mut listing = sub.listing(ListingRequest::default()).expect("Could not fetch listing!");
     /// let post = listing.nth(0).unwrap();
     /// for comment in post.reply_stream() {
     ///     println!("New comment received!");
     /// }
+    #[cfg(feature = "streams")]
     pub fn reply_stream(self) -> CommentStream<'a> {
         CommentStream::new(self.client, self.data.name, self.data.id)
     }
@@ -203,6 +261,45 @@ impl<'a> Submission<'a> {
         &self.data.title
     }
 
+    /// Detects the language of the title and self text (e.g. `en`), or returns `None` if the
+    /// post is too short to tell. See the `language` module for the supported languages.
+    #[cfg(feature = "language")]
+    pub fn detected_language(&self) -> Option<&'static str> {
+        language::detect(&format!("{}\n{}", self.data.title, self.data.selftext))
+    }
+
+    /// Sends a removal message to the author explaining why this was removed. See
+    /// `RemovalMessage` for examples.
+    #[cfg(feature = "moderation")]
+    pub fn send_removal_message(&self, message: &RemovalMessage) -> Result<(), APIError> {
+        message.send(self.client, &self.data.name, &self.template_vars())
+    }
+
+    /// Gets a link to the comments page of this post. Call `no_participation()` on the result
+    /// for an `np.reddit.com` link.
+    pub fn permalink(&self) -> Permalink {
+        Permalink::new(&self.data.permalink)
+    }
+
+    /// The ID of the flair template used for this post's flair, or `None` if the post has no
+    /// flair or the flair was not chosen from a template.
+    pub fn link_flair_template_id(&self) -> Option<&str> {
+        self.data.link_flair_template_id.as_deref()
+    }
+
+    /// Removes the post flair from this post. The author of the post and moderators can do
+    /// this. Use `Flairable.flair()` to change the flair instead.
+    pub fn clear_flair(&mut self) -> Result<(), APIError> {
+        // Selecting no template removes the flair.
+        let body = format!("api_type=json&link={}&flair_template_id=", self.data.name);
+        let url = endpoints::select_flair(&self.data.subreddit);
+        self.client.post_success(&url, &body, false)?;
+        self.data.link_flair_text = None;
+        self.data.link_flair_css_class = None;
+        self.data.link_flair_template_id = None;
+        Ok(())
+    }
+
     /// This is `true` if the post is a self post, and `false` if it is a link post.
     pub fn is_self_post(&self) -> bool {
         self.data.is_self
@@ -213,6 +310,63 @@ impl<'a> Submission<'a> {
         self.data.url.to_owned()
     }
 
+    /// Picks the preview image resolution whose width is closest to `width`, from the original
+    /// image and the downscaled copies Reddit generates. Returns `None` if the post has no
+    /// preview.
+    pub fn preview(&self, width: u64) -> Option<PreviewSource> {
+        let image = self.data.preview.as_ref()?.images.first()?;
+        image.resolutions
+            .iter()
+            .chain(Some(&image.source))
+            .min_by_key(|source| (source.width as i64 - width as i64).abs())
+            .cloned()
+    }
+
+    /// Downloads the preview image closest to `width` pixels wide (see `preview()`) to the
+    /// file at `path`, using the client's `HttpBackend`. Requires the `media-upload` feature.
+    /// Falls back to the thumbnail if the post has no preview (its width and height are
+    /// returned as 0, since Reddit does not report them). The chosen image is returned.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::options::ListingRequest;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let sub = client.subreddit("wallpapers");
+    /// for (i, post) in sub.listing(ListingRequest::default()).unwrap().take(5).enumerate() {
+    ///     let image = post.download_preview(&format!("wallpaper-{}.jpg", i), 1920).unwrap();
+    ///     println!("Saved {}x{} image", image.width, image.height);
+    /// }
+    /// ```
+    #[cfg(feature = "media-upload")]
+    pub fn download_preview<P>(&self, path: P, width: u64) -> Result<PreviewSource, APIError>
+        where P: AsRef<Path>
+    {
+        let image = match self.preview(width) {
+            Some(image) => image,
+            None if self.data.thumbnail.starts_with("http") => {
+                PreviewSource {
+                    url: self.data.thumbnail.to_owned(),
+                    width: 0,
+                    height: 0,
+                }
+            }
+            None => {
+                return Err(APIError::ValidationError(format!("{} has no preview image",
+                                                             self.data.name)))
+            }
+        };
+        let request = HttpRequest::new(Method::GET, &image.url)
+            .header(USER_AGENT, self.client.user_agent());
+        // The image is on Reddit's media hosts, which do not count against the API rate limit.
+        let response = self.client.backend.execute(request)?;
+        if !response.status.is_success() {
+            return Err(APIError::HTTPError(response.status));
+        }
+        fs::write(path, &response.body)?;
+        Ok(image)
+    }
+
     /// Returns `true` if the post is marked NSFW (over 18).
     pub fn nsfw(&self) -> bool {
         self.data.over_18
@@ -222,9 +376,9 @@ impl<'a> Submission<'a> {
     /// moderator) **and** the subreddit allows NSFW posts.
     pub fn mark_nsfw(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/marknsfw", &body, false);
+        let res = self.client.post_success(endpoints::MARK_NSFW, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.over_18 = true;
         }
 
@@ -234,18 +388,154 @@ impl<'a> Submission<'a> {
     /// Sets the post as **not** NSFW (over 18).
     pub fn unmark_nsfw(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/unmarknsfw", &body, false);
+        let res = self.client.post_success(endpoints::UNMARK_NSFW, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.over_18 = false;
         }
 
         res
     }
 
+    /// The number of times this post has been viewed, if available. Reddit only exposes view
+    /// counts to moderators of the subreddit, so this is usually `None` for other users.
+    pub fn view_count(&self) -> Option<u64> {
+        self.data.view_count
+    }
+
+    /// Fetches the latest reach data for this post (views, score, upvote ratio and comment/
+    /// crosspost counts). This always sends a new request, so the values are fresher than the
+    /// ones held by this `Submission`. View counts are only returned to moderators.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let insights = post.insights().expect("Could not get insights.");
+    /// if let Some(views) = insights.view_count {
+    ///     println!("{} views", views);
+    /// }
+    /// ```
+    pub fn insights(&self) -> Result<PostInsights, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::by_id(&self.data.name));
+        let string = self.client.get_json(&url, false)?;
+        let listing: listing::Listing = serde_json::from_str(&string)?;
+        match listing.data.children.into_iter().next() {
+            Some(item) => Ok(PostInsights::new(item.data)),
+            None => Err(APIError::ExhaustedListing),
+        }
+    }
+
+    /// Locks this thread and posts a distinguished, stickied comment explaining why. The created
+    /// comment is returned. Requires moderator permissions on the subreddit.
+    ///
+    /// The thread is locked before the comment is posted, so if posting the comment fails the
+    /// thread stays locked and the error is returned.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let mut post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// post.lock_with_comment("This thread has been locked because it is off-topic.")
+    ///     .expect("Could not lock thread.");
+    /// ```
+    pub fn lock_with_comment(&mut self, text: &str) -> Result<Comment<'a>, APIError> {
+        self.lock()?;
+        let body = format!("api_type=json&text={}&thing_id={}",
+                           self.client.url_escape(text.to_owned()),
+                           self.name());
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        let data = match result.json.data.things.into_iter().next() {
+            Some(thing) => thing.data,
+            None => return Err(APIError::ExhaustedListing),
+        };
+        let mut comment = Comment::new(self.client, data);
+        comment.stick()?;
+        Ok(comment)
+    }
+
+    /// Fetches this post and its entire comment tree into a single serializable
+    /// `ThreadSnapshot`, for archival or analysis. Up to `budget` extra requests are made to
+    /// expand 'load more comments' links; if the budget runs out, `ThreadSnapshot.complete` is
+    /// `false`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let snapshot = post.snapshot(20).expect("Could not fetch thread.");
+    /// let json = serde_json::to_string(&snapshot).unwrap();
+    /// ```
+    pub fn snapshot(&self, budget: usize) -> Result<ThreadSnapshot, APIError> {
+        ThreadSnapshot::fetch(self.client, &self.data.id, budget)
+    }
+
+    /// Fetches the replies to this post like `Submission.replies()`, and calls `callback` with
+    /// the number of comments loaded so far once the first page has arrived, and again every time
+    /// a 'load more comments' link is expanded. This lets clients show a progress bar while
+    /// consuming a very large thread.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let comments = post.replies_with_progress(|progress| {
+    ///     println!("{} of {:?} comments loaded, {} 'more' links left",
+    ///              progress.loaded, progress.expected, progress.pending);
+    /// }).expect("Could not get comments.");
+    /// let comments = comments.collect::<Vec<_>>();
+    /// ```
+    pub fn replies_with_progress<F>(self, callback: F) -> Result<CommentList<'a>, APIError>
+        where F: FnMut(&CommentProgress) + 'a
+    {
+        let expected = self.data.num_comments;
+        Ok(self.replies()?.with_progress(Some(expected), callback))
+    }
+
     fn vote(&self, dir: i8) -> Result<(), APIError> {
+        trace_span!("vote", thing = %self.data.name, dir = dir);
+        if self.data.archived {
+            return Err(APIError::Archived);
+        }
         let body = format!("dir={}&id={}", dir, self.data.name);
-        self.client.post_success("/api/vote", &body, false)
+        self.client.post_success(endpoints::VOTE, &body, false)
+    }
+}
+
+/// Per-post reach data, as returned by `Submission.insights()`. Useful for moderators who want
+/// to collect analytics about how far posts in their subreddit have spread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostInsights {
+    /// The full name of the post (e.g. `t3_4uule8`).
+    pub name: String,
+    /// The number of views, if you are a moderator of the subreddit.
+    pub view_count: Option<u64>,
+    /// The (fuzzed) points score of the post.
+    pub score: i64,
+    /// The ratio of upvotes to total votes, if available.
+    pub upvote_ratio: Option<f64>,
+    /// The number of comments on the post.
+    pub num_comments: u64,
+    /// The number of crossposts of the post, if available.
+    pub num_crossposts: Option<u64>,
+}
+
+impl PostInsights {
+    /// Creates a `PostInsights` from the raw submission data.
+    pub fn new(data: listing::SubmissionData) -> PostInsights {
+        PostInsights {
+            name: data.name,
+            view_count: data.view_count,
+            score: data.score,
+            upvote_ratio: data.upvote_ratio,
+            num_comments: data.num_comments,
+            num_crossposts: data.num_crossposts,
+        }
     }
 }
 
@@ -257,9 +547,9 @@ impl<'a> Stickable for Submission<'a> {
 
     fn stick(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&id={}&state=true", self.data.name);
-        let res = self.client.post_success("/api/set_subreddit_sticky", &body, false);
+        let res = self.client.post_success(endpoints::SET_SUBREDDIT_STICKY, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.stickied = true;
         }
 
@@ -268,9 +558,9 @@ impl<'a> Stickable for Submission<'a> {
 
     fn unstick(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&id={}&state=false", self.data.name);
-        let res = self.client.post_success("/api/set_subreddit_sticky", &body, false);
+        let res = self.client.post_success(endpoints::SET_SUBREDDIT_STICKY, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.stickied = false;
         }
 
@@ -285,9 +575,9 @@ impl<'a> Lockable for Submission<'a> {
 
     fn lock(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/lock", &body, false);
+        let res = self.client.post_success(endpoints::LOCK, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.locked = true;
         }
 
@@ -296,9 +586,9 @@ impl<'a> Lockable for Submission<'a> {
 
     fn unlock(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/unlock", &body, false);
+        let res = self.client.post_success(endpoints::UNLOCK, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.locked = false;
         }
 
@@ -308,10 +598,9 @@ impl<'a> Lockable for Submission<'a> {
 
 impl<'a> Reportable for Submission<'a> {
     fn report(&self, reason: &str) -> Result<(), APIError> {
-        let body = format!("api_type=json&thing_id={}&reason={}",
-                           self.data.name,
-                           self.client.url_escape(reason.to_owned()));
-        self.client.post_success("/api/report", &body, false)
+        let reason = self.subreddit().report_reason(reason)?;
+        let body = format!("api_type=json&thing_id={}&{}", self.data.name, reason);
+        self.client.post_success(endpoints::REPORT, &body, false)
     }
 
     fn report_count(&self) -> Option<u64> {
@@ -320,22 +609,22 @@ impl<'a> Reportable for Submission<'a> {
 }
 
 impl<'a> Distinguishable for Submission<'a> {
-    fn distinguished(&self) -> Option<String> {
-        self.data.distinguished.to_owned()
+    fn distinguished(&self) -> Option<Distinguish> {
+        self.data.distinguished
     }
 
     fn distinguish(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=yes&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
-            self.data.distinguished = Some(String::from("moderator"));
+            self.data.distinguished = Some(Distinguish::Moderator);
         }
         res
     }
 
     fn undistinguish(&mut self) -> Result<(), APIError> {
         let body = format!("api_type=json&how=no&id={}", self.data.name);
-        let res = self.client.post_success("/api/distinguish", &body, false);
+        let res = self.client.post_success(endpoints::DISTINGUISH, &body, false);
         if let Ok(()) = res {
             self.data.distinguished = None;
         }
@@ -354,18 +643,17 @@ impl<'a> Flairable for Submission<'a> {
 
     fn flair_options(&self) -> Result<FlairList, APIError> {
         let body = format!("link={}", self.data.name);
-        let url = format!("/r/{}/api/flairselector", self.data.subreddit);
-        let result = self.client
-            .post_json(&url, &body, false).unwrap();
-        let result: FlairSelectorResponse = serde_json::from_str(&*result).unwrap();
+        let url = endpoints::flair_selector(&self.data.subreddit);
+        let result = self.client.post_json(&url, &body, false)?;
+        let result: FlairSelectorResponse = serde_json::from_str(&result)?;
         Ok(FlairList::new(result.choices))
     }
 
-    fn flair(&self, template: &str) -> Result<(), APIError> {
+    fn flair<T: AsRef<str>>(&self, template: T) -> Result<(), APIError> {
         let body = format!("api_type=json&link={}&flair_template_id={}",
                            self.data.name,
-                           template);
-        let url = format!("/r/{}/api/selectflair", self.data.subreddit);
+                           template.as_ref());
+        let url = endpoints::select_flair(&self.data.subreddit);
         self.client.post_success(&url, &body, false)
     }
 }
@@ -377,9 +665,9 @@ impl<'a> Visible for Submission<'a> {
 
     fn hide(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/hide", &body, false);
+        let res = self.client.post_success(endpoints::HIDE, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.hidden = true;
         }
 
@@ -388,9 +676,9 @@ impl<'a> Visible for Submission<'a> {
 
     fn show(&mut self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
-        let res = self.client.post_success("/api/unhide", &body, false);
+        let res = self.client.post_success(endpoints::UNHIDE, &body, false);
 
-        if let Ok(_) = res {
+        if res.is_ok() {
             self.data.hidden = false;
         }
 
@@ -411,16 +699,39 @@ impl FlairList {
         FlairList { flairs: choices }
     }
 
+    /// Iterates over the available flairs without consuming the `FlairList`. Each `FlairChoice`
+    /// can be passed directly to `Flairable.flair()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::options::ListingRequest;
+    /// use new_rawr::traits::Flairable;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("learnprogramming");
+    /// let post = sub.listing(ListingRequest::default()).unwrap().next().unwrap();
+    /// let options = post.flair_options().unwrap();
+    /// for choice in options.iter() {
+    ///     println!("{} ({})", choice.flair_text, choice.flair_template_id);
+    /// }
+    /// if let Some(choice) = options.iter().find(|c| c.flair_text == "tutorial") {
+    ///     post.flair(choice).unwrap();
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, FlairChoice> {
+        self.flairs.iter()
+    }
+
     /// Finds the flair with the specified text, consuming the `FlairList`.
     /// # Examples
     /// ```rust,no_run
     /// use new_rawr::client::RedditClient;
     /// use new_rawr::auth::PasswordAuthenticator;
-    /// use new_rawr::options::ListingOptions;
+    /// use new_rawr::options::ListingRequest;
     /// use new_rawr::traits::Flairable;
     /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
     /// let sub = client.subreddit("learnprogramming");
-    /// let post = sub.hot(ListingOptions::default()).unwrap().next().unwrap();
+    /// let post = sub.listing(ListingRequest::default()).unwrap().next().unwrap();
     /// // NOTE: this would 403 unless you are a moderator or the creator of the post.
     /// let tutorial_flair = post.flair_options().unwrap().find_text("tutorial").unwrap();
     /// post.flair(&tutorial_flair);
@@ -436,6 +747,15 @@ impl FlairList {
     }
 }
 
+impl<'a> IntoIterator for &'a FlairList {
+    type Item = &'a FlairChoice;
+    type IntoIter = Iter<'a, FlairChoice>;
+
+    fn into_iter(self) -> Iter<'a, FlairChoice> {
+        self.flairs.iter()
+    }
+}
+
 /// A lazy object representing a submission. Used by the `Client.get_by_id()` method until the
 /// data is specified by the user (we don't know if they want the `Submission` or `CommentList`
 /// yet). The `LazySubmission` object is consumed when performing either of these actions.
@@ -448,28 +768,26 @@ impl<'a> LazySubmission<'a> {
     /// Internal method. Use `Client.get_by_id()` instead.
     pub fn new(client: &'a RedditClient, id: &str) -> LazySubmission<'a> {
         LazySubmission {
-            client: client,
+            client,
             id: id.to_owned(),
         }
     }
 
     /// Fetches the `Submission` with this ID, in order to access post title, body, link and
-    /// creation time.
+    /// creation time. Fails with `APIError::HTTPError(NotFound)` if there is no such post.
     pub fn get(self) -> Result<Submission<'a>, APIError> {
-        let url = format!("/by_id/{}?raw_json=1", self.id);
-        let string = self.client
-            .get_json(&url, false).unwrap();
-        let string: listing::Listing = serde_json::from_str(&*string).unwrap();
+        let url = format!("{}?raw_json=1", endpoints::by_id(&self.id));
+        let string = self.client.get_json(&url, false)?;
+        let string: listing::Listing = serde_json::from_str(&string)?;
         let mut string = Listing::new(self.client, url, string.data);
-        Ok(string.next().unwrap())
+        string.next().ok_or(APIError::HTTPError(StatusCode::NOT_FOUND))
     }
 
     /// Fetches a `CommentList` with replies to this submission.
     pub fn replies(self) -> Result<CommentList<'a>, APIError> {
-        let url = format!("/comments/{}?raw_json=1", self.id.split('_').nth(1).unwrap());
-        let string = self.client
-            .get_json(&url, false).unwrap();
-        let string :listing::CommentResponse =serde_json::from_str(&*string).unwrap();
+        let url = format!("{}?raw_json=1", endpoints::comments(self.id.trim_start_matches("t3_")));
+        let string = self.client.get_json(&url, false)?;
+        let string: listing::CommentResponse = serde_json::from_str(&string)?;
         Ok(CommentList::new(self.client,
                             self.id.to_owned(),
                             self.id.to_owned(),
@@ -1,15 +1,49 @@
-#![allow(unknown_lints, wrong_self_convention, new_ret_no_self)]
+#![allow(unknown_lints, clippy::wrong_self_convention, clippy::new_ret_no_self)]
 
+use crate::endpoints;
 use crate::client::RedditClient;
-use crate::options::{ListingOptions, TimeFilter, LinkPost, SelfPost};
+use crate::clock;
+use crate::options::{ListingOptions, ListingRequest, ListingSort, TimeFilter, LinkPost, SelfPost,
+                     ReportReason};
+#[cfg(feature = "moderation")]
+use crate::options::{ModPermission, ReplyIdentity};
+#[cfg(feature = "moderation")]
+use crate::responses::comment::NewComment;
+#[cfg(feature = "moderation")]
+use crate::structures::comment::Comment;
+use crate::responses::listing::SubredditRule;
+use crate::responses::{FlairCsvResult, FlairListResponse, LinkFlairTemplate, UserFlair};
+#[cfg(feature = "moderation")]
+use crate::responses::{ModeratorData, ModeratorListResponse, RelationshipData,
+                       RelationshipListResponse};
 use crate::structures::listing::Listing;
 use crate::responses::listing;
 use crate::traits::Created;
+#[cfg(feature = "moderation")]
+use crate::traits::{Distinguishable, Stickable};
 use crate::errors::APIError;
+#[cfg(feature = "streams")]
 use crate::structures::listing::PostStream;
-use hyper::Body;
 use crate::structures::user::UserListing;
-use std::error::Error;
+use crate::structures::wiki::WikiPage;
+use crate::structures::crawl::{CheckpointStore, TopCrawl};
+use crate::structures::ranking::RankTracker;
+use crate::structures::stats::SubredditStats;
+#[cfg(feature = "moderation")]
+use crate::structures::stats::ModActivityReport;
+#[cfg(feature = "moderation")]
+use crate::structures::modlog::ModAction;
+#[cfg(all(feature = "moderation", feature = "streams"))]
+use crate::structures::modlog::ModLogStream;
+#[cfg(feature = "moderation")]
+use crate::responses::modlog::ModLogListing;
+use crate::structures::styles::SubredditStyles;
+#[cfg(feature = "moderation")]
+use crate::structures::moderators::{plan_moderator_sync, ModSyncReport};
+#[cfg(feature = "moderation")]
+use crate::structures::automoderator::AutoModeratorConfig;
+#[cfg(feature = "moderation")]
+use crate::structures::usernotes::UserNotesPage;
 
 /// The `Subreddit` struct represents a subreddit and allows access to post listings
 /// and data about the subreddit.
@@ -26,39 +60,43 @@ impl<'a> PartialEq for Subreddit<'a> {
 }
 
 impl<'a> Subreddit<'a> {
-    fn get_feed(&self, ty: &str, opts: ListingOptions) -> Result<Listing, APIError> {
-        // We do not include the after/before parameter here so the pagination can adjust it later
-        // on.
-        let uri = format!("/r/{}/{}limit={}&raw_json=1", self.name, ty, opts.batch);
-        let full_uri = format!("{}&{}", uri, opts.anchor);
-        let string = self.client
-            .get_json(&full_uri, false).unwrap();
-        let string: listing::Listing = serde_json::from_str(&*string).unwrap();
-        Ok(Listing::new(self.client, uri, string.data))
-    }
-
     /// Creates a `Subreddit` from a client and the subreddit's name. Do not use this directly -
     /// use `Client.subreddit(NAME)` instead.
     pub fn create_new(client: &'a RedditClient, name: &str) -> Subreddit<'a> {
         Subreddit {
-            client: client,
+            client,
             name: name.to_owned(),
         }
     }
 
-    /// Gets a listing of the hot feed for this subreddit. The first page may include some sticky
-    /// posts in addtion to the expected posts.
+    /// Gets a listing of posts in this subreddit: the hot, new, rising, top or controversial
+    /// feed, or the results of a search, as configured by the `ListingRequest`. The first page of
+    /// the hot feed may include some sticky posts in addition to the expected posts.
     /// # Examples
     /// ```
     /// use new_rawr::client::RedditClient;
-    /// use new_rawr::options::ListingOptions;
+    /// use new_rawr::options::{ListingRequest, ListingSort, TimeFilter};
     /// use new_rawr::auth::AnonymousAuthenticator;
     /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
     /// let sub = client.subreddit("askreddit");
-    /// let hot = sub.hot(ListingOptions::default());
+    /// let hot = sub.listing(ListingRequest::default());
+    /// let top = sub.listing(ListingRequest::new(ListingSort::Top).time(TimeFilter::Week));
+    /// let search = sub.listing(ListingRequest::search("rust").limit(5));
     /// ```
-    pub fn hot(&self, opts: ListingOptions) -> Result<Listing, APIError> {
-        self.get_feed("hot?", opts)
+    pub fn listing(&self, request: ListingRequest) -> Result<Listing<'_>, APIError> {
+        // We do not include the after/before parameter in the stem so the pagination can adjust
+        // it later on.
+        let uri = request.query_stem(&self.name)?;
+        let full_uri = format!("{}&{}", uri, request.anchor);
+        let string = self.client.get_json(&full_uri, false)?;
+        let string: listing::Listing = serde_json::from_str(&string)?;
+        Ok(Listing::new(self.client, uri, string.data))
+    }
+
+    /// Gets a listing of the hot feed for this subreddit.
+    #[deprecated(note = "use `Subreddit.listing(ListingRequest::default())` instead")]
+    pub fn hot(&self, opts: ListingOptions) -> Result<Listing<'_>, APIError> {
+        self.listing(ListingRequest::from(opts))
     }
 
     /// Gets a `PostStream` of the new posts in the subreddit. The iterator will yield values
@@ -74,76 +112,107 @@ impl<'a> Subreddit<'a> {
     ///
     /// }
     /// ```
+    #[cfg(feature = "streams")]
     pub fn new_stream(self) -> PostStream<'a> {
-        let url = format!("/r/{}/new?limit=5", self.name);
-        PostStream::new(&self.client, url)
+        let url = format!("{}?limit=5", endpoints::subreddit_listing(&self.name, "new"));
+        PostStream::new(self.client, url)
+    }
+
+    /// Gets a `ModLogStream` of new entries in the moderation log of this subreddit. You must be
+    /// a moderator of the subreddit. See `ModLogStream` for examples.
+    #[cfg(all(feature = "moderation", feature = "streams"))]
+    pub fn mod_log_stream(self) -> ModLogStream<'a> {
+        let url = format!("{}?limit=25&raw_json=1", endpoints::mod_log(&self.name));
+        ModLogStream::new(self.client, url)
     }
 
     /// Gets a listing of the new feed for this subreddit.
-    /// # Examples
-    /// ```
-    /// use new_rawr::client::RedditClient;
-    /// use new_rawr::options::ListingOptions;
-    /// use new_rawr::traits::Content;
-    /// use new_rawr::auth::AnonymousAuthenticator;
-    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
-    /// let sub = client.subreddit("programming");
-    /// let mut new = sub.new(ListingOptions::default()).expect("Could not get new feed");
-    /// assert_eq!(new.next().unwrap().subreddit().name, "programming");
-    /// ```
-    pub fn new(&self, opts: ListingOptions) -> Result<Listing, APIError> {
-        self.get_feed("new?", opts)
+    #[deprecated(note = "use `Subreddit.listing(ListingRequest::new(ListingSort::New))` instead")]
+    pub fn new(&self, opts: ListingOptions) -> Result<Listing<'_>, APIError> {
+        self.listing(ListingRequest::from(opts).sort(ListingSort::New))
     }
 
-    /// Gets a listing of the rising feed for this subreddit. Usually much shorter than the other
-    /// listings; may be empty.
-    /// # Examples
-    /// ```ignore
-    /// use new_rawr::client::RedditClient;
-    /// use new_rawr::options::ListingOptions;
-    /// use new_rawr::auth::AnonymousAuthenticator;
-    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
-    /// let sub = client.subreddit("thanksobama");
-    /// let rising = sub.rising(ListingOptions::default()).unwrap();
-    /// assert_eq!(rising.count(), 0);
-    /// ```
-    pub fn rising(&self, opts: ListingOptions) -> Result<Listing, APIError> {
-        self.get_feed("rising?", opts)
+    /// Gets a listing of the rising feed for this subreddit.
+    #[deprecated(note = "use `Subreddit.listing(ListingRequest::new(ListingSort::Rising))` instead")]
+    pub fn rising(&self, opts: ListingOptions) -> Result<Listing<'_>, APIError> {
+        self.listing(ListingRequest::from(opts).sort(ListingSort::Rising))
     }
 
+    /// Gets a listing of the top feed for this subreddit.
+    #[deprecated(note = "use `Subreddit.listing(ListingRequest::new(ListingSort::Top).time(..))` \
+                         instead")]
+    pub fn top(&self, opts: ListingOptions, time: TimeFilter) -> Result<Listing<'_>, APIError> {
+        self.listing(ListingRequest::from(opts).sort(ListingSort::Top).time(time))
+    }
 
-    /// Gets a listing of the top feed for this subreddit. Also requires a time filter (
-    /// `new_rawr::options::TimeFilter`) which is equivalent to the "links from: all time" dropdown
-    /// on the website.
-    /// # Examples
-    /// ```ignore
-    /// use new_rawr::client::RedditClient;
-    /// use new_rawr::options::{ListingOptions, TimeFilter};
-    /// use new_rawr::auth::AnonymousAuthenticator;
-    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
-    /// let sub = client.subreddit("thanksobama");
-    /// let mut top = sub.top(ListingOptions::default(), TimeFilter::AllTime)
-    ///     .expect("Request failed");
-    /// assert_eq!(top.next().unwrap().title(), "Thanks Me");
-    /// ```
-    pub fn top(&self, opts: ListingOptions, time: TimeFilter) -> Result<Listing, APIError> {
-        let path = format!("top?{}&", time);
-        self.get_feed(&path, opts)
+    /// Crawls the top posts of this subreddit across every time filter, yielding each post once
+    /// and saving progress to the `CheckpointStore` so the crawl can resume after a failure. See
+    /// `TopCrawl` for examples.
+    pub fn top_all_time_crawl<S: CheckpointStore>(&self, store: S) -> TopCrawl<'a, S> {
+        TopCrawl::new(self.client, &self.name, store)
     }
 
-    /// Gets a listing of the controversial feed for this subreddit. Also requires a time filter (
-    /// `new_rawr::options::TimeFilter`) which is equivalent to the "links from: all time" dropdown
-    /// on the website.
+    /// Tracks the top `top` posts of this subreddit's hot listing, reporting when submissions
+    /// enter, leave or move. See `RankTracker` for examples.
+    pub fn rank_tracker(&self, top: usize) -> RankTracker<'a> {
+        RankTracker::new(self.client, &self.name, top)
+    }
+
+    /// Gets a listing of the controversial feed for this subreddit.
+    #[deprecated(note = "use `Subreddit.listing(ListingRequest::new(ListingSort::Controversial)\
+                         .time(..))` instead")]
     pub fn controversial(&self,
                          opts: ListingOptions,
                          time: TimeFilter)
-                         -> Result<Listing, APIError> {
-        let path = format!("controversial?{}&", time);
-        self.get_feed(&path, opts)
+                         -> Result<Listing<'_>, APIError> {
+        self.listing(ListingRequest::from(opts).sort(ListingSort::Controversial).time(time))
+    }
+
+    /// Crawls the new queue of this subreddit back to `days` days ago, and aggregates the posts
+    /// into a `SubredditStats` report (posts per day, top authors, flair distribution and
+    /// average score). Large subreddits may need many requests to crawl.
+    pub fn stats(&self, days: u64) -> Result<SubredditStats, APIError> {
+        let now = clock::unix_time()?.as_secs() as i64;
+        let since = now - (days as i64) * 86400;
+        let request = ListingRequest::new(ListingSort::New).limit(100);
+        Ok(SubredditStats::collect(self.listing(request)?, since))
+    }
+
+    /// Crawls the moderation log of this subreddit back to `days` days ago, and counts the
+    /// actions of each type taken by each moderator. You must be a moderator of the subreddit.
+    /// See `ModActivityReport` for an example.
+    #[cfg(feature = "moderation")]
+    pub fn mod_activity(&self, days: u64) -> Result<ModActivityReport, APIError> {
+        let now = clock::unix_time()?.as_secs() as i64;
+        let since = now - (days as i64) * 86400;
+        let mut actions = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let mut url = format!("{}?limit=500&raw_json=1", endpoints::mod_log(&self.name));
+            if let Some(ref after) = after {
+                url = format!("{}&after={}", url, after);
+            }
+            let string = self.client.get_json(&url, false)?;
+            let page: ModLogListing = serde_json::from_str(&string)?;
+            let mut reached_start = false;
+            for child in page.data.children {
+                let action = ModAction { data: child.data };
+                reached_start = action.created_utc() < since;
+                actions.push(action);
+            }
+            after = page.data.after;
+            if reached_start || after.is_none() {
+                break;
+            }
+        }
+        Ok(ModActivityReport::collect(actions.into_iter(), since))
     }
 
     /// Submits a link post to this subreddit using the specified parameters. If the link has
     /// already been posted, this will fail unless you specifically allow reposts.
+    ///
+    /// If the subreddit requires a post flair and none was set with `LinkPost::flair()`, this
+    /// fails with `APIError::ValidationError` listing the available flair templates.
     /// # Examples
     /// ## Allowing a link to be reposted
     /// ```
@@ -161,16 +230,24 @@ impl<'a> Subreddit<'a> {
     /// sub.submit_link(post).expect("Posting failed!");
     /// ```
     pub fn submit_link(&self, post: LinkPost) -> Result<(), APIError> {
-        let body = format!("api_type=json&extension=json&kind=link&resubmit={}&sendreplies=true&\
-                            sr={}&title={}&url={}",
+        trace_span!("submit", subreddit = %self.name, kind = "link");
+        let body = format!("api_type=json&extension=json&kind=link&resubmit={}&sr={}&title={}\
+                            &url={}{}",
                            post.resubmit,
                            self.name,
                            self.client.url_escape(post.title.to_owned()),
-                           self.client.url_escape(post.link.to_owned()));
-        self.client.post_success("/api/submit", &body, false)
+                           self.client.url_escape(post.link.to_owned()),
+                           post.options);
+        let body = format!("{}{}", body, self.flair_param(&post.flair_id)?);
+        self.client.post_success(endpoints::SUBMIT, &body, false)
     }
 
     /// Submits a text post (self post) to this subreddit using the specified title and body.
+    /// Like `submit_link()`, this fails early if the subreddit requires a post flair and none was
+    /// set with `SelfPost::flair()`.
+    ///
+    /// Tags such as OC, spoiler and NSFW are set as part of the submission with
+    /// `SelfPost::options()` (or `LinkPost::options()`), rather than with separate requests.
     /// # Examples
     /// ## Submitting a post
     /// ```rust,ignore
@@ -183,21 +260,57 @@ impl<'a> Subreddit<'a> {
     /// sub.submit_text(post).expect("Posting failed!");
     /// ```
     pub fn submit_text(&self, post: SelfPost) -> Result<(), APIError> {
-        let body = format!("api_type=json&extension=json&kind=self&sendreplies=true&sr={}\
-                            &title={}&text={}",
+        trace_span!("submit", subreddit = %self.name, kind = "self");
+        let body = format!("api_type=json&extension=json&kind=self&sr={}&title={}&text={}{}",
                            self.name,
                            self.client.url_escape(post.title),
-                           self.client.url_escape(post.text));
-        self.client.post_success("/api/submit", &body, false)
+                           self.client.url_escape(post.text),
+                           post.options);
+        let body = format!("{}{}", body, self.flair_param(&post.flair_id)?);
+        self.client.post_success(endpoints::SUBMIT, &body, false)
+    }
+
+    /// Fetches the post flair templates that can be applied to posts in this subreddit.
+    pub fn link_flair_templates(&self) -> Result<Vec<LinkFlairTemplate>, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::link_flair(&self.name));
+        let string = self.client.get_json(&url, true)?;
+        Ok(serde_json::from_str(&string)?)
+    }
+
+    /// Returns `true` if posts in this subreddit must have a post flair. The requirements are
+    /// fetched once per subreddit and then cached by the client (see `RedditClient.clear_cache()`).
+    pub fn flair_required(&self) -> Result<bool, APIError> {
+        Ok(self.client.post_requirements(&self.name)?.is_flair_required)
+    }
+
+    /// Builds the flair parameter for a submission. If no flair was chosen but the subreddit
+    /// requires one, fails with an error listing the available templates so the post is not
+    /// rejected by Reddit after the fact. If the requirements cannot be fetched (e.g. with an
+    /// authenticator that does not support OAuth), the post is submitted as it is.
+    fn flair_param(&self, flair_id: &Option<String>) -> Result<String, APIError> {
+        if let Some(ref flair_id) = *flair_id {
+            return Ok(format!("&flair_id={}", self.client.url_escape(flair_id.to_owned())));
+        }
+        if !self.flair_required().unwrap_or(false) {
+            return Ok(String::new());
+        }
+        let templates = self.link_flair_templates()
+            .map(|templates| {
+                templates.into_iter()
+                    .map(|template| format!("'{}' ({})", template.text, template.id))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|_| String::from("(the templates could not be fetched)"));
+        Err(APIError::ValidationError(format!("/r/{} requires a post flair; set one of: {}",
+                                              self.name,
+                                              templates)))
     }
     /// Invites a new member to the subreddit.
     pub fn invite_member(&self, username: String) -> Result<(), APIError> {
-        let path = format!("/r/{}/api/friend", self.name);
+        let path = endpoints::friend(&self.name);
         let body = format!("name={}&type=contributor", username);
-        let result = self.client.post_json(&*path, &body, false);
-        if result.is_err() {
-            return Err(result.err().unwrap());
-        }
+        self.client.post_json(&path, &body, false)?;
         Ok(())
     }
 
@@ -213,38 +326,339 @@ impl<'a> Subreddit<'a> {
     /// assert_eq!(learn_programming.display_name(), "learnprogramming");
     /// ```
     pub fn about(&self) -> Result<SubredditAbout, APIError> {
-        let url = format!("/r/{}/about?raw_json=1", self.name);
+        let url = format!("{}?raw_json=1", endpoints::subreddit_about(&self.name));
 
-        let string = self.client
-            .get_json(&url, false).unwrap();
-        let string: listing::SubredditAboutData = serde_json::from_str(&*string).unwrap();
+        let string = self.client.get_json(&url, false)?;
+        let string: listing::SubredditAboutData = serde_json::from_str(&string)?;
         Ok(SubredditAbout::new(string))
     }
     ///  Get users
-    pub fn contributors(&self) -> Result<UserListing, APIError> {
-        let url = format!("/r/{}/about/contributors?raw_json=1", self.name);
-        let string = self.client
-            .get_json(&url, false).unwrap();
-        let json: Result<listing::UserListing,  serde_json::Error> = serde_json::from_str(string.as_str());
-        if json.is_err() {
-            println!("{}", &json.err().unwrap().to_string());
-            return Err(APIError::ExhaustedListing);
-        }else {
-           return Ok(UserListing::new(self.client, url, json.unwrap()));
+    pub fn contributors(&self) -> Result<UserListing<'_>, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::contributors(&self.name));
+        let string = self.client.get_json(&url, false)?;
+        let json: listing::UserListing = serde_json::from_str(&string)?;
+        Ok(UserListing::new(self.client, url, json))
+    }
+
+    /// Fetches the moderators of this subreddit and their permissions.
+    #[cfg(feature = "moderation")]
+    pub fn moderators(&self) -> Result<Vec<ModeratorData>, APIError> {
+        let url = endpoints::moderators(&self.name);
+        let string = self.client.get_json(&url, false)?;
+        let list: ModeratorListResponse = serde_json::from_str(&string)?;
+        Ok(list.data.children)
+    }
+
+    /// Checks whether a user is banned from this subreddit, by searching the ban list for them
+    /// rather than fetching the whole list. This requires moderator access.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("new_rawr");
+    /// if sub.is_banned("spammer").unwrap() && !sub.is_muted("spammer").unwrap() {
+    ///     println!("spammer can still send modmail");
+    /// }
+    /// ```
+    #[cfg(feature = "moderation")]
+    pub fn is_banned(&self, username: &str) -> Result<bool, APIError> {
+        Ok(self.banned_user(username)?.is_some())
+    }
+
+    /// Fetches a user's ban from this subreddit (with its reason, and the days left for a
+    /// temporary ban), or `None` if they are not banned. This requires moderator access.
+    #[cfg(feature = "moderation")]
+    pub fn banned_user(&self, username: &str) -> Result<Option<RelationshipData>, APIError> {
+        self.find_relationship("banned", username)
+    }
+
+    /// Checks whether a user is muted in this subreddit's modmail, by searching the mute list for
+    /// them. This requires moderator access.
+    #[cfg(feature = "moderation")]
+    pub fn is_muted(&self, username: &str) -> Result<bool, APIError> {
+        Ok(self.find_relationship("muted", username)?.is_some())
+    }
+
+    #[cfg(feature = "moderation")]
+    fn find_relationship(&self, list: &str, username: &str)
+                         -> Result<Option<RelationshipData>, APIError> {
+        let url = format!("{}?user={}&limit=1",
+                          endpoints::relationships(&self.name, list),
+                          self.client.url_escape(username.to_owned()));
+        let string = self.client.get_json(&url, false)?;
+        let list: RelationshipListResponse = serde_json::from_str(&string)?;
+        Ok(list.data
+            .children
+            .into_iter()
+            .find(|user| user.name.eq_ignore_ascii_case(username)))
+    }
+
+    /// Brings the moderator list in line with the desired list of moderators and permissions,
+    /// by inviting missing moderators, changing permissions and removing moderators who are not
+    /// listed. The logged-in account is never changed or removed. See `plan_moderator_sync()` to
+    /// preview the changes without making them.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::ModPermission;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let team: Vec<(&str, &[ModPermission])> = vec![("alice", &[ModPermission::All]),
+    ///                                                ("bob", &[ModPermission::Posts])];
+    /// for name in &["network_one", "network_two"] {
+    ///     let report = client.subreddit(name).sync_moderators(&team).expect("Could not sync");
+    ///     for (change, err) in report.failed {
+    ///         println!("/r/{}: {:?} failed: {:?}", name, change, err);
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "moderation")]
+    pub fn sync_moderators(&self, desired: &[(&str, &[ModPermission])])
+                           -> Result<ModSyncReport, APIError> {
+        let current = self.moderators()?;
+        let me = self.client.get_authenticator().username();
+        let mut report = ModSyncReport::default();
+        for change in plan_moderator_sync(&current, desired, me.as_deref()) {
+            match change.apply(self.client, &self.name) {
+                Ok(()) => report.applied.push(change),
+                Err(err) => report.failed.push((change, err)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Replies to a submission or comment in this subreddit (identified by its full name, e.g.
+    /// `t3_4uule8`) as a moderator, with the reply distinguished (and stickied, for
+    /// `ReplyIdentity::StickiedModerator`). You must be a moderator of the subreddit.
+    ///
+    /// Reddit does not allow replying as the subreddit itself, except in removal messages, so
+    /// `ReplyIdentity::Subreddit` fails with `APIError::Unsupported` without sending anything.
+    /// Use `RemovalMessageType::PublicAsSubreddit` with a `RemovalMessage` instead.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::ReplyIdentity;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("new_rawr");
+    /// sub.reply_as("t3_4uule8", "Please keep discussion civil.", ReplyIdentity::StickiedModerator)
+    ///     .expect("Could not reply");
+    /// ```
+    #[cfg(feature = "moderation")]
+    pub fn reply_as(&self,
+                    parent: &str,
+                    text: &str,
+                    identity: ReplyIdentity)
+                    -> Result<Comment<'a>, APIError> {
+        if identity == ReplyIdentity::Subreddit {
+            return Err(APIError::Unsupported(String::from("Reddit only allows replying as the \
+                subreddit in removal messages; use RemovalMessageType::PublicAsSubreddit")));
+        }
+        if identity == ReplyIdentity::StickiedModerator && !parent.starts_with("t3_") {
+            return Err(APIError::ValidationError(String::from("Only replies to submissions can \
+                be stickied")));
+        }
+        let body = format!("api_type=json&text={}&thing_id={}",
+                           self.client.url_escape(text.to_owned()),
+                           parent);
+        let result = self.client.post_json(endpoints::COMMENT, &body, false)?;
+        let result: NewComment = serde_json::from_str(&result)?;
+        let thing = result.json.data.things.into_iter().next();
+        let thing = thing.ok_or_else(|| APIError::missing("new comment"))?;
+        let mut comment = Comment::new(self.client, thing.data);
+        if identity == ReplyIdentity::StickiedModerator {
+            // Stickying a comment also distinguishes it.
+            comment.stick()?;
+        } else {
+            comment.distinguish()?;
+        }
+        Ok(comment)
+    }
+
+    /// Starts an internal modmail conversation (a 'mod discussion') that is only visible to the
+    /// moderators of this subreddit. Useful for bots that report statistics to the mod team.
+    /// This requires an OAuth-based authenticator.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("new_rawr");
+    /// sub.modmail_broadcast("Daily stats", "42 posts today.").expect("Could not send modmail");
+    /// ```
+    pub fn modmail_broadcast(&self, subject: &str, body: &str) -> Result<(), APIError> {
+        let body = format!("body={}&isAuthorHidden=false&srName={}&subject={}&to={}",
+                           self.client.url_escape(body.to_owned()),
+                           self.name,
+                           self.client.url_escape(subject.to_owned()),
+                           self.name);
+        self.client.post_success(endpoints::MOD_CONVERSATIONS, &body, true)
+    }
+
+    /// Gets an interface to this subreddit's structured styles ('community appearance'), which
+    /// can be used to read and update the theme and upload banner/icon images.
+    pub fn styles(&self) -> SubredditStyles<'a> {
+        SubredditStyles::new(self.client, &self.name)
+    }
+
+    /// Fetches a page from this subreddit's wiki, e.g. `index`.
+    pub fn wiki_page(&self, page: &str) -> Result<WikiPage<'a>, APIError> {
+        WikiPage::fetch(self.client, &self.name, page)
+    }
+
+    /// Fetches this subreddit's AutoModerator configuration, which can be edited and saved.
+    /// You must be a moderator with wiki permissions to do this.
+    #[cfg(feature = "moderation")]
+    pub fn automoderator(&self) -> Result<AutoModeratorConfig<'a>, APIError> {
+        AutoModeratorConfig::fetch(self.client, &self.name)
+    }
+
+    /// Fetches this subreddit's Toolbox user notes, which can be edited and saved. You must be a
+    /// moderator with wiki permissions to do this.
+    #[cfg(feature = "moderation")]
+    pub fn usernotes(&self) -> Result<UserNotesPage<'a>, APIError> {
+        UserNotesPage::fetch(self.client, &self.name)
+    }
+
+    /// Fetches the rules of this subreddit, in priority order.
+    pub fn rules(&self) -> Result<Vec<SubredditRule>, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::rules(&self.name));
+        let string = self.client.get_json(&url, false)?;
+        let result: listing::SubredditRules = serde_json::from_str(&string)?;
+        Ok(result.rules)
+    }
+
+    /// Fetches the flair of every user in this subreddit who has one set, following
+    /// `/api/flairlist` pagination until all entries have been collected. You must be a
+    /// moderator with flair permissions to do this.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("new_rawr");
+    /// for flair in sub.flair_list().expect("Could not fetch flair list") {
+    ///     println!("{}: {:?}", flair.user, flair.flair_text);
+    /// }
+    /// ```
+    pub fn flair_list(&self) -> Result<Vec<UserFlair>, APIError> {
+        let mut flairs = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let mut url = format!("{}?limit=1000&raw_json=1", endpoints::flair_list(&self.name));
+            if let Some(ref after) = after {
+                url = format!("{}&after={}", url, after);
+            }
+            let string = self.client.get_json(&url, false)?;
+            let mut result: FlairListResponse = serde_json::from_str(&string)?;
+            flairs.append(&mut result.users);
+            match result.next {
+                Some(next) => after = Some(next),
+                None => return Ok(flairs),
+            }
         }
     }
+
+    /// Removes the user flair of the specified user in this subreddit. Only moderators can do
+    /// this; use `User.flair()` to change a flair instead.
+    pub fn clear_user_flair(&self, user: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&name={}", user);
+        let url = endpoints::delete_flair(&self.name);
+        self.client.post_success(&url, &body, false)
+    }
+
+    /// Removes the user flairs of all of the specified users in this subreddit, sending up to
+    /// 100 users per request. Only moderators can do this. Fails with
+    /// `APIError::ValidationError` listing the users whose flair could not be removed (e.g.
+    /// because the user does not exist), after trying every user.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("new_rawr");
+    /// let flairs = sub.flair_list().expect("Could not fetch flair list");
+    /// let stale = flairs.iter()
+    ///     .filter(|flair| flair.flair_text.as_deref() == Some("2019 contest winner"))
+    ///     .map(|flair| flair.user.as_str())
+    ///     .collect::<Vec<_>>();
+    /// sub.clear_user_flairs(&stale).expect("Could not clear flairs");
+    /// ```
+    pub fn clear_user_flairs(&self, users: &[&str]) -> Result<(), APIError> {
+        let url = endpoints::flair_csv(&self.name);
+        let mut failed = Vec::new();
+        for chunk in users.chunks(100) {
+            // Rows of `user,text,css_class`. An empty text and CSS class removes the flair.
+            let csv = chunk.iter().map(|user| format!("{},,", user)).collect::<Vec<_>>();
+            let body = format!("flair_csv={}", self.client.url_escape(csv.join("\n")));
+            let string = self.client.post_json(&url, &body, false)?;
+            let results: Vec<FlairCsvResult> = serde_json::from_str(&string)?;
+            for (user, result) in chunk.iter().zip(results) {
+                if !result.ok {
+                    let errors = result.errors
+                        .as_object()
+                        .map(|errors| {
+                            errors.values().filter_map(|error| error.as_str()).collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    let reason = if errors.is_empty() {
+                        result.status
+                    } else {
+                        errors.join("; ")
+                    };
+                    failed.push(format!("{} ({})", user, reason));
+                }
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(APIError::ValidationError(format!("Could not clear the flair of: {}",
+                                                  failed.join(", "))))
+        }
+    }
+
+    /// Works out how a report with the specified reason should be sent to this subreddit. If the
+    /// subreddit allows free-form reports, the reason is used as-is. Otherwise, the reason must
+    /// match the short name or violation reason of one of the subreddit's rules (ignoring
+    /// case), and `APIError::ValidationError` is returned if no rule matches. The subreddit's
+    /// settings and rules are only fetched by the first call for each subreddit, and are kept
+    /// until `RedditClient.clear_cache()` is called.
+    ///
+    /// This is used by `Reportable.report()`, so you do not normally need to call it.
+    pub fn report_reason(&self, reason: &str) -> Result<ReportReason, APIError> {
+        let rules = match self.client.report_rules(&self.name)? {
+            Some(rules) => rules,
+            None => return Ok(ReportReason::FreeForm(self.client.url_escape(reason.to_owned()))),
+        };
+        let reason = reason.to_lowercase();
+        rules.into_iter()
+            .find(|rule| {
+                rule.short_name.to_lowercase() == reason ||
+                rule.violation_reason.to_lowercase() == reason
+            })
+            .map(|rule| ReportReason::Rule(self.client.url_escape(rule.violation_reason)))
+            .ok_or_else(|| {
+                APIError::ValidationError(format!("/r/{} does not allow free-form reports, and \
+                                                   no rule matches '{}'",
+                                                  self.name,
+                                                  reason))
+            })
+    }
+
     /// Subscribes to the specified subredit, returning the result to show whether the API call
     /// succeeded or not.
     pub fn subscribe(&self) -> Result<(), APIError> {
         let body = format!("action=sub&sr_name={}", self.name);
-        self.client.post_success("/api/subscribe", &body, false)
+        self.client.post_success(endpoints::SUBSCRIBE, &body, false)
     }
 
     /// Unsubscribes to the specified subreddit, returning the result to show whether the API call
     /// succeeded or not.
     pub fn unsubscribe(&self) -> Result<(), APIError> {
         let body = format!("action=unsub&sr_name={}", self.name);
-        self.client.post_success("/api/subscribe", &body, false)
+        self.client.post_success(endpoints::SUBSCRIBE, &body, false)
     }
 }
 
@@ -267,7 +681,7 @@ impl SubredditAbout {
     /// Creates a new `SubredditAbout` instance. Use `Subreddit.about()` instead to get
     /// information about a subreddit.
     pub fn new(data: listing::SubredditAboutData) -> SubredditAbout {
-        SubredditAbout { data: data }
+        SubredditAbout { data }
     }
 
     /// The number of subscribers to this subreddit.
@@ -286,6 +700,12 @@ impl SubredditAbout {
         self.data.public_traffic
     }
 
+    /// Returns `true` if users may write their own report reasons. If not, reports must use one
+    /// of the subreddit's rules (see `Subreddit.rules()`).
+    pub fn free_form_reports(&self) -> bool {
+        self.data.free_form_reports.unwrap_or(true)
+    }
+
     /// The display name of the subreddit, not including leading /r/
     pub fn display_name(&self) -> &str {
         &self.data.display_name
@@ -0,0 +1,38 @@
+use crate::responses::trending::TrendingSearchData;
+
+/// A search that is currently trending on Reddit, as shown in the search bar and front page
+/// carousel. Get these with `RedditClient.trending_searches()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendingSearch {
+    /// The search query to send to find matching posts.
+    pub query: String,
+    /// The text that Reddit displays for the trend.
+    pub display: String,
+    /// The subreddits of the sample posts matching the search, in order of first appearance
+    /// and without duplicates.
+    pub subreddits: Vec<String>,
+    /// The full names of the sample posts matching the search.
+    pub posts: Vec<String>,
+}
+
+impl TrendingSearch {
+    /// Creates a `TrendingSearch` from the raw API data.
+    pub fn new(data: TrendingSearchData) -> TrendingSearch {
+        let mut subreddits: Vec<String> = Vec::new();
+        let mut posts = Vec::new();
+        if let Some(results) = data.results {
+            for child in results.data.children {
+                if !subreddits.contains(&child.data.subreddit) {
+                    subreddits.push(child.data.subreddit);
+                }
+                posts.push(child.data.name);
+            }
+        }
+        TrendingSearch {
+            query: data.query_string,
+            display: data.display_string,
+            subreddits,
+            posts,
+        }
+    }
+}
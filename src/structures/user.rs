@@ -2,12 +2,19 @@ use crate::structures::submission::FlairList;
 use crate::structures::listing::Listing;
 use crate::client::RedditClient;
 use crate::responses::{FlairSelectorResponse, listing};
-use crate::responses::user::{UserAbout as _UserAbout, UserAboutData, UserAboutDataCore};
-use crate::responses::listing::{Listing as _Listing, UserListingData};
+use crate::responses::user::{UserAboutData, UserAboutDataCore};
+use crate::responses::listing::Listing as _Listing;
 use crate::traits::{Created, PageListing};
+use crate::endpoints;
 use crate::errors::APIError;
-use crate::structures::comment_list::CommentList;
-use crate::responses::comment::CommentListing;
+use crate::options::ListingOptions;
+use crate::responses::comment::{CommentData, CommentListing};
+use crate::structures::interactions::{find_interactions, InteractionThread};
+use crate::structures::stats::UserActivitySummary;
+#[cfg(feature = "streams")]
+use crate::structures::comment_list::CommentStream;
+#[cfg(feature = "streams")]
+use crate::structures::listing::PostStream;
 
 /// Interface to a Reddit user, which can be used to access their karma and moderator status.
 pub struct User<'a> {
@@ -20,7 +27,7 @@ impl<'a> User<'a> {
     /// Internal method. Use `RedditClient.user(NAME)` instead.
     pub fn new(client: &'a RedditClient, name: &str) -> User<'a> {
         User {
-            client: client,
+            client,
             name: name.to_owned(),
         }
     }
@@ -44,23 +51,22 @@ impl<'a> User<'a> {
     /// a 403 error.
     pub fn flair_options(&self, subreddit: &str) -> Result<FlairList, APIError> {
         let body = format!("user={}", self.name);
-        let url = format!("/r/{}/api/flairselector", subreddit);
-        let string = self.client
-            .post_json(&url, &body, false).unwrap();
-        let string: FlairSelectorResponse = serde_json::from_str(&*string).unwrap();
+        let url = endpoints::flair_selector(subreddit);
+        let string = self.client.post_json(&url, &body, false)?;
+        let string: FlairSelectorResponse = serde_json::from_str(&string)?;
         Ok(FlairList::new(string.choices))
     }
 
     /// Sets the flair for this user in the specified subreddit, using the specified template
     /// string. You can get the template string from `flair_options`; either:
     /// - use the returned `FlairList` and call the method `find_text` which will return the
-    /// template ID of the flair with the specified text.
-    /// - iterate through the `FlairList`, and get the `FlairChoice.flair_template_id` value.
-    pub fn flair(&self, subreddit: &str, template: &str) -> Result<(), APIError> {
+    ///   template ID of the flair with the specified text.
+    /// - iterate through the `FlairList` with `iter()`, and pass the `FlairChoice` itself.
+    pub fn flair<T: AsRef<str>>(&self, subreddit: &str, template: T) -> Result<(), APIError> {
         let body = format!("api_type=json&user={}&flair_template_id={}",
                            self.name,
-                           template);
-        let url = format!("/r/{}/api/selectflair", subreddit);
+                           template.as_ref());
+        let url = endpoints::select_flair(subreddit);
         self.client.post_success(&url, &body, false)
     }
 
@@ -80,20 +86,154 @@ impl<'a> User<'a> {
     /// }
     /// assert_eq!(i, 5);
     /// ```
-    pub fn submissions(&self) -> Result<Listing, APIError> {
-        let url = format!("/user/{}/submitted?raw_json=1", self.name);
-        let result = self.client
-            .get_json(&url, false).unwrap();
-        let result: _Listing = serde_json::from_str(&*result).unwrap();
+    pub fn submissions(&self) -> Result<Listing<'_>, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::user_submitted(&self.name));
+        let result = self.client.get_json(&url, false)?;
+        let result: _Listing = serde_json::from_str(&result)?;
         Ok(Listing::new(self.client, url, result.data))
     }
+    /// Gets a list of the *submissions* that this user has saved, newest first. Only the user
+    /// themselves can see their saved items, so this requires logging in as this user.
+    ///
+    /// Reddit Premium users can organise saved items into categories; pass
+    /// `Some(CATEGORY)` to only list the items in that category. Like other listings, Reddit
+    /// stops paginating the saved feed after roughly 1000 items.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::auth::PasswordAuthenticator;
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::options::ListingOptions;
+    /// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let me = client.user("new_rawr");
+    /// for post in me.saved(ListingOptions::default(), Some("rust")).expect("Could not fetch!") {
+    ///     println!("{}", post.title());
+    /// }
+    /// ```
+    pub fn saved(&self, opts: ListingOptions, category: Option<&str>) -> Result<Listing<'a>, APIError> {
+        let mut url = format!("{}?limit={}&raw_json=1&type=links",
+                              endpoints::user_saved(&self.name),
+                              opts.batch);
+        if let Some(category) = category {
+            url.push_str(&format!("&category={}", self.client.url_escape(category.to_owned())));
+        }
+        let full_url = format!("{}&{}", url, opts.anchor);
+        let result = self.client.get_json(&full_url, false)?;
+        let result: _Listing = serde_json::from_str(&result)?;
+        Ok(Listing::new(self.client, url, result.data))
+    }
+
+    /// Crawls up to `limit` of this user's most recent submissions and up to `limit` of their
+    /// most recent comments, and summarizes them (most active subreddits, posting hours and
+    /// average score). See `UserActivitySummary` for an example.
+    ///
+    /// If a later page of submissions cannot be fetched, the summary only includes the
+    /// submissions fetched so far.
+    pub fn activity_summary(&self, limit: usize) -> Result<UserActivitySummary, APIError> {
+        let mut summary = UserActivitySummary::new(&self.name);
+
+        let url = format!("{}?limit=100&raw_json=1", endpoints::user_submitted(&self.name));
+        let result = self.client.get_json(&url, false)?;
+        let result: _Listing = serde_json::from_str(&result)?;
+        for post in Listing::new(self.client, url, result.data).take(limit) {
+            summary.add(false, &post.data.subreddit, post.created_utc(), post.data.score);
+        }
+
+        for comment in self.recent_comments(limit)? {
+            summary.add(true, &comment.subreddit, comment.created_utc as i64, comment.score);
+        }
+        Ok(summary)
+    }
+
+    /// Finds the threads in which this user and `other` recently replied to each other (to a
+    /// comment or to a submission), e.g. to investigate harassment or brigading. Up to `limit`
+    /// of each user's most recent comments are scanned, so older interactions are not found.
+    ///
+    /// The threads are sorted by their latest reply, newest first. Replies to or from deleted
+    /// comments are not included, since their authors are unknown.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let threads = client.user("alice").interactions_with("bob", 500).expect("Lookup failed");
+    /// for thread in threads.iter().filter(|thread| thread.is_mutual()) {
+    ///     println!("/r/{}: {} replies in {:?}", thread.subreddit, thread.replies.len(), thread.title);
+    /// }
+    /// ```
+    pub fn interactions_with(&self, other: &str, limit: usize)
+                             -> Result<Vec<InteractionThread>, APIError> {
+        if self.name.eq_ignore_ascii_case(other) {
+            return Err(APIError::ValidationError(String::from("Cannot look up interactions of a \
+                                                               user with themselves")));
+        }
+        let mut comments = self.recent_comments(limit)?;
+        comments.extend(User::new(self.client, other).recent_comments(limit)?);
+        find_interactions(self.client, &self.name, other, comments)
+    }
+
+    /// Fetches up to `limit` of this user's most recent comments, newest first.
+    fn recent_comments(&self, limit: usize) -> Result<Vec<CommentData>, APIError> {
+        let mut comments = Vec::new();
+        let mut after: Option<String> = None;
+        while comments.len() < limit {
+            let mut url = format!("{}?limit=100&raw_json=1", endpoints::user_comments(&self.name));
+            if let Some(ref after) = after {
+                url = format!("{}&after={}", url, after);
+            }
+            let result = self.client.get_json(&url, false)?;
+            let result: CommentListing = serde_json::from_str(&result)?;
+            for item in result.data.children.into_iter().take(limit - comments.len()) {
+                comments.push(serde_json::from_value(item.data)?);
+            }
+            after = result.data.after;
+            if after.is_none() {
+                break;
+            }
+        }
+        Ok(comments)
+    }
+
+    /// Gets a `PostStream` of this user's new submissions, which polls every 5 seconds and
+    /// yields each new post once, oldest first. Useful for notification bots that follow a
+    /// specific account. Posts by the account the client is logged in as are never yielded.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// for post in client.user("spez").submission_stream() {
+    ///     println!("New post in /r/{}: {}", post.data.subreddit, post.title());
+    /// }
+    /// ```
+    #[cfg(feature = "streams")]
+    pub fn submission_stream(&self) -> PostStream<'a> {
+        let url = format!("{}?sort=new&limit=5", endpoints::user_submitted(&self.name));
+        PostStream::new(self.client, url)
+    }
+
+    /// Gets a `CommentStream` of this user's new comments, which polls every 5 seconds and
+    /// yields each new comment once, oldest first.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::{Content, Editable};
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// for comment in client.user("spez").comment_stream() {
+    ///     println!("New comment in /r/{}: {}", comment.subreddit().name, comment.body().unwrap());
+    /// }
+    /// ```
+    #[cfg(feature = "streams")]
+    pub fn comment_stream(&self) -> CommentStream<'a> {
+        CommentStream::for_user(self.client, &self.name)
+    }
+
     // TODO: implement comment, overview, gilded listings etc.
     ///Incomplete get comments
     pub fn comments(&self) -> Result<CommentListing, APIError> {
-        let url = format!("/user/{}/comments?raw_json=1", self.name);
-        let result = self.client
-            .get_json(&url, false).unwrap();
-        let result: CommentListing = serde_json::from_str(&*result).unwrap();
+        let url = format!("{}?raw_json=1", endpoints::user_comments(&self.name));
+        let result = self.client.get_json(&url, false)?;
+        let result: CommentListing = serde_json::from_str(&result)?;
         //TODO make structure for Comments
         Ok(result)
     }
@@ -108,9 +248,9 @@ pub struct UserAbout {
 impl UserAbout {
     /// Internal method. Use `RedditClient.user(NAME).about()` instead.
     pub fn new(client: &RedditClient, name: String) -> Result<UserAbout, APIError> {
-        let url = format!("/user/{}/about?raw_json=1", name);
-        let result = client.get_json(&url, false).unwrap();
-        let result: UserAboutDataCore = serde_json::from_str(&*result).unwrap();
+        let url = format!("{}?raw_json=1", endpoints::user_about(&name));
+        let result = client.get_json(&url, false)?;
+        let result: UserAboutDataCore = serde_json::from_str(&result)?;
         Ok(UserAbout {
             data: result.data
         })
@@ -143,10 +283,12 @@ impl Created for UserAbout {
 }
 
 
+/// A paginated listing of users, such as the approved contributors of a subreddit.
 pub struct UserListing<'a> {
     client: &'a RedditClient,
     query_stem: String,
     data: listing::UserListing,
+    last_error: Option<APIError>,
 }
 
 impl<'a> UserListing<'a> {
@@ -154,11 +296,12 @@ impl<'a> UserListing<'a> {
     pub fn new(client: &RedditClient,
                query_stem: String,
                data: listing::UserListing)
-               -> UserListing {
+               -> UserListing<'_> {
         UserListing {
-            client: client,
-            query_stem: query_stem,
-            data: data,
+            client,
+            query_stem,
+            data,
+            last_error: None,
         }
     }
 }
@@ -175,16 +318,26 @@ impl<'a> PageListing for UserListing<'a> {
     fn modhash(&self) -> Option<String> {
         self.data.modhash.to_owned()
     }
+
+    fn dist(&self) -> Option<u64> {
+        self.data.dist
+    }
 }
 
 impl<'a> UserListing<'a> {
+    /// The error that ended iteration, if the last page could not be fetched. See
+    /// `Listing.last_error()` for details.
+    pub fn last_error(&self) -> Option<&APIError> {
+        self.last_error.as_ref()
+    }
+
     fn fetch_after(&mut self) -> Result<UserListing<'a>, APIError> {
         match self.after() {
             Some(after_id) => {
                 let url = format!("{}&after={}", self.query_stem, after_id);
                 let string = self.client
-                    .get_json(&url, false).unwrap();
-                let string: listing::UserListing = serde_json::from_str(&*string).unwrap();
+                    .get_json(&url, false)?;
+                let string: listing::UserListing = serde_json::from_str(&string)?;
                 Ok(UserListing::new(self.client, self.query_stem.to_owned(), string))
             }
             None => Err(APIError::ExhaustedListing),
@@ -199,10 +352,21 @@ impl<'a> Iterator for UserListing<'a> {
             if self.after().is_none() {
                 None
             } else {
-                let mut new_listing = self.fetch_after().expect("After does not exist!");
-                self.data.children.append(&mut new_listing.data.children);
-                self.data.after = new_listing.data.after;
-                self.next()
+                match self.fetch_after() {
+                    Ok(mut new_listing) => {
+                        self.last_error = None;
+                        self.data.children.append(&mut new_listing.data.children);
+                        self.data.after = new_listing.data.after;
+                        self.data.before = new_listing.data.before;
+                        self.data.dist = new_listing.data.dist;
+                        self.data.modhash = new_listing.data.modhash;
+                        self.next()
+                    }
+                    Err(err) => {
+                        self.last_error = Some(err);
+                        None
+                    }
+                }
             }
         } else {
             let child = self.data.children.drain(..1).next().unwrap();
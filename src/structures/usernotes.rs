@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use crate::client::RedditClient;
+use crate::errors::APIError;
+use crate::structures::wiki::WikiPage;
+use crate::zlib;
+
+/// The wiki page that holds a subreddit's Toolbox user notes.
+pub const USERNOTES_PAGE: &str = "usernotes";
+
+/// The version of the Toolbox user notes format that can be read and written.
+pub const USERNOTES_SCHEMA: u64 = 6;
+
+/// A single Toolbox note about a user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserNote {
+    /// The text of the note.
+    pub text: String,
+    /// When the note was added, as a UNIX timestamp in seconds.
+    pub time: i64,
+    /// The moderator who added the note.
+    pub moderator: String,
+    /// The note type (e.g. `spamwarn`, `abusewarn`, `ban`), if any. Types are configured per
+    /// subreddit in the Toolbox settings.
+    pub warning: Option<String>,
+    /// The thing the note refers to, in Toolbox's compact form: `l,POST_ID` for a post,
+    /// `l,POST_ID,COMMENT_ID` for a comment or `m,MESSAGE_ID` for a message.
+    pub link: Option<String>,
+}
+
+impl UserNote {
+    /// The Reddit URL of the thing that the note refers to, if any.
+    /// # Examples
+    /// ```
+    /// use new_rawr::structures::usernotes::UserNote;
+    /// let note = UserNote {
+    ///     text: String::from("Spamming links"),
+    ///     time: 1500000000,
+    ///     moderator: String::from("spez"),
+    ///     warning: Some(String::from("spamwarn")),
+    ///     link: Some(String::from("l,4uule8,d5t5ebn")),
+    /// };
+    /// assert_eq!(note.permalink().unwrap(), "https://www.reddit.com/comments/4uule8/-/d5t5ebn");
+    /// ```
+    pub fn permalink(&self) -> Option<String> {
+        let link = self.link.as_ref()?;
+        let parts = link.split(',').collect::<Vec<_>>();
+        match parts.as_slice() {
+            ["l", post] => Some(format!("https://www.reddit.com/comments/{}", post)),
+            ["l", post, comment] => {
+                Some(format!("https://www.reddit.com/comments/{}/-/{}", post, comment))
+            }
+            ["m", message] => Some(format!("https://www.reddit.com/message/messages/{}", message)),
+            _ => None,
+        }
+    }
+}
+
+/// The page format, with moderators and note types stored once and referenced by index.
+#[derive(Serialize, Deserialize)]
+struct RawPage {
+    ver: u64,
+    constants: RawConstants,
+    blob: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawConstants {
+    users: Vec<Option<String>>,
+    warnings: Vec<Option<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawUser {
+    ns: Vec<RawNote>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawNote {
+    n: String,
+    t: i64,
+    m: usize,
+    #[serde(default)]
+    l: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    w: Option<usize>,
+}
+
+/// The Toolbox user notes of a subreddit, keyed by username. Notes for each user are stored
+/// newest first, as Toolbox expects.
+///
+/// The notes are stored in the `usernotes` wiki page as JSON, with the notes themselves in a
+/// base64-encoded, zlib-compressed blob. `parse()` and `to_page()` convert between that format
+/// and typed `UserNote`s.
+/// # Examples
+/// ```
+/// use new_rawr::structures::usernotes::{UserNote, UserNotes};
+/// let page = r#"{"ver":6,"constants":{"users":["spez"],"warnings":["spamwarn"]},
+///     "blob":"eNqrVirOz03Nz0tVsqpWyitWsooGUkpWSsEFibkKOZl52cVKOkolSlaGpgZQoKOUq2QFJHOAqnJ0TEpLc1ItgGrKgYK1sbW1AN2XFys="}"#;
+/// let mut notes = UserNotes::parse(page).unwrap();
+/// assert_eq!(notes.notes_for("someone")[0].text, "Spam links");
+/// notes.add("other_user", UserNote {
+///     text: String::from("Warned about self-promotion"),
+///     time: 1500000100,
+///     moderator: String::from("spez"),
+///     warning: None,
+///     link: None,
+/// });
+/// let reparsed = UserNotes::parse(&notes.to_page().unwrap()).unwrap();
+/// assert_eq!(reparsed, notes);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserNotes {
+    /// The notes for each user, newest first.
+    pub notes: BTreeMap<String, Vec<UserNote>>,
+}
+
+impl UserNotes {
+    /// Parses the contents of a `usernotes` wiki page. Returns a `ValidationError` if the page
+    /// uses a different schema version than `USERNOTES_SCHEMA`, or if the blob is corrupt.
+    pub fn parse(content: &str) -> Result<UserNotes, APIError> {
+        if content.trim().is_empty() {
+            return Ok(UserNotes::default());
+        }
+        let page: RawPage = serde_json::from_str(content)?;
+        if page.ver != USERNOTES_SCHEMA {
+            return Err(APIError::ValidationError(format!("Unsupported usernotes schema version \
+                                                          {}",
+                                                         page.ver)));
+        }
+        let compressed = base64::decode(&page.blob)
+            .map_err(|err| APIError::ValidationError(format!("Invalid usernotes blob: {}", err)))?;
+        let json = zlib::decompress(&compressed)
+            .map_err(|err| APIError::ValidationError(format!("Invalid usernotes blob: {}", err)))?;
+        let users: BTreeMap<String, RawUser> = serde_json::from_slice(&json)?;
+        let lookup = |table: &[Option<String>], index: usize| {
+            table.get(index).cloned().unwrap_or_default()
+        };
+        let mut notes = BTreeMap::new();
+        for (name, user) in users {
+            let user_notes = user.ns
+                .into_iter()
+                .map(|note| {
+                    UserNote {
+                        text: note.n,
+                        time: note.t,
+                        moderator: lookup(&page.constants.users, note.m).unwrap_or_default(),
+                        warning: note.w.and_then(|w| lookup(&page.constants.warnings, w)),
+                        link: Some(note.l).filter(|link| !link.is_empty()),
+                    }
+                })
+                .collect();
+            notes.insert(name, user_notes);
+        }
+        Ok(UserNotes { notes })
+    }
+
+    /// Serializes the notes into the contents of a `usernotes` wiki page.
+    pub fn to_page(&self) -> Result<String, APIError> {
+        let mut users: Vec<Option<String>> = Vec::new();
+        let mut warnings: Vec<Option<String>> = Vec::new();
+        let index_of = |table: &mut Vec<Option<String>>, value: &str| {
+            match table.iter().position(|item| item.as_deref() == Some(value)) {
+                Some(index) => index,
+                None => {
+                    table.push(Some(value.to_owned()));
+                    table.len() - 1
+                }
+            }
+        };
+        let mut raw = BTreeMap::new();
+        for (name, user_notes) in &self.notes {
+            if user_notes.is_empty() {
+                continue;
+            }
+            let ns = user_notes.iter()
+                .map(|note| {
+                    RawNote {
+                        n: note.text.to_owned(),
+                        t: note.time,
+                        m: index_of(&mut users, &note.moderator),
+                        l: note.link.to_owned().unwrap_or_default(),
+                        w: note.warning.as_ref().map(|warning| index_of(&mut warnings, warning)),
+                    }
+                })
+                .collect();
+            raw.insert(name.to_owned(), RawUser { ns });
+        }
+        let json = serde_json::to_vec(&raw)?;
+        let page = RawPage {
+            ver: USERNOTES_SCHEMA,
+            constants: RawConstants {
+                users,
+                warnings,
+            },
+            blob: base64::encode(zlib::compress(&json)),
+        };
+        Ok(serde_json::to_string(&page)?)
+    }
+
+    /// The notes about a user, newest first. Usernames are compared case-insensitively.
+    pub fn notes_for(&self, username: &str) -> &[UserNote] {
+        self.notes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(username))
+            .map(|(_, notes)| notes.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Adds a note about a user, as the newest note.
+    pub fn add(&mut self, username: &str, note: UserNote) {
+        let key = self.notes
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(username))
+            .cloned()
+            .unwrap_or_else(|| username.to_owned());
+        self.notes.entry(key).or_default().insert(0, note);
+    }
+
+    /// Removes all notes about a user, returning them.
+    pub fn remove(&mut self, username: &str) -> Vec<UserNote> {
+        let key = self.notes.keys().find(|name| name.eq_ignore_ascii_case(username)).cloned();
+        key.and_then(|key| self.notes.remove(&key)).unwrap_or_default()
+    }
+}
+
+/// A subreddit's Toolbox user notes, fetched from the wiki. Get one with
+/// `Subreddit.usernotes()`.
+/// # Examples
+/// ```rust,no_run
+/// use std::time::{SystemTime, UNIX_EPOCH};
+/// use new_rawr::client::RedditClient;
+/// use new_rawr::auth::PasswordAuthenticator;
+/// use new_rawr::structures::usernotes::UserNote;
+/// let client = RedditClient::new("new_rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut page = client.subreddit("new_rawr").usernotes().expect("Could not fetch notes");
+/// page.notes.add("spammer", UserNote {
+///     text: String::from("Automatic: posted a blacklisted domain"),
+///     time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64,
+///     moderator: String::from("my_bot"),
+///     warning: Some(String::from("spamwarn")),
+///     link: Some(String::from("l,4uule8")),
+/// });
+/// page.save("Add note for spammer").expect("Could not save notes");
+/// ```
+pub struct UserNotesPage<'a> {
+    page: WikiPage<'a>,
+    /// The notes, which can be edited before calling `save()`.
+    pub notes: UserNotes,
+}
+
+impl<'a> UserNotesPage<'a> {
+    /// Internal method. Use `Subreddit.usernotes()` instead.
+    pub fn fetch(client: &'a RedditClient, subreddit: &str) -> Result<UserNotesPage<'a>, APIError> {
+        let page = WikiPage::fetch(client, subreddit, USERNOTES_PAGE)?;
+        Ok(UserNotesPage {
+            notes: UserNotes::parse(page.content())?,
+            page,
+        })
+    }
+
+    /// Saves the notes to the wiki. Fails with `APIError::HTTPError(Conflict)` if the notes have
+    /// been changed by someone else (e.g. a moderator using Toolbox) since they were fetched; in
+    /// that case, fetch them again and reapply your changes.
+    pub fn save(&mut self, reason: &str) -> Result<(), APIError> {
+        let content = self.notes.to_page()?;
+        self.page.edit(&content, reason)
+    }
+}
@@ -0,0 +1,247 @@
+use crate::client::RedditClient;
+use crate::endpoints;
+use crate::errors::APIError;
+use crate::responses::wiki;
+
+/// A page of a subreddit's wiki. Get one with `Subreddit.wiki_page(NAME)`.
+pub struct WikiPage<'a> {
+    client: &'a RedditClient,
+    /// The name of the subreddit that this page belongs to.
+    pub subreddit: String,
+    /// The name of the page, e.g. `index` or `config/automoderator`.
+    pub page: String,
+    data: wiki::WikiPageData,
+}
+
+impl<'a> WikiPage<'a> {
+    /// Internal method. Use `Subreddit.wiki_page(NAME)` instead.
+    pub fn new(client: &'a RedditClient,
+               subreddit: &str,
+               page: &str,
+               data: wiki::WikiPageData)
+               -> WikiPage<'a> {
+        WikiPage {
+            client,
+            subreddit: subreddit.to_owned(),
+            page: page.to_owned(),
+            data,
+        }
+    }
+
+    /// Fetches the page with the specified name from the subreddit's wiki.
+    pub fn fetch(client: &'a RedditClient, subreddit: &str, page: &str) -> Result<WikiPage<'a>, APIError> {
+        let url = format!("{}?raw_json=1", endpoints::wiki_page(subreddit, page));
+        let string = client.get_json(&url, false)?;
+        let result: wiki::WikiPage = serde_json::from_str(&string)?;
+        Ok(WikiPage::new(client, subreddit, page, result.data))
+    }
+
+    /// Fetches the contents of this page at the specified revision ID.
+    #[cfg(feature = "wiki-diff")]
+    pub fn revision(&self, revision_id: &str) -> Result<String, APIError> {
+        let url = format!("{}?v={}&raw_json=1",
+                          endpoints::wiki_page(&self.subreddit, &self.page),
+                          revision_id);
+        let string = self.client.get_json(&url, false)?;
+        let result: wiki::WikiPage = serde_json::from_str(&string)?;
+        Ok(result.data.content_md)
+    }
+
+    /// Fetches two revisions of this page and returns a unified diff from `rev_a` to `rev_b`,
+    /// with three lines of context. The diff is empty if the revisions have the same contents.
+    /// Requires the `wiki-diff` feature.
+    /// # Examples
+    /// ```rust,no_run
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// let page = client.subreddit("rust").wiki_page("index").expect("Could not fetch page!");
+    /// let diff = page.diff("REVISION_A", "REVISION_B").expect("Could not fetch revisions!");
+    /// println!("```\n{}```", diff);
+    /// ```
+    #[cfg(feature = "wiki-diff")]
+    pub fn diff(&self, rev_a: &str, rev_b: &str) -> Result<String, APIError> {
+        let old = self.revision(rev_a)?;
+        let new = self.revision(rev_b)?;
+        Ok(unified_diff(&old,
+                        &new,
+                        &format!("{}@{}", self.page, rev_a),
+                        &format!("{}@{}", self.page, rev_b),
+                        3))
+    }
+
+    /// The Markdown contents of the page.
+    pub fn content(&self) -> &str {
+        &self.data.content_md
+    }
+
+    /// The HTML contents of the page, if available.
+    pub fn content_html(&self) -> Option<String> {
+        self.data.content_html.to_owned()
+    }
+
+    /// The ID of the revision that was fetched. This is sent when editing so that Reddit can
+    /// detect if somebody else has changed the page in the meantime.
+    pub fn revision_id(&self) -> Option<String> {
+        self.data.revision_id.to_owned()
+    }
+
+    /// The timestamp of the revision that was fetched, if available.
+    pub fn revision_date(&self) -> Option<i64> {
+        self.data.revision_date.map(|date| date as i64)
+    }
+
+    /// `true` if the logged-in user may edit this page.
+    pub fn may_revise(&self) -> bool {
+        self.data.may_revise
+    }
+
+    /// Replaces the contents of the page with the specified Markdown. The revision ID of the
+    /// fetched page is sent with the edit, so if the page has been changed since it was fetched,
+    /// Reddit will reject the edit with `APIError::HTTPError(Conflict)`. In that case, fetch the
+    /// page again and reapply your changes.
+    ///
+    /// After a successful edit, the page is fetched again, so that `revision_id()` is the ID of
+    /// the new revision and further edits of this `WikiPage` still detect conflicts. If that
+    /// fetch fails, the edit has still been made: the contents are updated locally and
+    /// `revision_id()` is cleared, so the next edit is sent without conflict detection.
+    pub fn edit(&mut self, content: &str, reason: &str) -> Result<(), APIError> {
+        let mut body = format!("content={}&page={}&reason={}",
+                               self.client.url_escape(content.to_owned()),
+                               self.page,
+                               self.client.url_escape(reason.to_owned()));
+        if let Some(ref previous) = self.data.revision_id {
+            body.push_str(&format!("&previous={}", self.client.url_escape(previous.to_owned())));
+        }
+        let url = endpoints::wiki_edit(&self.subreddit);
+        self.client.post_success(&url, &body, false)?;
+        match WikiPage::fetch(self.client, &self.subreddit, &self.page) {
+            Ok(page) => self.data = page.data,
+            Err(_) => {
+                self.data.content_md = content.to_owned();
+                self.data.content_html = None;
+                self.data.revision_id = None;
+                self.data.revision_date = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Produces a unified diff of two texts, line by line, with `context` unchanged lines around each
+/// change. `old_name` and `new_name` are used in the `---` and `+++` header lines. Returns an
+/// empty string if the texts have the same lines. Requires the `wiki-diff` feature.
+/// # Examples
+/// ```
+/// use new_rawr::structures::wiki::unified_diff;
+/// let diff = unified_diff("a\nb\nc\n", "a\nB\nc\n", "old", "new", 1);
+/// assert_eq!(diff, "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n");
+/// ```
+#[cfg(feature = "wiki-diff")]
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str, context: usize) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // Longest common subsequence table; `lcs[i][j]` is the LCS length of `old[i..]` and
+    // `new[j..]`.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // Each operation is (prefix, old line index, new line index).
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', i, j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', i, j));
+            i += 1;
+        } else {
+            ops.push(('+', i, j));
+            j += 1;
+        }
+    }
+    if ops.iter().all(|op| op.0 == ' ') {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut start = 0;
+    while let Some(first) = ops[start..].iter().position(|op| op.0 != ' ') {
+        // Extend the hunk until the gap between two changes is wider than twice the context.
+        let first = start + first;
+        let mut last = first;
+        let mut k = first + 1;
+        while k < ops.len() {
+            if ops[k].0 != ' ' {
+                last = k;
+            } else if k - last > 2 * context {
+                break;
+            }
+            k += 1;
+        }
+        let begin = first.saturating_sub(context);
+        let end = (last + context + 1).min(ops.len());
+        let hunk = &ops[begin..end];
+        let old_count = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_count = hunk.iter().filter(|op| op.0 != '-').count();
+        let old_start = if old_count == 0 { hunk[0].1 } else { hunk[0].1 + 1 };
+        let new_start = if new_count == 0 { hunk[0].2 } else { hunk[0].2 + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+        for &(prefix, i, j) in hunk {
+            let line = if prefix == '+' { new[j] } else { old[i] };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+        }
+        start = end;
+    }
+    out
+}
+
+#[cfg(all(test, feature = "wiki-diff"))]
+mod tests {
+    use super::unified_diff;
+
+    #[test]
+    fn same_lines() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new", 3), "");
+        assert_eq!(unified_diff("a\nb", "a\nb\n", "old", "new", 3), "");
+    }
+
+    #[test]
+    fn added_and_removed_files() {
+        assert_eq!(unified_diff("", "a\nb\n", "old", "new", 3),
+                   "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+        assert_eq!(unified_diff("a\nb\n", "", "old", "new", 3),
+                   "--- old\n+++ new\n@@ -1,2 +0,0 @@\n-a\n-b\n");
+    }
+
+    #[test]
+    fn separate_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\nTWO\n3\n4\n5\n6\n7\n8\nNINE\n10\n";
+        assert_eq!(unified_diff(old, new, "old", "new", 1),
+                   "--- old\n+++ new\n@@ -1,3 +1,3 @@\n 1\n-2\n+TWO\n 3\n\
+                    @@ -8,3 +8,3 @@\n 8\n-9\n+NINE\n 10\n");
+    }
+
+    #[test]
+    fn merged_hunks() {
+        assert_eq!(unified_diff("1\n2\n3\n4\n5\n", "1\nTWO\n3\nFOUR\n5\n", "old", "new", 1),
+                   "--- old\n+++ new\n@@ -1,5 +1,5 @@\n 1\n-2\n+TWO\n 3\n-4\n+FOUR\n 5\n");
+    }
+
+    #[test]
+    fn no_context() {
+        assert_eq!(unified_diff("a\nb\nc\n", "a\nc\nd\n", "old", "new", 0),
+                   "--- old\n+++ new\n@@ -2,1 +1,0 @@\n-b\n@@ -3,0 +3,1 @@\n+d\n");
+    }
+}
@@ -1,19 +1,54 @@
 
-use crate::responses::comment::CommentData;
+use crate::responses::Distinguish;
 use crate::errors::APIError;
+use crate::clock;
 use crate::structures::comment_list::CommentList;
 use crate::structures::user::User;
 use crate::structures::subreddit::Subreddit;
 use crate::structures::submission::FlairList;
 use crate::structures::comment::Comment;
+use crate::structures::reply_queue::ReplyQueue;
+use crate::structures::score::Score;
+use crate::markdown::ReplyTemplate;
+use crate::html::to_plain_text;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::thread;
+use std::time::Duration;
 
 /// An object that can be voted upon and has a score based on the upvotes - downvotes.
 /// ## Notes
-/// The `ups` and `downs` values from the API no longer represent the true upvotes and downvotes,
-/// so this trait does not expose them.
+/// The `ups` and `downs` values from the API no longer represent the true upvotes and downvotes.
+/// They are exposed for completeness, but use `score_details()` for estimates derived from the
+/// upvote ratio.
+///
+/// Voting on archived content fails with `APIError::Archived` without sending a request.
 pub trait Votable {
     /// The (fuzzed) points score of the object.
     fn score(&self) -> i64;
+    /// The (fuzzed) number of upvotes reported by Reddit.
+    fn ups(&self) -> i64;
+    /// The (fuzzed) number of downvotes reported by Reddit. This is usually 0.
+    fn downs(&self) -> i64;
+    /// The ratio of upvotes to total votes (e.g. `0.95`), if Reddit provided it. Comments do not
+    /// have one.
+    fn upvote_ratio(&self) -> Option<f64> {
+        None
+    }
+    /// `true` if the subreddit is currently hiding the score of this object.
+    fn score_hidden(&self) -> bool;
+    /// All of the vote-related values combined into a `Score`.
+    fn score_details(&self) -> Score {
+        Score {
+            score: self.score(),
+            ups: self.ups(),
+            downs: self.downs(),
+            upvote_ratio: self.upvote_ratio(),
+            hidden: self.score_hidden(),
+        }
+    }
     /// Indicates the logged-in user's current vote on this object:
     /// - Some(true) = Upvoted
     /// - Some(false) = Downvoted
@@ -36,6 +71,111 @@ pub trait PageListing {
     /// The modhash (CSRF token) sent with this listing. Largely redundant, since the main
     /// modhash can be used.
     fn modhash(&self) -> Option<String>;
+    /// The number of items in the most recently fetched page, if Reddit reported it. Useful for
+    /// showing result counts. Returns `None` unless implemented.
+    fn dist(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// A stream that fetches new items by polling the API, such as `PostStream` or `CommentStream`.
+/// Streams yield items forever, sleeping between polls until new items arrive.
+pub trait PollingStream: Iterator {
+    /// Polls the API once **without** sleeping, returning only the items that have not been
+    /// seen by this stream before (oldest first). Returns an empty list if the request fails.
+    /// This is mainly used to drive several streams from one loop (see `Streams::merge`).
+    fn poll(&mut self) -> Vec<Self::Item>;
+
+    /// Consumes the stream, passing each item to `handler` on one of `workers` threads, so a
+    /// slow handler (e.g. one that downloads and processes images) does not stop the stream
+    /// from polling. Items wait in a queue of up to `workers` items; if the queue is full, the
+    /// stream waits for a worker to become free, so memory use stays bounded.
+    ///
+    /// Items are handled in parallel, so they may finish in a different order than they were
+    /// yielded. Streams never end, so this only returns if the stream ends or a handler panics.
+    /// After a panic, the other workers finish the item they are handling but take no more, and
+    /// the stream is not polled again once it yields the item it is waiting for; the panic is
+    /// then propagated to the caller. Items still in the queue are dropped.
+    /// # Examples
+    /// ```rust,no_run
+    /// # #[cfg(feature = "streams")] {
+    /// use new_rawr::client::RedditClient;
+    /// use new_rawr::auth::AnonymousAuthenticator;
+    /// use new_rawr::traits::PollingStream;
+    /// let client = RedditClient::new("new_rawr", AnonymousAuthenticator::new());
+    /// client.subreddit("pics").new_stream().for_each_concurrent(4, |post| {
+    ///     if let Some(url) = post.link_url() {
+    ///         println!("Processing {}", url); // Slow work, e.g. downloading the image.
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    fn for_each_concurrent<F>(self, workers: usize, handler: F)
+        where Self: Sized,
+              Self::Item: Send,
+              F: Fn(Self::Item) + Sync
+    {
+        let workers = workers.max(1);
+        let (sender, receiver) = sync_channel::<Self::Item>(workers);
+        // Each worker holds a reference, so the receiver is dropped (and sending fails) once
+        // every worker has stopped.
+        let receiver = Arc::new(Mutex::new(receiver));
+        let panicked = AtomicBool::new(false);
+        let (handler, panicked_flag) = (&handler, &panicked);
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let receiver = Arc::clone(&receiver);
+                scope.spawn(move || {
+                    let _guard = PanicFlag(panicked_flag);
+                    while !panicked_flag.load(Ordering::SeqCst) {
+                        // The lock is released before the handler runs, so workers run in
+                        // parallel.
+                        let item = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+                        match item {
+                            Ok(item) => handler(item),
+                            Err(_) => return,
+                        }
+                    }
+                });
+            }
+            drop(receiver);
+            for item in self {
+                if panicked.load(Ordering::SeqCst) || sender.send(item).is_err() {
+                    break;
+                }
+            }
+            drop(sender);
+        });
+    }
+}
+
+/// Sets the flag if the thread panics while this is alive, so `for_each_concurrent()` can stop.
+struct PanicFlag<'a>(&'a AtomicBool);
+
+impl<'a> Drop for PanicFlag<'a> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// A hook that streams run on every new item before it is yielded, which can be used to attach
+/// enrichment (e.g. language detection or sentiment analysis) without wrapping the stream.
+/// See the `structures::processors` module for the built-in processors.
+pub trait ItemProcessor<T> {
+    /// Inspects a new item. Called exactly once for each item that the stream yields.
+    fn process(&mut self, item: &T);
+}
+
+/// A service that stores a copy of a web page, such as the Wayback Machine. Attach one to a
+/// `PostStream` with `ArchivingProcessor` to archive every link as it is posted.
+pub trait Archiver {
+    /// Asks the service to archive the page at the URL.
+    fn archive(&mut self, url: &str) -> Result<(), APIError>;
 }
 
 /// An object that was created at some point (e.g. a subreddit, a submission or a comment)
@@ -45,6 +185,12 @@ pub trait Created {
     fn created(&self) -> i64;
     /// The timestamp of post creation, in UTC.
     fn created_utc(&self) -> i64;
+    /// How long ago the object was created. This is zero if the creation time is in the future
+    /// (e.g. because the local clock is wrong), or the clock cannot be read.
+    fn age(&self) -> Duration {
+        let now = clock::unix_time().unwrap_or_default().as_secs();
+        Duration::from_secs(now.saturating_sub(self.created_utc().max(0) as u64))
+    }
 }
 
 /// An object that can be edited (anything that has a body).
@@ -60,20 +206,26 @@ pub trait Editable {
     fn body(&self) -> Option<String>;
     /// Gets the comment/message body HTML or self text HTML if available.
     fn body_html(&self) -> Option<String>;
+    /// Gets the body as plain text, without Markdown formatting or HTML, for indexing or
+    /// display. This is `body_html()` with tags stripped and entities decoded (see
+    /// `html::to_plain_text()`).
+    fn body_plaintext(&self) -> Option<String> {
+        self.body_html().map(|html| to_plain_text(&html))
+    }
     // TODO: anything editable has a body: refactor to handle this!
 }
 
 /// An object that was created by an author and is in a subreddit (i.e. a submission or comment)
 pub trait Content {
     /// The author of the object.
-    fn author(&self) -> User;
+    fn author(&self) -> User<'_>;
     /// The flair text of the user flair, if present.
     fn author_flair_text(&self) -> Option<String>;
     /// The flair CSS class of the user flair, if present.
     fn author_flair_css(&self) -> Option<String>;
     /// For submissions (link/self posts), this is the subreddit where it was posted. For comments,
     /// this is the subreddit of the parent submission.
-    fn subreddit(&self) -> Subreddit;
+    fn subreddit(&self) -> Subreddit<'_>;
     /// Deletes the specified object, if possible. **This may be irreversible. Use with caution.**
     fn delete(self) -> Result<(), APIError>;
     /// Gets the full ID of this comment (kind + id)
@@ -99,12 +251,23 @@ pub trait Approvable {
 pub trait Commentable<'a> {
     /// The number of comments on this object. Prefer this to `replies().count()`.
     fn reply_count(&self) -> u64;
-    /// Sends a reply with the specified body.
-    fn reply(&self, comment: &str) -> Result<Comment, APIError>;
+    /// Sends a reply with the specified body. Fails with `APIError::Archived` without sending
+    /// anything if the object has been archived.
+    fn reply(&self, comment: &str) -> Result<Comment<'_>, APIError>;
     /// Gets all replies as a self-paginating `CommentList`, which can be iterated through as
     /// necessary. Comments cannot be batched like submission listings, so there may be
     /// multiple requests on large threads to get all comments.
     fn replies(self) -> Result<CommentList<'a>, APIError>;
+    /// The variables available to a `ReplyTemplate` when replying to this object: `author`,
+    /// `title`, `permalink` and `subreddit`.
+    fn template_vars(&self) -> HashMap<String, String>;
+    /// Renders the template with this object's `template_vars()` and adds the reply to the
+    /// queue. Nothing is sent until the queue is flushed; see `ReplyQueue` for an example.
+    fn reply_with_template(&self, template: &ReplyTemplate, queue: &mut ReplyQueue)
+        where Self: Content
+    {
+        queue.push(self.name(), &template.render(&self.template_vars()));
+    }
 }
 
 
@@ -151,7 +314,9 @@ pub trait Lockable {
 /// An object that can be reported (submission, comment, private message). Also exposes moderation
 /// options to review reports.
 pub trait Reportable {
-    /// Reports the object for the specified reason (must be less than 100 characters).
+    /// Reports the object for the specified reason (must be less than 100 characters). If the
+    /// subreddit does not allow free-form reports, the reason must name one of the subreddit's
+    /// rules instead (see `Subreddit.report_reason()`).
     fn report(&self, reason: &str) -> Result<(), APIError>;
     /// Gets the number of reports for this post. If you do not have the rights to view this value,
     /// this will return `None`.
@@ -169,11 +334,11 @@ pub trait Flairable {
     /// a flair to this (i.e. not moderator or author), this will give an
     /// `APIError::HTTPError(Forbidden)`.
     fn flair_options(&self) -> Result<FlairList, APIError>;
-    /// Sets the flair for this post, if possible. The `template` parameter is the
-    /// `FlairChoice.flair_template_id` field. The template will also be returned if using
-    /// `FlairList.find_text`. This may fail with `APIError::HTTPError(Forbidden)` if you are
-    /// not authorized to do this.
-    fn flair(&self, template: &str) -> Result<(), APIError>;
+    /// Sets the flair for this post, if possible. The `template` parameter is either a
+    /// `FlairChoice` from `flair_options()` or its `flair_template_id` field. The template ID
+    /// will also be returned if using `FlairList.find_text`. This may fail with
+    /// `APIError::HTTPError(Forbidden)` if you are not authorized to do this.
+    fn flair<T: AsRef<str>>(&self, template: T) -> Result<(), APIError>;
 }
 
 /// An object that can be shown/hidden in listings.
@@ -201,10 +366,10 @@ pub trait Distinguishable {
     /// Possible values:
     ///
     /// - `None` - Normal user
-    /// - `Some("moderator")` - [M]
-    /// - `Some("admin")` - [A]
-    /// - `Some("special")` - other special 'distinguishes' e.g. [Δ]
-    fn distinguished(&self) -> Option<String>;
+    /// - `Some(Distinguish::Moderator)` - [M]
+    /// - `Some(Distinguish::Admin)` - [A]
+    /// - `Some(Distinguish::Special)` - other special 'distinguishes' e.g. [Δ]
+    fn distinguished(&self) -> Option<Distinguish>;
     /// Sets the post to have a [M] distinguish.
     fn distinguish(&mut self) -> Result<(), APIError>;
     /// Removes any distinguish on the comment. This will also unsticky a comment, if it is
@@ -212,10 +377,68 @@ pub trait Distinguishable {
     fn undistinguish(&mut self) -> Result<(), APIError>;
     /// Distinguishes if undistinguished, and vice versa.
     fn toggle_distinguish(&mut self) -> Result<(), APIError> {
-        if let Some(_) = self.distinguished() {
+        if self.distinguished().is_some() {
             self.undistinguish()
         } else {
             self.distinguish()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::PollingStream;
+
+    /// A stream of the numbers from the first to the second (exclusive).
+    struct Counter(usize, usize);
+
+    impl Iterator for Counter {
+        type Item = usize;
+        fn next(&mut self) -> Option<usize> {
+            if self.0 == self.1 {
+                return None;
+            }
+            self.0 += 1;
+            Some(self.0 - 1)
+        }
+    }
+
+    impl PollingStream for Counter {
+        fn poll(&mut self) -> Vec<usize> {
+            self.next().into_iter().collect()
+        }
+    }
+
+    #[test]
+    fn handles_every_item() {
+        let total = AtomicUsize::new(0);
+        Counter(0, 100).for_each_concurrent(4, |item| {
+            total.fetch_add(item, Ordering::SeqCst);
+        });
+        assert_eq!(total.load(Ordering::SeqCst), 4950);
+    }
+
+    #[test]
+    fn propagates_panics() {
+        // The stream does not end, so returning at all shows that it stopped after the panic.
+        let result = panic::catch_unwind(|| {
+            Counter(0, usize::MAX).for_each_concurrent(4, |item| {
+                if item == 10 {
+                    panic!("handler failed");
+                }
+            });
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn stops_when_every_worker_panics() {
+        let result = panic::catch_unwind(|| {
+            Counter(0, usize::MAX).for_each_concurrent(2, |_| panic!("handler failed"));
+        });
+        assert!(result.is_err());
+    }
+}
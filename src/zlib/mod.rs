@@ -0,0 +1,145 @@
+//! zlib (RFC 1950/1951) compression for the blobs that Toolbox stores in wiki pages, and
+//! decompression of compressed HTTP responses (which also use gzip, RFC 1952), using `flate2`.
+//! Decompressed data is limited to `MAX_OUTPUT` bytes.
+
+use std::io::{Read, Write};
+
+use flate2::Compression;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+
+/// The most data that is decompressed from one stream, so that a small malicious response (a
+/// "decompression bomb") cannot exhaust memory. Reddit's responses are far smaller.
+pub const MAX_OUTPUT: usize = 64 * 1024 * 1024;
+
+/// Decompresses a zlib stream, checking its header and checksum.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    read_limited(ZlibDecoder::new(data), MAX_OUTPUT)
+}
+
+/// Decompresses a gzip (RFC 1952) stream, checking its header, checksum and length. Only the
+/// first member is decompressed.
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    read_limited(GzDecoder::new(data), MAX_OUTPUT)
+}
+
+/// Decompresses raw deflate (RFC 1951) data.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    read_limited(DeflateDecoder::new(data), MAX_OUTPUT)
+}
+
+/// Compresses data into a zlib stream.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)
+        .and_then(|_| encoder.finish())
+        .expect("Writing to a Vec cannot fail")
+}
+
+/// Reads everything from the decoder, failing if it decompresses to more than `limit` bytes.
+fn read_limited<R>(decoder: R, limit: usize) -> Result<Vec<u8>, String>
+    where R: Read
+{
+    let mut out = Vec::new();
+    decoder.take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|err| err.to_string())?;
+    if out.len() > limit {
+        return Err(format!("Decompressed data is larger than {} bytes", limit));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::read::ZlibDecoder;
+    use flate2::write::GzEncoder;
+
+    use super::{compress, decompress, gunzip, inflate, read_limited};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut pseudo_random = Vec::new();
+        let mut state = 12345u32;
+        for _ in 0..5000 {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            pseudo_random.push((state >> 16) as u8);
+        }
+        vec![Vec::new(),
+             b"a".to_vec(),
+             br#"{"ver":6,"constants":{"users":["a","b"],"warnings":["spamwarn"]}}"#.to_vec(),
+             b"abcabcabcabcabcabcabcabcabcabcabcabcabcabc".repeat(100),
+             vec![0; 100000],
+             pseudo_random]
+    }
+
+    #[test]
+    fn zlib_round_trip() {
+        for sample in samples() {
+            assert_eq!(decompress(&compress(&sample)).unwrap(), sample);
+        }
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        for sample in samples() {
+            assert_eq!(gunzip(&gzip(&sample)).unwrap(), sample);
+        }
+    }
+
+    #[test]
+    fn raw_deflate() {
+        let zlib = compress(b"hello, world");
+        // The deflate data is between the 2-byte header and the 4-byte checksum.
+        assert_eq!(inflate(&zlib[2..zlib.len() - 4]).unwrap(), b"hello, world");
+    }
+
+    #[test]
+    fn corrupt_headers_are_rejected() {
+        assert!(decompress(b"").is_err());
+        assert!(decompress(b"not zlib at all").is_err());
+        assert!(gunzip(b"not gzip at all, not even close").is_err());
+    }
+
+    #[test]
+    fn corrupt_checksums_are_rejected() {
+        let mut zlib = compress(b"hello, world");
+        let last = zlib.len() - 1;
+        zlib[last] ^= 1;
+        assert!(decompress(&zlib).is_err());
+
+        let mut gzip_data = gzip(b"hello, world");
+        let length = gzip_data.len() - 1;
+        gzip_data[length] ^= 1;
+        assert!(gunzip(&gzip_data).is_err());
+    }
+
+    #[test]
+    fn truncated_data_is_rejected() {
+        let zlib = compress(&b"hello, world ".repeat(50));
+        for end in 0..zlib.len() {
+            assert!(decompress(&zlib[..end]).is_err());
+        }
+        let gzip_data = gzip(&b"hello, world ".repeat(50));
+        for end in 0..gzip_data.len() {
+            assert!(gunzip(&gzip_data[..end]).is_err());
+        }
+    }
+
+    #[test]
+    fn output_is_limited() {
+        let bomb = compress(&vec![0; 100000]);
+        assert!(bomb.len() < 1000);
+        assert!(read_limited(ZlibDecoder::new(&bomb[..]), 100000).is_ok());
+        let err = read_limited(ZlibDecoder::new(&bomb[..]), 99999).unwrap_err();
+        assert!(err.contains("larger than"));
+    }
+}